@@ -0,0 +1,173 @@
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use futures_util::{SinkExt, StreamExt};
+use serde_json::Value;
+use tokio_tungstenite::{connect_async, tungstenite::Message};
+use tracing::{error, info, warn};
+use tracing_subscriber::fmt;
+
+mod protocol;
+mod strategy;
+
+use protocol::{
+    get_state_message, hello_message, join_game_message, make_move_message, reset_game_message,
+    Player, ServerMessage,
+};
+use strategy::{Strategy, StrategyKind};
+
+/// Joins a game on a Tic-Tac-Toe server and plays it with a built-in strategy.
+#[derive(Debug, Parser)]
+#[command(version)]
+struct Args {
+    /// The game to join; it is created if nobody has yet. Left out, a new game is hosted.
+    game_id: Option<String>,
+    /// WebSocket URL of the server.
+    #[arg(long, default_value = "ws://127.0.0.1:3000/ws")]
+    url: String,
+    /// Name to play under.
+    #[arg(long, default_value = "Bot")]
+    name: String,
+    /// Seat to ask for; the other one is taken if it is gone.
+    #[arg(long, value_parser = parse_player)]
+    player: Option<Player>,
+    #[arg(long, value_enum, default_value_t)]
+    strategy: StrategyKind,
+    /// Rounds to play before disconnecting.
+    #[arg(long, default_value_t = 1)]
+    rounds: u32,
+}
+
+fn parse_player(value: &str) -> Result<Player, String> {
+    match value {
+        "X" | "x" => Ok(Player::X),
+        "O" | "o" => Ok(Player::O),
+        _ => Err("expected X or O".to_string()),
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    fmt::init();
+
+    if let Err(e) = play(&args, args.strategy.build()).await {
+        error!("❌ Bot stopped: {:#}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Connects, joins or hosts `args.game_id` and plays `args.rounds` rounds with `strategy`.
+async fn play(args: &Args, mut strategy: Box<dyn Strategy>) -> Result<()> {
+    let (socket, _) = connect_async(args.url.as_str())
+        .await
+        .with_context(|| format!("can't connect to {}", args.url))?;
+    info!("🔌 Connected to {}", args.url);
+    let (mut sink, mut stream) = socket.split();
+
+    let send = |value: Value| Message::Text(value.to_string().into());
+    sink.send(send(hello_message())).await?;
+    // Empty until the server picks an ID for a hosted game
+    let mut joined = args.game_id.clone().unwrap_or_default();
+    sink.send(send(join_game_message(&joined, &args.name, args.player)))
+        .await?;
+
+    let mut seat = None;
+    let mut moved_at_version = None;
+    let mut finished_round = None;
+    let mut rounds_played = 0;
+
+    while let Some(frame) = stream.next().await {
+        let text = match frame? {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+        let message = match serde_json::from_str::<ServerMessage>(&text) {
+            Ok(message) => message,
+            Err(e) => {
+                warn!("⚠️ Skipping a message the bot doesn't understand: {}", e);
+                continue;
+            }
+        };
+
+        match message {
+            ServerMessage::JoinSuccess { game_id, player }
+                if joined.is_empty() || game_id == joined =>
+            {
+                info!("🪑 Playing {:?} in game {}", player, game_id);
+                seat = Some(player);
+                joined = game_id;
+            }
+            ServerMessage::UpdateState { game_id, game } if game_id == joined => {
+                let Some(me) = seat else { continue };
+
+                if game.game_over {
+                    if finished_round == Some(game.round) {
+                        continue;
+                    }
+                    finished_round = Some(game.round);
+                    rounds_played += 1;
+                    match game.winner {
+                        Some(winner) if winner == me => info!("🏆 Won round {}", rounds_played),
+                        Some(_) => info!("💀 Lost round {}", rounds_played),
+                        None => info!("🤝 Drew round {}", rounds_played),
+                    }
+                    if rounds_played >= args.rounds {
+                        break;
+                    }
+                    // Both players see the game end, so only X asks for the next round
+                    if me == Player::X {
+                        sink.send(send(reset_game_message(&game_id))).await?;
+                    }
+                    continue;
+                }
+
+                if !game.awaits(me) || moved_at_version == Some(game.version) {
+                    continue;
+                }
+                let Some(cell) = strategy.choose_move(&game, me) else {
+                    warn!("🤷 Strategy passed on version {}", game.version);
+                    continue;
+                };
+                info!("🎯 Playing {:?} at {:?}", me, cell);
+                moved_at_version = Some(game.version);
+                sink.send(send(make_move_message(&game_id, me, cell)))
+                    .await?;
+            }
+            ServerMessage::Error { code, message } => {
+                if seat.is_none() && code.as_deref() != Some("SEAT_TAKEN") {
+                    bail!("can't join {:?}: {}", joined, message);
+                }
+                warn!("⚠️ Server error: {}", message);
+            }
+            ServerMessage::MoveFailed { message } => {
+                warn!("⚠️ Move refused: {}", message);
+                // Fetch the state to try again rather than wait for an update that won't come
+                moved_at_version = None;
+                sink.send(send(get_state_message(&joined))).await?;
+            }
+            ServerMessage::SeatsRotated {
+                game_id,
+                player,
+                left,
+                seated,
+            } if game_id == joined => {
+                if left == args.name {
+                    info!("🎟️ Back in line for a seat");
+                    seat = None;
+                }
+                if seated == args.name {
+                    info!("🪑 Our turn at the board, playing {:?}", player);
+                    seat = Some(player);
+                }
+            }
+            ServerMessage::Removed { game_id } if game_id == joined => {
+                bail!("removed from game {} by its host", game_id);
+            }
+            _ => {}
+        }
+    }
+
+    info!("👋 Done after {} round(s)", rounds_played);
+    Ok(())
+}
@@ -0,0 +1,161 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Player {
+    X,
+    O,
+}
+
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        }
+    }
+}
+
+/// Indexed `board[row][col]`.
+pub type Board = Vec<Vec<Option<Player>>>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameKind {
+    #[default]
+    TicTacToe,
+    /// Discs drop to the lowest free row of the chosen column.
+    ConnectFour,
+}
+
+/// The rules of the game, as the server reports them.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub kind: GameKind,
+    /// Marks in a row needed to win.
+    pub win_length: usize,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            kind: GameKind::default(),
+            win_length: 3,
+        }
+    }
+}
+
+/// The part of the server's game state a bot needs. Every field falls back to its default
+/// and anything else the server sends is ignored.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Game {
+    pub config: GameConfig,
+    pub board: Board,
+    pub current_turn: Player,
+    pub game_over: bool,
+    pub winner: Option<Player>,
+    pub players: Vec<Player>,
+    pub version: u64,
+    pub round: u32,
+    /// Set while nobody may move.
+    pub paused: Option<Value>,
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Game {
+            config: GameConfig::default(),
+            board: Vec::new(),
+            current_turn: Player::X,
+            game_over: false,
+            winner: None,
+            players: Vec::new(),
+            version: 0,
+            round: 0,
+            paused: None,
+        }
+    }
+}
+
+impl Game {
+    /// Whether `player` is expected to move right now.
+    pub fn awaits(&self, player: Player) -> bool {
+        !self.game_over
+            && self.paused.is_none()
+            && self.players.len() == 2
+            && self.current_turn == player
+    }
+}
+
+/// The server messages a bot acts on, tagged by `type`. Every other type is `Other`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ServerMessage {
+    JoinSuccess {
+        game_id: String,
+        player: Player,
+    },
+    UpdateState {
+        game_id: String,
+        game: Game,
+    },
+    Error {
+        #[serde(default)]
+        code: Option<String>,
+        #[serde(default)]
+        message: String,
+    },
+    /// A MAKE_MOVE the server refused; the game is unchanged.
+    MoveFailed {
+        #[serde(default)]
+        message: String,
+    },
+    Removed {
+        game_id: String,
+    },
+    /// In a winner-stays-on room, `left` lost and `seated` took over their seat.
+    SeatsRotated {
+        game_id: String,
+        player: Player,
+        left: String,
+        seated: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+pub fn hello_message() -> Value {
+    json!({
+        "type": "HELLO",
+        "version": env!("CARGO_PKG_VERSION")
+    })
+}
+
+pub fn join_game_message(game_id: &str, name: &str, player: Option<Player>) -> Value {
+    json!({
+        "type": "JOIN_GAME",
+        "game_id": game_id,
+        "name": name,
+        "player": player
+    })
+}
+
+pub fn make_move_message(game_id: &str, player: Player, (x, y): (usize, usize)) -> Value {
+    json!({
+        "type": "MAKE_MOVE",
+        "game_id": game_id,
+        "player": player,
+        "x": x,
+        "y": y
+    })
+}
+
+pub fn get_state_message(game_id: &str) -> Value {
+    json!({ "type": "GET_STATE", "game_id": game_id })
+}
+
+pub fn reset_game_message(game_id: &str) -> Value {
+    json!({ "type": "RESET_GAME", "game_id": game_id })
+}
@@ -0,0 +1,114 @@
+use crate::protocol::{Board, Game, GameKind, Player};
+use clap::ValueEnum;
+use rand::seq::SliceRandom;
+
+/// Picks the bot's moves. Implement this to enter your own bot; the rest of the binary
+/// takes care of connecting, joining and keeping up with the game.
+pub trait Strategy: Send {
+    /// The `(row, column)` to play as `me`, called only when it is `me`'s turn. Connect
+    /// Four only looks at the column. `None` passes, which lets the move clock run out.
+    fn choose_move(&mut self, game: &Game, me: Player) -> Option<(usize, usize)>;
+}
+
+/// The strategies that ship with the bot, picked with `--strategy`.
+#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+pub enum StrategyKind {
+    /// Any legal move.
+    Random,
+    /// Wins when it can, blocks the opponent's win, otherwise plays towards the middle.
+    #[default]
+    Greedy,
+}
+
+impl StrategyKind {
+    pub fn build(self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Random => Box::new(RandomStrategy),
+            StrategyKind::Greedy => Box::new(GreedyStrategy),
+        }
+    }
+}
+
+pub struct RandomStrategy;
+
+impl Strategy for RandomStrategy {
+    fn choose_move(&mut self, game: &Game, _me: Player) -> Option<(usize, usize)> {
+        legal_moves(game).choose(&mut rand::thread_rng()).copied()
+    }
+}
+
+pub struct GreedyStrategy;
+
+impl Strategy for GreedyStrategy {
+    fn choose_move(&mut self, game: &Game, me: Player) -> Option<(usize, usize)> {
+        let moves = legal_moves(game);
+        let completes = |player: Player| {
+            moves.iter().copied().find(|&(row, col)| {
+                let mut board = game.board.clone();
+                board[row][col] = Some(player);
+                wins_at(&board, row, col, game.config.win_length)
+            })
+        };
+
+        completes(me)
+            .or_else(|| completes(me.opponent()))
+            .or_else(|| {
+                let middle = |len: usize| (len as f64 - 1.0) / 2.0;
+                let (mid_row, mid_col) = (middle(game.board.len()), middle(columns(&game.board)));
+                moves.iter().copied().min_by(|a, b| {
+                    let distance = |(row, col): (usize, usize)| {
+                        (row as f64 - mid_row).abs() + (col as f64 - mid_col).abs()
+                    };
+                    distance(*a).total_cmp(&distance(*b))
+                })
+            })
+    }
+}
+
+fn columns(board: &Board) -> usize {
+    board.first().map_or(0, Vec::len)
+}
+
+/// Every cell that can be played, as `(row, column)`. In Connect Four that is the lowest
+/// free cell of each column that isn't full.
+pub fn legal_moves(game: &Game) -> Vec<(usize, usize)> {
+    let board = &game.board;
+    match game.config.kind {
+        GameKind::TicTacToe => (0..board.len())
+            .flat_map(|row| (0..columns(board)).map(move |col| (row, col)))
+            .filter(|&(row, col)| board[row][col].is_none())
+            .collect(),
+        GameKind::ConnectFour => (0..columns(board))
+            .filter_map(|col| {
+                (0..board.len())
+                    .rev()
+                    .find(|&row| board[row][col].is_none())
+                    .map(|row| (row, col))
+            })
+            .collect(),
+    }
+}
+
+/// Whether the mark at `(row, col)` is part of `win_length` in a row.
+fn wins_at(board: &Board, row: usize, col: usize, win_length: usize) -> bool {
+    let Some(player) = board[row][col] else {
+        return false;
+    };
+    let owns = |r: isize, c: isize| {
+        r >= 0
+            && c >= 0
+            && board
+                .get(r as usize)
+                .and_then(|cells| cells.get(c as usize))
+                .is_some_and(|cell| *cell == Some(player))
+    };
+    let run = |dr: isize, dc: isize| {
+        (1..)
+            .take_while(|step| owns(row as isize + dr * step, col as isize + dc * step))
+            .count()
+    };
+
+    [(0, 1), (1, 0), (1, 1), (1, -1)]
+        .into_iter()
+        .any(|(dr, dc)| 1 + run(dr, dc) + run(-dr, -dc) >= win_length)
+}
@@ -0,0 +1,200 @@
+use crate::game_service::GameService;
+
+use eframe::egui;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::Mutex;
+use zip::write::SimpleFileOptions;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Where "Send" posts reports; without it reports can only be saved locally.
+pub fn endpoint() -> Option<String> {
+    std::env::var("BUG_REPORT_URL")
+        .ok()
+        .filter(|url| !url.trim().is_empty())
+}
+
+pub struct BugReport {
+    pub description: String,
+    pub protocol_log: Vec<String>,
+    pub screenshot: Option<Arc<egui::ColorImage>>,
+}
+
+impl BugReport {
+    /// Bundles `report.txt`, `protocol.log` and an optional `screenshot.png`.
+    pub fn to_zip(&self) -> Result<Vec<u8>, BoxError> {
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+
+        zip.start_file("report.txt", options)?;
+        zip.write_all(self.summary().as_bytes())?;
+
+        zip.start_file("protocol.log", options)?;
+        zip.write_all(self.protocol_log.join("\n").as_bytes())?;
+
+        if let Some(image) = &self.screenshot {
+            zip.start_file("screenshot.png", options)?;
+            zip.write_all(&encode_png(image)?)?;
+        }
+
+        Ok(zip.finish()?.into_inner())
+    }
+
+    /// Writes the bundle into the working directory.
+    pub fn save(&self) -> Result<PathBuf, BoxError> {
+        let path = PathBuf::from(format!("bug-report-{}.zip", unix_secs()));
+        std::fs::write(&path, self.to_zip()?)?;
+        Ok(path)
+    }
+
+    pub async fn submit(&self, endpoint: &str) -> Result<(), BoxError> {
+        reqwest::Client::new()
+            .post(endpoint)
+            .header(reqwest::header::CONTENT_TYPE, "application/zip")
+            .body(self.to_zip()?)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn summary(&self) -> String {
+        format!(
+            "Client version: {}\nOS: {} ({})\nReported at: {}\n\n{}\n",
+            crate::version::short(),
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            unix_secs(),
+            self.description
+        )
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct BugReportDialog {
+    open: bool,
+    awaiting_screenshot: bool,
+    description: String,
+    attach_screenshot: bool,
+    screenshot: Option<Arc<egui::ColorImage>>,
+    status: Arc<Mutex<Option<String>>>,
+}
+
+impl BugReportDialog {
+    /// Captures the current frame, then opens the dialog once the screenshot arrives.
+    pub fn open(&mut self, ctx: &egui::Context) {
+        self.awaiting_screenshot = true;
+        self.attach_screenshot = true;
+        ctx.send_viewport_cmd(egui::ViewportCommand::Screenshot(Default::default()));
+    }
+
+    pub fn show(&mut self, ctx: &egui::Context, game_service: &Arc<GameService>) {
+        if self.awaiting_screenshot {
+            let screenshot = ctx.input(|i| {
+                i.raw.events.iter().find_map(|event| match event {
+                    egui::Event::Screenshot { image, .. } => Some(Arc::clone(image)),
+                    _ => None,
+                })
+            });
+
+            match screenshot {
+                Some(image) => {
+                    self.screenshot = Some(image);
+                    self.awaiting_screenshot = false;
+                    self.open = true;
+                }
+                None => {
+                    ctx.request_repaint();
+                    return;
+                }
+            }
+        }
+
+        let mut open = self.open;
+        egui::Window::new("🐞 Report a problem")
+            .open(&mut open)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label("What went wrong?");
+                ui.text_edit_multiline(&mut self.description);
+                ui.checkbox(&mut self.attach_screenshot, "Attach screenshot");
+                ui.label(
+                    egui::RichText::new(
+                        "Recent network messages, the client version and your OS are included.",
+                    )
+                    .small()
+                    .color(egui::Color32::GRAY),
+                );
+
+                let endpoint = endpoint();
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Save").clicked() {
+                        self.submit(game_service, None);
+                    }
+
+                    if ui
+                        .add_enabled(endpoint.is_some(), egui::Button::new("📤 Send"))
+                        .on_disabled_hover_text("Set BUG_REPORT_URL to enable sending")
+                        .clicked()
+                    {
+                        self.submit(game_service, endpoint);
+                    }
+                });
+
+                if let Some(status) = self.status.try_lock().ok().and_then(|s| s.clone()) {
+                    ui.label(status);
+                }
+            });
+        self.open = open;
+    }
+
+    fn submit(&self, game_service: &Arc<GameService>, endpoint: Option<String>) {
+        let game_service = Arc::clone(game_service);
+        let description = self.description.clone();
+        let screenshot = self.screenshot.clone().filter(|_| self.attach_screenshot);
+        let status = Arc::clone(&self.status);
+
+        tokio::spawn(async move {
+            *status.lock().await = Some("⏳ Preparing report...".to_string());
+
+            let report = BugReport {
+                description,
+                protocol_log: game_service.protocol_log().await,
+                screenshot,
+            };
+
+            let outcome = match endpoint {
+                Some(url) => report
+                    .submit(&url)
+                    .await
+                    .map(|_| "✅ Report sent, thank you!".to_string()),
+                None => report
+                    .save()
+                    .map(|path| format!("✅ Saved to {}", path.display())),
+            };
+
+            *status.lock().await = Some(outcome.unwrap_or_else(|e| format!("❌ {e}")));
+        });
+    }
+}
+
+fn encode_png(image: &egui::ColorImage) -> Result<Vec<u8>, BoxError> {
+    let [width, height] = image.size;
+    let rgba = image.pixels.iter().flat_map(|p| p.to_array()).collect();
+    let buffer = image::RgbaImage::from_raw(width as u32, height as u32, rgba)
+        .ok_or("screenshot size does not match its pixel data")?;
+
+    let mut png = Cursor::new(Vec::new());
+    buffer.write_to(&mut png, image::ImageFormat::Png)?;
+    Ok(png.into_inner())
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
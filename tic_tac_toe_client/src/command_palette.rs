@@ -0,0 +1,203 @@
+use eframe::egui;
+
+/// Actions reachable from the command palette.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    NewGame,
+    NextRound,
+    Resign,
+    OfferDraw,
+    ToggleTheme,
+    CopyGameId,
+    ShowShortcuts,
+    ReportProblem,
+    ExportGame,
+}
+
+impl Command {
+    pub const ALL: [Command; 9] = [
+        Command::NewGame,
+        Command::NextRound,
+        Command::Resign,
+        Command::OfferDraw,
+        Command::ToggleTheme,
+        Command::CopyGameId,
+        Command::ShowShortcuts,
+        Command::ReportProblem,
+        Command::ExportGame,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Command::NewGame => "🆕 New game",
+            Command::NextRound => "🔄 Next round",
+            Command::Resign => "🏳️ Resign",
+            Command::OfferDraw => "🤝 Offer draw",
+            Command::ToggleTheme => "🌓 Toggle light/dark theme",
+            Command::CopyGameId => "📋 Copy game ID",
+            Command::ShowShortcuts => "⌨ Keyboard shortcuts",
+            Command::ReportProblem => "🐞 Report a problem",
+            Command::ExportGame => "💾 Export finished game",
+        }
+    }
+
+    fn shortcut(self) -> Option<egui::KeyboardShortcut> {
+        match self {
+            Command::ToggleTheme => Some(TOGGLE_THEME),
+            Command::ShowShortcuts => Some(SHOW_SHORTCUTS),
+            _ => None,
+        }
+    }
+}
+
+const OPEN_PALETTE: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::K);
+const TOGGLE_THEME: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::COMMAND, egui::Key::D);
+const SHOW_SHORTCUTS: egui::KeyboardShortcut =
+    egui::KeyboardShortcut::new(egui::Modifiers::NONE, egui::Key::F1);
+
+/// Shortcuts that aren't palette commands, shown on the cheat-sheet.
+const OTHER_SHORTCUTS: [(&str, &str); 3] = [
+    ("↑ / ↓", "Move through the palette"),
+    ("Enter", "Run the highlighted command"),
+    ("Esc", "Close the palette or this sheet"),
+];
+
+/// Ctrl+K command palette plus the F1 keyboard shortcut cheat-sheet.
+#[derive(Clone, Default)]
+pub struct CommandPalette {
+    open: bool,
+    query: String,
+    selected: usize,
+    shortcuts_open: bool,
+}
+
+impl CommandPalette {
+    pub fn show_shortcuts(&mut self) {
+        self.shortcuts_open = true;
+    }
+
+    /// Handles the global shortcuts and draws whichever overlay is open. Returns the
+    /// command to run, picked in the palette or pressed directly.
+    pub fn show(&mut self, ctx: &egui::Context, available: &[Command]) -> Option<Command> {
+        let mut picked = ctx.input_mut(|i| {
+            if i.consume_shortcut(&OPEN_PALETTE) {
+                self.open = !self.open;
+                self.query.clear();
+                self.selected = 0;
+            }
+            let overlay_open = self.open || self.shortcuts_open;
+            if overlay_open && i.consume_key(egui::Modifiers::NONE, egui::Key::Escape) {
+                self.open = false;
+                self.shortcuts_open = false;
+            }
+
+            available.iter().copied().find(|command| {
+                command
+                    .shortcut()
+                    .is_some_and(|shortcut| i.consume_shortcut(&shortcut))
+            })
+        });
+
+        if self.open {
+            picked = picked.or_else(|| self.show_palette(ctx, available));
+        }
+        if self.shortcuts_open {
+            self.show_cheat_sheet(ctx);
+        }
+
+        picked
+    }
+
+    fn show_palette(&mut self, ctx: &egui::Context, available: &[Command]) -> Option<Command> {
+        let query = self.query.to_lowercase();
+        let matches: Vec<Command> = available
+            .iter()
+            .copied()
+            .filter(|command| command.label().to_lowercase().contains(&query))
+            .collect();
+        self.selected = self.selected.min(matches.len().saturating_sub(1));
+
+        let (up, down, enter) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Enter),
+            )
+        });
+        if up {
+            self.selected = self.selected.saturating_sub(1);
+        }
+        if down && self.selected + 1 < matches.len() {
+            self.selected += 1;
+        }
+
+        let mut picked = enter.then(|| matches.get(self.selected).copied()).flatten();
+
+        egui::Window::new("Command palette")
+            .title_bar(false)
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 60.0))
+            .show(ctx, |ui| {
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.query)
+                        .hint_text("Type a command...")
+                        .desired_width(300.0),
+                )
+                .request_focus();
+
+                if matches.is_empty() {
+                    ui.label(egui::RichText::new("No matching commands").italics());
+                }
+
+                for (index, command) in matches.iter().enumerate() {
+                    let text = match command.shortcut() {
+                        Some(shortcut) => {
+                            format!("{}  ({})", command.label(), ctx.format_shortcut(&shortcut))
+                        }
+                        None => command.label().to_string(),
+                    };
+                    if ui.selectable_label(index == self.selected, text).clicked() {
+                        picked = Some(*command);
+                    }
+                }
+            });
+
+        if picked.is_some() {
+            self.open = false;
+        }
+        picked
+    }
+
+    fn show_cheat_sheet(&mut self, ctx: &egui::Context) {
+        let mut open = self.shortcuts_open;
+        egui::Window::new("⌨ Keyboard shortcuts")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new("shortcuts").striped(true).show(ui, |ui| {
+                    ui.label(ctx.format_shortcut(&OPEN_PALETTE));
+                    ui.label("Open the command palette");
+                    ui.end_row();
+
+                    for command in Command::ALL {
+                        if let Some(shortcut) = command.shortcut() {
+                            ui.label(ctx.format_shortcut(&shortcut));
+                            ui.label(command.label());
+                            ui.end_row();
+                        }
+                    }
+
+                    for (keys, action) in OTHER_SHORTCUTS {
+                        ui.label(keys);
+                        ui.label(action);
+                        ui.end_row();
+                    }
+                });
+            });
+        self.shortcuts_open = open;
+    }
+}
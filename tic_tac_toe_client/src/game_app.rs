@@ -1,19 +1,71 @@
-use crate::game_service::{GameService, Player};
+use crate::bug_report::BugReportDialog;
+use crate::command_palette::{Command, CommandPalette};
+use crate::game_service::{
+    format_clock, parse_spectate_link, AiDifficulty, GameKind, GameService, JoinOptions, Pause,
+    Player, TournamentFormat, TournamentStanding, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE,
+    MAX_NAME_LENGTH, REACTIONS,
+};
+use crate::i18n;
+use crate::skins;
 
 use eframe::egui;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tracing::error;
 
+/// Move clocks offered when creating a game: the server's default, off, or a fixed limit.
+const TURN_TIMER_CHOICES: [Option<u64>; 5] = [None, Some(0), Some(15), Some(30), Some(60)];
+
+fn turn_timer_label(turn_seconds: Option<u64>) -> String {
+    match turn_seconds {
+        None => "Server default".to_string(),
+        Some(0) => "Off".to_string(),
+        Some(secs) => format!("{secs}s per move"),
+    }
+}
+
+/// Chess clocks offered when creating a game, in seconds per player.
+const CHESS_CLOCK_CHOICES: [Option<u64>; 4] = [None, Some(60), Some(120), Some(300)];
+
+fn chess_clock_label(clock_seconds: Option<u64>) -> String {
+    match clock_seconds {
+        None => "Off".to_string(),
+        Some(secs) => format!("{} min each", secs / 60),
+    }
+}
+
+fn mark_label(mark: Option<Player>) -> String {
+    match mark {
+        None => "Whichever is free".to_string(),
+        Some(player) => format!("{player:?}"),
+    }
+}
+
 #[derive(Clone)]
 pub struct GameApp {
     game_service: Arc<GameService>,
-    game_id: Arc<Mutex<String>>,
     input_game_id: String,
     input_player_name: String,
+    input_ai_difficulty: AiDifficulty,
+    input_options: JoinOptions,
+    input_chat: String,
+    input_team_chat: bool,
+    /// Local switch for muting what spectators say, on top of the game's own setting.
+    show_spectator_chat: bool,
+    input_notify_email: String,
+    input_notify_webhook: String,
+    input_password: String,
     joined: Arc<Mutex<bool>>,
     error_message: Option<String>,
     cached_player: Arc<Mutex<Option<Player>>>,
+    polling_player: Arc<AtomicBool>,
+    /// Why the last sign-in, log-in or registration failed, e.g. someone else holds the name.
+    sign_in_error: Arc<Mutex<Option<String>>>,
+    bug_report: BugReportDialog,
+    command_palette: CommandPalette,
+    /// Where the last export was saved, or why it failed.
+    export_status: Arc<Mutex<Option<String>>>,
 }
 impl Default for GameApp {
     fn default() -> Self {
@@ -21,53 +73,238 @@ impl Default for GameApp {
             game_service: Arc::new(GameService::new(
                 "https://tic-tac-toe-multiplayer-zg0e.onrender.com".to_string(),
             )),
-            game_id: Arc::new(Mutex::new(String::new())),
             input_game_id: String::new(),
             input_player_name: String::new(),
+            input_ai_difficulty: AiDifficulty::default(),
+            input_options: JoinOptions::default(),
+            input_chat: String::new(),
+            input_team_chat: false,
+            show_spectator_chat: true,
+            input_notify_email: String::new(),
+            input_notify_webhook: String::new(),
+            input_password: String::new(),
             joined: Arc::new(Mutex::new(false)),
             error_message: None,
             cached_player: Arc::new(Mutex::new(None)),
+            polling_player: Arc::new(AtomicBool::new(false)),
+            sign_in_error: Arc::new(Mutex::new(None)),
+            bug_report: BugReportDialog::default(),
+            command_palette: CommandPalette::default(),
+            export_status: Arc::new(Mutex::new(None)),
         }
     }
 }
 
 impl eframe::App for GameApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        let game_service = Arc::clone(&self.game_service);
-        let cached_player = Arc::clone(&self.cached_player);
-        let joined_state = Arc::clone(&self.joined);
+        if self.game_service.join_rejection().is_some() {
+            if let Ok(mut joined) = self.joined.try_lock() {
+                *joined = false;
+            }
+        }
 
-        tokio::spawn(async move {
-            if let Some(player) = game_service.get_player().await {
-                if let Ok(mut cached) = cached_player.try_lock() {
-                    *cached = Some(player);
+        let joined = self.joined.try_lock().map(|guard| *guard).unwrap_or(false);
+
+        // The join screen is purely input-driven; only poll once we're in a game
+        if joined {
+            // Winner-stays-on rooms seat us and stand us down between rounds
+            if self.game_service.is_challenger() {
+                if let Ok(mut cached) = self.cached_player.try_lock() {
+                    *cached = None;
+                }
+            } else if let Some(seat) = self.game_service.seat() {
+                if let Ok(mut cached) = self.cached_player.try_lock() {
+                    *cached = Some(seat);
                 }
             }
-        });
 
-        let joined = joined_state.try_lock().map(|guard| *guard).unwrap_or(false);
+            let has_player = self
+                .cached_player
+                .try_lock()
+                .map(|guard| guard.is_some())
+                .unwrap_or(true);
+
+            if !has_player
+                && !self.game_service.is_spectating()
+                && !self.polling_player.swap(true, Ordering::SeqCst)
+            {
+                let game_service = Arc::clone(&self.game_service);
+                let cached_player = Arc::clone(&self.cached_player);
+                let polling_player = Arc::clone(&self.polling_player);
+
+                tokio::spawn(async move {
+                    if let Some(player) = game_service.get_player().await {
+                        if let Ok(mut cached) = cached_player.try_lock() {
+                            *cached = Some(player);
+                        }
+                    }
+                    polling_player.store(false, Ordering::SeqCst);
+                });
+            }
 
-        ctx.request_repaint_after(std::time::Duration::from_millis(100));
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+
+        egui::TopBottomPanel::bottom("footer").show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                if ui.small_button("🐞 Report a problem").clicked() {
+                    self.bug_report.open(ctx);
+                }
+                if ui.small_button("⌨ Shortcuts").clicked() {
+                    self.command_palette.show_shortcuts();
+                }
+                ui.label(
+                    egui::RichText::new("Ctrl+K for commands")
+                        .small()
+                        .color(egui::Color32::GRAY),
+                );
+            });
+        });
 
         egui::CentralPanel::default().show(ctx, |ui| {
             self.handle_game_ui(ui, &Arc::new(ctx.clone()), joined);
         });
+
+        self.bug_report.show(ctx, &self.game_service);
+
+        let available = self.available_commands(joined);
+        if let Some(command) = self.command_palette.show(ctx, &available) {
+            self.run_command(command, ctx);
+        }
     }
 }
 
 impl GameApp {
+    /// Palette commands that make sense right now.
+    fn available_commands(&self, joined: bool) -> Vec<Command> {
+        let player = self.cached_player.try_lock().ok().and_then(|p| *p);
+        let seated = player.is_some() && !self.game_service.is_spectating();
+
+        Command::ALL
+            .into_iter()
+            .filter(|command| match command {
+                Command::NewGame | Command::CopyGameId => joined,
+                Command::ExportGame => {
+                    joined
+                        && self
+                            .game_service
+                            .get_game()
+                            .try_lock()
+                            .is_ok_and(|game| game.game_over)
+                }
+                Command::NextRound => {
+                    joined
+                        && seated
+                        && self
+                            .game_service
+                            .get_game()
+                            .try_lock()
+                            .is_ok_and(|game| game.game_over)
+                }
+                Command::Resign | Command::OfferDraw => {
+                    joined
+                        && seated
+                        && self
+                            .game_service
+                            .get_game()
+                            .try_lock()
+                            .is_ok_and(|game| !game.game_over && game.players.len() == 2)
+                }
+                Command::ToggleTheme | Command::ShowShortcuts | Command::ReportProblem => true,
+            })
+            .collect()
+    }
+
+    fn run_command(&mut self, command: Command, ctx: &egui::Context) {
+        let player = self.cached_player.try_lock().ok().and_then(|p| *p);
+        let game_service = Arc::clone(&self.game_service);
+
+        match command {
+            Command::NewGame => {
+                // Back to the join screen; the next join releases the old seat
+                if let Ok(mut joined) = self.joined.try_lock() {
+                    *joined = false;
+                }
+                if let Ok(mut cached) = self.cached_player.try_lock() {
+                    *cached = None;
+                }
+            }
+            Command::NextRound => {
+                tokio::spawn(async move {
+                    game_service.reset_game().await;
+                });
+            }
+            Command::Resign => {
+                if let Some(player) = player {
+                    tokio::spawn(async move {
+                        game_service.resign(player).await;
+                    });
+                }
+            }
+            Command::OfferDraw => {
+                if let Some(player) = player {
+                    tokio::spawn(async move {
+                        game_service.offer_draw(player).await;
+                    });
+                }
+            }
+            Command::ToggleTheme => {
+                let visuals = if ctx.style().visuals.dark_mode {
+                    egui::Visuals::light()
+                } else {
+                    egui::Visuals::dark()
+                };
+                ctx.set_visuals(visuals);
+            }
+            Command::CopyGameId => ctx.copy_text(self.game_service.game_id()),
+            Command::ShowShortcuts => self.command_palette.show_shortcuts(),
+            Command::ReportProblem => self.bug_report.open(ctx),
+            Command::ExportGame => self.export_game(),
+        }
+    }
+
+    /// Saves the round that just finished as notation, reporting where it went.
+    fn export_game(&self) {
+        let game_service = Arc::clone(&self.game_service);
+        let export_status = Arc::clone(&self.export_status);
+        tokio::spawn(async move {
+            let status = match game_service.export_game().await {
+                Ok(path) => format!("💾 Saved to {}", path.display()),
+                Err(e) => format!("⚠️ Export failed: {e}"),
+            };
+            *export_status.lock().await = Some(status);
+        });
+    }
+
     fn handle_game_ui(&mut self, ui: &mut egui::Ui, ctx_arc: &Arc<egui::Context>, joined: bool) {
         ui.vertical_centered(|ui| {
             ui.group(|ui| {
                 ui.set_width(400.0);
                 ui.set_height(500.0);
 
+                self.render_server_banner(ui);
+                if joined {
+                    self.render_diagnostic(ui);
+                }
+
                 if !joined {
                     ui.label("Your Name:");
 
                     ui.add_space(3.0);
 
-                    ui.text_edit_singleline(&mut self.input_player_name);
+                    match self.game_service.signed_in_as() {
+                        Some(name) => {
+                            ui.label(format!("🔐 Signed in as {name}"));
+                            self.input_player_name = name;
+                        }
+                        None => {
+                            ui.add(
+                                egui::TextEdit::singleline(&mut self.input_player_name)
+                                    .char_limit(MAX_NAME_LENGTH),
+                            );
+                            self.render_sign_in(ui);
+                        }
+                    }
                 }
 
                 ui.add_space(10.0);
@@ -80,8 +317,142 @@ impl GameApp {
 
                 ui.add_space(10.0);
 
+                let spectate_link = parse_spectate_link(&self.input_game_id);
                 let can_join = !self.input_game_id.trim().is_empty()
-                    && !self.input_player_name.trim().is_empty();
+                    && !self.input_player_name.trim().is_empty()
+                    && spectate_link.is_none();
+
+                if !joined {
+                    ui.checkbox(
+                        &mut self.input_options.private,
+                        "🔒 Private (spectators need a link)",
+                    );
+                    ui.checkbox(
+                        &mut self.input_options.unlisted,
+                        "🙈 Unlisted (only joinable by ID or invite code)",
+                    );
+                    ui.checkbox(
+                        &mut self.input_options.correspondence,
+                        "📮 Correspondence (no time limit, come back any time)",
+                    );
+                    ui.checkbox(
+                        &mut self.input_options.team,
+                        "👥 Team game (2v2, partners alternate moves)",
+                    );
+                    ui.checkbox(
+                        &mut self.input_options.hide_spectator_chat,
+                        "🙊 Keep spectator chat away from the players",
+                    );
+                    ui.checkbox(
+                        &mut self.input_options.winner_stays_on,
+                        "🏆 Winner stays on (others wait in line to play)",
+                    );
+                    if self.input_options.team {
+                        ui.horizontal(|ui| {
+                            ui.label("Team name:");
+                            ui.text_edit_singleline(&mut self.input_options.team_name);
+                        });
+                    }
+
+                    egui::ComboBox::from_label("Game")
+                        .selected_text(self.input_options.kind.label())
+                        .show_ui(ui, |ui| {
+                            for kind in [GameKind::TicTacToe, GameKind::ConnectFour] {
+                                ui.selectable_value(
+                                    &mut self.input_options.kind,
+                                    kind,
+                                    kind.label(),
+                                );
+                            }
+                        });
+
+                    if self.input_options.kind == GameKind::TicTacToe {
+                        let resized = ui
+                            .add(
+                                egui::Slider::new(
+                                    &mut self.input_options.board_size,
+                                    DEFAULT_BOARD_SIZE..=MAX_BOARD_SIZE,
+                                )
+                                .text("Board size"),
+                            )
+                            .changed();
+                        let board_size = self.input_options.board_size;
+                        // A new size starts out needing a full line; shorten it afterwards if wanted
+                        if resized {
+                            self.input_options.win_length = board_size;
+                        }
+                        ui.add_enabled(
+                            board_size > DEFAULT_BOARD_SIZE,
+                            egui::Slider::new(
+                                &mut self.input_options.win_length,
+                                DEFAULT_BOARD_SIZE..=board_size,
+                            )
+                            .text("In a row to win"),
+                        );
+                    }
+
+                    egui::ComboBox::from_label("Play as")
+                        .selected_text(mark_label(self.input_options.mark))
+                        .show_ui(ui, |ui| {
+                            for mark in [None, Some(Player::X), Some(Player::O)] {
+                                ui.selectable_value(
+                                    &mut self.input_options.mark,
+                                    mark,
+                                    mark_label(mark),
+                                );
+                            }
+                        });
+
+                    egui::ComboBox::from_label("Moves first")
+                        .selected_text(format!("{:?}", self.input_options.first_player))
+                        .show_ui(ui, |ui| {
+                            for player in [Player::X, Player::O] {
+                                ui.selectable_value(
+                                    &mut self.input_options.first_player,
+                                    player,
+                                    format!("{player:?}"),
+                                );
+                            }
+                        });
+
+                    if !self.input_options.correspondence {
+                        egui::ComboBox::from_label("Turn timer")
+                            .selected_text(turn_timer_label(self.input_options.turn_seconds))
+                            .show_ui(ui, |ui| {
+                                for turn_seconds in TURN_TIMER_CHOICES {
+                                    ui.selectable_value(
+                                        &mut self.input_options.turn_seconds,
+                                        turn_seconds,
+                                        turn_timer_label(turn_seconds),
+                                    );
+                                }
+                            });
+
+                        egui::ComboBox::from_label("Chess clock")
+                            .selected_text(chess_clock_label(self.input_options.clock_seconds))
+                            .show_ui(ui, |ui| {
+                                for clock_seconds in CHESS_CLOCK_CHOICES {
+                                    ui.selectable_value(
+                                        &mut self.input_options.clock_seconds,
+                                        clock_seconds,
+                                        chess_clock_label(clock_seconds),
+                                    );
+                                }
+                            });
+                    }
+
+                    egui::ComboBox::from_label("Room language")
+                        .selected_text(i18n::locale_name(&self.input_options.locale))
+                        .show_ui(ui, |ui| {
+                            for (code, name) in i18n::LOCALES {
+                                ui.selectable_value(
+                                    &mut self.input_options.locale,
+                                    code.to_string(),
+                                    name,
+                                );
+                            }
+                        });
+                }
                 if ui
                     .add_enabled(
                         can_join,
@@ -93,50 +464,205 @@ impl GameApp {
                     let game_service_clone = Arc::clone(&self.game_service);
                     let input_game_id = self.input_game_id.clone();
                     let player_name = self.input_player_name.clone();
+                    let options = self.input_options.clone();
                     let joined_state = Arc::clone(&self.joined);
-                    let game_id_lock = Arc::clone(&self.game_id);
 
                     tokio::spawn(async move {
-                        let id = input_game_id.clone();
                         game_service_clone
-                            .join_game(input_game_id, player_name, ctx_clone)
+                            .join_game(input_game_id, player_name, options, ctx_clone)
                             .await;
 
                         if let Ok(mut joined) = joined_state.try_lock() {
                             *joined = true;
                         }
-                        if let Ok(mut game_id) = game_id_lock.try_lock() {
-                            *game_id = id;
+                    });
+                }
+
+                let can_create =
+                    !self.input_player_name.trim().is_empty() && spectate_link.is_none();
+                if !joined
+                    && ui
+                        .add_enabled(
+                            can_create,
+                            egui::Button::new("✨ Create Game").min_size(egui::vec2(100.0, 30.0)),
+                        )
+                        .on_hover_text(
+                            "New game with these settings; leave the ID empty for a random one",
+                        )
+                        .clicked()
+                {
+                    let ctx_clone = Arc::clone(ctx_arc);
+                    let game_service_clone = Arc::clone(&self.game_service);
+                    let game_id = self.input_game_id.trim().to_string();
+                    let player_name = self.input_player_name.clone();
+                    let options = self.input_options.clone();
+                    let joined_state = Arc::clone(&self.joined);
+
+                    tokio::spawn(async move {
+                        match game_service_clone
+                            .create_game(game_id, player_name, options, ctx_clone)
+                            .await
+                        {
+                            Ok(()) => *joined_state.lock().await = true,
+                            Err(e) => error!("❌ Failed to create game: {:?}", e),
+                        }
+                    });
+                }
+
+                if ui
+                    .add_enabled(
+                        can_join,
+                        egui::Button::new("🔑 Join by invite code")
+                            .min_size(egui::vec2(100.0, 30.0)),
+                    )
+                    .clicked()
+                {
+                    let ctx_clone = Arc::clone(ctx_arc);
+                    let game_service_clone = Arc::clone(&self.game_service);
+                    let code = self.input_game_id.trim().to_string();
+                    let player_name = self.input_player_name.clone();
+                    let joined_state = Arc::clone(&self.joined);
+
+                    tokio::spawn(async move {
+                        match game_service_clone
+                            .join_by_code(code, player_name, ctx_clone)
+                            .await
+                        {
+                            Ok(()) => *joined_state.lock().await = true,
+                            Err(e) => error!("❌ Failed to join by invite code: {:?}", e),
+                        }
+                    });
+                }
+
+                if let (false, Some((game_id, token))) = (joined, spectate_link) {
+                    if ui.button("👀 Open spectate link").clicked() {
+                        let ctx_clone = Arc::clone(ctx_arc);
+                        let game_service_clone = Arc::clone(&self.game_service);
+                        let joined_state = Arc::clone(&self.joined);
+
+                        tokio::spawn(async move {
+                            match game_service_clone
+                                .spectate(game_id, Some(token), ctx_clone)
+                                .await
+                            {
+                                Ok(()) => *joined_state.lock().await = true,
+                                Err(e) => error!("❌ Failed to spectate: {:?}", e),
+                            }
+                        });
+                    }
+                }
+
+                if !joined {
+                    egui::ComboBox::from_label("AI difficulty")
+                        .selected_text(self.input_ai_difficulty.label())
+                        .show_ui(ui, |ui| {
+                            for difficulty in AiDifficulty::ALL {
+                                ui.selectable_value(
+                                    &mut self.input_ai_difficulty,
+                                    difficulty,
+                                    difficulty.label(),
+                                );
+                            }
+                        });
+                }
+
+                if !joined
+                    && ui
+                        .add_enabled(
+                            !self.input_player_name.trim().is_empty(),
+                            egui::Button::new("🤖 Play vs computer")
+                                .min_size(egui::vec2(100.0, 30.0)),
+                        )
+                        .clicked()
+                {
+                    let ctx_clone = Arc::clone(ctx_arc);
+                    let game_service_clone = Arc::clone(&self.game_service);
+                    let input_game_id = self.input_game_id.trim().to_string();
+                    let player_name = self.input_player_name.clone();
+                    let difficulty = self.input_ai_difficulty;
+                    let options = self.input_options.clone();
+                    let joined_state = Arc::clone(&self.joined);
+
+                    tokio::spawn(async move {
+                        match game_service_clone
+                            .join_vs_ai(input_game_id, player_name, difficulty, options, ctx_clone)
+                            .await
+                        {
+                            Ok(()) => *joined_state.lock().await = true,
+                            Err(e) => error!("❌ Failed to start game vs computer: {:?}", e),
                         }
                     });
                 }
                 ui.add_space(10.0);
 
-                if let Some(error) = &self.error_message {
+                if !joined {
+                    self.render_waiting_games(ui, ctx_arc);
+                }
+
+                if !joined {
+                    if let Some(rejection) = self.game_service.join_rejection() {
+                        self.render_join_rejection(ui, ctx_arc, &rejection);
+                    }
+                }
+
+                if let Some(error) = self
+                    .error_message
+                    .clone()
+                    .or_else(|| self.game_service.last_error())
+                {
                     ui.colored_label(egui::Color32::RED, error);
                     ui.add_space(10.0);
                 }
 
                 if joined {
-                    ui.label("🎮 Game in progress...");
+                    let spectating = self.game_service.is_spectating();
 
                     let player = {
                         let player_guard = self.cached_player.try_lock().ok();
                         player_guard.and_then(|p| *p)
                     };
 
-                    if let Some(player) = player {
-                        self.render_board(ui, ctx_arc, player);
+                    if spectating {
+                        ui.label("👀 Spectating");
+                        self.render_board(ui, ctx_arc, None);
+                    } else if let Some(player) = player {
+                        if !self.render_queue_status(ui) {
+                            ui.label("🎮 Game in progress...");
+                        }
+                        self.render_board(ui, ctx_arc, Some(player));
+                    } else if self.game_service.is_challenger() {
+                        self.render_line_place(ui);
+                        self.render_board(ui, ctx_arc, None);
                     } else {
+                        ui.label("🎮 Game in progress...");
                         ui.label("🔄 Waiting for player assignment...");
                     }
 
                     self.display_game_status(ui);
+                    self.render_reactions(ui);
+
+                    if self.game_service.unconfirmed_move().is_some() {
+                        self.render_unconfirmed_move(ui, ctx_arc);
+                    }
+
+                    if !spectating && player.is_some() {
+                        self.render_invite_code(ui);
+                        self.render_spectate_link(ui);
+                    }
+
+                    self.render_chat(ui);
+                    if !spectating {
+                        self.render_notifications(ui);
+                    }
+                    self.render_leaderboard(ui);
+                    self.render_tournament(ui);
+                    self.render_online_players(ui);
+                    self.render_debug_menu(ui);
 
                     ui.add_space(5.0);
 
                     if let Ok(game) = self.game_service.get_game().try_lock() {
-                        if game.game_over {
+                        if game.game_over && !spectating {
                             ctx_arc.request_repaint();
 
                             if ui
@@ -155,6 +681,67 @@ impl GameApp {
                                     game_service_clone.reset_game().await;
                                 });
                             }
+                        } else if let Some(player) =
+                            player.filter(|_| !game.game_over && !spectating)
+                        {
+                            if game.players.len() == 2 {
+                                ui.horizontal(|ui| {
+                                    if ui.button("🏳️ Resign").clicked() {
+                                        let game_service_clone = Arc::clone(&self.game_service);
+                                        tokio::spawn(async move {
+                                            game_service_clone.resign(player).await;
+                                        });
+                                    }
+
+                                    let draw_label =
+                                        if game.draw_offer.is_some_and(|seat| seat != player) {
+                                            "🤝 Accept draw"
+                                        } else {
+                                            "🤝 Offer draw"
+                                        };
+                                    if ui
+                                        .add_enabled(
+                                            game.draw_offer != Some(player),
+                                            egui::Button::new(draw_label),
+                                        )
+                                        .clicked()
+                                    {
+                                        let game_service_clone = Arc::clone(&self.game_service);
+                                        tokio::spawn(async move {
+                                            game_service_clone.offer_draw(player).await;
+                                        });
+                                    }
+
+                                    // Reconnection pauses end by themselves
+                                    let on_break =
+                                        game.paused.is_some_and(|p| p.waiting_for.is_none());
+                                    let (offer, break_label) = if on_break {
+                                        (game.resume_offer, ("▶ Resume", "▶ Agree to resume"))
+                                    } else {
+                                        (game.pause_offer, ("⏸ Pause", "⏸ Agree to pause"))
+                                    };
+                                    let break_label = if offer.is_some_and(|seat| seat != player) {
+                                        break_label.1
+                                    } else {
+                                        break_label.0
+                                    };
+                                    if ui
+                                        .add_enabled(
+                                            offer != Some(player)
+                                                && (on_break || game.paused.is_none()),
+                                            egui::Button::new(break_label),
+                                        )
+                                        .clicked()
+                                    {
+                                        let game_service_clone = Arc::clone(&self.game_service);
+                                        tokio::spawn(async move {
+                                            game_service_clone
+                                                .request_break(player, on_break)
+                                                .await;
+                                        });
+                                    }
+                                });
+                            }
                         }
                     }
                 }
@@ -162,7 +749,196 @@ impl GameApp {
         });
     }
 
-    fn render_board(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, player: Player) {
+    fn render_server_banner(&self, ui: &mut egui::Ui) {
+        let hello = self.game_service.hello();
+
+        if let Some(banner) = skins::for_theme(hello.theme.as_deref()).banner {
+            ui.label(egui::RichText::new(banner).size(18.0).strong());
+        }
+        if let Some(motd) = hello.motd {
+            ui.label(egui::RichText::new(format!("📢 {motd}")).italics());
+        }
+    }
+
+    /// Shows where we stand in the quick-match queue, with a way out. Returns whether we
+    /// are still waiting for an opponent.
+    fn render_queue_status(&self, ui: &mut egui::Ui) -> bool {
+        let Some(status) = self.game_service.queue_status() else {
+            return false;
+        };
+
+        let wait = match status.estimated_wait_secs {
+            Some(0) => "any moment now".to_string(),
+            Some(secs) => format!("about {secs}s"),
+            None => "estimating...".to_string(),
+        };
+
+        ui.horizontal(|ui| {
+            ui.add(egui::Spinner::new());
+            ui.label(format!(
+                "⏳ Looking for an opponent: #{} in the queue, estimated wait {wait}, {} online",
+                status.position, status.players_online
+            ));
+            if ui.button("✖ Cancel").clicked() {
+                let game_service_clone = Arc::clone(&self.game_service);
+                tokio::spawn(async move {
+                    game_service_clone.cancel_quick_match().await;
+                });
+                if let Ok(mut joined) = self.joined.try_lock() {
+                    *joined = false;
+                }
+                if let Ok(mut cached) = self.cached_player.try_lock() {
+                    *cached = None;
+                }
+            }
+        });
+        true
+    }
+
+    /// Shows our place in a winner-stays-on room's line for a seat.
+    fn render_line_place(&self, ui: &mut egui::Ui) {
+        let game = self.game_service.get_game();
+        let Ok(game) = game.try_lock() else {
+            return;
+        };
+        let place = game
+            .challengers
+            .iter()
+            .position(|name| *name == self.input_player_name.trim())
+            .map_or_else(|| "soon".to_string(), |i| format!("#{}", i + 1));
+        ui.label(format!(
+            "🎟 Waiting in line to play ({place} of {}); the loser of each round makes way",
+            game.challengers.len()
+        ));
+    }
+
+    /// Shows what the connection watchdog last had to recover from.
+    fn render_diagnostic(&self, ui: &mut egui::Ui) {
+        let Some(diagnostic) = self.game_service.diagnostic() else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.colored_label(
+                egui::Color32::from_rgb(240, 148, 0),
+                format!("🐕 {diagnostic}"),
+            );
+            if ui.small_button("Dismiss").clicked() {
+                let game_service_clone = Arc::clone(&self.game_service);
+                tokio::spawn(async move {
+                    game_service_clone.dismiss_diagnostic().await;
+                });
+            }
+        });
+    }
+
+    fn render_waiting_games(&mut self, ui: &mut egui::Ui, ctx_arc: &Arc<egui::Context>) {
+        if ui
+            .add_enabled(
+                !self.input_player_name.trim().is_empty(),
+                egui::Button::new("📬 Games waiting on me"),
+            )
+            .clicked()
+        {
+            let ctx_clone = Arc::clone(ctx_arc);
+            let game_service_clone = Arc::clone(&self.game_service);
+            let player_name = self.input_player_name.trim().to_string();
+
+            tokio::spawn(async move {
+                if let Err(e) = game_service_clone.login(player_name, ctx_clone).await {
+                    error!("❌ Failed to fetch waiting games: {:?}", e);
+                }
+            });
+        }
+
+        for waiting in self.game_service.waiting_games() {
+            ui.horizontal(|ui| {
+                let opponent = waiting
+                    .opponent
+                    .as_deref()
+                    .unwrap_or("waiting for opponent");
+                let marker = if waiting.your_turn { "🔔" } else { "⏳" };
+                ui.label(format!("{marker} {} vs {opponent}", waiting.game_id));
+
+                if ui.button("Open").clicked() {
+                    let ctx_clone = Arc::clone(ctx_arc);
+                    let game_service_clone = Arc::clone(&self.game_service);
+                    let player_name = self.input_player_name.trim().to_string();
+                    let joined_state = Arc::clone(&self.joined);
+                    self.input_game_id = waiting.game_id.clone();
+
+                    tokio::spawn(async move {
+                        game_service_clone
+                            .join_game(
+                                waiting.game_id,
+                                player_name,
+                                JoinOptions::default(),
+                                ctx_clone,
+                            )
+                            .await;
+                        *joined_state.lock().await = true;
+                    });
+                }
+            });
+        }
+
+        ui.add_space(10.0);
+    }
+
+    fn render_join_rejection(
+        &mut self,
+        ui: &mut egui::Ui,
+        ctx_arc: &Arc<egui::Context>,
+        rejection: &str,
+    ) {
+        ui.colored_label(
+            egui::Color32::from_rgb(240, 148, 0),
+            format!("⚠️ {rejection}"),
+        );
+        ui.add_space(5.0);
+
+        ui.horizontal(|ui| {
+            if ui.button("👀 Spectate instead").clicked() {
+                let ctx_clone = Arc::clone(ctx_arc);
+                let game_service_clone = Arc::clone(&self.game_service);
+                let game_id = self.input_game_id.clone();
+                let joined_state = Arc::clone(&self.joined);
+
+                tokio::spawn(async move {
+                    match game_service_clone.spectate(game_id, None, ctx_clone).await {
+                        Ok(()) => *joined_state.lock().await = true,
+                        Err(e) => error!("❌ Failed to spectate: {:?}", e),
+                    }
+                });
+            }
+
+            if ui.button("Try another ID").clicked() {
+                self.input_game_id.clear();
+                let game_service_clone = Arc::clone(&self.game_service);
+                tokio::spawn(async move {
+                    game_service_clone.clear_join_rejection().await;
+                });
+            }
+
+            if ui.button("⚡ Quick match").clicked() {
+                let ctx_clone = Arc::clone(ctx_arc);
+                let game_service_clone = Arc::clone(&self.game_service);
+                let player_name = self.input_player_name.clone();
+                let joined_state = Arc::clone(&self.joined);
+
+                tokio::spawn(async move {
+                    match game_service_clone.quick_match(player_name, ctx_clone).await {
+                        Ok(()) => *joined_state.lock().await = true,
+                        Err(e) => error!("❌ Failed to start quick match: {:?}", e),
+                    }
+                });
+            }
+        });
+
+        ui.add_space(10.0);
+    }
+
+    fn render_board(&mut self, ui: &mut egui::Ui, ctx: &egui::Context, player: Option<Player>) {
         let game_arc = Arc::clone(&self.game_service.get_game());
 
         let game = match game_arc.try_lock() {
@@ -173,50 +949,588 @@ impl GameApp {
             }
         };
 
-        let button_size = 100.0;
+        // Bigger boards shrink their cells to fit the same area as the classic 3x3
+        let rows = game.board.len();
+        let columns = game.board.first().map_or(0, Vec::len);
+        let button_size = 300.0 / rows.max(columns).max(1) as f32;
+        let mark_size = button_size / 2.0;
+        let skin = skins::for_theme(self.game_service.hello().theme.as_deref());
+        let my_turn = !game.game_over && player.is_some() && player == Some(game.current_turn);
+        // Connect Four is played by picking a column; the disc finds its own row
+        let drops = game.config.kind == GameKind::ConnectFour;
 
         ui.vertical_centered(|ui| {
-            for row in 0..3 {
+            if drops {
+                ui.horizontal(|ui| {
+                    ui.add_space(40.0);
+                    for col in 0..columns {
+                        let open = game.board[0][col].is_none();
+                        let drop_button = ui.add_enabled(
+                            my_turn && open,
+                            egui::Button::new(egui::RichText::new("⬇").size(mark_size / 2.0))
+                                .min_size(egui::vec2(button_size, button_size / 2.0)),
+                        );
+                        if let (true, Some(player)) = (drop_button.clicked(), player) {
+                            self.send_move(ctx, player, 0, col);
+                        }
+                    }
+                });
+            }
+
+            for row in 0..rows {
                 ui.horizontal(|ui| {
                     ui.add_space(40.0);
-                    for col in 0..3 {
+                    for col in 0..columns {
                         let cell = game.board[row][col];
 
-                        let can_move =
-                            !game.game_over && player == game.current_turn && cell.is_none();
+                        let can_move = my_turn && !drops && cell.is_none();
 
                         let button = ui.add_enabled(
                             can_move,
                             egui::Button::new(match cell {
-                                Some(Player::X) => egui::RichText::new("X")
-                                    .size(50.0)
-                                    .color(egui::Color32::from_rgb(255, 99, 71)),
-                                Some(Player::O) => egui::RichText::new("O")
-                                    .size(50.0)
-                                    .color(egui::Color32::from_rgb(34, 139, 34)),
+                                Some(Player::X) => egui::RichText::new(skin.x_mark)
+                                    .size(mark_size)
+                                    .color(skin.x_color),
+                                Some(Player::O) => egui::RichText::new(skin.o_mark)
+                                    .size(mark_size)
+                                    .color(skin.o_color),
                                 None => egui::RichText::new(" ")
-                                    .size(50.0)
+                                    .size(mark_size)
                                     .color(egui::Color32::from_rgb(180, 180, 180)),
                             })
                             .min_size(egui::vec2(button_size, button_size)),
                         );
 
                         if button.clicked() && can_move {
-                            let game_service_clone = Arc::clone(&self.game_service);
-                            let ctx_clone = ctx.clone();
+                            let Some(player) = player else { continue };
+                            self.send_move(ctx, player, row, col);
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    fn send_move(&self, ctx: &egui::Context, player: Player, row: usize, col: usize) {
+        let game_service_clone = Arc::clone(&self.game_service);
+        let ctx_clone = ctx.clone();
+
+        tokio::spawn(async move {
+            let game_id = game_service_clone.current_game_id().await;
+            game_service_clone
+                .make_move(game_id, player, row, col, ctx_clone.into())
+                .await;
+        });
+    }
+
+    fn render_unconfirmed_move(&self, ui: &mut egui::Ui, ctx: &Arc<egui::Context>) {
+        ui.add_space(5.0);
+        ui.colored_label(
+            egui::Color32::from_rgb(240, 148, 0),
+            "⚠️ Move not confirmed — retry?",
+        );
+
+        ui.horizontal(|ui| {
+            if ui.button("🔁 Retry").clicked() {
+                let game_service_clone = Arc::clone(&self.game_service);
+                let ctx_clone = Arc::clone(ctx);
+                tokio::spawn(async move {
+                    game_service_clone.retry_move(ctx_clone).await;
+                });
+            }
+
+            if ui.button("🔄 Resync").clicked() {
+                self.resync();
+            }
+
+            if ui.button("Dismiss").clicked() {
+                let game_service_clone = Arc::clone(&self.game_service);
+                tokio::spawn(async move {
+                    game_service_clone.dismiss_pending_move().await;
+                });
+            }
+        });
+    }
+
+    fn resync(&self) {
+        let game_service_clone = Arc::clone(&self.game_service);
+        tokio::spawn(async move {
+            game_service_clone.dismiss_pending_move().await;
+            game_service_clone.request_state().await;
+        });
+    }
+
+    fn render_debug_menu(&self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🛠 Debug").show(ui, |ui| {
+            if let Ok(game) = self.game_service.get_game().try_lock() {
+                ui.label(format!("State version: {}", game.version));
+            }
+
+            if let Some(reason) = self.game_service.last_update_reason() {
+                ui.label(format!("Last update: {reason}"));
+            }
+
+            if ui.button("🔄 Resync").clicked() {
+                self.resync();
+            }
+
+            ui.label(format!("Client build: {}", crate::version::short()));
+            if let Some(server) = self.game_service.hello().server {
+                ui.label(format!(
+                    "Server build: {} ({})",
+                    server.version, server.git_hash
+                ));
+            }
+
+            let mut net_sim = self.game_service.net_sim();
+            ui.checkbox(&mut net_sim.enabled, "🐢 Simulate slow network");
+            ui.add_enabled_ui(net_sim.enabled, |ui| {
+                ui.add(egui::Slider::new(&mut net_sim.latency_ms, 0..=3000).text("latency ms"));
+                ui.add(egui::Slider::new(&mut net_sim.jitter_ms, 0..=1000).text("jitter ms"));
+                ui.add(egui::Slider::new(&mut net_sim.drop_rate, 0.0..=0.5).text("drop rate"));
+            });
+            if net_sim != self.game_service.net_sim() {
+                self.game_service.set_net_sim(net_sim);
+            }
+
+            let mut binary_frames = self.game_service.binary_frames();
+            if ui
+                .checkbox(&mut binary_frames, "📦 Binary frames (MessagePack)")
+                .on_hover_text("Applies on the next connection")
+                .changed()
+            {
+                self.game_service.set_binary_frames(binary_frames);
+            }
+        });
+    }
+
+    fn render_invite_code(&self, ui: &mut egui::Ui) {
+        let Some(code) = self
+            .game_service
+            .get_game()
+            .try_lock()
+            .ok()
+            .map(|game| game.invite_code.clone())
+            .filter(|code| !code.is_empty())
+        else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            ui.label(format!("🔑 Invite code: {code}"));
+            if ui.small_button("📋").on_hover_text("Copy").clicked() {
+                ui.ctx().copy_text(code);
+            }
+        });
+    }
+
+    /// Claims the typed name on the server so its rating can't be played under by others,
+    /// either for this client alone or behind a password that works from anywhere.
+    fn render_sign_in(&mut self, ui: &mut egui::Ui) {
+        let name = self.input_player_name.trim().to_string();
+        ui.horizontal(|ui| {
+            ui.label("Password:");
+            ui.add(egui::TextEdit::singleline(&mut self.input_password).password(true));
+        });
+        let password = self.input_password.clone();
+        let has_password = !name.is_empty() && !password.is_empty();
+
+        let (claim, log_in, register) = ui
+            .horizontal(|ui| {
+                let claim = ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("🔐 Claim this name"))
+                    .on_hover_text(
+                        "Only this client will be able to play under the name afterwards",
+                    )
+                    .clicked();
+                let log_in = ui
+                    .add_enabled(has_password, egui::Button::new("🔑 Log in"))
+                    .clicked();
+                let register = ui
+                    .add_enabled(has_password, egui::Button::new("🪪 Register"))
+                    .on_hover_text("Protect the name with this password")
+                    .clicked();
+                (claim, log_in, register)
+            })
+            .inner;
+        if let Some(error) = self.sign_in_error.try_lock().ok().and_then(|e| e.clone()) {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if !(claim || log_in || register) {
+            return;
+        }
+
+        let game_service_clone = Arc::clone(&self.game_service);
+        let sign_in_error = Arc::clone(&self.sign_in_error);
+        tokio::spawn(async move {
+            let result = if register {
+                game_service_clone.register(name, password).await
+            } else if log_in {
+                game_service_clone.log_in(name, password).await
+            } else {
+                game_service_clone.sign_in(name).await
+            };
+            *sign_in_error.lock().await = result.err();
+        });
+    }
+
+    fn render_spectate_link(&self, ui: &mut egui::Ui) {
+        let private = self
+            .game_service
+            .get_game()
+            .try_lock()
+            .is_ok_and(|game| game.config.private);
+        if !private {
+            return;
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("🔗 Share spectate link").clicked() {
+                let game_service_clone = Arc::clone(&self.game_service);
+                tokio::spawn(async move {
+                    game_service_clone.request_spectate_link().await;
+                });
+            }
+
+            if let Some(link) = self.game_service.spectate_link() {
+                if ui.button("📋 Copy").clicked() {
+                    ui.ctx().copy_text(link.clone());
+                }
+                ui.add(egui::Label::new(egui::RichText::new(link).small()).truncate());
+            }
+        });
+    }
+
+    fn render_notifications(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("🔔 Turn notifications").show(ui, |ui| {
+            ui.label("Get pinged when a game is waiting on your move while you're away.");
+            ui.horizontal(|ui| {
+                ui.label("Email:");
+                ui.text_edit_singleline(&mut self.input_notify_email);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Webhook:");
+                ui.text_edit_singleline(&mut self.input_notify_webhook);
+            });
+
+            if ui.button("Save").clicked() {
+                let game_service_clone = Arc::clone(&self.game_service);
+                let email = self.input_notify_email.clone();
+                let webhook_url = self.input_notify_webhook.clone();
+                tokio::spawn(async move {
+                    game_service_clone
+                        .register_notifications(email, webhook_url)
+                        .await;
+                });
+            }
+        });
+    }
+
+    /// What the others in the game reacted with in the last few seconds.
+    fn render_reactions(&self, ui: &mut egui::Ui) {
+        let reactions: Vec<_> = self
+            .game_service
+            .recent_reactions()
+            .into_iter()
+            .filter(|reaction| !reaction.spectator || self.show_spectator_chat)
+            .collect();
+        if reactions.is_empty() {
+            return;
+        }
+
+        ui.horizontal_wrapped(|ui| {
+            for reaction in reactions {
+                ui.label(egui::RichText::new(&reaction.emoji).size(24.0))
+                    .on_hover_text(&reaction.sender);
+                ui.label(egui::RichText::new(&reaction.sender).small());
+            }
+        });
+    }
+
+    fn render_chat(&mut self, ui: &mut egui::Ui) {
+        egui::CollapsingHeader::new("💬 Chat")
+            .default_open(true)
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(120.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        let now = std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_secs())
+                            .unwrap_or(0);
+
+                        let hosting = self.game_service.get_game().try_lock().is_ok_and(|game| {
+                            game.host.as_deref() == Some(self.input_player_name.trim())
+                        });
+                        for message in self.game_service.chat_messages() {
+                            let minutes_ago = now.saturating_sub(message.timestamp) / 60;
+                            let when = if minutes_ago == 0 {
+                                "now".to_string()
+                            } else {
+                                format!("{minutes_ago}m")
+                            };
+
+                            if message.system {
+                                ui.label(
+                                    egui::RichText::new(format!("[{when}] {}", message.text))
+                                        .italics()
+                                        .color(egui::Color32::GRAY),
+                                );
+                                continue;
+                            }
+
+                            if message.spectator && !self.show_spectator_chat {
+                                continue;
+                            }
+                            let channel = if message.team.is_some() {
+                                " [team]"
+                            } else if message.spectator {
+                                " 👀"
+                            } else {
+                                ""
+                            };
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "[{when}]{channel} {}:",
+                                        message.sender
+                                    ))
+                                    .strong(),
+                                );
+                                ui.label(&message.text);
+                                if message.sender != self.input_player_name.trim()
+                                    && ui
+                                        .small_button("🚩")
+                                        .on_hover_text("Report this player to the moderators")
+                                        .clicked()
+                                {
+                                    let name = message.sender.clone();
+                                    let reason = format!("Chat: {}", message.text);
+                                    let game_service_clone = Arc::clone(&self.game_service);
+                                    tokio::spawn(async move {
+                                        game_service_clone.report_player(name, reason).await;
+                                    });
+                                }
+                                if hosting
+                                    && message.spectator
+                                    && ui
+                                        .small_button("🚫")
+                                        .on_hover_text("Ban this spectator from the game")
+                                        .clicked()
+                                {
+                                    let name = message.sender.clone();
+                                    let game_service_clone = Arc::clone(&self.game_service);
+                                    tokio::spawn(async move {
+                                        game_service_clone.ban_spectator(name).await;
+                                    });
+                                }
+                            });
+                        }
+                    });
+
+                ui.horizontal(|ui| {
+                    let input = ui.text_edit_singleline(&mut self.input_chat);
+                    let submitted =
+                        input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
 
-                            let game_id_clone = Arc::clone(&self.game_id);
+                    let team_game = self
+                        .game_service
+                        .get_game()
+                        .try_lock()
+                        .is_ok_and(|game| game.team_mode);
+                    if team_game {
+                        ui.checkbox(&mut self.input_team_chat, "Team only");
+                    }
+                    ui.checkbox(&mut self.show_spectator_chat, "👀")
+                        .on_hover_text("Show what spectators say");
+
+                    if (ui.button("Send").clicked() || submitted)
+                        && !self.input_chat.trim().is_empty()
+                    {
+                        let text = std::mem::take(&mut self.input_chat);
+                        let team = team_game && self.input_team_chat;
+                        let game_service_clone = Arc::clone(&self.game_service);
+                        tokio::spawn(async move {
+                            game_service_clone.send_chat(text, team).await;
+                        });
+                    }
+                });
+
+                ui.horizontal(|ui| {
+                    for emoji in REACTIONS {
+                        if ui.button(emoji).clicked() {
+                            let game_service_clone = Arc::clone(&self.game_service);
                             tokio::spawn(async move {
-                                let game_id = game_id_clone.lock().await.clone();
-                                game_service_clone
-                                    .make_move(game_id, player, row, col, ctx_clone.into())
-                                    .await;
+                                game_service_clone.send_reaction(emoji).await;
                             });
                         }
                     }
                 });
+            });
+    }
+
+    fn render_leaderboard(&self, ui: &mut egui::Ui) {
+        let response = egui::CollapsingHeader::new("🏆 Leaderboard").show(ui, |ui| {
+            let entries = self.game_service.leaderboard();
+
+            if entries.is_empty() {
+                ui.label("No rated games yet.");
+            }
+
+            for (rank, entry) in entries.iter().enumerate() {
+                ui.label(format!(
+                    "{}. {} — {} ({}W {}L {}D)",
+                    rank + 1,
+                    entry.name,
+                    entry.rating,
+                    entry.wins,
+                    entry.losses,
+                    entry.draws
+                ));
             }
         });
+
+        if response.header_response.clicked() {
+            let game_service_clone = Arc::clone(&self.game_service);
+            tokio::spawn(async move {
+                game_service_clone.request_leaderboard().await;
+            });
+        }
+    }
+
+    /// Standings for a Swiss tournament or brackets for a double-elimination one, shown
+    /// for tournament games and fetched when opened.
+    fn render_tournament(&self, ui: &mut egui::Ui) {
+        let in_tournament = self
+            .game_service
+            .get_game()
+            .try_lock()
+            .is_ok_and(|game| game.tournament.is_some());
+        if !in_tournament {
+            return;
+        }
+
+        let response = egui::CollapsingHeader::new("🏅 Tournament").show(ui, |ui| {
+            let Some(tournament) = self.game_service.tournament() else {
+                ui.label("Loading…");
+                return;
+            };
+
+            let progress = match (tournament.finished, tournament.rounds) {
+                (true, _) => "finished".to_string(),
+                (false, Some(rounds)) => format!("round {} of {rounds}", tournament.round),
+                (false, None) => format!("round {}", tournament.round),
+            };
+            ui.strong(format!("{} — {progress}", tournament.name));
+
+            match tournament.format {
+                TournamentFormat::Swiss => {
+                    egui::Grid::new("tournament_standings")
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.label("#");
+                            ui.label("Player");
+                            ui.label("Pts");
+                            for tie_break in &tournament.tie_breaks {
+                                ui.label(tie_break_label(tie_break));
+                            }
+                            ui.label("W-D-L");
+                            ui.end_row();
+
+                            for (rank, standing) in tournament.standings.iter().enumerate() {
+                                ui.label((rank + 1).to_string());
+                                ui.label(&standing.name);
+                                ui.label(standing.points.to_string());
+                                for tie_break in &tournament.tie_breaks {
+                                    ui.label(standing.tie_break(tie_break));
+                                }
+                                ui.label(format!(
+                                    "{}-{}-{}",
+                                    standing.wins + standing.byes,
+                                    standing.draws,
+                                    standing.losses
+                                ));
+                                ui.end_row();
+                            }
+                        });
+                }
+                TournamentFormat::DoubleElimination => {
+                    let bracket = |s: &TournamentStanding| match (s.eliminated, s.losses) {
+                        (true, _) => 2,
+                        (false, 0) => 0,
+                        (false, _) => 1,
+                    };
+                    let titles = ["Winners' bracket", "Losers' bracket", "Eliminated"];
+                    for (index, title) in titles.into_iter().enumerate() {
+                        let names: Vec<&str> = tournament
+                            .standings
+                            .iter()
+                            .filter(|s| bracket(s) == index)
+                            .map(|s| s.name.as_str())
+                            .collect();
+                        if !names.is_empty() {
+                            ui.label(format!("{title}: {}", names.join(", ")));
+                        }
+                    }
+                }
+            }
+
+            ui.separator();
+            for pairing in tournament
+                .pairings
+                .iter()
+                .filter(|p| p.round == tournament.round)
+            {
+                let Some(o) = &pairing.o else {
+                    ui.label(format!("{} has a bye", pairing.x));
+                    continue;
+                };
+                let result = match (&pairing.winner, pairing.finished) {
+                    (_, false) => "playing".to_string(),
+                    (Some(winner), true) if pairing.by_seed => {
+                        format!("draw, {winner} through on seed")
+                    }
+                    (Some(winner), true) => format!("{winner} won"),
+                    (None, true) => "draw".to_string(),
+                };
+                ui.label(format!("{} vs {o} — {result}", pairing.x));
+            }
+        });
+
+        if response.header_response.clicked() {
+            let game_service_clone = Arc::clone(&self.game_service);
+            tokio::spawn(async move {
+                if let Err(e) = game_service_clone.refresh_tournament().await {
+                    error!("❌ Failed to fetch the tournament: {}", e);
+                }
+            });
+        }
+    }
+
+    fn render_online_players(&self, ui: &mut egui::Ui) {
+        let response = egui::CollapsingHeader::new("🟢 Online").show(ui, |ui| {
+            let players = self.game_service.online_players();
+
+            if players.is_empty() {
+                ui.label("Nobody is online.");
+            }
+
+            for player in players {
+                if player.playing {
+                    ui.label(format!("{} (playing)", player.name));
+                } else {
+                    ui.label(player.name);
+                }
+            }
+        });
+
+        if response.header_response.clicked() {
+            let game_service_clone = Arc::clone(&self.game_service);
+            tokio::spawn(async move {
+                game_service_clone.request_online_players().await;
+            });
+        }
     }
 
     fn display_game_status(&self, ui: &mut egui::Ui) {
@@ -237,23 +1551,113 @@ impl GameApp {
 
             let score_text = format!("{name_x} {} : {} {name_o}", score_x, score_o);
 
+            if game.config.kind == GameKind::ConnectFour {
+                ui.label("Connect Four: drop a disc into a column, four in a row wins");
+            }
+            ui.label(egui::RichText::new(game.config.summary()).weak());
+            if let Some(profile) = self.game_service.profile() {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "🪪 {} · {} · {}W {}L {}D",
+                        profile.display_name,
+                        profile.rating,
+                        profile.wins,
+                        profile.losses,
+                        profile.draws
+                    ))
+                    .weak(),
+                );
+            }
+            if let Some(record) = self.game_service.head_to_head() {
+                let name = self.input_player_name.trim();
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{name} vs {}: {}–{}, {} draws",
+                        record.opponent, record.wins, record.losses, record.draws
+                    ))
+                    .weak(),
+                );
+            }
+
+            let rating_x = game.ratings.get(&Player::X);
+            let rating_o = game.ratings.get(&Player::O);
+
             ui.label(
                 egui::RichText::new(score_text)
                     .size(24.0)
                     .color(egui::Color32::from_rgb(0, 191, 255)),
             );
 
+            if let (Some(rating_x), Some(rating_o)) = (rating_x, rating_o) {
+                ui.label(
+                    egui::RichText::new(format!("Rating {rating_x} : {rating_o}"))
+                        .size(16.0)
+                        .color(egui::Color32::GRAY),
+                );
+            }
+
+            if game.spectators > 0 {
+                ui.label(
+                    egui::RichText::new(format!("👀 {} watching", game.spectators))
+                        .color(egui::Color32::GRAY),
+                );
+            }
+
+            let me = self.input_player_name.trim();
+            if game.host.as_deref() == Some(me) {
+                ui.horizontal_wrapped(|ui| {
+                    for name in game.seated_names().into_iter().filter(|name| name != me) {
+                        if ui
+                            .small_button(format!("🚫 Kick {name}"))
+                            .on_hover_text("Free their seat and keep them out of this game")
+                            .clicked()
+                        {
+                            let game_service_clone = Arc::clone(&self.game_service);
+                            tokio::spawn(async move {
+                                game_service_clone.kick_player(name).await;
+                            });
+                        }
+                    }
+                });
+            }
+
+            if game.team_mode {
+                for player in [Player::X, Player::O] {
+                    if let Some(members) = game.team_members.get(&player) {
+                        let team = game
+                            .player_names
+                            .get(&player)
+                            .cloned()
+                            .unwrap_or(format!("{player:?}"));
+                        ui.label(format!("👥 {team}: {}", members.join(", ")));
+                    }
+                }
+            }
+
+            for player in &game.disconnected {
+                let name = game
+                    .player_names
+                    .get(player)
+                    .cloned()
+                    .unwrap_or(format!("{player:?}"));
+                ui.colored_label(
+                    egui::Color32::from_rgb(240, 148, 0),
+                    format!("🔌 {name} disconnected"),
+                );
+            }
+
             ui.add_space(10.0);
 
             if game.game_over {
                 let status_message = if game.draw {
                     "It's a draw!".to_string()
                 } else {
+                    let winner = game.winner.unwrap_or(game.current_turn);
                     let winner_name = game
                         .player_names
-                        .get(&game.current_turn)
+                        .get(&winner)
                         .cloned()
-                        .unwrap_or_else(|| format!("{:?}", game.current_turn));
+                        .unwrap_or_else(|| format!("{:?}", winner));
 
                     format!("🏆 {} wins!", winner_name)
                 };
@@ -263,6 +1667,30 @@ impl GameApp {
                         .size(30.0)
                         .color(egui::Color32::from_rgb(255, 0, 0)),
                 );
+
+                if let Some(length) = game.round_length() {
+                    ui.label(format!("⏱ Round lasted {}", format_clock(length)));
+                }
+
+                if let Some(digest) = &game.result_digest {
+                    let short = digest.get(..16).unwrap_or(digest);
+                    ui.label(
+                        egui::RichText::new(format!("🔏 Result digest {short}…"))
+                            .monospace()
+                            .color(egui::Color32::GRAY),
+                    )
+                    .on_hover_text(digest);
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("💾 Export game").clicked() {
+                        self.export_game();
+                    }
+                    if let Some(status) = self.export_status.try_lock().ok().and_then(|s| s.clone())
+                    {
+                        ui.label(status);
+                    }
+                });
             } else {
                 let current_turn_name = game
                     .player_names
@@ -270,7 +1698,37 @@ impl GameApp {
                     .cloned()
                     .unwrap_or_else(|| format!("{:?}", game.current_turn));
 
-                let turn_message = format!("🕐 {}'s turn", current_turn_name);
+                let current_turn_name = match game.submitter(game.current_turn) {
+                    Some(submitter) => format!("{current_turn_name} ({submitter})"),
+                    None => current_turn_name,
+                };
+
+                let turn_message = match (game.paused, self.game_service.turn_remaining()) {
+                    (
+                        Some(Pause {
+                            waiting_for: Some(missing),
+                        }),
+                        _,
+                    ) => {
+                        let missing = game
+                            .player_names
+                            .get(&missing)
+                            .cloned()
+                            .unwrap_or_else(|| format!("{:?}", missing));
+                        let remaining = self.game_service.pause_remaining().unwrap_or_default();
+                        format!(
+                            "⏸ Waiting for {missing} to reconnect ({}s)",
+                            remaining.as_secs()
+                        )
+                    }
+                    (Some(Pause { waiting_for: None }), _) => {
+                        "⏸ On a break until both players resume".to_string()
+                    }
+                    (None, Some(remaining)) => {
+                        format!("🕐 {}'s turn ({}s)", current_turn_name, remaining.as_secs())
+                    }
+                    (None, None) => format!("🕐 {}'s turn", current_turn_name),
+                };
 
                 ui.label(
                     egui::RichText::new(turn_message)
@@ -278,8 +1736,37 @@ impl GameApp {
                         .color(egui::Color32::from_rgb(0, 255, 0)),
                 );
             }
+
+            if game.config.clock_seconds.is_some() {
+                let live = !game.game_over && game.paused.is_none() && game.players.len() == 2;
+                let readings: Vec<String> = [Player::X, Player::O]
+                    .into_iter()
+                    .filter_map(|player| {
+                        let running = live && player == game.current_turn;
+                        let left = self.game_service.clock_remaining(player, running)?;
+                        let name = game
+                            .player_names
+                            .get(&player)
+                            .cloned()
+                            .unwrap_or_else(|| format!("{:?}", player));
+                        let marker = if running { "▶ " } else { "" };
+                        Some(format!("{marker}{name} {}", format_clock(left)))
+                    })
+                    .collect();
+                ui.label(egui::RichText::new(format!("⏱ {}", readings.join("  ·  "))).size(22.0));
+            }
         } else {
             ui.colored_label(egui::Color32::RED, "⚠️ Unable to fetch game state.");
         }
     }
 }
+
+/// Column heading for one of the server's tie-break names.
+fn tie_break_label(name: &str) -> &str {
+    match name {
+        "buchholz" => "Buch",
+        "sonneborn_berger" => "SB",
+        "wins" => "Wins",
+        other => other,
+    }
+}
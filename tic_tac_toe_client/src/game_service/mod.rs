@@ -1,6 +1,8 @@
 // src/game_service/mod.rs
 pub mod model;
+pub mod net_sim;
 pub mod service;
+pub mod watchdog;
 
 pub use model::*;
 pub use service::*;
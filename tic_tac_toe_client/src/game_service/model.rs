@@ -1,5 +1,32 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// Board sides the server accepts when creating a game.
+pub const DEFAULT_BOARD_SIZE: usize = 3;
+pub const MAX_BOARD_SIZE: usize = 10;
+
+/// Longest player name the server accepts, in characters.
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// Which game a table plays; picked when the game is created.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GameKind {
+    #[default]
+    TicTacToe,
+    /// 7x6 board where discs drop to the bottom of the chosen column.
+    ConnectFour,
+}
+
+impl GameKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            GameKind::TicTacToe => "Tic-tac-toe",
+            GameKind::ConnectFour => "Connect Four",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum Player {
@@ -7,27 +34,701 @@ pub enum Player {
     O,
 }
 
+/// The rules a game was created with, as the server reports them.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GameConfig {
+    pub kind: GameKind,
+    pub rows: usize,
+    pub columns: usize,
+    /// Marks in a row needed to win.
+    pub win_length: usize,
+    pub first_player: Player,
+    /// Move clock, `None` when turns are untimed.
+    pub turn_seconds: Option<u64>,
+    /// Each player's chess clock for the round, `None` without one.
+    pub clock_seconds: Option<u64>,
+    pub private: bool,
+    /// Spectator chat never reaches the players.
+    pub hide_spectator_chat: bool,
+    /// Extra players wait in line and the loser of each round makes way for the next.
+    pub winner_stays_on: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            kind: GameKind::default(),
+            rows: DEFAULT_BOARD_SIZE,
+            columns: DEFAULT_BOARD_SIZE,
+            win_length: DEFAULT_BOARD_SIZE,
+            first_player: Player::X,
+            turn_seconds: None,
+            clock_seconds: None,
+            private: false,
+            hide_spectator_chat: false,
+            winner_stays_on: false,
+        }
+    }
+}
+
+impl GameConfig {
+    /// One line spelling out the rules, e.g. `Tic-tac-toe · 5x5 · 4 in a row · O first`.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![
+            self.kind.label().to_string(),
+            format!("{}x{}", self.columns, self.rows),
+            format!("{} in a row", self.win_length),
+            format!("{:?} first", self.first_player),
+        ];
+        if let Some(secs) = self.turn_seconds {
+            parts.push(format!("{secs}s per move"));
+        }
+        if let Some(secs) = self.clock_seconds {
+            parts.push(format!("{} each", format_clock(Duration::from_secs(secs))));
+        }
+        if self.private {
+            parts.push("private".to_string());
+        }
+        if self.hide_spectator_chat {
+            parts.push("quiet spectators".to_string());
+        }
+        if self.winner_stays_on {
+            parts.push("winner stays on".to_string());
+        }
+        parts.join(" · ")
+    }
+}
+
+/// Chess clock reading as `m:ss`, rounded up so it only shows `0:00` once time is out.
+pub fn format_clock(time: Duration) -> String {
+    let secs = time.as_millis().div_ceil(1000);
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Every field falls back to its default, so an older or newer server's state still loads.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
 pub struct Game {
-    pub board: [[Option<Player>; 3]; 3],
+    pub config: GameConfig,
+    /// Indexed `board[row][col]`; square for tic-tac-toe, 6 rows of 7 for Connect Four.
+    pub board: Vec<Vec<Option<Player>>>,
     pub current_turn: Player,
     pub game_over: bool,
     pub draw: bool,
+    pub winner: Option<Player>,
     pub players: Vec<Player>,
     pub player_names: HashMap<Player, String>,
+    /// Seats whose player has lost their connection.
+    pub disconnected: Vec<Player>,
     pub scores: HashMap<Player, u32>,
+    pub ratings: HashMap<Player, u32>,
+    pub version: u64,
+    pub invite_code: String,
+    /// Seat that has offered a draw and is waiting for an answer.
+    pub draw_offer: Option<Player>,
+    /// Each seat is a team; `player_names` then holds the team names.
+    pub team_mode: bool,
+    pub team_members: HashMap<Player, Vec<String>>,
+    /// Set on a finished rated round; checkable later against `GET /replays/{id}/verify`.
+    pub result_digest: Option<String>,
+    /// People watching without a seat.
+    pub spectators: usize,
+    /// Name of whoever created the game, who may kick players and ban spectators.
+    pub host: Option<String>,
+    /// Names the host has removed from the game.
+    pub banned: Vec<String>,
+    /// Set while nobody may move: a player dropped, or both agreed to a break.
+    pub paused: Option<Pause>,
+    /// Seat asking for a break, waiting for the other to agree.
+    pub pause_offer: Option<Player>,
+    /// Seat asking to end an agreed break.
+    pub resume_offer: Option<Player>,
+    /// Names waiting for a seat in a winner-stays-on room, next up first.
+    pub challengers: Vec<String>,
+    /// The tournament this game is a pairing of, if any.
+    pub tournament: Option<String>,
+    /// Joins, moves, results and resets with when they happened, oldest first.
+    pub timeline: Vec<TimelineEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimelineEntry {
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch.
+    pub at: u64,
+    pub round: u32,
+    #[serde(flatten)]
+    pub event: TimelineEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TimelineEvent {
+    Join {
+        player: Player,
+        name: String,
+    },
+    Move {
+        player: Player,
+        x: usize,
+        y: usize,
+    },
+    Result {
+        winner: Option<Player>,
+        draw: bool,
+    },
+    Reset,
+    /// Anything a newer server records that this client doesn't know about.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Pause {
+    /// The player who dropped; `None` when both players agreed to a break.
+    pub waiting_for: Option<Player>,
+}
+
+impl Game {
+    /// Everyone playing, team members included.
+    pub fn seated_names(&self) -> Vec<String> {
+        if self.team_mode {
+            return self.team_members.values().flatten().cloned().collect();
+        }
+        self.player_names.values().cloned().collect()
+    }
+
+    /// In a team game, the member who submits `player`'s next move. Mirrors the server's
+    /// rotation: teammates alternate, starting over each round.
+    pub fn submitter(&self, player: Player) -> Option<&str> {
+        let members = self
+            .team_members
+            .get(&player)
+            .filter(|members| self.team_mode && !members.is_empty())?;
+        let played = self
+            .board
+            .iter()
+            .flatten()
+            .filter(|cell| **cell == Some(player))
+            .count();
+        Some(&members[played % members.len()])
+    }
+
+    /// Plays a `MOVE_MADE` onto this copy of the game. False when it skips a version, i.e.
+    /// an update was missed and the whole game needs fetching again.
+    /// How long the latest round took to finish, from the join or reset that started it
+    /// to its result. `None` while it is still being played.
+    pub fn round_length(&self) -> Option<Duration> {
+        let round = self.timeline.last()?.round;
+        let entries: Vec<&TimelineEntry> = self
+            .timeline
+            .iter()
+            .filter(|entry| entry.round == round)
+            .collect();
+        let end = entries
+            .iter()
+            .rev()
+            .find(|entry| matches!(entry.event, TimelineEvent::Result { .. }))?;
+        let first_move = entries
+            .iter()
+            .position(|entry| matches!(entry.event, TimelineEvent::Move { .. }))
+            .unwrap_or(entries.len());
+        let start = entries[..first_move]
+            .iter()
+            .rev()
+            .find(|entry| {
+                matches!(
+                    entry.event,
+                    TimelineEvent::Join { .. } | TimelineEvent::Reset
+                )
+            })
+            .unwrap_or(&entries[0]);
+        Some(Duration::from_millis(end.at.saturating_sub(start.at)))
+    }
+
+    pub fn apply_move(&mut self, delta: &MoveMade) -> bool {
+        // Our own moves are already in from the full state that answered them
+        if delta.version <= self.version {
+            return true;
+        }
+        if delta.version != self.version + 1 {
+            return false;
+        }
+        let Some(cell) = self
+            .board
+            .get_mut(delta.x)
+            .and_then(|row| row.get_mut(delta.y))
+        else {
+            return false;
+        };
+        *cell = Some(delta.player);
+        self.current_turn = delta.next_turn;
+        self.draw_offer = None;
+        self.pause_offer = None;
+        if let Some(result) = delta.result {
+            self.game_over = true;
+            self.winner = result.winner;
+            self.draw = result.draw;
+        }
+        self.version = delta.version;
+        true
+    }
+}
+
+/// A single move, sent instead of the whole game to clients that asked for deltas.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MoveMade {
+    pub player: Player,
+    pub x: usize,
+    pub y: usize,
+    pub next_turn: Player,
+    /// Set on the move that ended the round.
+    pub result: Option<RoundResult>,
+    pub version: u64,
+    pub turn_remaining: Option<u64>,
+    pub clock_remaining: Option<HashMap<Player, u64>>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct RoundResult {
+    pub winner: Option<Player>,
+    pub draw: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub rating: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentFormat {
+    #[default]
+    Swiss,
+    DoubleElimination,
+}
+
+/// One player's line in a tournament's standings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TournamentStanding {
+    pub name: String,
+    pub points: f32,
+    pub buchholz: f32,
+    pub sonneborn_berger: f32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub byes: u32,
+    /// Out of a double-elimination tournament.
+    pub eliminated: bool,
+}
+
+impl TournamentStanding {
+    /// The tie-break the server calls `name`, formatted for a standings column.
+    pub fn tie_break(&self, name: &str) -> String {
+        match name {
+            "buchholz" => format!("{}", self.buchholz),
+            "sonneborn_berger" => format!("{}", self.sonneborn_berger),
+            "wins" => self.wins.to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// One game of a tournament round, or a bye when `o` is empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TournamentPairing {
+    pub round: u32,
+    pub game_id: Option<String>,
+    pub x: String,
+    pub o: Option<String>,
+    pub finished: bool,
+    pub winner: Option<String>,
+    /// A repeat draw in double elimination, sending the better seed through.
+    pub by_seed: bool,
+}
+
+/// A tournament as `GET /tournaments/{id}` returns it, best placed first.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Tournament {
+    pub id: String,
+    pub name: String,
+    pub format: TournamentFormat,
+    /// Rounds of a Swiss tournament; `None` in double elimination.
+    pub rounds: Option<u32>,
+    pub round: u32,
+    pub finished: bool,
+    pub tie_breaks: Vec<String>,
+    pub standings: Vec<TournamentStanding>,
+    pub pairings: Vec<TournamentPairing>,
+}
+
+/// Someone connected under a name, from ONLINE_PLAYERS.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OnlinePlayer {
+    pub name: String,
+    /// In the middle of a game.
+    pub playing: bool,
+}
+
+/// Our standing as the server reports it in JOIN_SUCCESS.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    pub display_name: String,
+    /// Whether the name has an account with a password.
+    pub registered: bool,
+    pub rating: u32,
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+/// Lifetime results against the player across the board, from our side.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct HeadToHead {
+    pub opponent: String,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ChatMessage {
+    pub sender: String,
+    pub text: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Server-generated line (join, resign, ...) rather than something a person typed.
+    pub system: bool,
+    /// Set on team chat, which only the sender's team sees.
+    pub team: Option<Player>,
+    /// From someone watching rather than playing.
+    pub spectator: bool,
+}
+
+/// Emoji the server relays as reactions; anything else is refused.
+pub const REACTIONS: [&str; 8] = ["👍", "👏", "😂", "😮", "😢", "😡", "🔥", "🎉"];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Reaction {
+    pub sender: String,
+    pub emoji: String,
+    /// From someone watching rather than playing.
+    pub spectator: bool,
+}
+
+/// Settings sent with CREATE_GAME, and with JOIN_GAME in case it creates the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct JoinOptions {
+    pub private: bool,
+    /// Kept out of the server's lobby, so only those given the ID or invite code can join.
+    pub unlisted: bool,
+    /// Untimed game that stays open while both players are away.
+    pub correspondence: bool,
+    /// 2v2 game where teammates take turns submitting moves.
+    pub team: bool,
+    pub team_name: String,
+    pub locale: String,
+    pub kind: GameKind,
+    /// Tic-tac-toe only; Connect Four is always 7x6, four in a row.
+    pub board_size: usize,
+    /// At most `board_size`.
+    pub win_length: usize,
+    pub first_player: Player,
+    /// `None` leaves the move clock to the server, `Some(0)` turns it off.
+    pub turn_seconds: Option<u64>,
+    /// Seconds on each player's chess clock; `None` plays without one.
+    pub clock_seconds: Option<u64>,
+    pub hide_spectator_chat: bool,
+    /// Lets more than two join, queueing the rest for the loser's seat.
+    pub winner_stays_on: bool,
+    /// The mark we'd like to play; `None` takes whichever seat is free.
+    pub mark: Option<Player>,
+}
+
+impl Default for JoinOptions {
+    fn default() -> Self {
+        JoinOptions {
+            private: false,
+            unlisted: false,
+            correspondence: false,
+            team: false,
+            team_name: String::new(),
+            locale: crate::i18n::DEFAULT_LOCALE.to_string(),
+            kind: GameKind::default(),
+            board_size: DEFAULT_BOARD_SIZE,
+            win_length: DEFAULT_BOARD_SIZE,
+            first_player: Player::X,
+            turn_seconds: None,
+            clock_seconds: None,
+            hide_spectator_chat: false,
+            winner_stays_on: false,
+            mark: None,
+        }
+    }
+}
+
+/// Everything the server sends, tagged by its `type` field. Fields this client doesn't
+/// know are ignored and unknown message types are skipped, so servers can add to the
+/// protocol without breaking older clients.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ServerMessage {
+    Hello(ServerHello),
+    JoinSuccess {
+        game_id: String,
+        player: Player,
+        /// Missing from servers without accounts.
+        profile: Option<Profile>,
+        /// Only once the opponent's seat is taken.
+        head_to_head: Option<HeadToHead>,
+    },
+    SpectateSuccess {
+        game_id: String,
+    },
+    /// Confirms CREATE_GAME; a JOIN_SUCCESS for the creator follows.
+    GameCreated {
+        game_id: String,
+        invite_code: String,
+        config: GameConfig,
+    },
+    UpdateState {
+        game: Box<Game>,
+        reason: Option<String>,
+        /// Seconds left on the move clock, if one is running.
+        turn_remaining: Option<u64>,
+        /// Seconds until a paused game resumes on its own.
+        pause_remaining: Option<u64>,
+        /// Milliseconds left on each chess clock, for games played with one.
+        clock_remaining: Option<HashMap<Player, u64>>,
+    },
+    MoveMade(MoveMade),
+    PlayerLeft {
+        player: Player,
+        name: String,
+    },
+    WaitingGames {
+        games: Vec<WaitingGame>,
+    },
+    SpectateLink {
+        url: String,
+    },
+    NotificationsRegistered {
+        webhook_url: Option<String>,
+        email: Option<String>,
+    },
+    Chat(ChatMessage),
+    Reaction(Reaction),
+    System {
+        key: String,
+        #[serde(default)]
+        params: serde_json::Value,
+        locale: Option<String>,
+        #[serde(default)]
+        timestamp: u64,
+    },
+    Leaderboard {
+        players: Vec<LeaderboardEntry>,
+    },
+    OnlinePlayers {
+        players: Vec<OnlinePlayer>,
+    },
+    /// `name` came online or went offline.
+    Presence {
+        name: String,
+        online: bool,
+    },
+    MoveFailed {
+        message: String,
+        code: Option<String>,
+        #[serde(default)]
+        params: serde_json::Value,
+        /// Which of our moves was refused.
+        request_id: Option<String>,
+    },
+    QueueStatus(QueueStatus),
+    /// The host kicked or banned us; the server has stopped sending this game.
+    Removed {
+        message: String,
+    },
+    /// Confirms CANCEL_QUICK_MATCH; the waiting game is gone.
+    QueueLeft {
+        game_id: String,
+    },
+    /// The winner-stays-on room was full, so we wait in line instead of sitting down.
+    ChallengerQueued {
+        game_id: String,
+        position: usize,
+    },
+    /// `left` lost and gave their seat to `seated`, who now plays `player`.
+    SeatsRotated {
+        player: Player,
+        left: String,
+        seated: String,
+    },
+    /// Our REPORT_PLAYER about `target` is in the moderators' queue.
+    ReportReceived {
+        report_id: u64,
+        target: String,
+    },
+    /// Confirms LEAVE_GAME; the server has stopped sending that game.
+    LeftGame {
+        game_id: String,
+    },
+    /// `code` and `params` let us word it in our own language; `message` is the server's
+    /// English, for codes we don't know.
+    Error {
+        message: String,
+        code: Option<String>,
+        #[serde(default)]
+        params: serde_json::Value,
+    },
+}
+
+/// Server messages about one particular game. A socket can follow several games at once,
+/// so these are only applied when they are about the game on screen.
+const GAME_EVENTS: &[&str] = &[
+    "UPDATE_STATE",
+    "CHAT",
+    "REACTION",
+    "SYSTEM",
+    "PLAYER_LEFT",
+    "QUEUE_STATUS",
+    "REMOVED",
+    "SEATS_ROTATED",
+];
+
+/// The game a raw server frame reports on, if it is a per-game event.
+pub fn event_game_id(frame: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Routing {
+        #[serde(rename = "type")]
+        kind: String,
+        game_id: Option<String>,
+    }
+
+    let routing: Routing = serde_json::from_str(frame).ok()?;
+    if !GAME_EVENTS.contains(&routing.kind.as_str()) {
+        return None;
+    }
+    routing.game_id
+}
+
+/// Reply to `POST /auth`, `/login` and `/register`: a token proving we own `name`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AuthToken {
+    pub token: String,
+    pub name: String,
+    pub expires_at: u64,
+}
+
+/// Sent by the server as the first frame on every connection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServerHello {
+    pub motd: Option<String>,
+    /// Event skin id, e.g. `halloween`.
+    pub theme: Option<String>,
+    /// Missing from servers that predate version reporting.
+    pub server: Option<ServerBuild>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ServerBuild {
+    pub version: String,
+    pub git_hash: String,
+    pub built_at: u64,
+}
+
+/// Sent every few seconds while we wait for a quick-match opponent.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct QueueStatus {
+    /// 1 is next to be matched.
+    pub position: usize,
+    /// Missing until the server has seen enough matches to guess.
+    pub estimated_wait_secs: Option<u64>,
+    pub players_online: usize,
+}
+
+/// A correspondence game listed in the WAITING_GAMES reply to LOGIN.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WaitingGame {
+    pub game_id: String,
+    pub opponent: Option<String>,
+    pub your_turn: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiDifficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+impl AiDifficulty {
+    pub const ALL: [AiDifficulty; 3] =
+        [AiDifficulty::Easy, AiDifficulty::Medium, AiDifficulty::Hard];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            AiDifficulty::Easy => "Easy",
+            AiDifficulty::Medium => "Medium",
+            AiDifficulty::Hard => "Hard",
+        }
+    }
 }
 
 impl Default for Game {
     fn default() -> Self {
         Game {
-            board: [[None; 3]; 3],
+            config: GameConfig::default(),
+            board: vec![vec![None; DEFAULT_BOARD_SIZE]; DEFAULT_BOARD_SIZE],
             current_turn: Player::X,
             game_over: false,
             draw: false,
+            winner: None,
             players: Vec::new(),
             player_names: HashMap::new(),
+            disconnected: Vec::new(),
             scores: HashMap::from([(Player::X, 0), (Player::O, 0)]),
+            ratings: HashMap::new(),
+            version: 0,
+            invite_code: String::new(),
+            draw_offer: None,
+            team_mode: false,
+            team_members: HashMap::new(),
+            result_digest: None,
+            spectators: 0,
+            host: None,
+            banned: Vec::new(),
+            paused: None,
+            pause_offer: None,
+            resume_offer: None,
+            challengers: Vec::new(),
+            tournament: None,
+            timeline: Vec::new(),
         }
     }
 }
@@ -0,0 +1,43 @@
+use rand::Rng;
+use std::time::Duration;
+
+/// Developer setting that makes a good connection behave like a bad one, so pending-move,
+/// reconnect and timer states can be exercised locally.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NetSim {
+    pub enabled: bool,
+    pub latency_ms: u64,
+    pub jitter_ms: u64,
+    /// Probability in `0.0..=1.0` that a frame is silently lost.
+    pub drop_rate: f64,
+}
+
+impl Default for NetSim {
+    fn default() -> Self {
+        NetSim {
+            enabled: false,
+            latency_ms: 300,
+            jitter_ms: 150,
+            drop_rate: 0.1,
+        }
+    }
+}
+
+impl NetSim {
+    /// Waits out the simulated delay; returns `false` if the frame should be dropped.
+    pub async fn apply(self) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let (delay, dropped) = {
+            let mut rng = rand::thread_rng();
+            let jitter = rng.gen_range(0..=self.jitter_ms * 2) as i64 - self.jitter_ms as i64;
+            let delay = (self.latency_ms as i64 + jitter).max(0) as u64;
+            (delay, rng.gen_bool(self.drop_rate.clamp(0.0, 1.0)))
+        };
+
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+        !dropped
+    }
+}
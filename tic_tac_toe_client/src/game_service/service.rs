@@ -1,19 +1,99 @@
-use crate::game_service::model::{Game, Player};
+use crate::game_service::model::{
+    event_game_id, AiDifficulty, AuthToken, ChatMessage, Game, HeadToHead, JoinOptions,
+    LeaderboardEntry, OnlinePlayer, Player, Profile, QueueStatus, Reaction, ServerHello,
+    ServerMessage, Tournament, WaitingGame,
+};
+use crate::game_service::net_sim::NetSim;
+use crate::game_service::watchdog::{Fault, Watchdog, CHECK_INTERVAL, HEARTBEAT_INTERVAL};
+use crate::i18n;
+use crate::version;
 
 use eframe::egui;
 use futures_util::stream::StreamExt;
 use futures_util::stream::{SplitSink, SplitStream};
 use futures_util::SinkExt;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 use std::{sync::Arc, time::Duration};
 use tokio::net::TcpStream;
 use tokio::sync::Mutex;
 use tokio_tungstenite::connect_async;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use tokio_tungstenite::tungstenite::http::header::{
+    HeaderValue, AUTHORIZATION, SEC_WEBSOCKET_PROTOCOL,
+};
 use tokio_tungstenite::MaybeTlsStream;
 use tokio_tungstenite::WebSocketStream;
 use tracing::{error, info};
 use tungstenite::Message;
 
+/// Older chat lines are dropped once the log grows past this.
+const MAX_CHAT_HISTORY: usize = 200;
+
+/// How long a received reaction stays on screen.
+pub const REACTION_LIFETIME: Duration = Duration::from_secs(4);
+
+/// Number of recent sent/received frames kept for bug reports.
+const MAX_PROTOCOL_LOG: usize = 300;
+
+/// How long a sent move may go without an UPDATE_STATE or MOVE_FAILED reply.
+pub const MOVE_ACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone)]
+pub struct PendingMove {
+    pub game_id: String,
+    pub player: Player,
+    pub row: usize,
+    pub col: usize,
+    /// Sent with every attempt at this move, so the server plays it at most once.
+    pub request_id: String,
+    pub sent_at: Instant,
+}
+
+impl PendingMove {
+    fn message(&self) -> serde_json::Value {
+        serde_json::json!({
+            "type": "MAKE_MOVE",
+            "game_id": self.game_id,
+            "player": self.player,
+            "x": self.row,
+            "y": self.col,
+            "request_id": self.request_id
+        })
+    }
+}
+
+/// Chess clock readings from the last state update.
+#[derive(Debug, Clone)]
+struct ClockReadings {
+    /// Milliseconds left per seat.
+    left: HashMap<Player, u64>,
+    received_at: Instant,
+}
+
+/// Extracts `(game_id, token)` from a shared spectate URL such as
+/// `https://host/?spectate=abc123&token=...`.
+pub fn parse_spectate_link(link: &str) -> Option<(String, String)> {
+    let (_, query) = link.trim().split_once('?')?;
+    let mut game_id = None;
+    let mut token = None;
+
+    for pair in query.split('&') {
+        match pair.split_once('=') {
+            Some(("spectate", value)) => game_id = Some(value.to_string()),
+            Some(("token", value)) => token = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    Some((game_id?, token?))
+}
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+type WsWriter = SplitSink<WsStream, Message>;
+type WsReader = SplitStream<WsStream>;
+
 #[derive(Clone)]
 pub struct GameService {
     server_url: String,
@@ -21,12 +101,51 @@ pub struct GameService {
     player: Arc<Mutex<Option<Player>>>,
     connected: Arc<Mutex<bool>>,
     game_id: Arc<Mutex<String>>,
-    socket: Arc<Mutex<Option<WebSocketStream<MaybeTlsStream<TcpStream>>>>>,
+    socket: Arc<Mutex<Option<WsStream>>>,
     last_ping_time: Arc<Mutex<Option<Instant>>>,
-    socket_write:
-        Arc<Mutex<Option<SplitSink<WebSocketStream<MaybeTlsStream<TcpStream>>, Message>>>>,
-    socket_read: Arc<Mutex<Option<SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>>>>,
+    socket_write: Arc<Mutex<Option<WsWriter>>>,
+    socket_read: Arc<Mutex<Option<WsReader>>>,
     player_name: Arc<Mutex<String>>,
+    turn_deadline: Arc<Mutex<Option<Instant>>>,
+    /// When a paused game resumes whether or not the missing player is back.
+    pause_deadline: Arc<Mutex<Option<Instant>>>,
+    clocks: Arc<Mutex<Option<ClockReadings>>>,
+    pending_move: Arc<Mutex<Option<PendingMove>>>,
+    last_error: Arc<Mutex<Option<String>>>,
+    /// The language we last asked for a room in, which server errors are worded in too.
+    locale: Arc<Mutex<String>>,
+    leaderboard: Arc<Mutex<Vec<LeaderboardEntry>>>,
+    /// The tournament of the current game, as last fetched.
+    tournament: Arc<Mutex<Option<Tournament>>>,
+    online_players: Arc<Mutex<Vec<OnlinePlayer>>>,
+    spectating: Arc<Mutex<bool>>,
+    join_rejection: Arc<Mutex<Option<String>>>,
+    last_update_reason: Arc<Mutex<Option<String>>>,
+    chat: Arc<Mutex<Vec<ChatMessage>>>,
+    /// Reactions from the others in the game, with when each arrived.
+    reactions: Arc<Mutex<Vec<(Reaction, Instant)>>>,
+    spectate_link: Arc<Mutex<Option<String>>>,
+    protocol_log: Arc<Mutex<VecDeque<String>>>,
+    net_sim: Arc<Mutex<NetSim>>,
+    waiting_games: Arc<Mutex<Vec<WaitingGame>>>,
+    hello: Arc<Mutex<ServerHello>>,
+    /// Whether to offer MessagePack when the next connection is opened.
+    binary_frames: Arc<Mutex<bool>>,
+    /// Whether the current connection actually negotiated MessagePack.
+    msgpack: Arc<Mutex<bool>>,
+    /// Token from the spectate link, so a reconnect can watch the same game again.
+    spectate_token: Arc<Mutex<Option<String>>>,
+    /// Set while we sit in the quick-match queue without an opponent.
+    queue_status: Arc<Mutex<Option<QueueStatus>>>,
+    /// Set while we wait in line for a seat in a winner-stays-on room.
+    challenger: Arc<Mutex<bool>>,
+    /// Presented on every new connection once we have signed in.
+    auth: Arc<Mutex<Option<AuthToken>>>,
+    /// Our profile from the last JOIN_SUCCESS.
+    profile: Arc<Mutex<Option<Profile>>>,
+    /// Our record against the opponent, if they were seated when we joined.
+    head_to_head: Arc<Mutex<Option<HeadToHead>>>,
+    watchdog: Watchdog,
 }
 
 impl GameService {
@@ -44,6 +163,34 @@ impl GameService {
             socket_write: Arc::new(Mutex::new(None)),
             socket_read: Arc::new(Mutex::new(None)),
             player_name: Arc::new(Mutex::new(String::new())),
+            turn_deadline: Arc::new(Mutex::new(None)),
+            pause_deadline: Arc::new(Mutex::new(None)),
+            clocks: Arc::new(Mutex::new(None)),
+            pending_move: Arc::new(Mutex::new(None)),
+            last_error: Arc::new(Mutex::new(None)),
+            locale: Arc::new(Mutex::new(i18n::DEFAULT_LOCALE.to_string())),
+            leaderboard: Arc::new(Mutex::new(Vec::new())),
+            tournament: Arc::new(Mutex::new(None)),
+            online_players: Arc::new(Mutex::new(Vec::new())),
+            spectating: Arc::new(Mutex::new(false)),
+            join_rejection: Arc::new(Mutex::new(None)),
+            last_update_reason: Arc::new(Mutex::new(None)),
+            chat: Arc::new(Mutex::new(Vec::new())),
+            reactions: Arc::new(Mutex::new(Vec::new())),
+            spectate_link: Arc::new(Mutex::new(None)),
+            protocol_log: Arc::new(Mutex::new(VecDeque::new())),
+            net_sim: Arc::new(Mutex::new(NetSim::default())),
+            waiting_games: Arc::new(Mutex::new(Vec::new())),
+            hello: Arc::new(Mutex::new(ServerHello::default())),
+            binary_frames: Arc::new(Mutex::new(false)),
+            msgpack: Arc::new(Mutex::new(false)),
+            spectate_token: Arc::new(Mutex::new(None)),
+            queue_status: Arc::new(Mutex::new(None)),
+            challenger: Arc::new(Mutex::new(false)),
+            profile: Arc::new(Mutex::new(None)),
+            head_to_head: Arc::new(Mutex::new(None)),
+            auth: Arc::new(Mutex::new(None)),
+            watchdog: Watchdog::default(),
         }
     }
 
@@ -51,6 +198,325 @@ impl GameService {
         Arc::clone(&self.game)
     }
 
+    pub fn turn_remaining(&self) -> Option<Duration> {
+        let deadline = (*self.turn_deadline.try_lock().ok()?)?;
+        Some(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    pub fn pause_remaining(&self) -> Option<Duration> {
+        let deadline = (*self.pause_deadline.try_lock().ok()?)?;
+        Some(deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Time left on `player`'s chess clock, counting down since the last update if
+    /// `running`. `None` when the game has no chess clock.
+    pub fn clock_remaining(&self, player: Player, running: bool) -> Option<Duration> {
+        let clocks = self.clocks.try_lock().ok()?;
+        let readings = clocks.as_ref()?;
+        let left = Duration::from_millis(*readings.left.get(&player)?);
+        Some(if running {
+            left.saturating_sub(readings.received_at.elapsed())
+        } else {
+            left
+        })
+    }
+
+    /// The last sent move if it has gone unconfirmed for longer than `MOVE_ACK_TIMEOUT`.
+    pub fn unconfirmed_move(&self) -> Option<PendingMove> {
+        let pending = self.pending_move.try_lock().ok()?.clone()?;
+        (pending.sent_at.elapsed() >= MOVE_ACK_TIMEOUT).then_some(pending)
+    }
+
+    pub fn leaderboard(&self) -> Vec<LeaderboardEntry> {
+        self.leaderboard
+            .try_lock()
+            .map(|entries| entries.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn tournament(&self) -> Option<Tournament> {
+        self.tournament.try_lock().ok()?.clone()
+    }
+
+    pub fn online_players(&self) -> Vec<OnlinePlayer> {
+        self.online_players
+            .try_lock()
+            .map(|players| players.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn game_id(&self) -> String {
+        self.game_id
+            .try_lock()
+            .map(|id| id.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn is_spectating(&self) -> bool {
+        self.spectating.try_lock().map(|s| *s).unwrap_or(false)
+    }
+
+    pub fn is_challenger(&self) -> bool {
+        self.challenger.try_lock().map(|c| *c).unwrap_or(false)
+    }
+
+    /// The seat we hold right now, without waiting for one to be assigned.
+    pub fn seat(&self) -> Option<Player> {
+        self.player.try_lock().ok().and_then(|p| *p)
+    }
+
+    /// Set when the server turned down our JOIN_GAME, e.g. because the game is full.
+    pub fn join_rejection(&self) -> Option<String> {
+        self.join_rejection.try_lock().ok()?.clone()
+    }
+
+    pub async fn clear_join_rejection(&self) {
+        *self.join_rejection.lock().await = None;
+    }
+
+    pub async fn current_game_id(&self) -> String {
+        self.game_id.lock().await.clone()
+    }
+
+    /// Why the server sent the most recent UPDATE_STATE (join, move, reset, timeout, resync...).
+    pub fn last_update_reason(&self) -> Option<String> {
+        self.last_update_reason.try_lock().ok()?.clone()
+    }
+
+    pub fn chat_messages(&self) -> Vec<ChatMessage> {
+        self.chat
+            .try_lock()
+            .map(|chat| chat.clone())
+            .unwrap_or_default()
+    }
+
+    /// Reactions received within the last `REACTION_LIFETIME`, oldest first.
+    pub fn recent_reactions(&self) -> Vec<Reaction> {
+        self.reactions
+            .try_lock()
+            .map(|reactions| {
+                reactions
+                    .iter()
+                    .filter(|(_, at)| at.elapsed() < REACTION_LIFETIME)
+                    .map(|(reaction, _)| reaction.clone())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// The most recent shareable spectate URL issued by the server.
+    pub fn spectate_link(&self) -> Option<String> {
+        self.spectate_link.try_lock().ok()?.clone()
+    }
+
+    pub fn hello(&self) -> ServerHello {
+        self.hello
+            .try_lock()
+            .map(|hello| hello.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn waiting_games(&self) -> Vec<WaitingGame> {
+        self.waiting_games
+            .try_lock()
+            .map(|games| games.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn queue_status(&self) -> Option<QueueStatus> {
+        self.queue_status.try_lock().ok()?.clone()
+    }
+
+    pub fn profile(&self) -> Option<Profile> {
+        self.profile.try_lock().ok()?.clone()
+    }
+
+    pub fn head_to_head(&self) -> Option<HeadToHead> {
+        self.head_to_head.try_lock().ok()?.clone()
+    }
+
+    /// The name our token proves, once signed in.
+    pub fn signed_in_as(&self) -> Option<String> {
+        Some(self.auth.try_lock().ok()?.as_ref()?.name.clone())
+    }
+
+    /// Claims `name` on the server (or renews our token for it), so nobody else can play
+    /// under it. An open connection is dropped so the next one presents the token.
+    pub async fn sign_in(&self, name: String) -> Result<String, String> {
+        self.authenticate("auth", serde_json::json!({ "name": name }))
+            .await
+    }
+
+    /// Signs in to an existing account.
+    pub async fn log_in(&self, name: String, password: String) -> Result<String, String> {
+        self.authenticate(
+            "login",
+            serde_json::json!({ "name": name, "password": password }),
+        )
+        .await
+    }
+
+    /// Creates an account for `name` protected by `password`, and signs in to it.
+    pub async fn register(&self, name: String, password: String) -> Result<String, String> {
+        self.authenticate(
+            "register",
+            serde_json::json!({ "name": name, "password": password }),
+        )
+        .await
+    }
+
+    /// Downloads the latest finished round of the current game in the server's text
+    /// notation and saves it in the working directory, returning where it went.
+    pub async fn export_game(&self) -> Result<PathBuf, String> {
+        let game_id = self.game_id.lock().await.clone();
+        // Private games' rounds need our sign-in or the link we are watching with
+        let mut request =
+            reqwest::Client::new().get(format!("{}/replays/{game_id}/notation", self.server_url));
+        if let Some(auth) = self.auth.lock().await.as_ref() {
+            request = request.bearer_auth(&auth.token);
+        }
+        if let Some(token) = self.spectate_token.lock().await.as_ref() {
+            request = request.query(&[("token", token)]);
+        }
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("No finished round of {game_id} to export"));
+        }
+
+        // Keep only the file name the server suggests, never a path it might smuggle in
+        let path = response
+            .headers()
+            .get(reqwest::header::CONTENT_DISPOSITION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split("filename=").nth(1))
+            .and_then(|name| Path::new(name.trim_matches('"')).file_name())
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(format!("{game_id}.ttn")));
+        let notation = response.text().await.map_err(|e| e.to_string())?;
+        tokio::fs::write(&path, notation)
+            .await
+            .map_err(|e| e.to_string())?;
+        info!("💾 Exported {} to {:?}", game_id, path);
+        Ok(path)
+    }
+
+    /// Fetches the standings and pairings of the tournament the current game belongs to.
+    pub async fn refresh_tournament(&self) -> Result<(), String> {
+        let Some(tournament_id) = self.game.lock().await.tournament.clone() else {
+            *self.tournament.lock().await = None;
+            return Ok(());
+        };
+        let response = reqwest::get(format!("{}/tournaments/{tournament_id}", self.server_url))
+            .await
+            .map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("Tournament {tournament_id} not found"));
+        }
+
+        let body = response.text().await.map_err(|e| e.to_string())?;
+        let tournament: Tournament = serde_json::from_str(&body).map_err(|e| e.to_string())?;
+        *self.tournament.lock().await = Some(tournament);
+        Ok(())
+    }
+
+    /// Posts `body` to one of the token endpoints and keeps the token it hands back.
+    async fn authenticate(&self, path: &str, body: serde_json::Value) -> Result<String, String> {
+        let mut request = reqwest::Client::new()
+            .post(format!("{}/{path}", self.server_url))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body.to_string());
+        if let Some(auth) = self.auth.lock().await.as_ref() {
+            request = request.bearer_auth(&auth.token);
+        }
+
+        let response = request.send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            let reason = response.text().await.unwrap_or_default();
+            return Err(if reason.is_empty() {
+                "Sign-in is not available on this server".to_string()
+            } else {
+                reason
+            });
+        }
+        let body = response.bytes().await.map_err(|e| e.to_string())?;
+        let auth: AuthToken = serde_json::from_slice(&body).map_err(|e| e.to_string())?;
+        info!("🔐 Signed in as {}", auth.name);
+
+        let signed_in_as = auth.name.clone();
+        *self.player_name.lock().await = signed_in_as.clone();
+        *self.auth.lock().await = Some(auth);
+
+        if self.socket_write.lock().await.is_some() {
+            self.watchdog.stop_tasks().await;
+            *self.socket_write.lock().await = None;
+            *self.connected.lock().await = false;
+        }
+        Ok(signed_in_as)
+    }
+
+    pub fn net_sim(&self) -> NetSim {
+        self.net_sim.try_lock().map(|sim| *sim).unwrap_or_default()
+    }
+
+    pub fn set_net_sim(&self, settings: NetSim) {
+        if let Ok(mut sim) = self.net_sim.try_lock() {
+            *sim = settings;
+        }
+    }
+
+    pub fn binary_frames(&self) -> bool {
+        self.binary_frames
+            .try_lock()
+            .map(|b| *b)
+            .unwrap_or_default()
+    }
+
+    pub fn set_binary_frames(&self, enabled: bool) {
+        if let Ok(mut binary_frames) = self.binary_frames.try_lock() {
+            *binary_frames = enabled;
+        }
+    }
+
+    /// Recent protocol traffic, oldest first, for attaching to bug reports.
+    pub async fn protocol_log(&self) -> Vec<String> {
+        self.protocol_log.lock().await.iter().cloned().collect()
+    }
+
+    async fn push_chat(&self, message: ChatMessage) {
+        let mut chat = self.chat.lock().await;
+        chat.push(message);
+        if chat.len() > MAX_CHAT_HISTORY {
+            let overflow = chat.len() - MAX_CHAT_HISTORY;
+            chat.drain(..overflow);
+        }
+    }
+
+    async fn record_frame(&self, direction: &str, text: &str) {
+        let millis = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0);
+
+        let mut log = self.protocol_log.lock().await;
+        log.push_back(format!("{millis} {direction} {text}"));
+        if log.len() > MAX_PROTOCOL_LOG {
+            log.pop_front();
+        }
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.try_lock().ok()?.clone()
+    }
+
+    /// What the watchdog last had to recover from, for the diagnostic banner.
+    pub fn diagnostic(&self) -> Option<String> {
+        self.watchdog.diagnostic()
+    }
+
+    pub async fn dismiss_diagnostic(&self) {
+        self.watchdog.dismiss().await;
+    }
+
     pub async fn is_connected(&self) -> bool {
         let mut socket_guard = self.socket.lock().await;
         let mut socket_write_guard = self.socket_write.lock().await;
@@ -94,10 +560,9 @@ impl GameService {
         None
     }
 
-    pub async fn start_websocket(
+    /// Opens the WebSocket and starts the listener unless a live connection already exists.
+    async fn ensure_connected(
         &self,
-        game_id: String,
-        player_name: String,
         ctx: Arc<egui::Context>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         let mut connected = self.connected.lock().await;
@@ -118,39 +583,350 @@ impl GameService {
                 .replace("https://", "wss://")
         );
 
-        let (stream, _) = connect_async(&websocket_url).await?;
+        let stream = match self.open_socket(&websocket_url).await {
+            Ok(result) => result,
+            Err(e) => {
+                *self.connected.lock().await = false;
+                return Err(e.into());
+            }
+        };
         let (write, read) = stream.split();
 
         // ✅ store pieces where needed
         *self.socket_write.lock().await = Some(write);
         *self.socket_read.lock().await = Some(read);
+        self.send_hello().await;
+
+        let socket_read = self.socket_read.lock().await.take();
+        if let Some(socket_read) = socket_read {
+            self.spawn_listener(socket_read, Arc::clone(&ctx)).await;
+        }
+        self.spawn_heartbeat().await;
+
+        if self.watchdog.start() {
+            tokio::spawn(Arc::new(self.clone()).supervise(ctx));
+        }
+
+        Ok(())
+    }
+
+    async fn spawn_listener(&self, socket_read: WsReader, ctx: Arc<egui::Context>) {
+        let self_clone = Arc::new(self.clone());
+        let listener = tokio::spawn(async move {
+            if let Err(e) = self_clone.listen_for_messages(socket_read, ctx).await {
+                error!("Error in WebSocket listener: {:?}", e);
+            }
+        });
+        self.watchdog.watch_listener(listener).await;
+    }
+
+    /// Pings the server regularly; the pongs keep the listener's stall clock from running out.
+    async fn spawn_heartbeat(&self) {
+        let socket_write = Arc::clone(&self.socket_write);
+        let last_ping_time = Arc::clone(&self.last_ping_time);
+
+        let heartbeat = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(HEARTBEAT_INTERVAL).await;
+
+                let mut writer = socket_write.lock().await;
+                let Some(writer) = writer.as_mut() else {
+                    continue;
+                };
+                if let Err(e) = writer.send(Message::Ping(vec![].into())).await {
+                    error!("💔 Heartbeat ping failed: {}", e);
+                    return;
+                }
+                *last_ping_time.lock().await = Some(Instant::now());
+            }
+        });
+        self.watchdog.watch_heartbeat(heartbeat).await;
+    }
+
+    /// Restarts the listener and heartbeat whenever the watchdog finds one dead or stalled,
+    /// so a broken connection shows up as a banner instead of a board that stops changing.
+    async fn supervise(self: Arc<Self>, ctx: Arc<egui::Context>) {
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            let game_id = self.game_id.lock().await.clone();
+            if game_id.is_empty() {
+                continue;
+            }
+
+            let connected = *self.connected.lock().await;
+            let Some(fault) = self.watchdog.check(connected).await else {
+                continue;
+            };
+            error!("🐕 Watchdog: {:?}", fault);
+
+            let diagnostic = match fault {
+                Fault::HeartbeatDied => {
+                    self.spawn_heartbeat().await;
+                    format!("{}; restarted it.", fault.describe())
+                }
+                Fault::ListenerDied | Fault::Stalled(_) => {
+                    self.watchdog.stop_tasks().await;
+                    *self.socket_write.lock().await = None;
+                    *self.connected.lock().await = false;
+
+                    match self.reconnect(game_id, Arc::clone(&ctx)).await {
+                        Ok(()) => format!("{}; reconnected.", fault.describe()),
+                        Err(e) => format!("{}; reconnecting failed: {}", fault.describe(), e),
+                    }
+                }
+            };
+            self.watchdog.report(diagnostic).await;
+            ctx.request_repaint();
+        }
+    }
+
+    /// Connects to `url`, offering MessagePack frames when they are switched on.
+    async fn open_socket(&self, url: &str) -> Result<WsStream, tungstenite::Error> {
+        let mut request = url.into_client_request()?;
+        if let Some(auth) = self.auth.lock().await.as_ref() {
+            if let Ok(value) = HeaderValue::from_str(&format!("Bearer {}", auth.token)) {
+                request.headers_mut().insert(AUTHORIZATION, value);
+            }
+        }
+        if *self.binary_frames.lock().await {
+            request.headers_mut().insert(
+                SEC_WEBSOCKET_PROTOCOL,
+                HeaderValue::from_static("ttt.msgpack, ttt.json"),
+            );
+        }
+
+        let (stream, response) = connect_async(request).await?;
+        let msgpack = response
+            .headers()
+            .get(SEC_WEBSOCKET_PROTOCOL)
+            .is_some_and(|protocol| protocol == "ttt.msgpack");
+        info!(
+            "🔌 Connected using {} frames",
+            if msgpack { "MessagePack" } else { "JSON" }
+        );
+        *self.msgpack.lock().await = msgpack;
+
+        Ok(stream)
+    }
+
+    /// Tells the server which client build is connecting.
+    async fn send_hello(&self) {
+        let hello = serde_json::json!({
+            "type": "HELLO",
+            "version": version::VERSION,
+            "git_hash": version::GIT_HASH,
+            "deltas": true
+        });
+
+        if let Err(e) = self.send_json(hello).await {
+            error!("❌ Failed to send HELLO: {}", e);
+        }
+    }
+
+    async fn send_json(
+        &self,
+        message: serde_json::Value,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.record_frame(">>", &message.to_string()).await;
+
+        let net_sim = *self.net_sim.lock().await;
+        if !net_sim.apply().await {
+            info!("🐢 Simulated network dropped an outgoing message");
+            return Ok(());
+        }
+
+        let frame = if *self.msgpack.lock().await {
+            Message::Binary(rmp_serde::to_vec_named(&message)?.into())
+        } else {
+            Message::Text(message.to_string().into())
+        };
+
+        match self.socket_write.lock().await.as_mut() {
+            Some(writer) => {
+                writer.send(frame).await?;
+                Ok(())
+            }
+            None => Err("WebSocket writer unavailable".into()),
+        }
+    }
+
+    pub async fn start_websocket(
+        &self,
+        game_id: String,
+        player_name: String,
+        options: JoinOptions,
+        ctx: Arc<egui::Context>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_connected(ctx).await?;
+        self.switch_game(&game_id).await;
         *self.player_name.lock().await = player_name.clone();
+        *self.spectating.lock().await = false;
+        *self.challenger.lock().await = false;
+        *self.join_rejection.lock().await = None;
+        *self.locale.lock().await = options.locale.clone();
 
-        let join_request = serde_json::json!({
-            "type": "JOIN_GAME",
+        let join_request = game_request("JOIN_GAME", &game_id, &player_name, &options);
+        self.send_json(join_request).await
+    }
+
+    /// Creates a game with exactly `options`, failing rather than joining if `game_id` is
+    /// taken. An empty `game_id` lets the server pick one.
+    pub async fn create_game(
+        &self,
+        game_id: String,
+        player_name: String,
+        options: JoinOptions,
+        ctx: Arc<egui::Context>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_connected(ctx).await?;
+        self.switch_game(&game_id).await;
+        *self.player_name.lock().await = player_name.clone();
+        *self.spectating.lock().await = false;
+        *self.challenger.lock().await = false;
+        *self.join_rejection.lock().await = None;
+        *self.locale.lock().await = options.locale.clone();
+
+        let create_request = game_request("CREATE_GAME", &game_id, &player_name, &options);
+        self.send_json(create_request).await
+    }
+
+    pub async fn spectate(
+        &self,
+        game_id: String,
+        token: Option<String>,
+        ctx: Arc<egui::Context>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_connected(ctx).await?;
+        self.switch_game(&game_id).await;
+        *self.join_rejection.lock().await = None;
+        *self.spectate_token.lock().await = token.clone();
+
+        let player_name = self.player_name.lock().await.clone();
+        let spectate_request = serde_json::json!({
+            "type": "SPECTATE",
             "game_id": game_id,
+            "name": player_name,
+            "token": token
+        });
+
+        self.send_json(spectate_request).await
+    }
+
+    pub async fn quick_match(
+        &self,
+        player_name: String,
+        ctx: Arc<egui::Context>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_connected(ctx).await?;
+        self.switch_game("").await;
+        *self.player_name.lock().await = player_name.clone();
+        *self.spectating.lock().await = false;
+        *self.challenger.lock().await = false;
+        *self.join_rejection.lock().await = None;
+        *self.queue_status.lock().await = None;
+
+        let quick_match_request = serde_json::json!({
+            "type": "QUICK_MATCH",
             "name": player_name
         });
 
-        if let Some(writer) = &mut *self.socket_write.lock().await {
-            writer
-                .send(Message::Text(join_request.to_string().into()))
-                .await?;
+        self.send_json(quick_match_request).await
+    }
+
+    /// Stops waiting for a quick-match opponent.
+    pub async fn cancel_quick_match(&self) {
+        *self.queue_status.lock().await = None;
+        let cancel_request = serde_json::json!({ "type": "CANCEL_QUICK_MATCH" });
+
+        if let Err(e) = self.send_json(cancel_request).await {
+            error!("❌ Failed to send CANCEL_QUICK_MATCH request: {}", e);
         }
+    }
 
-        let socket_read = self.socket_read.lock().await.take();
-        if let Some(socket_read) = socket_read {
-            let self_clone = Arc::new(self.clone());
-            let ctx_clone = Arc::clone(&ctx);
+    /// The server keeps a socket in every game it has joined, so the game on screen is left
+    /// before moving on to `next`. Rejoining the same game keeps it.
+    async fn switch_game(&self, next: &str) {
+        let current = self.game_id.lock().await.clone();
+        if current.is_empty() || current == next {
+            return;
+        }
 
-            tokio::spawn(async move {
-                if let Err(e) = self_clone.listen_for_messages(socket_read, ctx_clone).await {
-                    error!("Error in WebSocket listener: {:?}", e);
-                }
-            });
+        let leave_request = serde_json::json!({
+            "type": "LEAVE_GAME",
+            "game_id": current
+        });
+        if let Err(e) = self.send_json(leave_request).await {
+            error!("❌ Failed to send LEAVE_GAME request: {}", e);
         }
+        self.game_id.lock().await.clear();
+    }
 
-        Ok(())
+    /// Asks for the correspondence games this name is seated in.
+    pub async fn login(
+        &self,
+        player_name: String,
+        ctx: Arc<egui::Context>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_connected(ctx).await?;
+        *self.player_name.lock().await = player_name.clone();
+
+        let login_request = serde_json::json!({
+            "type": "LOGIN",
+            "name": player_name
+        });
+
+        self.send_json(login_request).await
+    }
+
+    pub async fn join_by_code(
+        &self,
+        code: String,
+        player_name: String,
+        ctx: Arc<egui::Context>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_connected(ctx).await?;
+        self.switch_game("").await;
+        *self.player_name.lock().await = player_name.clone();
+        *self.spectating.lock().await = false;
+        *self.challenger.lock().await = false;
+        *self.join_rejection.lock().await = None;
+
+        let join_request = serde_json::json!({
+            "type": "JOIN_BY_CODE",
+            "code": code,
+            "name": player_name
+        });
+
+        self.send_json(join_request).await
+    }
+
+    pub async fn join_vs_ai(
+        &self,
+        game_id: String,
+        player_name: String,
+        difficulty: AiDifficulty,
+        options: JoinOptions,
+        ctx: Arc<egui::Context>,
+    ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+        self.ensure_connected(ctx).await?;
+        self.switch_game(&game_id).await;
+        *self.player_name.lock().await = player_name.clone();
+        *self.spectating.lock().await = false;
+        *self.challenger.lock().await = false;
+        *self.join_rejection.lock().await = None;
+
+        let join_request = serde_json::json!({
+            "type": "JOIN_VS_AI",
+            "game_id": game_id,
+            "name": player_name,
+            "difficulty": difficulty,
+            "kind": options.kind,
+            "board_size": options.board_size,
+            "win_length": options.win_length
+        });
+
+        self.send_json(join_request).await
     }
 
     pub async fn reconnect(
@@ -175,37 +951,49 @@ impl GameService {
                 .replace("https://", "wss://")
                 + "/ws";
 
-            match connect_async(&websocket_url).await {
-                Ok((socket, _)) => {
+            match self.open_socket(&websocket_url).await {
+                Ok(socket) => {
                     info!("✅ Reconnected successfully.");
                     let (write, read) = socket.split();
                     *self.socket_write.lock().await = Some(write);
                     *self.socket_read.lock().await = Some(read);
+                    self.send_hello().await;
 
                     let player_name = self.player_name.lock().await.clone();
-                    let join_request = serde_json::json!({
-                        "type": "JOIN_GAME",
-                        "game_id": game_id,
-                        "name": player_name
-                    });
+                    let join_request = if *self.spectating.lock().await {
+                        serde_json::json!({
+                            "type": "SPECTATE",
+                            "game_id": game_id,
+                            "name": player_name,
+                            "token": self.spectate_token.lock().await.clone()
+                        })
+                    } else {
+                        serde_json::json!({
+                            "type": "JOIN_GAME",
+                            "game_id": game_id,
+                            "name": player_name
+                        })
+                    };
 
-                    if let Some(writer) = &mut *self.socket_write.lock().await {
-                        writer
-                            .send(Message::Text(join_request.to_string().into()))
-                            .await?;
-                    }
+                    self.send_json(join_request).await?;
 
                     if let Some(socket_read) = self.socket_read.lock().await.take() {
-                        let ctx_clone = Arc::clone(&ctx);
-                        let self_clone = Arc::new(self.clone());
-
-                        tokio::spawn(async move {
-                            if let Err(e) =
-                                self_clone.listen_for_messages(socket_read, ctx_clone).await
-                            {
-                                error!("❌ Error after reconnect: {:?}", e);
-                            }
-                        });
+                        self.spawn_listener(socket_read, Arc::clone(&ctx)).await;
+                    }
+                    self.spawn_heartbeat().await;
+
+                    // Whatever happened while we were away, start from the server's view
+                    self.request_state().await;
+
+                    // A move sent just before the drop may or may not have landed; the
+                    // server only plays it if it didn't
+                    let pending = self.pending_move.lock().await.clone();
+                    if let Some(pending) = pending {
+                        info!(
+                            "🔁 Resending move {} after reconnecting",
+                            pending.request_id
+                        );
+                        self.send_json(pending.message()).await?;
                     }
 
                     return Ok(());
@@ -224,45 +1012,322 @@ impl GameService {
 
     async fn listen_for_messages(
         &self,
-        mut socket_read: SplitStream<WebSocketStream<MaybeTlsStream<TcpStream>>>,
+        mut socket_read: WsReader,
         ctx: Arc<egui::Context>,
     ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
         while let Some(message) = socket_read.next().await {
-            match message? {
-                Message::Text(text) => {
-                    let parsed: serde_json::Value = serde_json::from_str(&text)?;
-
-                    match parsed["type"].as_str() {
-                        Some("JOIN_SUCCESS") => {
-                            if let Some(received_game_id) = parsed["game_id"].as_str() {
-                                *self.game_id.lock().await = received_game_id.to_string();
-                            }
-
-                            if let Some(player_str) = parsed["player"].as_str() {
-                                let player_type = match player_str {
-                                    "X" => Some(Player::X),
-                                    "O" => Some(Player::O),
-                                    _ => None,
-                                };
-
-                                if let Some(p) = player_type {
-                                    *self.player.lock().await = Some(p);
-                                    *self.connected.lock().await = true;
-                                }
-                            }
-                        }
-                        Some("UPDATE_STATE") => {
-                            if let Ok(updated_game) =
-                                serde_json::from_value::<Game>(parsed["game"].clone())
-                            {
-                                *self.game.lock().await = updated_game;
-                                ctx.request_repaint();
-                            }
+            let frame = message?;
+
+            let net_sim = *self.net_sim.lock().await;
+            if !net_sim.apply().await {
+                info!("🐢 Simulated network dropped an incoming message");
+                continue;
+            }
+            self.watchdog.saw_event().await;
+
+            let text = match frame {
+                Message::Text(text) => text.to_string(),
+                Message::Binary(bytes) => {
+                    match rmp_serde::from_slice::<serde_json::Value>(&bytes) {
+                        Ok(value) => value.to_string(),
+                        Err(e) => {
+                            error!("⚠️ Undecodable MessagePack frame: {}", e);
+                            continue;
                         }
-                        _ => error!("⚠️ Unknown message type: {}", text),
                     }
                 }
-                _ => {}
+                _ => continue,
+            };
+
+            self.record_frame("<<", &text).await;
+            if let Some(event_game_id) = event_game_id(&text) {
+                if *self.game_id.lock().await != event_game_id {
+                    continue;
+                }
+            }
+            let message: ServerMessage = match serde_json::from_str(&text) {
+                Ok(message) => message,
+                Err(e) => {
+                    error!("⚠️ Unrecognised server message {}: {}", text, e);
+                    continue;
+                }
+            };
+
+            match message {
+                ServerMessage::JoinSuccess {
+                    game_id,
+                    player,
+                    profile,
+                    head_to_head,
+                } => {
+                    *self.profile.lock().await = profile;
+                    *self.head_to_head.lock().await = head_to_head;
+                    *self.game_id.lock().await = game_id;
+                    *self.player.lock().await = Some(player);
+                    *self.connected.lock().await = true;
+                }
+                ServerMessage::GameCreated {
+                    game_id,
+                    invite_code,
+                    config,
+                } => {
+                    info!(
+                        "🆕 Created game {} (invite code {}): {}",
+                        game_id,
+                        invite_code,
+                        config.summary()
+                    );
+                }
+                ServerMessage::UpdateState {
+                    game: updated_game,
+                    reason,
+                    turn_remaining,
+                    pause_remaining,
+                    clock_remaining,
+                } => {
+                    if updated_game.players.len() == 2 {
+                        *self.queue_status.lock().await = None;
+                    }
+
+                    let mut game = self.game.lock().await;
+                    let missed_updates =
+                        game.version != 0 && updated_game.version > game.version + 1;
+                    *game = *updated_game;
+                    drop(game);
+
+                    *self.last_update_reason.lock().await = reason;
+
+                    if missed_updates {
+                        info!("🔄 Detected a state version gap. Resyncing...");
+                        self.request_state().await;
+                    }
+
+                    *self.pending_move.lock().await = None;
+                    *self.last_error.lock().await = None;
+                    *self.turn_deadline.lock().await =
+                        turn_remaining.map(|secs| Instant::now() + Duration::from_secs(secs));
+                    *self.pause_deadline.lock().await =
+                        pause_remaining.map(|secs| Instant::now() + Duration::from_secs(secs));
+                    *self.clocks.lock().await = clock_remaining.map(|left| ClockReadings {
+                        left,
+                        received_at: Instant::now(),
+                    });
+                    ctx.request_repaint();
+                }
+                ServerMessage::MoveMade(delta) => {
+                    let applied = self.game.lock().await.apply_move(&delta);
+                    if !applied {
+                        info!(
+                            "🔄 Move {} doesn't follow our copy. Resyncing...",
+                            delta.version
+                        );
+                        self.request_state().await;
+                    }
+
+                    *self.last_update_reason.lock().await = Some("move".to_string());
+                    *self.pending_move.lock().await = None;
+                    *self.last_error.lock().await = None;
+                    *self.turn_deadline.lock().await = delta
+                        .turn_remaining
+                        .map(|secs| Instant::now() + Duration::from_secs(secs));
+                    *self.clocks.lock().await = delta.clock_remaining.map(|left| ClockReadings {
+                        left,
+                        received_at: Instant::now(),
+                    });
+                    ctx.request_repaint();
+                }
+                ServerMessage::NotificationsRegistered { webhook_url, email } => {
+                    info!(
+                        "🔔 Turn notifications set: email={:?}, webhook={:?}",
+                        email, webhook_url
+                    );
+                }
+                ServerMessage::Hello(hello) => {
+                    info!("👋 Server hello: {:?}", hello);
+                    *self.hello.lock().await = hello;
+                    ctx.request_repaint();
+                }
+                ServerMessage::PlayerLeft { player, name } => {
+                    info!("🔌 {} disconnected", name);
+                    let mut game = self.game.lock().await;
+                    if !game.disconnected.contains(&player) {
+                        game.disconnected.push(player);
+                    }
+                    ctx.request_repaint();
+                }
+                ServerMessage::WaitingGames { games } => {
+                    *self.waiting_games.lock().await = games;
+                    ctx.request_repaint();
+                }
+                ServerMessage::SpectateLink { url } => {
+                    *self.spectate_link.lock().await = Some(url);
+                    ctx.request_repaint();
+                }
+                ServerMessage::SpectateSuccess { game_id } => {
+                    *self.game_id.lock().await = game_id;
+                    *self.player.lock().await = None;
+                    *self.spectating.lock().await = true;
+                    ctx.request_repaint();
+                }
+                ServerMessage::Error {
+                    message,
+                    code,
+                    params,
+                } => {
+                    error!("❌ Server error: {}", message);
+                    let locale = self.locale.lock().await.clone();
+                    let full = code.as_deref() == Some("GAME_FULL");
+                    let message = i18n::render_error(&locale, code.as_deref(), &params, &message);
+                    if full {
+                        *self.join_rejection.lock().await = Some(message);
+                    } else {
+                        *self.last_error.lock().await = Some(message);
+                    }
+                    ctx.request_repaint();
+                }
+                ServerMessage::Chat(message) => {
+                    self.push_chat(message).await;
+                    ctx.request_repaint();
+                }
+                ServerMessage::Reaction(reaction) => {
+                    let mut reactions = self.reactions.lock().await;
+                    reactions.retain(|(_, at)| at.elapsed() < REACTION_LIFETIME);
+                    reactions.push((reaction, Instant::now()));
+                    // Repaint again once it is due to disappear
+                    ctx.request_repaint_after(REACTION_LIFETIME);
+                    ctx.request_repaint();
+                }
+                ServerMessage::System {
+                    key,
+                    params,
+                    locale,
+                    timestamp,
+                } => {
+                    let text = i18n::render(
+                        locale.as_deref().unwrap_or(i18n::DEFAULT_LOCALE),
+                        &key,
+                        &params,
+                    );
+                    self.push_chat(ChatMessage {
+                        sender: String::new(),
+                        text,
+                        timestamp,
+                        system: true,
+                        team: None,
+                        spectator: false,
+                    })
+                    .await;
+                    ctx.request_repaint();
+                }
+                ServerMessage::Leaderboard { players } => {
+                    *self.leaderboard.lock().await = players;
+                    ctx.request_repaint();
+                }
+                ServerMessage::OnlinePlayers { players } => {
+                    *self.online_players.lock().await = players;
+                    ctx.request_repaint();
+                }
+                ServerMessage::Presence { name, online } => {
+                    let mut players = self.online_players.lock().await;
+                    players.retain(|player| player.name != name);
+                    if online {
+                        let at = players.partition_point(|player| player.name < name);
+                        players.insert(
+                            at,
+                            OnlinePlayer {
+                                name,
+                                playing: false,
+                            },
+                        );
+                    }
+                    ctx.request_repaint();
+                }
+                ServerMessage::QueueStatus(status) => {
+                    *self.queue_status.lock().await = Some(status);
+                    ctx.request_repaint();
+                }
+                ServerMessage::Removed { message } => {
+                    info!("🚫 {}", message);
+                    *self.game.lock().await = Game::default();
+                    *self.player.lock().await = None;
+                    self.game_id.lock().await.clear();
+                    *self.join_rejection.lock().await = Some(message);
+                    ctx.request_repaint();
+                }
+                ServerMessage::ChallengerQueued { game_id, position } => {
+                    info!("🎟️ Waiting in line for game {}: #{}", game_id, position);
+                    *self.game_id.lock().await = game_id;
+                    *self.player.lock().await = None;
+                    *self.challenger.lock().await = true;
+                    *self.connected.lock().await = true;
+                    ctx.request_repaint();
+                }
+                ServerMessage::SeatsRotated {
+                    player,
+                    left,
+                    seated,
+                } => {
+                    info!("🔁 {} makes way for {} as {:?}", left, seated, player);
+                    let name = self.player_name.lock().await.clone();
+                    if name == left {
+                        *self.player.lock().await = None;
+                        *self.challenger.lock().await = true;
+                    }
+                    if name == seated {
+                        *self.player.lock().await = Some(player);
+                        *self.challenger.lock().await = false;
+                    }
+                    ctx.request_repaint();
+                }
+                ServerMessage::ReportReceived { report_id, target } => {
+                    info!("🚩 Report {} about {} received", report_id, target);
+                    let locale = self.locale.lock().await.clone();
+                    let params = serde_json::json!({ "name": target });
+                    self.push_chat(ChatMessage {
+                        sender: String::new(),
+                        text: i18n::render(&locale, "report_received", &params),
+                        timestamp: std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .map(|d| d.as_secs())
+                            .unwrap_or(0),
+                        system: true,
+                        team: None,
+                        spectator: false,
+                    })
+                    .await;
+                    ctx.request_repaint();
+                }
+                ServerMessage::LeftGame { game_id } => {
+                    info!("🚪 Left game {}", game_id);
+                }
+                ServerMessage::QueueLeft { game_id } => {
+                    info!("🚪 Left the quick-match queue for game {}", game_id);
+                    *self.queue_status.lock().await = None;
+                    *self.game.lock().await = Game::default();
+                    *self.player.lock().await = None;
+                    self.game_id.lock().await.clear();
+                    ctx.request_repaint();
+                }
+                ServerMessage::MoveFailed {
+                    message,
+                    code,
+                    params,
+                    request_id,
+                } => {
+                    error!("❌ Move rejected by server: {}", message);
+                    // A late answer to an earlier attempt leaves the move in flight alone
+                    let mut pending = self.pending_move.lock().await;
+                    if request_id.is_none()
+                        || pending.as_ref().map(|p| &p.request_id) == request_id.as_ref()
+                    {
+                        *pending = None;
+                    }
+                    drop(pending);
+                    let locale = self.locale.lock().await.clone();
+                    let message = i18n::render_error(&locale, code.as_deref(), &params, &message);
+                    *self.last_error.lock().await = Some(message);
+                    ctx.request_repaint();
+                }
             }
         }
 
@@ -271,8 +1336,16 @@ impl GameService {
 
         Ok(())
     }
-    pub async fn join_game(&self, game_id: String, player_name: String, ctx: Arc<egui::Context>) {
-        let result = self.start_websocket(game_id, player_name, ctx).await;
+    pub async fn join_game(
+        &self,
+        game_id: String,
+        player_name: String,
+        options: JoinOptions,
+        ctx: Arc<egui::Context>,
+    ) {
+        let result = self
+            .start_websocket(game_id, player_name, options, ctx)
+            .await;
         if let Err(e) = result {
             error!("Failed to join game: {:?}", e);
         }
@@ -291,23 +1364,26 @@ impl GameService {
             return;
         }
 
-        let move_request = serde_json::json!({
-            "type": "MAKE_MOVE",
-            "game_id": game_id,
-            "player": match player {
-                Player::X => "X",
-                Player::O => "O",
-            },
-            "x": row,
-            "y": col
-        });
+        let pending = PendingMove {
+            game_id,
+            player,
+            row,
+            col,
+            request_id: format!("{:016x}", rand::random::<u64>()),
+            sent_at: Instant::now(),
+        };
+        self.send_move(pending, ctx).await;
+    }
 
+    /// Sends `pending`, reconnecting first if the socket is down, and keeps it until the
+    /// server confirms or refuses it.
+    async fn send_move(&self, mut pending: PendingMove, ctx: Arc<egui::Context>) {
         info!("📤 Attempting to send MOVE request...");
 
         if !self.is_connected().await {
             error!("🔌 WebSocket is disconnected. Trying to reconnect...");
 
-            if let Err(e) = self.reconnect(game_id.clone(), ctx.clone()).await {
+            if let Err(e) = self.reconnect(pending.game_id.clone(), ctx.clone()).await {
                 error!("❌ Reconnection failed: {}", e);
                 return;
             }
@@ -315,23 +1391,179 @@ impl GameService {
             tokio::time::sleep(Duration::from_millis(150)).await;
         }
 
-        match self.socket_write.lock().await.as_mut() {
-            Some(writer) => {
-                if let Err(e) = writer
-                    .send(Message::Text(move_request.to_string().into()))
-                    .await
-                {
-                    error!("❌ Failed to send MOVE request: {}", e);
-                } else {
-                    info!(
-                        "✅ MOVE request sent: Player {:?} -> ({}, {})",
-                        player, row, col
-                    );
-                }
-            }
-            None => {
-                error!("❌ No active WebSocket writer. Cannot send move.");
+        match self.send_json(pending.message()).await {
+            Ok(()) => {
+                info!(
+                    "✅ MOVE request sent: Player {:?} -> ({}, {})",
+                    pending.player, pending.row, pending.col
+                );
+                pending.sent_at = Instant::now();
+                *self.pending_move.lock().await = Some(pending);
             }
+            Err(e) => error!("❌ Failed to send MOVE request: {}", e),
+        }
+    }
+
+    /// Sends the unconfirmed move again under the same request id, so the server answers
+    /// it without playing it twice if the first attempt did get through.
+    pub async fn retry_move(&self, ctx: Arc<egui::Context>) {
+        let pending = self.pending_move.lock().await.take();
+
+        if let Some(pending) = pending {
+            info!(
+                "🔁 Retrying unconfirmed move at ({}, {})",
+                pending.row, pending.col
+            );
+            self.send_move(pending, ctx).await;
+        }
+    }
+
+    pub async fn dismiss_pending_move(&self) {
+        *self.pending_move.lock().await = None;
+    }
+
+    /// Host only: takes `name`'s seat and keeps them out of the game.
+    pub async fn kick_player(&self, name: String) {
+        let request = serde_json::json!({ "type": "KICK_PLAYER", "name": name });
+        if let Err(e) = self.send_json(request).await {
+            error!("❌ Failed to send KICK_PLAYER message: {}", e);
+        }
+    }
+
+    /// Host only: stops `name` watching and keeps them out of the game.
+    pub async fn ban_spectator(&self, name: String) {
+        let request = serde_json::json!({ "type": "BAN_SPECTATOR", "name": name });
+        if let Err(e) = self.send_json(request).await {
+            error!("❌ Failed to send BAN_SPECTATOR message: {}", e);
+        }
+    }
+
+    /// Asks a moderator to look at `name`, quoting what they said as the reason.
+    pub async fn report_player(&self, name: String, reason: String) {
+        let request = serde_json::json!({
+            "type": "REPORT_PLAYER",
+            "target": name,
+            "reason": reason
+        });
+        if let Err(e) = self.send_json(request).await {
+            error!("❌ Failed to send REPORT_PLAYER message: {}", e);
+        }
+    }
+
+    pub async fn send_chat(&self, text: String, team: bool) {
+        let chat_request = serde_json::json!({
+            "type": "CHAT",
+            "text": text,
+            "team": team
+        });
+
+        if let Err(e) = self.send_json(chat_request).await {
+            error!("❌ Failed to send CHAT message: {}", e);
+        }
+    }
+
+    pub async fn send_reaction(&self, emoji: &str) {
+        let reaction_request = serde_json::json!({
+            "type": "REACTION",
+            "emoji": emoji
+        });
+
+        if let Err(e) = self.send_json(reaction_request).await {
+            error!("❌ Failed to send REACTION message: {}", e);
+        }
+    }
+
+    pub async fn resign(&self, player: Player) {
+        let game_id = self.game_id.lock().await.clone();
+        let resign_request = serde_json::json!({
+            "type": "RESIGN",
+            "game_id": game_id,
+            "player": player
+        });
+
+        if let Err(e) = self.send_json(resign_request).await {
+            error!("❌ Failed to send RESIGN request: {}", e);
+        }
+    }
+
+    /// Offers a draw, or accepts the opponent's standing offer.
+    pub async fn offer_draw(&self, player: Player) {
+        let game_id = self.game_id.lock().await.clone();
+        let draw_request = serde_json::json!({
+            "type": "OFFER_DRAW",
+            "game_id": game_id,
+            "player": player
+        });
+
+        if let Err(e) = self.send_json(draw_request).await {
+            error!("❌ Failed to send OFFER_DRAW request: {}", e);
+        }
+    }
+
+    /// Asks for a break (REQUEST_PAUSE) or to end one (RESUME); the game only changes once
+    /// the opponent asks for the same.
+    pub async fn request_break(&self, player: Player, resume: bool) {
+        let game_id = self.game_id.lock().await.clone();
+        let kind = if resume { "RESUME" } else { "REQUEST_PAUSE" };
+        let break_request = serde_json::json!({
+            "type": kind,
+            "game_id": game_id,
+            "player": player
+        });
+
+        if let Err(e) = self.send_json(break_request).await {
+            error!("❌ Failed to send {} request: {}", kind, e);
+        }
+    }
+
+    /// Asks the server to ping us by email and/or webhook when a game is waiting on our move.
+    pub async fn register_notifications(&self, email: String, webhook_url: String) {
+        let register_request = serde_json::json!({
+            "type": "REGISTER_NOTIFICATIONS",
+            "email": email.trim(),
+            "webhook_url": webhook_url.trim()
+        });
+
+        if let Err(e) = self.send_json(register_request).await {
+            error!("❌ Failed to send REGISTER_NOTIFICATIONS request: {}", e);
+        }
+    }
+
+    pub async fn request_spectate_link(&self) {
+        let link_request = serde_json::json!({ "type": "CREATE_SPECTATE_LINK" });
+
+        if let Err(e) = self.send_json(link_request).await {
+            error!("❌ Failed to send CREATE_SPECTATE_LINK request: {}", e);
+        }
+    }
+
+    pub async fn request_state(&self) {
+        let game_id = self.game_id.lock().await.clone();
+        let state_request = serde_json::json!({
+            "type": "GET_STATE",
+            "game_id": game_id,
+            "token": self.spectate_token.lock().await.clone()
+        });
+
+        match self.send_json(state_request).await {
+            Ok(()) => info!("✅ GET_STATE request sent for game {}", game_id),
+            Err(e) => error!("❌ Failed to send GET_STATE request: {}", e),
+        }
+    }
+
+    pub async fn request_leaderboard(&self) {
+        let leaderboard_request = serde_json::json!({ "type": "GET_LEADERBOARD" });
+
+        if let Err(e) = self.send_json(leaderboard_request).await {
+            error!("❌ Failed to send GET_LEADERBOARD request: {}", e);
+        }
+    }
+
+    pub async fn request_online_players(&self) {
+        let online_request = serde_json::json!({ "type": "WHO_IS_ONLINE" });
+
+        if let Err(e) = self.send_json(online_request).await {
+            error!("❌ Failed to send WHO_IS_ONLINE request: {}", e);
         }
     }
 
@@ -354,18 +1586,38 @@ impl GameService {
             "game_id": game_id
         });
 
-        let mut socket_write_guard = self.socket_write.lock().await;
-        if let Some(writer) = socket_write_guard.as_mut() {
-            if let Err(e) = writer
-                .send(Message::Text(reset_request.to_string().into()))
-                .await
-            {
-                error!("❌ Failed to send RESET_GAME request: {}", e);
-            } else {
-                info!("✅ RESET_GAME request sent successfully");
-            }
-        } else {
-            error!("❌ WebSocket writer unavailable");
+        match self.send_json(reset_request).await {
+            Ok(()) => info!("✅ RESET_GAME request sent successfully"),
+            Err(e) => error!("❌ Failed to send RESET_GAME request: {}", e),
         }
     }
 }
+
+/// A JOIN_GAME or CREATE_GAME frame carrying the options for a new game.
+fn game_request(
+    message_type: &str,
+    game_id: &str,
+    player_name: &str,
+    options: &JoinOptions,
+) -> serde_json::Value {
+    serde_json::json!({
+        "type": message_type,
+        "game_id": game_id,
+        "name": player_name,
+        "private": options.private,
+        "visibility": if options.unlisted { "private" } else { "public" },
+        "correspondence": options.correspondence,
+        "team": options.team,
+        "team_name": options.team_name,
+        "locale": options.locale,
+        "kind": options.kind,
+        "board_size": options.board_size,
+        "win_length": options.win_length,
+        "first_player": options.first_player,
+        "turn_seconds": options.turn_seconds,
+        "clock_seconds": options.clock_seconds,
+        "hide_spectator_chat": options.hide_spectator_chat,
+        "winner_stays_on": options.winner_stays_on,
+        "player": options.mark
+    })
+}
@@ -0,0 +1,116 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+
+/// How often the heartbeat task pings the server.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// How often the supervisor looks at the background tasks.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Silence on a connection we believe is open before the listener counts as stalled.
+/// Several heartbeats fit in it, so a healthy server always answers well within it.
+const STALL_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Something wrong with the background tasks that keep the board up to date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    ListenerDied,
+    HeartbeatDied,
+    Stalled(Duration),
+}
+
+impl Fault {
+    pub fn describe(self) -> String {
+        match self {
+            Fault::ListenerDied => "The connection to the server dropped".to_string(),
+            Fault::HeartbeatDied => "The connection heartbeat stopped".to_string(),
+            Fault::Stalled(silent) => {
+                format!("No word from the server for {}s", silent.as_secs())
+            }
+        }
+    }
+}
+
+/// Keeps hold of the listener and heartbeat tasks so the supervisor can tell when they have
+/// died or gone quiet, and remembers what it last found for the diagnostic banner.
+#[derive(Clone, Default)]
+pub struct Watchdog {
+    listener: Arc<Mutex<Option<JoinHandle<()>>>>,
+    heartbeat: Arc<Mutex<Option<JoinHandle<()>>>>,
+    last_event: Arc<Mutex<Option<Instant>>>,
+    diagnostic: Arc<Mutex<Option<String>>>,
+    started: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    /// True the first time it is called, so only one supervisor is ever spawned.
+    pub fn start(&self) -> bool {
+        !self.started.swap(true, Ordering::SeqCst)
+    }
+
+    /// Tracks a freshly spawned listener, stopping any previous one.
+    pub async fn watch_listener(&self, handle: JoinHandle<()>) {
+        if let Some(old) = self.listener.lock().await.replace(handle) {
+            old.abort();
+        }
+        self.saw_event().await;
+    }
+
+    pub async fn watch_heartbeat(&self, handle: JoinHandle<()>) {
+        if let Some(old) = self.heartbeat.lock().await.replace(handle) {
+            old.abort();
+        }
+    }
+
+    /// Records that the listener just received a frame.
+    pub async fn saw_event(&self) {
+        *self.last_event.lock().await = Some(Instant::now());
+    }
+
+    /// Looks for a dead or silent task. `connected` is whether we think the socket is open.
+    pub async fn check(&self, connected: bool) -> Option<Fault> {
+        if is_finished(&self.listener).await {
+            return Some(Fault::ListenerDied);
+        }
+
+        let silent = self.last_event.lock().await.map(|at| at.elapsed());
+        if let Some(silent) = silent.filter(|silent| connected && *silent > STALL_TIMEOUT) {
+            return Some(Fault::Stalled(silent));
+        }
+
+        is_finished(&self.heartbeat)
+            .await
+            .then_some(Fault::HeartbeatDied)
+    }
+
+    /// Aborts both tasks ahead of a reconnect.
+    pub async fn stop_tasks(&self) {
+        for task in [&self.listener, &self.heartbeat] {
+            if let Some(handle) = task.lock().await.take() {
+                handle.abort();
+            }
+        }
+    }
+
+    pub async fn report(&self, diagnostic: String) {
+        *self.diagnostic.lock().await = Some(diagnostic);
+    }
+
+    pub fn diagnostic(&self) -> Option<String> {
+        self.diagnostic.try_lock().ok()?.clone()
+    }
+
+    pub async fn dismiss(&self) {
+        *self.diagnostic.lock().await = None;
+    }
+}
+
+async fn is_finished(task: &Mutex<Option<JoinHandle<()>>>) -> bool {
+    task.lock()
+        .await
+        .as_ref()
+        .is_some_and(|handle| handle.is_finished())
+}
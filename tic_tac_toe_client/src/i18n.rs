@@ -0,0 +1,223 @@
+use serde_json::Value;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+/// Languages the client can render system messages in, as `(code, display name)`.
+pub const LOCALES: [(&str, &str); 4] = [
+    ("en", "English"),
+    ("es", "Español"),
+    ("fr", "Français"),
+    ("de", "Deutsch"),
+];
+
+pub fn locale_name(code: &str) -> &str {
+    LOCALES
+        .iter()
+        .find(|(locale, _)| *locale == code)
+        .map(|(_, name)| *name)
+        .unwrap_or(code)
+}
+
+/// Renders a server system message, falling back from e.g. `fr-CA` to `fr` to English,
+/// and finally to the bare key when it is unknown.
+pub fn render(locale: &str, key: &str, params: &Value) -> String {
+    match lookup(locale, key) {
+        Some(template) => fill(template, params),
+        None => key.to_string(),
+    }
+}
+
+/// Renders a server ERROR by its `code`, keeping the server's English `message` for codes
+/// this client doesn't know yet. A `scope` param picks a narrower template, e.g.
+/// `BANNED.server`, and a moderator's free-text `reason` is shown as they wrote it.
+pub fn render_error(locale: &str, code: Option<&str>, params: &Value, message: &str) -> String {
+    let Some(code) = code else {
+        return message.to_string();
+    };
+    let scoped = params["scope"]
+        .as_str()
+        .and_then(|scope| lookup(locale, &format!("{code}.{scope}")));
+    let Some(template) = scoped.or_else(|| lookup(locale, code)) else {
+        return message.to_string();
+    };
+
+    let text = fill(template, params);
+    match params["reason"].as_str() {
+        Some(reason) if !template.contains("{reason}") => format!("{text}: {reason}"),
+        _ => text,
+    }
+}
+
+fn lookup(locale: &str, key: &str) -> Option<&'static str> {
+    let language = locale.split(['-', '_']).next().unwrap_or(locale);
+    template(locale, key)
+        .or_else(|| template(language, key))
+        .or_else(|| template(DEFAULT_LOCALE, key))
+}
+
+fn fill(template: &str, params: &Value) -> String {
+    let mut text = template.to_string();
+    if let Some(params) = params.as_object() {
+        for (name, value) in params {
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            text = text.replace(&format!("{{{name}}}"), &value);
+        }
+    }
+    text
+}
+
+fn template(locale: &str, key: &str) -> Option<&'static str> {
+    let template = match (locale, key) {
+        ("en", "player_joined") => "{name} joined the game",
+        ("en", "round_starting") => "Round {round} starting",
+        ("en", "player_resigned") => "{name} resigned",
+        ("en", "turn_timeout") => "{name} ran out of time",
+        ("en", "flag_fell") => "{name}'s clock ran out",
+        ("en", "player_abandoned") => "{name} left and forfeited the game",
+        ("en", "draw_offered") => "{name} offers a draw",
+        ("en", "draw_agreed") => "Draw agreed",
+        ("en", "player_kicked") => "The host removed {name} from the game",
+        ("en", "spectator_banned") => "The host banned spectator {name}",
+        ("en", "pause_requested") => "{name} asks for a break",
+        ("en", "pause_agreed") => "Game paused; it continues once both players resume",
+        ("en", "resume_requested") => "{name} is ready to carry on",
+        ("en", "resume_agreed") => "Break over, play on",
+        ("en", "seats_rotated") => "{left} makes way for {seated}",
+        ("en", "tournament_round_paired") => {
+            "Round {round} of {tournament} is paired; check the standings for your next game"
+        }
+        ("en", "tournament_finished") => "{tournament} is over, won by {winner}",
+        ("en", "moderator_removed") => "A moderator removed {name} from the game",
+        ("en", "report_received") => "Thanks, a moderator will look into {name}",
+        ("en", "NOT_YOUR_TURN") => "It's not {player}'s turn",
+        ("en", "NOT_YOUR_SEAT") => "You aren't playing as {player}",
+        ("en", "GAME_OVER") => "The game is over",
+        ("en", "CELL_TAKEN") => "That cell is already taken",
+        ("en", "COLUMN_FULL") => "That column is full",
+        ("en", "OUT_OF_BOUNDS") => "That move is off the board",
+        ("en", "PAUSED_FOR_RECONNECT") => "Paused while {name} reconnects",
+        ("en", "PAUSED_FOR_BREAK") => "Paused until both players resume",
+        ("en", "WAITING_FOR_OPPONENT") => "Waiting for an opponent",
+        ("en", "GAME_NOT_FOUND") => "Game not found",
+        ("en", "GAME_FULL") => "This game is full",
+        ("en", "BANNED") => "The host removed you from this game",
+        ("en", "BANNED.server") => "You are banned from this server",
+        ("en", "SERVER_FULL") => "The server is full right now, please try again shortly",
+
+        ("es", "player_joined") => "{name} se unió a la partida",
+        ("es", "round_starting") => "Comienza la ronda {round}",
+        ("es", "player_resigned") => "{name} se rindió",
+        ("es", "turn_timeout") => "A {name} se le acabó el tiempo",
+        ("es", "flag_fell") => "Se agotó el reloj de {name}",
+        ("es", "player_abandoned") => "{name} abandonó y perdió la partida",
+        ("es", "draw_offered") => "{name} ofrece tablas",
+        ("es", "draw_agreed") => "Tablas acordadas",
+        ("es", "player_kicked") => "El anfitrión expulsó a {name} de la partida",
+        ("es", "spectator_banned") => "El anfitrión vetó al espectador {name}",
+        ("es", "pause_requested") => "{name} pide una pausa",
+        ("es", "pause_agreed") => "Partida en pausa; sigue cuando ambos jugadores reanuden",
+        ("es", "resume_requested") => "{name} está listo para seguir",
+        ("es", "resume_agreed") => "Se acabó la pausa, a jugar",
+        ("es", "seats_rotated") => "{left} cede su sitio a {seated}",
+        ("es", "tournament_round_paired") => {
+            "Emparejada la ronda {round} de {tournament}; consulta la clasificación para tu próxima partida"
+        }
+        ("es", "tournament_finished") => "{tournament} ha terminado, ganó {winner}",
+        ("es", "moderator_removed") => "Un moderador expulsó a {name} de la partida",
+        ("es", "report_received") => "Gracias, un moderador revisará a {name}",
+        ("es", "NOT_YOUR_TURN") => "No es el turno de {player}",
+        ("es", "NOT_YOUR_SEAT") => "No estás jugando como {player}",
+        ("es", "GAME_OVER") => "La partida ha terminado",
+        ("es", "CELL_TAKEN") => "Esa casilla ya está ocupada",
+        ("es", "COLUMN_FULL") => "Esa columna está llena",
+        ("es", "OUT_OF_BOUNDS") => "Esa jugada está fuera del tablero",
+        ("es", "PAUSED_FOR_RECONNECT") => "En pausa mientras {name} se reconecta",
+        ("es", "PAUSED_FOR_BREAK") => "En pausa hasta que ambos jugadores reanuden",
+        ("es", "WAITING_FOR_OPPONENT") => "Esperando a un rival",
+        ("es", "GAME_NOT_FOUND") => "No se encontró la partida",
+        ("es", "GAME_FULL") => "Esta partida está completa",
+        ("es", "BANNED") => "El anfitrión te expulsó de esta partida",
+        ("es", "BANNED.server") => "Tienes prohibida la entrada a este servidor",
+        ("es", "SERVER_FULL") => "El servidor está lleno, vuelve a intentarlo en un momento",
+
+        ("fr", "player_joined") => "{name} a rejoint la partie",
+        ("fr", "round_starting") => "La manche {round} commence",
+        ("fr", "player_resigned") => "{name} a abandonné",
+        ("fr", "turn_timeout") => "{name} n'a plus de temps",
+        ("fr", "flag_fell") => "La pendule de {name} est tombée",
+        ("fr", "player_abandoned") => "{name} est parti et perd la partie",
+        ("fr", "draw_offered") => "{name} propose la nulle",
+        ("fr", "draw_agreed") => "Partie nulle d'un commun accord",
+        ("fr", "player_kicked") => "L'hôte a exclu {name} de la partie",
+        ("fr", "spectator_banned") => "L'hôte a banni le spectateur {name}",
+        ("fr", "pause_requested") => "{name} demande une pause",
+        ("fr", "pause_agreed") => {
+            "Partie en pause ; elle reprend quand les deux joueurs le souhaitent"
+        }
+        ("fr", "resume_requested") => "{name} est prêt à reprendre",
+        ("fr", "resume_agreed") => "Fin de la pause, la partie reprend",
+        ("fr", "seats_rotated") => "{left} cède sa place à {seated}",
+        ("fr", "tournament_round_paired") => {
+            "La ronde {round} de {tournament} est appariée ; consultez le classement pour votre prochaine partie"
+        }
+        ("fr", "tournament_finished") => "{tournament} est terminé, remporté par {winner}",
+        ("fr", "moderator_removed") => "Un modérateur a exclu {name} de la partie",
+        ("fr", "report_received") => "Merci, un modérateur va examiner le cas de {name}",
+        ("fr", "NOT_YOUR_TURN") => "Ce n'est pas le tour de {player}",
+        ("fr", "NOT_YOUR_SEAT") => "Vous ne jouez pas les {player}",
+        ("fr", "GAME_OVER") => "La partie est terminée",
+        ("fr", "CELL_TAKEN") => "Cette case est déjà prise",
+        ("fr", "COLUMN_FULL") => "Cette colonne est pleine",
+        ("fr", "OUT_OF_BOUNDS") => "Ce coup est hors du plateau",
+        ("fr", "PAUSED_FOR_RECONNECT") => "En pause le temps que {name} se reconnecte",
+        ("fr", "PAUSED_FOR_BREAK") => "En pause jusqu'à ce que les deux joueurs reprennent",
+        ("fr", "WAITING_FOR_OPPONENT") => "En attente d'un adversaire",
+        ("fr", "GAME_NOT_FOUND") => "Partie introuvable",
+        ("fr", "GAME_FULL") => "Cette partie est complète",
+        ("fr", "BANNED") => "L'hôte vous a exclu de cette partie",
+        ("fr", "BANNED.server") => "Vous êtes banni de ce serveur",
+        ("fr", "SERVER_FULL") => "Le serveur est plein, réessayez dans un instant",
+
+        ("de", "player_joined") => "{name} ist dem Spiel beigetreten",
+        ("de", "round_starting") => "Runde {round} beginnt",
+        ("de", "player_resigned") => "{name} hat aufgegeben",
+        ("de", "turn_timeout") => "{name} ist die Zeit abgelaufen",
+        ("de", "flag_fell") => "{name}s Uhr ist abgelaufen",
+        ("de", "player_abandoned") => "{name} hat das Spiel verlassen und verloren",
+        ("de", "draw_offered") => "{name} bietet ein Remis an",
+        ("de", "draw_agreed") => "Remis vereinbart",
+        ("de", "player_kicked") => "Der Gastgeber hat {name} aus dem Spiel entfernt",
+        ("de", "spectator_banned") => "Der Gastgeber hat den Zuschauer {name} gesperrt",
+        ("de", "pause_requested") => "{name} bittet um eine Pause",
+        ("de", "pause_agreed") => "Spiel pausiert; es geht weiter, sobald beide fortsetzen",
+        ("de", "resume_requested") => "{name} möchte weiterspielen",
+        ("de", "resume_agreed") => "Pause vorbei, weiter geht's",
+        ("de", "seats_rotated") => "{left} macht Platz für {seated}",
+        ("de", "tournament_round_paired") => {
+            "Runde {round} von {tournament} ist ausgelost; deine nächste Partie steht in der Tabelle"
+        }
+        ("de", "tournament_finished") => "{tournament} ist vorbei, gewonnen von {winner}",
+        ("de", "moderator_removed") => "Ein Moderator hat {name} aus dem Spiel entfernt",
+        ("de", "report_received") => "Danke, ein Moderator sieht sich {name} an",
+        ("de", "NOT_YOUR_TURN") => "{player} ist nicht am Zug",
+        ("de", "NOT_YOUR_SEAT") => "Du spielst nicht als {player}",
+        ("de", "GAME_OVER") => "Das Spiel ist vorbei",
+        ("de", "CELL_TAKEN") => "Dieses Feld ist schon belegt",
+        ("de", "COLUMN_FULL") => "Diese Spalte ist voll",
+        ("de", "OUT_OF_BOUNDS") => "Dieser Zug liegt außerhalb des Bretts",
+        ("de", "PAUSED_FOR_RECONNECT") => "Pausiert, bis {name} wieder verbunden ist",
+        ("de", "PAUSED_FOR_BREAK") => "Pausiert, bis beide Spieler fortsetzen",
+        ("de", "WAITING_FOR_OPPONENT") => "Warte auf einen Gegner",
+        ("de", "GAME_NOT_FOUND") => "Spiel nicht gefunden",
+        ("de", "GAME_FULL") => "Dieses Spiel ist voll",
+        ("de", "BANNED") => "Der Gastgeber hat dich aus diesem Spiel entfernt",
+        ("de", "BANNED.server") => "Du bist auf diesem Server gesperrt",
+        ("de", "SERVER_FULL") => "Der Server ist gerade voll, bitte versuch es gleich noch einmal",
+
+        _ => return None,
+    };
+    Some(template)
+}
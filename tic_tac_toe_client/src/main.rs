@@ -1,13 +1,27 @@
 use tracing::info;
 use tracing_subscriber::fmt;
 
+mod bug_report;
+mod command_palette;
 mod game_app;
 mod game_service;
+mod i18n;
+mod skins;
+mod version;
 
 use game_app::GameApp;
 
 #[tokio::main]
 async fn main() {
+    if std::env::args().any(|arg| arg == "--version" || arg == "-V") {
+        println!(
+            "tic_tac_toe {} (built at {})",
+            version::short(),
+            version::BUILD_TIME
+        );
+        return;
+    }
+
     fmt::init();
 
     info!("🚀 Starting Tic-Tac-Toe Client...");
@@ -0,0 +1,49 @@
+use eframe::egui::Color32;
+
+/// How marks are drawn on the board.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skin {
+    pub x_mark: &'static str,
+    pub o_mark: &'static str,
+    pub x_color: Color32,
+    pub o_color: Color32,
+    /// Shown above the game while the event is running.
+    pub banner: Option<&'static str>,
+}
+
+pub const DEFAULT_SKIN: Skin = Skin {
+    x_mark: "X",
+    o_mark: "O",
+    x_color: Color32::from_rgb(255, 99, 71),
+    o_color: Color32::from_rgb(34, 139, 34),
+    banner: None,
+};
+
+/// The bundled skin for an event theme the server advertises. Themes this build
+/// doesn't know about fall back to the default board.
+pub fn for_theme(theme: Option<&str>) -> Skin {
+    match theme {
+        Some("halloween") => Skin {
+            x_mark: "🎃",
+            o_mark: "👻",
+            x_color: Color32::from_rgb(255, 140, 0),
+            o_color: Color32::from_rgb(200, 200, 220),
+            banner: Some("🎃 Happy Halloween!"),
+        },
+        Some("winter") => Skin {
+            x_mark: "❄",
+            o_mark: "⛄",
+            x_color: Color32::from_rgb(135, 206, 250),
+            o_color: Color32::from_rgb(240, 248, 255),
+            banner: Some("❄ Winter event"),
+        },
+        Some("spring") => Skin {
+            x_mark: "🌷",
+            o_mark: "🌼",
+            x_color: Color32::from_rgb(255, 105, 180),
+            o_color: Color32::from_rgb(255, 215, 0),
+            banner: Some("🌷 Spring has sprung"),
+        },
+        _ => DEFAULT_SKIN,
+    }
+}
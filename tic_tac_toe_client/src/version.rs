@@ -0,0 +1,10 @@
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Short commit hash, or `unknown` when built outside a git checkout.
+pub const GIT_HASH: &str = env!("GIT_HASH");
+/// Unix seconds.
+pub const BUILD_TIME: &str = env!("BUILD_TIME");
+
+/// e.g. `0.1.0 (abc1234)`.
+pub fn short() -> String {
+    format!("{VERSION} ({GIT_HASH})")
+}
@@ -0,0 +1,60 @@
+use std::env;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Embeds the commit and build time reported by `--version`, `GET /version` and HELLO.
+/// Hosts that build without a `.git` directory can pass the commit in `GIT_HASH`.
+fn main() {
+    let git_hash = env::var("GIT_HASH")
+        .ok()
+        .or_else(|| {
+            let output = Command::new("git")
+                .args(["rev-parse", "--short", "HEAD"])
+                .output()
+                .ok()?;
+            output.status.success().then_some(())?;
+            String::from_utf8(output.stdout).ok()
+        })
+        .map(|hash| hash.trim().to_string())
+        .filter(|hash| !hash.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let built_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0);
+
+    println!("cargo:rustc-env=GIT_HASH={git_hash}");
+    println!("cargo:rustc-env=BUILD_TIME={built_at}");
+    println!("cargo:rerun-if-env-changed=GIT_HASH");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+    println!("cargo:rerun-if-changed=src");
+
+    #[cfg(feature = "grpc")]
+    bots_service();
+}
+
+/// Generates the gRPC service from the message types in `src/grpc.rs`, so that building
+/// doesn't need `protoc`. `proto/bots.proto` must describe the same service.
+#[cfg(feature = "grpc")]
+fn bots_service() {
+    let play = tonic_build::manual::Method::builder()
+        .name("play")
+        .route_name("Play")
+        .input_type("crate::grpc::BotMessage")
+        .output_type("crate::grpc::ServerEvent")
+        .codec_path("tonic_prost::ProstCodec")
+        .client_streaming()
+        .server_streaming()
+        .build();
+    let service = tonic_build::manual::Service::builder()
+        .name("Bots")
+        .package("tictactoe.v1")
+        .method(play)
+        .build();
+    tonic_build::manual::Builder::new()
+        .build_client(false)
+        .build_transport(false)
+        .compile(&[service]);
+}
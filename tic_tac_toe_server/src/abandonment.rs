@@ -0,0 +1,38 @@
+use crate::app_state::AppState;
+use crate::game::message::UpdateReason;
+
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tracing::info;
+
+/// Awards live games to the remaining player once their opponent's grace period runs out.
+pub async fn enforce_abandonment(app_state: Arc<AppState>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        // Read every tick, since a config reload may turn the grace period on or off
+        let Some(grace) = app_state.tunables().abandon_grace else {
+            continue;
+        };
+
+        let mut games = app_state.games.write().await;
+
+        for (game_id, game) in games.iter_mut() {
+            if let Some(loser) = game.forfeit_on_abandon(grace) {
+                info!(
+                    "🚪 {:?} abandoned game {}. {:?} wins.",
+                    loser, game_id, game.winner
+                );
+                app_state.finish_game(game_id, game).await;
+                app_state.broadcast_state(game_id, game, UpdateReason::Abandon);
+
+                let name = game.name_of(loser);
+                app_state.broadcast_system(
+                    game_id,
+                    game,
+                    "player_abandoned",
+                    json!({ "name": name }),
+                );
+            }
+        }
+    }
+}
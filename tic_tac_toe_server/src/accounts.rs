@@ -0,0 +1,288 @@
+use crate::app_state::AppState;
+use crate::auth::{self, AuthResponse, MAX_NAME_LENGTH};
+use crate::bans;
+use crate::clock::now_secs;
+use crate::players::{PlayerRegistry, DEFAULT_RATING};
+use crate::versioning::{self, Migration};
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tracing::{error, info, warn};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// PBKDF2-HMAC-SHA256 work factor for stored passwords.
+const PBKDF2_ROUNDS: u32 = 100_000;
+const MIN_PASSWORD_LENGTH: usize = 8;
+
+/// A registered player. Ratings and results stay in the player registry, keyed by the
+/// same name, so they carry over from before the player registered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Account {
+    pub display_name: String,
+    /// Hex PBKDF2 output and the hex salt it was derived with.
+    password_hash: String,
+    salt: String,
+    /// Seconds since the Unix epoch.
+    pub created_at: u64,
+}
+
+pub struct AccountStore {
+    path: PathBuf,
+    accounts: HashMap<String, Account>,
+}
+
+impl AccountStore {
+    pub fn load(path: PathBuf) -> Self {
+        let accounts = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse accounts {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        info!("🪪 Loaded {} accounts from {:?}", accounts.len(), path);
+        AccountStore { path, accounts }
+    }
+
+    pub async fn save(&self) {
+        let contents = match serde_json::to_string_pretty(&versioning::versioned(
+            &self.accounts,
+            MIGRATIONS,
+        )) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("❌ Failed to serialize accounts: {}", e);
+                return;
+            }
+        };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write accounts {:?}: {}", self.path, e);
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.accounts.contains_key(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Account> {
+        self.accounts.get(name)
+    }
+
+    fn create(&mut self, name: &str, password: &str, display_name: String) {
+        let salt = hex::encode(rand::random::<[u8; 16]>());
+        let account = Account {
+            display_name,
+            password_hash: hash_password(password, &salt),
+            salt,
+            created_at: now_secs(),
+        };
+        self.accounts.insert(name.to_string(), account);
+    }
+
+    fn check_password(&self, name: &str, password: &str) -> bool {
+        let Some(account) = self.accounts.get(name) else {
+            // Same work either way, so response times don't reveal which names exist
+            hash_password(password, "00");
+            return false;
+        };
+        let expected = hash_password(password, &account.salt);
+        // Constant-time comparison
+        expected.len() == account.password_hash.len()
+            && expected
+                .bytes()
+                .zip(account.password_hash.bytes())
+                .fold(0, |diff, (a, b)| diff | (a ^ b))
+                == 0
+    }
+}
+
+fn hash_password(password: &str, salt: &str) -> String {
+    let mut output = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(
+        password.as_bytes(),
+        salt.as_bytes(),
+        PBKDF2_ROUNDS,
+        &mut output,
+    );
+    hex::encode(output)
+}
+
+/// What `GET /players/{name}` and `JOIN_SUCCESS` show about a player.
+#[derive(Debug, Clone, Serialize)]
+pub struct Profile {
+    pub name: String,
+    pub display_name: String,
+    pub registered: bool,
+    /// Seconds since the Unix epoch; only for registered players.
+    pub member_since: Option<u64>,
+    pub rating: u32,
+    pub games_played: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+/// `name`'s profile, or `None` if they have neither an account nor a rated game.
+pub fn profile(players: &PlayerRegistry, accounts: &AccountStore, name: &str) -> Option<Profile> {
+    let record = players.players.get(name);
+    let account = accounts.get(name);
+    if record.is_none() && account.is_none() {
+        return None;
+    }
+
+    let record = record.cloned().unwrap_or_default();
+    Some(Profile {
+        name: name.to_string(),
+        display_name: account.map_or_else(|| name.to_string(), |a| a.display_name.clone()),
+        registered: account.is_some(),
+        member_since: account.map(|a| a.created_at),
+        rating: record.rating,
+        games_played: record.wins + record.losses + record.draws,
+        wins: record.wins,
+        losses: record.losses,
+        draws: record.draws,
+    })
+}
+
+/// A profile for `name` even if they have never played, for showing at join time.
+pub async fn profile_or_new(state: &AppState, name: &str) -> Profile {
+    let players = state.players.read().await;
+    let accounts = state.accounts.read().await;
+    profile(&players, &accounts, name).unwrap_or_else(|| Profile {
+        name: name.to_string(),
+        display_name: name.to_string(),
+        registered: false,
+        member_since: None,
+        rating: DEFAULT_RATING,
+        games_played: 0,
+        wins: 0,
+        losses: 0,
+        draws: 0,
+    })
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterRequest {
+    pub name: String,
+    pub password: String,
+    /// Shown instead of `name` where there is room; defaults to `name`.
+    pub display_name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoginRequest {
+    pub name: String,
+    pub password: String,
+}
+
+/// `POST /register`: creates an account for `name` and signs them in. A name already
+/// claimed with a token can only be registered by whoever holds that token.
+pub async fn register_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<RegisterRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    let Some(secret) = &state.config.jwt_secret else {
+        return Err((StatusCode::NOT_FOUND, "Accounts are disabled".to_string()));
+    };
+
+    let name = auth::account_name(&request.name, &state.word_filter)?;
+    bans::refuse_banned(&state, &name, None).await?;
+    if request.password.chars().count() < MIN_PASSWORD_LENGTH {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Passwords need at least {MIN_PASSWORD_LENGTH} characters"),
+        ));
+    }
+    let display_name = request
+        .display_name
+        .as_deref()
+        .map(str::trim)
+        .filter(|display_name| !display_name.is_empty())
+        .unwrap_or(&name)
+        .chars()
+        .take(MAX_NAME_LENGTH)
+        .collect();
+
+    let holder = auth::bearer(&headers).and_then(|token| auth::verify(secret, token));
+    let mut players = state.players.write().await;
+    let mut accounts = state.accounts.write().await;
+    if accounts.contains(&name) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("{name} is already registered"),
+        ));
+    }
+    if players.is_claimed(&name) && holder.as_deref() != Some(name.as_str()) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("{name} is already claimed by another player"),
+        ));
+    }
+
+    accounts.create(&name, &request.password, display_name);
+    players.claim(&name);
+    accounts.save().await;
+    players.save().await;
+    drop(accounts);
+    drop(players);
+    info!("🪪 Registered account {}", name);
+
+    let (token, expires_at) = auth::issue(secret, &name, state.config.jwt_ttl.as_secs());
+    Ok(Json(AuthResponse {
+        token,
+        name,
+        expires_at,
+    }))
+}
+
+/// `POST /login`: trades a registered name and password for a token.
+pub async fn login_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<LoginRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    let Some(secret) = &state.config.jwt_secret else {
+        return Err((StatusCode::NOT_FOUND, "Accounts are disabled".to_string()));
+    };
+
+    let name = request.name.trim().to_string();
+    bans::refuse_banned(&state, &name, None).await?;
+    if !state
+        .accounts
+        .read()
+        .await
+        .check_password(&name, &request.password)
+    {
+        warn!("🪪 Failed login for {:?}", name);
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Wrong name or password".to_string(),
+        ));
+    }
+
+    let (token, expires_at) = auth::issue(secret, &name, state.config.jwt_ttl.as_secs());
+    Ok(Json(AuthResponse {
+        token,
+        name,
+        expires_at,
+    }))
+}
+
+/// `GET /players/{name}`: a player's public profile.
+pub async fn profile_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<Profile>, StatusCode> {
+    let players = state.players.read().await;
+    let accounts = state.accounts.read().await;
+    profile(&players, &accounts, &name)
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
@@ -0,0 +1,217 @@
+use crate::anticheat::CheatFlag;
+use crate::app_state::AppState;
+use crate::capacity::CapacityStats;
+use crate::game::ids::{find_by_invite_code, generate_invite_code};
+use crate::game::message::game_created_message;
+use crate::game::models::Game;
+use crate::latency::LatencyStats;
+use crate::move_audit::AuditEntry;
+use crate::version::ClientVersionStats;
+use crate::word_filter::WordFlag;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tracing::{info, warn};
+
+const DEFAULT_AUDIT_LIMIT: usize = 100;
+const MAX_AUDIT_LIMIT: usize = 1000;
+
+/// Admin routes only exist when `ADMIN_TOKEN` is set, and need it as a bearer token.
+pub fn authorize(state: &AppState, headers: &HeaderMap) -> Result<(), StatusCode> {
+    let Some(token) = &state.config.admin_token else {
+        return Err(StatusCode::NOT_FOUND);
+    };
+
+    let presented = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    if presented.is_some_and(|presented| token_matches(presented, token)) {
+        Ok(())
+    } else {
+        Err(StatusCode::UNAUTHORIZED)
+    }
+}
+
+/// Compares digests in constant time, so neither how much of the token a guess got right
+/// nor its length shows in the response time.
+fn token_matches(presented: &str, token: &str) -> bool {
+    let presented = Sha256::digest(presented.as_bytes());
+    presented.ct_eq(&Sha256::digest(token.as_bytes())).into()
+}
+
+/// Accounts whose move timing looks automated. Nothing is banned automatically.
+pub async fn cheat_flags_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<CheatFlag>>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    Ok(Json(state.move_timings.read().await.flags()))
+}
+
+/// How full the in-memory games map and the connection pool are, and what keeping them
+/// bounded has cost.
+pub async fn capacity_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<CapacityStats>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    let active_games = state.games.read().await.len();
+    Ok(Json(state.capacity.stats(active_games, &state.connections)))
+}
+
+/// Move handler and broadcast timings against `MOVE_BUDGET_MS`.
+pub async fn latency_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<LatencyStats>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    Ok(Json(state.move_latency.stats()))
+}
+
+/// Connected clients grouped by the build they reported, next to the server's own.
+pub async fn client_versions_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<ClientVersionStats>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    Ok(Json(state.client_versions.read().await.stats()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveAuditQuery {
+    pub game_id: Option<String>,
+    /// Only moves sent by this player.
+    pub name: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// The latest submitted moves, accepted and rejected, oldest first, for settling disputes
+/// like "the server skipped my move".
+pub async fn move_audit_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<MoveAuditQuery>,
+) -> Result<Json<Vec<AuditEntry>>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_AUDIT_LIMIT)
+        .min(MAX_AUDIT_LIMIT);
+    let entries = state
+        .move_audit
+        .query(query.game_id.as_deref(), query.name.as_deref(), limit)
+        .await;
+    Ok(Json(entries))
+}
+
+/// Names and chat lines that got through with a blocked word, newest first, when
+/// `WORD_FILTER_ACTION=flag`.
+pub async fn word_flags_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<WordFlag>>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    Ok(Json(state.word_filter.flags()))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LogLevel {
+    /// `EnvFilter` directives, as in `LOG_LEVEL`.
+    pub level: String,
+}
+
+/// `GET /admin/log-level`: the log filter in force.
+pub async fn log_level_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<LogLevel>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    Ok(Json(LogLevel {
+        level: state.log_level(),
+    }))
+}
+
+/// `PUT /admin/log-level`: swaps the log filter without a restart, e.g. to
+/// `info,tic_tac_toe_server::game::models=debug` while looking into a stuck game. It
+/// lasts until the settings are reloaded with a different `LOG_LEVEL`, or the next restart.
+pub async fn set_log_level_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<LogLevel>,
+) -> Result<Json<LogLevel>, (StatusCode, String)> {
+    authorize(&state, &headers).map_err(|status| (status, String::new()))?;
+
+    let level = request.level.trim();
+    if level.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "No log level given".to_string()));
+    }
+    state
+        .set_log_level(level)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    warn!("🔧 Log level changed to {:?} at runtime", level);
+    Ok(Json(LogLevel {
+        level: state.log_level(),
+    }))
+}
+
+/// Puts a game back from its serialized state, such as the `game` of a saved UPDATE_STATE
+/// or `GET /games/{id}/state` on another server, so a match interrupted by a crash or a
+/// migration carries on. Its players rejoin under their names.
+pub async fn import_game_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(game_id): Path<String>,
+    Json(mut game): Json<Game>,
+) -> Response {
+    if let Err(status) = authorize(&state, &headers) {
+        return status.into_response();
+    }
+    if let Err(reason) = game.prepare_import() {
+        return (StatusCode::UNPROCESSABLE_ENTITY, reason.to_string()).into_response();
+    }
+
+    let mut games = state.games.write().await;
+    if games.contains_key(&game_id) {
+        return (StatusCode::CONFLICT, "Game ID already in use").into_response();
+    }
+    if !state.capacity.make_room(&mut games) {
+        return (StatusCode::SERVICE_UNAVAILABLE, "Server is full").into_response();
+    }
+    if game.invite_code.is_empty() || find_by_invite_code(&games, &game.invite_code).is_some() {
+        game.invite_code = generate_invite_code(&games);
+    }
+
+    info!("📥 Imported game {} at round {}", game_id, game.round);
+    let created = game_created_message(&game_id, &game);
+    games.insert(game_id, game);
+    (StatusCode::CREATED, Json(created)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_exact_token_matches() {
+        assert!(token_matches("s3cret-admin", "s3cret-admin"));
+        assert!(!token_matches("s3cret-admiN", "s3cret-admin"));
+        assert!(!token_matches("s3cret", "s3cret-admin"));
+        assert!(!token_matches("s3cret-admin ", "s3cret-admin"));
+        assert!(!token_matches("", "s3cret-admin"));
+    }
+}
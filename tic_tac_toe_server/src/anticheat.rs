@@ -0,0 +1,133 @@
+use crate::game::models::Game;
+use crate::versioning::{self, Migration};
+
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf};
+use tracing::{error, info, warn};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// Faster than any human can see the board and click.
+pub const FAST_MOVE_MS: u64 = 50;
+
+/// Rated moves needed before an account can be flagged.
+const MIN_SAMPLE: u32 = 10;
+
+/// Share of fast, engine-perfect moves above which an account is flagged.
+const FLAG_RATIO: f64 = 0.9;
+
+/// Move timing totals for one player across their rated games.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MoveTiming {
+    pub moves: u32,
+    /// Moves made in under `FAST_MOVE_MS` that perfect play would also have chosen.
+    pub fast_engine_moves: u32,
+    pub total_think_ms: u64,
+}
+
+impl MoveTiming {
+    fn suspicious(&self) -> bool {
+        self.moves >= MIN_SAMPLE && self.fast_engine_moves as f64 / self.moves as f64 >= FLAG_RATIO
+    }
+}
+
+/// An account whose timing looks automated, for a human to review.
+#[derive(Debug, Clone, Serialize)]
+pub struct CheatFlag {
+    pub name: String,
+    pub moves: u32,
+    pub fast_engine_moves: u32,
+    pub mean_think_ms: u64,
+}
+
+pub struct MoveTimingStore {
+    path: PathBuf,
+    players: HashMap<String, MoveTiming>,
+}
+
+impl MoveTimingStore {
+    pub fn load(path: PathBuf) -> Self {
+        let players: HashMap<String, MoveTiming> = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse move timings {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        info!(
+            "⏱️ Loaded move timings for {} players from {:?}",
+            players.len(),
+            path
+        );
+        MoveTimingStore { path, players }
+    }
+
+    pub async fn save(&self) {
+        let contents =
+            match serde_json::to_string(&versioning::versioned(&self.players, MIGRATIONS)) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("❌ Failed to serialize move timings: {}", e);
+                    return;
+                }
+            };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write move timings {:?}: {}", self.path, e);
+        }
+    }
+
+    /// Adds the moves of a finished rated game to each player's totals.
+    pub fn record(&mut self, game: &Game) {
+        // Only rated games are worth cheating in
+        if game.ai_player.is_some() || game.team_mode || game.player_names.len() < 2 {
+            return;
+        }
+
+        for record in &game.moves {
+            let Some(name) = game.player_names.get(&record.player) else {
+                continue;
+            };
+
+            let timing = self.players.entry(name.clone()).or_default();
+            let was_suspicious = timing.suspicious();
+
+            timing.moves += 1;
+            timing.total_think_ms += record.think_ms;
+            if record.engine_move && record.think_ms < FAST_MOVE_MS {
+                timing.fast_engine_moves += 1;
+            }
+
+            if !was_suspicious && timing.suspicious() {
+                warn!(
+                    "🚩 {} flagged: {} of {} rated moves were engine-perfect in under {}ms",
+                    name, timing.fast_engine_moves, timing.moves, FAST_MOVE_MS
+                );
+            }
+        }
+    }
+
+    /// Accounts currently flagged, most suspicious first.
+    pub fn flags(&self) -> Vec<CheatFlag> {
+        let mut flags: Vec<CheatFlag> = self
+            .players
+            .iter()
+            .filter(|(_, timing)| timing.suspicious())
+            .map(|(name, timing)| CheatFlag {
+                name: name.clone(),
+                moves: timing.moves,
+                fast_engine_moves: timing.fast_engine_moves,
+                mean_think_ms: timing.total_think_ms / timing.moves as u64,
+            })
+            .collect();
+
+        flags.sort_by(|a, b| {
+            b.fast_engine_moves
+                .cmp(&a.fast_engine_moves)
+                .then(a.name.cmp(&b.name))
+        });
+        flags
+    }
+}
@@ -1,19 +1,503 @@
-use crate::game::models::Game;
+use crate::accounts::AccountStore;
+use crate::anticheat::MoveTimingStore;
+use crate::bans::BanList;
+use crate::capacity::{ConnectionLimit, GameCapacity};
+use crate::clock::now_secs;
+use crate::config::{Config, Tunables};
+use crate::correspondence;
+use crate::game::{
+    ids::generate_invite_code,
+    message::{ChatMessage, GameEvent, SystemMessage, UpdateReason},
+    models::{empty_board, Game, Player, Seat, TimelineEvent},
+    rules::GameConfig,
+};
+use crate::latency::MoveLatency;
+use crate::log_files::LogFilter;
+use crate::matchmaking::QuickMatchQueue;
+use crate::metrics::Metrics;
+use crate::moderation::ModerationQueue;
+use crate::move_audit::MoveAudit;
+use crate::notifications::NotificationRelay;
+use crate::players::PlayerRegistry;
+use crate::presence::{OnlinePlayer, PresenceChange, PresenceTracker};
+use crate::replays::ReplayStore;
+use crate::scoreboards::ScoreStore;
+use crate::seasons::SeasonStore;
+use crate::snapshot;
+use crate::stats::ServerStats;
+use crate::tournaments::TournamentStore;
+use crate::version::ClientVersions;
+use crate::webhooks::{WebhookEvent, Webhooks};
+use crate::word_filter::WordFilter;
 
-use std::{collections::HashMap, sync::Arc};
+use serde_json::json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock as StdRwLock},
+    time::{Instant, SystemTime},
+};
 use tokio::sync::broadcast;
 use tokio::sync::RwLock;
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+pub type GameUpdate = (String, GameEvent);
 
 #[derive(Clone)]
 pub struct AppState {
     pub games: Arc<RwLock<HashMap<String, Game>>>,
-    pub tx: broadcast::Sender<(String, Game)>,
+    pub tx: broadcast::Sender<GameUpdate>,
+    pub config: Config,
+    /// The reloadable part of `config`; read it through `tunables()`.
+    tunables: Arc<StdRwLock<Tunables>>,
+    log_filter: LogFilter,
+    pub players: Arc<RwLock<PlayerRegistry>>,
+    pub seasons: Arc<RwLock<SeasonStore>>,
+    pub reports: Arc<RwLock<ModerationQueue>>,
+    pub bans: Arc<RwLock<BanList>>,
+    pub replays: Arc<RwLock<ReplayStore>>,
+    pub presence: Arc<RwLock<PresenceTracker>>,
+    /// Names coming online and going offline, for every socket rather than one game's.
+    pub presence_tx: broadcast::Sender<PresenceChange>,
+    pub accounts: Arc<RwLock<AccountStore>>,
+    pub notifications: Arc<RwLock<NotificationRelay>>,
+    pub move_timings: Arc<RwLock<MoveTimingStore>>,
+    pub scoreboards: Arc<RwLock<ScoreStore>>,
+    pub capacity: Arc<GameCapacity>,
+    pub connections: Arc<ConnectionLimit>,
+    pub move_latency: Arc<MoveLatency>,
+    pub client_versions: Arc<RwLock<ClientVersions>>,
+    pub quick_match_queue: Arc<RwLock<QuickMatchQueue>>,
+    pub webhooks: Arc<Webhooks>,
+    pub move_audit: Arc<MoveAudit>,
+    pub tournaments: Arc<RwLock<TournamentStore>>,
+    pub word_filter: Arc<WordFilter>,
+    pub stats: Arc<ServerStats>,
+    pub metrics: Arc<Metrics>,
 }
 impl AppState {
-    pub fn new(tx: broadcast::Sender<(String, Game)>) -> Self {
+    pub fn new(tx: broadcast::Sender<GameUpdate>, config: Config, log_filter: LogFilter) -> Self {
+        let mut players = PlayerRegistry::load(config.players_file.clone());
+        let seasons = SeasonStore::load(config.seasons_file.clone());
+        players.season = seasons.current.number;
+        let reports = ModerationQueue::load(config.reports_file.clone());
+        let bans = BanList::load(config.bans_file.clone());
+        let replays = ReplayStore::load(config.replays_file.clone(), config.max_replays);
+        let accounts = AccountStore::load(config.accounts_file.clone());
+        let notifications = NotificationRelay::load(config.notifications_file.clone());
+        let move_timings = MoveTimingStore::load(config.move_timings_file.clone());
+        let scoreboards = ScoreStore::load(config.scores_file.clone());
+        let mut games = correspondence::load(&config.correspondence_file);
+        // The snapshot is newer than the correspondence file for any game in both
+        games.extend(snapshot::load(
+            &config.snapshot_file,
+            config.tunables.reconnect_grace,
+        ));
+        if let Some(game_id) = &config.kiosk_game {
+            info!("🎪 Kiosk mode: hosting only game {}", game_id);
+            let invite_code = generate_invite_code(&games);
+            games.entry(game_id.clone()).or_insert_with(|| Game {
+                config: GameConfig {
+                    turn_seconds: config.tunables.turn_seconds,
+                    ..GameConfig::default()
+                },
+                invite_code,
+                ..Game::default()
+            });
+        }
+        let capacity = GameCapacity::new(config.tunables.max_games);
+        let connections = ConnectionLimit::new(config.tunables.max_connections);
+        let move_latency = MoveLatency::new(config.move_budget);
+        let webhooks = Webhooks::new(config.webhook_urls.clone());
+        let move_audit = MoveAudit::new(config.move_audit_file.clone());
+        let (presence_tx, _) = broadcast::channel(config.channel_capacity);
+        let word_filter = WordFilter::new(&config.blocked_words, config.word_filter_action);
+        if !config.blocked_words.is_empty() {
+            info!(
+                "🧼 Filtering {} blocked words ({:?})",
+                config.blocked_words.len(),
+                config.word_filter_action
+            );
+        }
+
         AppState {
-            games: Arc::new(RwLock::new(HashMap::new())),
+            games: Arc::new(RwLock::new(games)),
             tx,
+            tunables: Arc::new(StdRwLock::new(config.tunables.clone())),
+            log_filter,
+            config,
+            players: Arc::new(RwLock::new(players)),
+            seasons: Arc::new(RwLock::new(seasons)),
+            reports: Arc::new(RwLock::new(reports)),
+            bans: Arc::new(RwLock::new(bans)),
+            replays: Arc::new(RwLock::new(replays)),
+            presence: Arc::new(RwLock::new(PresenceTracker::default())),
+            presence_tx,
+            accounts: Arc::new(RwLock::new(accounts)),
+            notifications: Arc::new(RwLock::new(notifications)),
+            move_timings: Arc::new(RwLock::new(move_timings)),
+            scoreboards: Arc::new(RwLock::new(scoreboards)),
+            capacity: Arc::new(capacity),
+            connections: Arc::new(connections),
+            move_latency: Arc::new(move_latency),
+            client_versions: Arc::new(RwLock::new(ClientVersions::default())),
+            quick_match_queue: Arc::new(RwLock::new(QuickMatchQueue::default())),
+            webhooks: Arc::new(webhooks),
+            move_audit: Arc::new(move_audit),
+            tournaments: Arc::new(RwLock::new(TournamentStore::default())),
+            word_filter: Arc::new(word_filter),
+            stats: Arc::new(ServerStats::default()),
+            metrics: Arc::new(Metrics::default()),
+        }
+    }
+
+    /// The current timeouts, limits and log level, as last loaded.
+    pub fn tunables(&self) -> Tunables {
+        self.tunables
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Swaps in freshly loaded tunables without touching games or connections. Games
+    /// already created keep their move clock, and open connections their heartbeat.
+    pub fn apply_tunables(&self, tunables: Tunables) -> Result<(), String> {
+        if tunables.log_level != self.tunables().log_level {
+            self.log_filter
+                .reload(EnvFilter::new(&tunables.log_level))
+                .map_err(|e| format!("Failed to change the log level: {}", e))?;
         }
+        self.capacity.set_max_games(tunables.max_games);
+        self.connections
+            .set_max_connections(tunables.max_connections);
+        *self.tunables.write().unwrap_or_else(|e| e.into_inner()) = tunables;
+        Ok(())
+    }
+
+    /// Swaps in `directives` as the log filter, e.g. `info,tic_tac_toe_server::game=debug`.
+    /// It holds until the settings are reloaded with a different `LOG_LEVEL`; the configured
+    /// level itself is left as it was.
+    pub fn set_log_level(&self, directives: &str) -> Result<(), String> {
+        let filter = EnvFilter::try_new(directives).map_err(|e| e.to_string())?;
+        self.log_filter
+            .reload(filter)
+            .map_err(|e| format!("Failed to change the log level: {}", e))
+    }
+
+    /// The log filter in force, whether it came from `LOG_LEVEL` or `set_log_level`.
+    pub fn log_level(&self) -> String {
+        self.log_filter
+            .with_current(|filter| filter.to_string())
+            .unwrap_or_default()
+    }
+
+    /// Records ratings, move timings, the score and the replay for a game that has just
+    /// ended. Rated games also get their result digest, which goes out with the final state.
+    /// The stores are written to disk in the background, since callers hold the games lock.
+    pub async fn finish_game(&self, game_id: &str, game: &mut Game) {
+        game.record(TimelineEvent::Result {
+            winner: game.winner,
+            draw: game.draw,
+        });
+        self.stats.record_finished(game);
+
+        self.players.write().await.record_result(game);
+        self.move_timings.write().await.record(game);
+        self.scoreboards.write().await.record(game_id, game);
+        game.result_digest = self.replays.write().await.record(game_id, game);
+
+        let state = self.clone();
+        tokio::spawn(async move { state.save_results().await });
+
+        self.webhooks
+            .emit(WebhookEvent::game_finished(game_id, game));
+
+        if let Some(tournament_id) = &game.tournament {
+            let round_over = match self.tournaments.write().await.get_mut(tournament_id) {
+                Some(tournament) => tournament.record(game_id, game.winner),
+                None => false,
+            };
+            if round_over {
+                // The caller holds the games lock the next round's games go into
+                let state = self.clone();
+                let tournament_id = tournament_id.clone();
+                tokio::spawn(async move { state.start_tournament_round(&tournament_id).await });
+            }
+        }
+    }
+
+    /// Writes the stores `finish_game` records into in the background. Each is saved under
+    /// its read lock, so a later result can't be overwritten by an earlier, slower save.
+    pub async fn save_results(&self) {
+        self.players.read().await.save().await;
+        self.move_timings.read().await.save().await;
+        self.scoreboards.read().await.save().await;
+        self.replays.read().await.save().await;
+    }
+
+    /// Pairs a tournament's next round and creates its games with both seats waiting for
+    /// their players, or announces the winner after the last round.
+    pub async fn start_tournament_round(&self, tournament_id: &str) {
+        let mut tournaments = self.tournaments.write().await;
+        let Some(tournament) = tournaments.get_mut(tournament_id) else {
+            return;
+        };
+        let previous_games: Vec<String> = tournament
+            .pairings
+            .iter()
+            .filter(|p| p.round == tournament.round)
+            .filter_map(|p| p.game_id.clone())
+            .collect();
+        let pairings = tournament.pair_next_round();
+        let name = tournament.name.clone();
+        let round = tournament.round;
+        let config = tournament.config.clone();
+        let locale = tournament.locale.clone();
+        let leader = tournament.standings().first().map(|s| s.name.clone());
+        let finished = tournament.finished;
+        drop(tournaments);
+
+        let mut games = self.games.write().await;
+        if finished {
+            info!(
+                "🏅 Tournament {} is over, won by {:?}",
+                tournament_id, leader
+            );
+        } else {
+            info!("🏅 Paired round {} of tournament {}", round, tournament_id);
+        }
+        for game_id in &previous_games {
+            if let Some(game) = games.get(game_id) {
+                let (key, params) = if finished {
+                    (
+                        "tournament_finished",
+                        json!({ "tournament": name, "winner": leader }),
+                    )
+                } else {
+                    (
+                        "tournament_round_paired",
+                        json!({ "tournament": name, "round": round }),
+                    )
+                };
+                self.broadcast_system(game_id, game, key, params);
+            }
+        }
+
+        let players = self.players.read().await;
+        let now = SystemTime::now();
+        for pairing in pairings {
+            let (Some(game_id), Some(o)) = (pairing.game_id, pairing.o) else {
+                continue;
+            };
+            // A round can't wait for room, so its games go in even over the cap
+            if !self.capacity.make_room(&mut games) {
+                warn!("⚠️ Creating tournament game {} over the game cap", game_id);
+            }
+            let mut game = Game {
+                current_turn: config.first_player,
+                board: empty_board(config.rows, config.columns),
+                config: config.clone(),
+                players: vec![Player::X, Player::O],
+                player_names: [(Player::X, pairing.x), (Player::O, o)]
+                    .into_iter()
+                    .collect(),
+                // Both seats wait for their player, who forfeits by not turning up in time
+                disconnected: vec![Player::X, Player::O],
+                left_at: [(Player::X, now), (Player::O, now)].into_iter().collect(),
+                locale: locale.clone(),
+                invite_code: generate_invite_code(&games),
+                tournament: Some(tournament_id.to_string()),
+                ..Game::default()
+            };
+            players.refresh_ratings(&mut game);
+            info!(
+                "🆕 Tournament game {}: {} (X) vs {} (O)",
+                game_id,
+                game.name_of(Player::X),
+                game.name_of(Player::O)
+            );
+            self.webhooks
+                .emit(WebhookEvent::game_created(&game_id, &game));
+            games.insert(game_id, game);
+        }
+    }
+
+    /// Counts a socket for `name`, telling everyone if that brings them online.
+    pub async fn mark_online(&self, name: &str) {
+        if self.presence.write().await.connect(name) {
+            info!("🟢 {} is online", name);
+            let _ = self.presence_tx.send(PresenceChange {
+                name: name.to_string(),
+                online: true,
+            });
+        }
+    }
+
+    /// Drops a socket for `name`, telling everyone if it was their last one.
+    pub async fn mark_offline(&self, name: &str) {
+        if self.presence.write().await.disconnect(name) {
+            info!("⚪ {} is offline", name);
+            let _ = self.presence_tx.send(PresenceChange {
+                name: name.to_string(),
+                online: false,
+            });
+        }
+    }
+
+    /// Everyone online, and whether each is in the middle of a game.
+    pub async fn online_players(&self) -> Vec<OnlinePlayer> {
+        let names = self.presence.read().await.online();
+        let games = self.games.read().await;
+        names
+            .into_iter()
+            .map(|name| {
+                let playing = games.values().any(|game| {
+                    !game.game_over && game.players.len() == 2 && game.seat_of(&name).is_some()
+                });
+                OnlinePlayer { name, playing }
+            })
+            .collect()
+    }
+
+    /// Releases a socket's hold on its seat, telling the room once nobody holds it any more.
+    /// A seat with no opponent yet is vacated outright; otherwise it stays marked
+    /// disconnected until its player rejoins under the same name or abandons it.
+    pub async fn leave_seat(&self, seat: &Seat) {
+        let mut games = self.games.write().await;
+        let Some(game) = games.get_mut(&seat.game_id) else {
+            return;
+        };
+        // Already vacated, e.g. by the host kicking its player
+        if !game.players.contains(&seat.player) {
+            return;
+        }
+        if !game.disconnect(seat.player) {
+            return;
+        }
+
+        let name = game.name_of(seat.player);
+        info!("🔌 {} ({:?}) left game {}", name, seat.player, seat.game_id);
+        let event = GameEvent::PlayerLeft {
+            player: seat.player,
+            name,
+        };
+        let _ = self.tx.send((seat.game_id.clone(), event));
+
+        // Nobody else has sat down yet, so there is no match to hold the seat for.
+        // Correspondence games keep it, since their players leave between every move.
+        if game.players.len() < 2 && !game.correspondence {
+            info!(
+                "🪑 Seat {:?} in game {} is free again",
+                seat.player, seat.game_id
+            );
+            game.vacate(seat.player);
+            self.broadcast_state(&seat.game_id, game, UpdateReason::Leave);
+        } else if let Some(grace) = self.tunables().reconnect_grace {
+            if game.pause(seat.player, grace) {
+                info!("⏸️ Game {} paused for {:?}", seat.game_id, grace);
+                self.broadcast_state(&seat.game_id, game, UpdateReason::Pause);
+            }
+        }
+    }
+
+    /// Takes `name` out of the line for a seat in `game_id`, telling the room if they were in it.
+    pub async fn leave_line(&self, game_id: &str, name: &str) {
+        let mut games = self.games.write().await;
+        let Some(game) = games.get_mut(game_id) else {
+            return;
+        };
+        if game.leave_line(name) {
+            info!("🎟️ {} left the line for game {}", name, game_id);
+            self.broadcast_state(game_id, game, UpdateReason::Queue);
+        }
+    }
+
+    /// The winner-stays-on rooms `name` is waiting in line for a seat in.
+    pub async fn lines_joined(&self, name: &str) -> Vec<String> {
+        self.games
+            .read()
+            .await
+            .iter()
+            .filter(|(_, game)| game.challengers.iter().any(|n| n == name))
+            .map(|(game_id, _)| game_id.clone())
+            .collect()
+    }
+
+    pub fn broadcast_state(&self, game_id: &str, game: &Game, reason: UpdateReason) {
+        let event = GameEvent::StateUpdate {
+            game: Box::new(game.clone()),
+            reason,
+            sent_at: Instant::now(),
+        };
+        let _ = self.tx.send((game_id.to_string(), event));
+    }
+
+    /// Counts a spectator, watching under `name` if they gave one, in or out of `game_id`
+    /// and tells the room how many are watching.
+    pub async fn count_spectator(&self, game_id: &str, name: Option<&str>, watching: bool) {
+        let mut games = self.games.write().await;
+        let Some(game) = games.get_mut(game_id) else {
+            return;
+        };
+        game.spectators = if watching {
+            game.spectators + 1
+        } else {
+            game.spectators.saturating_sub(1)
+        };
+        match name {
+            Some(name) if watching => game.spectator_names.push(name.to_string()),
+            Some(name) => {
+                if let Some(index) = game.spectator_names.iter().position(|n| n == name) {
+                    game.spectator_names.remove(index);
+                }
+            }
+            None => {}
+        }
+        self.broadcast_state(game_id, game, UpdateReason::Spectators);
+    }
+
+    /// Takes `name`'s seat or spectator place in the game and keeps them out of it, telling
+    /// the room with an update for `reason`, the system message `key`, and `name`'s sockets
+    /// with REMOVED.
+    pub fn remove_from_game(
+        &self,
+        game_id: &str,
+        game: &mut Game,
+        name: &str,
+        reason: UpdateReason,
+        key: &'static str,
+    ) {
+        if game.ban(name).is_none() {
+            game.version += 1;
+        }
+        self.broadcast_state(game_id, game, reason);
+        self.broadcast_system(game_id, game, key, json!({ "name": name }));
+        let event = GameEvent::Removed {
+            name: name.to_string(),
+        };
+        let _ = self.tx.send((game_id.to_string(), event));
+    }
+
+    pub fn broadcast_system(
+        &self,
+        game_id: &str,
+        game: &Game,
+        key: &'static str,
+        params: serde_json::Value,
+    ) {
+        let timestamp = now_secs();
+        let system = SystemMessage {
+            key,
+            params,
+            locale: game.locale.clone(),
+            timestamp,
+        };
+        let _ = self
+            .tx
+            .send((game_id.to_string(), GameEvent::System(system)));
+    }
+
+    pub fn broadcast_chat(&self, game_id: &str, chat: ChatMessage) {
+        let _ = self.tx.send((game_id.to_string(), GameEvent::Chat(chat)));
     }
 }
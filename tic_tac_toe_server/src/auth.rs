@@ -0,0 +1,278 @@
+use crate::app_state::AppState;
+use crate::bans::refuse_banned;
+use crate::clock::now_secs;
+use crate::word_filter::{Verdict, WordFilter};
+
+use axum::extract::State;
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::Json;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// `{"alg":"HS256","typ":"JWT"}`, the only header we issue or accept.
+const HEADER: &str = "eyJhbGciOiJIUzI1NiIsInR5cCI6IkpXVCJ9";
+
+pub const MAX_NAME_LENGTH: usize = 32;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    /// The player name the token speaks for.
+    sub: String,
+    iat: u64,
+    exp: u64,
+}
+
+/// Issues an HS256 JWT for `name`, returning it with its expiry (seconds since the epoch).
+pub fn issue(secret: &[u8], name: &str, ttl_secs: u64) -> (String, u64) {
+    let iat = now_secs();
+    let claims = Claims {
+        sub: name.to_string(),
+        iat,
+        exp: iat + ttl_secs,
+    };
+    let payload = URL_SAFE_NO_PAD.encode(serde_json::to_vec(&claims).expect("claims serialize"));
+    let signing_input = format!("{HEADER}.{payload}");
+    let signature = URL_SAFE_NO_PAD.encode(mac(secret, &signing_input).finalize().into_bytes());
+    (format!("{signing_input}.{signature}"), claims.exp)
+}
+
+/// The player name `token` was issued for, if we signed it and it hasn't expired.
+pub fn verify(secret: &[u8], token: &str) -> Option<String> {
+    let (signing_input, signature) = token.rsplit_once('.')?;
+    let (header, payload) = signing_input.split_once('.')?;
+    // Pinning the header rules out `alg: none` and algorithm confusion
+    if header != HEADER {
+        return None;
+    }
+
+    let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+    mac(secret, signing_input).verify_slice(&signature).ok()?;
+
+    let claims: Claims = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()?;
+    (claims.exp > now_secs()).then_some(claims.sub)
+}
+
+/// Proof of holding `name`'s seat in `game_id`, handed out in JOIN_SUCCESS for moves over
+/// HTTP. It is only good for that one seat, and for as long as `secret` stays the same.
+pub fn seat_token(secret: &[u8], game_id: &str, name: &str) -> String {
+    let signature = mac(secret, &seat_signing_input(game_id, name)).finalize();
+    URL_SAFE_NO_PAD.encode(signature.into_bytes())
+}
+
+pub fn verify_seat_token(secret: &[u8], game_id: &str, name: &str, token: &str) -> bool {
+    URL_SAFE_NO_PAD.decode(token).is_ok_and(|signature| {
+        mac(secret, &seat_signing_input(game_id, name))
+            .verify_slice(&signature)
+            .is_ok()
+    })
+}
+
+/// Newlines never appear in a JWT's signing input, so neither kind of token passes for
+/// the other.
+fn seat_signing_input(game_id: &str, name: &str) -> String {
+    format!("seat\n{game_id}\n{name}")
+}
+
+/// The token from an `Authorization: Bearer` header, if there is one.
+pub fn bearer(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)?
+        .to_str()
+        .ok()?
+        .strip_prefix("Bearer ")
+}
+
+/// The name proven by the request's bearer token, when accounts are enabled and it checks out.
+pub fn bearer_name(state: &AppState, headers: &HeaderMap) -> Option<String> {
+    let secret = state.config.jwt_secret.as_ref()?;
+    verify(secret, bearer(headers)?)
+}
+
+/// `name` cleaned up by `clean_name`, or the 400 to answer with if it can't be used.
+/// A name to be claimed must get past `filter` as it is, since starring it out would
+/// claim a different one.
+pub fn account_name(name: &str, filter: &WordFilter) -> Result<String, (StatusCode, String)> {
+    let name = clean_name(name).map_err(|reason| (StatusCode::BAD_REQUEST, reason))?;
+    match filter.check("name", &name, &name) {
+        Verdict::Allowed => Ok(name),
+        Verdict::Masked(_) | Verdict::Rejected => Err((
+            StatusCode::BAD_REQUEST,
+            "That name isn't allowed".to_string(),
+        )),
+    }
+}
+
+/// `name` without control or invisible characters, with whitespace runs squeezed to one
+/// space, or why it can't be used as a name once that is done.
+pub fn clean_name(name: &str) -> Result<String, String> {
+    // Zero-width and bidirectional formatting characters, which can disguise a name
+    let invisible = |c: char| {
+        matches!(
+            c,
+            '\u{200B}'..='\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2060}'..='\u{2069}' | '\u{FEFF}'
+        )
+    };
+    let name = name
+        .split_whitespace()
+        .map(|word| {
+            word.chars()
+                .filter(|c| !c.is_control() && !invisible(*c))
+                .collect::<String>()
+        })
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+        return Err(format!("Names must be 1 to {MAX_NAME_LENGTH} characters"));
+    }
+    Ok(name)
+}
+
+fn mac(secret: &[u8], signing_input: &str) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(signing_input.as_bytes());
+    mac
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuthRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuthResponse {
+    pub token: String,
+    pub name: String,
+    pub expires_at: u64,
+}
+
+/// `POST /auth`: claims `name` and returns a token that proves it. The first caller to ask
+/// for a name gets it; after that only a holder of a valid token for that name can get a
+/// fresh one, so the name's rating and stats can't be played under by anyone else.
+pub async fn auth_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<AuthRequest>,
+) -> Result<Json<AuthResponse>, (StatusCode, String)> {
+    let Some(secret) = &state.config.jwt_secret else {
+        return Err((StatusCode::NOT_FOUND, "Token auth is disabled".to_string()));
+    };
+
+    let name = account_name(&request.name, &state.word_filter)?;
+    refuse_banned(&state, &name, None).await?;
+    let holder = bearer(&headers).and_then(|token| verify(secret, token));
+    let mut players = state.players.write().await;
+    if state.accounts.read().await.contains(&name) && holder.as_deref() != Some(name.as_str()) {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("{name} has an account; log in with its password"),
+        ));
+    }
+    if players.is_claimed(&name) && holder.as_deref() != Some(name.as_str()) {
+        warn!("🔐 Refused a token for claimed name {:?}", name);
+        return Err((
+            StatusCode::CONFLICT,
+            format!("{name} is already claimed by another player"),
+        ));
+    }
+    if players.claim(&name) {
+        info!("🔐 {} claimed their name", name);
+        players.save().await;
+    }
+    drop(players);
+
+    let (token, expires_at) = issue(secret, &name, state.config.jwt_ttl.as_secs());
+    Ok(Json(AuthResponse {
+        token,
+        name,
+        expires_at,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECRET: &[u8] = b"test secret";
+
+    #[test]
+    fn a_fresh_token_names_its_player() {
+        let (token, expires_at) = issue(SECRET, "ann", 60);
+        assert_eq!(verify(SECRET, &token).as_deref(), Some("ann"));
+        assert!(expires_at > now_secs());
+    }
+
+    #[test]
+    fn a_tampered_token_is_refused() {
+        let (token, _) = issue(SECRET, "ann", 60);
+        let (signing_input, signature) = token.rsplit_once('.').unwrap();
+
+        // A signature that differs anywhere
+        let mut forged = signature.to_string();
+        let last = if forged.ends_with('A') { "B" } else { "A" };
+        forged.replace_range(forged.len() - 1.., last);
+        assert_eq!(verify(SECRET, &format!("{signing_input}.{forged}")), None);
+
+        // Another player's claims under the original signature
+        let (other, _) = issue(SECRET, "bob", 60);
+        let other_payload = other.split('.').nth(1).unwrap();
+        assert_eq!(
+            verify(SECRET, &format!("{HEADER}.{other_payload}.{signature}")),
+            None
+        );
+
+        // The right token checked against another secret
+        assert_eq!(verify(b"another secret", &token), None);
+    }
+
+    #[test]
+    fn an_expired_token_is_refused() {
+        let (token, _) = issue(SECRET, "ann", 0);
+        assert_eq!(verify(SECRET, &token), None);
+    }
+
+    #[test]
+    fn a_foreign_alg_header_is_refused() {
+        let (token, _) = issue(SECRET, "ann", 60);
+        let payload = token.split('.').nth(1).unwrap();
+        for header in [
+            r#"{"alg":"none","typ":"JWT"}"#,
+            r#"{"alg":"HS512","typ":"JWT"}"#,
+            r#"{"typ":"JWT","alg":"HS256"}"#,
+        ] {
+            let header = URL_SAFE_NO_PAD.encode(header);
+            let signing_input = format!("{header}.{payload}");
+            // Even signed with our own secret, only the pinned header is accepted
+            let signature =
+                URL_SAFE_NO_PAD.encode(mac(SECRET, &signing_input).finalize().into_bytes());
+            assert_eq!(
+                verify(SECRET, &format!("{signing_input}.{signature}")),
+                None
+            );
+            assert_eq!(verify(SECRET, &format!("{signing_input}.")), None);
+        }
+    }
+
+    #[test]
+    fn a_seat_token_is_only_good_for_its_own_seat() {
+        let token = seat_token(SECRET, "g1", "ann");
+        assert!(verify_seat_token(SECRET, "g1", "ann", &token));
+        assert!(!verify_seat_token(SECRET, "g1", "bob", &token));
+        assert!(!verify_seat_token(SECRET, "g2", "ann", &token));
+        assert!(!verify_seat_token(b"another secret", "g1", "ann", &token));
+        assert!(!verify_seat_token(SECRET, "g1", "ann", "not base64!"));
+
+        // Nor does a sign-in token pass for one, or the other way round
+        let (jwt, _) = issue(SECRET, "ann", 60);
+        assert!(!verify_seat_token(SECRET, "g1", "ann", &jwt));
+        assert_eq!(verify(SECRET, &token), None);
+    }
+}
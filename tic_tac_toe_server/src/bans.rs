@@ -0,0 +1,308 @@
+//! Server-wide bans, as opposed to a host keeping someone out of one game: a banned name,
+//! account or address is turned away at the handshake or on its next join, on every game.
+
+use crate::admin::authorize;
+use crate::app_state::AppState;
+use crate::client_ip::{in_network, parse_network};
+use crate::clock::now_secs;
+use crate::game::error::{BanScope, GameError};
+use crate::versioning::{self, Migration};
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::{net::IpAddr, path::PathBuf, sync::Arc};
+use tracing::{error, info, warn};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// What a ban matches against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BanKind {
+    /// Anyone playing under the name, signed in or not.
+    Name,
+    /// Only the signed-in account; a guest may still use the name where it isn't claimed.
+    Account,
+    /// An address or CIDR range, e.g. `203.0.113.7` or `2001:db8::/32`.
+    Ip,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ban {
+    pub id: u64,
+    pub kind: BanKind,
+    pub value: String,
+    /// Shown to the banned player alongside the `BANNED` error.
+    pub reason: Option<String>,
+    pub banned_at: u64,
+}
+
+impl Ban {
+    fn matches(&self, name: Option<&str>, account: Option<&str>, ip: Option<IpAddr>) -> bool {
+        let same = |other: Option<&str>| {
+            other.is_some_and(|other| other.eq_ignore_ascii_case(&self.value))
+        };
+        match self.kind {
+            BanKind::Name => same(name) || same(account),
+            BanKind::Account => same(account),
+            BanKind::Ip => ip.is_some_and(|ip| {
+                parse_network(&self.value)
+                    .is_some_and(|(network, prefix)| in_network(ip.to_canonical(), network, prefix))
+            }),
+        }
+    }
+}
+
+/// Every ban in force, saved to `BANS_FILE` so they outlive a restart.
+pub struct BanList {
+    path: PathBuf,
+    bans: Vec<Ban>,
+}
+
+impl BanList {
+    pub fn load(path: PathBuf) -> Self {
+        let bans: Vec<Ban> = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse bans {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        info!("🚫 Loaded {} bans from {:?}", bans.len(), path);
+        BanList { path, bans }
+    }
+
+    pub async fn save(&self) {
+        let contents =
+            match serde_json::to_string_pretty(&versioning::versioned(&self.bans, MIGRATIONS)) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("❌ Failed to serialize bans: {}", e);
+                    return;
+                }
+            };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write bans {:?}: {}", self.path, e);
+        }
+    }
+
+    /// The first ban covering any of the given name, signed-in account or address.
+    pub fn find(
+        &self,
+        name: Option<&str>,
+        account: Option<&str>,
+        ip: Option<IpAddr>,
+    ) -> Option<&Ban> {
+        self.bans.iter().find(|ban| ban.matches(name, account, ip))
+    }
+
+    /// Bans `value`, or updates the reason on an existing ban of the same thing.
+    fn add(&mut self, kind: BanKind, value: String, reason: Option<String>) -> Ban {
+        if let Some(ban) = self
+            .bans
+            .iter_mut()
+            .find(|ban| ban.kind == kind && ban.value.eq_ignore_ascii_case(&value))
+        {
+            ban.reason = reason;
+            return ban.clone();
+        }
+
+        let ban = Ban {
+            id: self
+                .bans
+                .iter()
+                .map(|ban| ban.id)
+                .max()
+                .map_or(1, |id| id + 1),
+            kind,
+            value,
+            reason,
+            banned_at: now_secs(),
+        };
+        self.bans.push(ban.clone());
+        ban
+    }
+
+    fn remove(&mut self, id: u64) -> Option<Ban> {
+        let index = self.bans.iter().position(|ban| ban.id == id)?;
+        Some(self.bans.remove(index))
+    }
+}
+
+/// Refuses a banned name or account, or a banned address when `ip` is known: tokens on
+/// `/auth`, `/register` and `/login`, and moves on `POST /games/{id}/moves`.
+pub async fn refuse_banned(
+    state: &AppState,
+    name: &str,
+    ip: Option<IpAddr>,
+) -> Result<(), (StatusCode, String)> {
+    let bans = state.bans.read().await;
+    match bans.find(Some(name), Some(name), ip) {
+        Some(ban) => {
+            warn!("🚫 Refused banned {:?} ({:?})", name, ip);
+            let error = GameError::Banned {
+                scope: BanScope::Server,
+                reason: ban.reason.clone(),
+            };
+            Err((StatusCode::FORBIDDEN, error.to_string()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// `GET /admin/bans`: every ban in force, oldest first.
+pub async fn bans_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Ban>>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    Ok(Json(state.bans.read().await.bans.clone()))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BanRequest {
+    pub kind: BanKind,
+    pub value: String,
+    pub reason: Option<String>,
+}
+
+/// `POST /admin/bans`: bans a name, account or address. Players already in a game stay
+/// there until they next join one; `POST /admin/reports/{id}` with `remove` takes them out.
+pub async fn ban_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<BanRequest>,
+) -> Result<Json<Ban>, (StatusCode, String)> {
+    authorize(&state, &headers).map_err(|status| (status, String::new()))?;
+
+    let value = request.value.trim().to_string();
+    if value.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Nothing to ban".to_string()));
+    }
+    if request.kind == BanKind::Ip && parse_network(&value).is_none() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("{value:?} isn't an address or CIDR range"),
+        ));
+    }
+    let reason = request.reason.filter(|reason| !reason.trim().is_empty());
+
+    let mut bans = state.bans.write().await;
+    let ban = bans.add(request.kind, value, reason);
+    bans.save().await;
+    info!("🚫 Banned {:?} {} (ban {})", ban.kind, ban.value, ban.id);
+    Ok(Json(ban))
+}
+
+/// `DELETE /admin/bans/{id}`: lifts a ban, answering with what it was.
+pub async fn unban_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+) -> Result<Json<Ban>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    let mut bans = state.bans.write().await;
+    let ban = bans.remove(id).ok_or(StatusCode::NOT_FOUND)?;
+    bans.save().await;
+    info!("🚫 Lifted ban {} on {:?} {}", ban.id, ban.kind, ban.value);
+    Ok(Json(ban))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list() -> BanList {
+        BanList {
+            path: PathBuf::from("bans.json"),
+            bans: Vec::new(),
+        }
+    }
+
+    fn ip(address: &str) -> Option<IpAddr> {
+        Some(address.parse().unwrap())
+    }
+
+    #[test]
+    fn name_bans_cover_guests_and_accounts() {
+        let mut bans = list();
+        bans.add(BanKind::Name, "Mallory".to_string(), None);
+        assert!(bans.find(Some("mallory"), None, None).is_some());
+        assert!(bans.find(None, Some("MALLORY"), None).is_some());
+        assert!(bans.find(Some("mal"), None, None).is_none());
+    }
+
+    #[test]
+    fn account_bans_leave_unclaimed_guests_alone() {
+        let mut bans = list();
+        bans.add(BanKind::Account, "eve".to_string(), None);
+        assert!(bans.find(Some("eve"), None, None).is_none());
+        assert!(bans.find(Some("eve"), Some("eve"), None).is_some());
+    }
+
+    #[test]
+    fn address_bans_match_ranges_and_mapped_addresses() {
+        let mut bans = list();
+        bans.add(BanKind::Ip, "203.0.113.0/24".to_string(), None);
+        bans.add(BanKind::Ip, "2001:db8::/32".to_string(), None);
+        assert!(bans.find(None, None, ip("203.0.113.7")).is_some());
+        assert!(bans.find(None, None, ip("::ffff:203.0.113.7")).is_some());
+        assert!(bans.find(None, None, ip("2001:db8:1::9")).is_some());
+        assert!(bans.find(None, None, ip("203.0.114.7")).is_none());
+        assert!(bans.find(Some("ann"), None, None).is_none());
+    }
+
+    #[test]
+    fn banning_twice_updates_the_reason() {
+        let mut bans = list();
+        let first = bans.add(BanKind::Name, "bob".to_string(), None);
+        let again = bans.add(BanKind::Name, "BOB".to_string(), Some("spam".to_string()));
+        assert_eq!(again.id, first.id);
+        assert_eq!(bans.bans.len(), 1);
+        assert_eq!(
+            bans.find(Some("bob"), None, None)
+                .unwrap()
+                .reason
+                .as_deref(),
+            Some("spam")
+        );
+    }
+
+    #[test]
+    fn lifted_bans_stop_matching() {
+        let mut bans = list();
+        let bob = bans.add(BanKind::Name, "bob".to_string(), None);
+        let eve = bans.add(BanKind::Name, "eve".to_string(), None);
+        assert_eq!(bans.remove(bob.id).map(|ban| ban.value), Some("bob".into()));
+        assert!(bans.remove(bob.id).is_none());
+        assert!(bans.find(Some("bob"), None, None).is_none());
+        // Ids aren't handed out again while a later ban still holds one
+        assert!(bans.add(BanKind::Name, "cat".to_string(), None).id > eve.id);
+    }
+
+    #[tokio::test]
+    async fn bans_outlive_a_restart() {
+        let path = std::env::temp_dir().join(format!("bans-{}.json", std::process::id()));
+        let mut bans = BanList {
+            path: path.clone(),
+            bans: Vec::new(),
+        };
+        bans.add(
+            BanKind::Ip,
+            "198.51.100.1".to_string(),
+            Some("abuse".into()),
+        );
+        bans.save().await;
+
+        let reloaded = BanList::load(path.clone());
+        std::fs::remove_file(&path).unwrap();
+        let ban = reloaded.find(None, None, ip("198.51.100.1")).unwrap();
+        assert_eq!(ban.reason.as_deref(), Some("abuse"));
+    }
+}
@@ -0,0 +1,199 @@
+use crate::game::models::Game;
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Share of `MAX_GAMES` in use at which the server starts warning.
+const WARN_RATIO: f64 = 0.8;
+
+/// Keeps the games map under `MAX_GAMES` so a flood of joins can't exhaust memory.
+pub struct GameCapacity {
+    /// 0 when there is no cap, so a reload can change it in place.
+    max_games: AtomicUsize,
+    evicted: AtomicU64,
+    rejected: AtomicU64,
+    warned: AtomicBool,
+}
+
+/// Which cap a `SERVER_FULL` error was hit by.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CapacityLimit {
+    Games,
+    Connections,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CapacityStats {
+    pub active_games: usize,
+    pub max_games: Option<usize>,
+    /// Finished games dropped from memory to make room.
+    pub evicted: u64,
+    /// New games refused because every slot held a game still in play.
+    pub rejected: u64,
+    pub active_connections: usize,
+    pub max_connections: Option<usize>,
+    /// WebSocket connections turned away at `MAX_CONNECTIONS`.
+    pub refused_connections: u64,
+}
+
+impl GameCapacity {
+    pub fn new(max_games: Option<usize>) -> Self {
+        GameCapacity {
+            max_games: AtomicUsize::new(max_games.unwrap_or(0)),
+            evicted: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
+            warned: AtomicBool::new(false),
+        }
+    }
+
+    pub fn max_games(&self) -> Option<usize> {
+        cap(&self.max_games)
+    }
+
+    pub fn set_max_games(&self, max_games: Option<usize>) {
+        self.max_games
+            .store(max_games.unwrap_or(0), Ordering::Relaxed);
+    }
+
+    /// Makes room for one more game by evicting the least recently active finished games,
+    /// which `finish_game` has already archived as replays. Returns false when every game
+    /// in memory is still being played.
+    pub fn make_room(&self, games: &mut HashMap<String, Game>) -> bool {
+        let Some(max_games) = self.max_games() else {
+            return true;
+        };
+
+        while games.len() >= max_games {
+            let oldest_finished = games
+                .iter()
+                .filter(|(_, game)| game.game_over)
+                .min_by_key(|(_, game)| game.last_activity)
+                .map(|(id, _)| id.clone());
+
+            let Some(game_id) = oldest_finished else {
+                self.rejected.fetch_add(1, Ordering::Relaxed);
+                warn!(
+                    "🚫 Game cap of {} reached with no finished games to evict",
+                    max_games
+                );
+                return false;
+            };
+
+            games.remove(&game_id);
+            self.evicted.fetch_add(1, Ordering::Relaxed);
+            info!("♻️ Evicted finished game {} to stay under the cap", game_id);
+        }
+
+        self.check_pressure(games.len() + 1, max_games);
+        true
+    }
+
+    /// Warns once when usage crosses `WARN_RATIO`, and again after it has dropped back.
+    fn check_pressure(&self, active: usize, max_games: usize) {
+        let high = active as f64 >= max_games as f64 * WARN_RATIO;
+        if high && !self.warned.swap(true, Ordering::Relaxed) {
+            warn!(
+                "⚠️ {} of {} game slots in use; finished games will start being evicted",
+                active, max_games
+            );
+        } else if !high {
+            self.warned.store(false, Ordering::Relaxed);
+        }
+    }
+
+    pub fn stats(&self, active_games: usize, connections: &ConnectionLimit) -> CapacityStats {
+        CapacityStats {
+            active_games,
+            max_games: self.max_games(),
+            evicted: self.evicted.load(Ordering::Relaxed),
+            rejected: self.rejected.load(Ordering::Relaxed),
+            active_connections: connections.open.load(Ordering::Relaxed),
+            max_connections: connections.max_connections(),
+            refused_connections: connections.refused.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Caps open WebSocket connections at `MAX_CONNECTIONS`. Each one holds a broadcast
+/// receiver and a task, so past the cap new connections are shed instead of slowing
+/// everyone down.
+pub struct ConnectionLimit {
+    /// 0 when there is no cap.
+    max_connections: AtomicUsize,
+    open: AtomicUsize,
+    refused: AtomicU64,
+    next_id: AtomicU64,
+}
+
+/// One counted connection, given back when dropped. Its id tells connections apart in logs
+/// and the move audit.
+pub struct ConnectionSlot(Arc<ConnectionLimit>, u64);
+
+impl ConnectionLimit {
+    pub fn new(max_connections: Option<usize>) -> Self {
+        ConnectionLimit {
+            max_connections: AtomicUsize::new(max_connections.unwrap_or(0)),
+            open: AtomicUsize::new(0),
+            refused: AtomicU64::new(0),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// A slot for a new connection, or `None` if the server is already at its cap.
+    pub fn try_acquire(self: &Arc<Self>) -> Option<ConnectionSlot> {
+        let max = self.max_connections().unwrap_or(usize::MAX);
+        let acquired = self
+            .open
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |open| {
+                (open < max).then_some(open + 1)
+            })
+            .is_ok();
+
+        if !acquired {
+            self.refused.fetch_add(1, Ordering::Relaxed);
+            warn!(
+                "🚫 Connection cap of {} reached, shedding a new connection",
+                max
+            );
+            return None;
+        }
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        Some(ConnectionSlot(Arc::clone(self), id))
+    }
+}
+
+impl ConnectionLimit {
+    pub fn open_connections(&self) -> usize {
+        self.open.load(Ordering::Relaxed)
+    }
+
+    pub fn max_connections(&self) -> Option<usize> {
+        cap(&self.max_connections)
+    }
+
+    /// Connections already past a lowered cap stay open; only new ones are refused.
+    pub fn set_max_connections(&self, max_connections: Option<usize>) {
+        self.max_connections
+            .store(max_connections.unwrap_or(0), Ordering::Relaxed);
+    }
+}
+
+fn cap(limit: &AtomicUsize) -> Option<usize> {
+    Some(limit.load(Ordering::Relaxed)).filter(|&max| max > 0)
+}
+
+impl ConnectionSlot {
+    pub fn id(&self) -> u64 {
+        self.1
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.open.fetch_sub(1, Ordering::Relaxed);
+    }
+}
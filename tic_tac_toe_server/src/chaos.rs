@@ -0,0 +1,59 @@
+//! Fault injection for resilience testing. Only compiled with `--features chaos`.
+
+use rand::Rng;
+use std::{env, sync::OnceLock, time::Duration};
+use tracing::warn;
+
+#[derive(Debug, Clone)]
+pub struct ChaosConfig {
+    pub max_delay_ms: u64,
+    pub drop_rate: f64,
+    pub disconnect_rate: f64,
+}
+
+impl ChaosConfig {
+    fn from_env() -> Self {
+        let rate = |key: &str| {
+            env::var(key)
+                .ok()
+                .and_then(|v| v.parse::<f64>().ok())
+                .unwrap_or(0.0)
+                .clamp(0.0, 1.0)
+        };
+
+        ChaosConfig {
+            max_delay_ms: env::var("CHAOS_MAX_DELAY_MS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            drop_rate: rate("CHAOS_DROP_RATE"),
+            disconnect_rate: rate("CHAOS_DISCONNECT_RATE"),
+        }
+    }
+}
+
+pub fn config() -> &'static ChaosConfig {
+    static CONFIG: OnceLock<ChaosConfig> = OnceLock::new();
+    CONFIG.get_or_init(|| {
+        let config = ChaosConfig::from_env();
+        warn!("🐒 Chaos mode enabled: {:?}", config);
+        config
+    })
+}
+
+/// Sleeps for a random duration up to `CHAOS_MAX_DELAY_MS`.
+pub async fn delay_frame() {
+    let max_delay_ms = config().max_delay_ms;
+    if max_delay_ms > 0 {
+        let delay = rand::thread_rng().gen_range(0..=max_delay_ms);
+        tokio::time::sleep(Duration::from_millis(delay)).await;
+    }
+}
+
+pub fn drop_broadcast() -> bool {
+    rand::thread_rng().gen_bool(config().drop_rate)
+}
+
+pub fn force_disconnect() -> bool {
+    rand::thread_rng().gen_bool(config().disconnect_rate)
+}
@@ -1,21 +1,39 @@
 use crate::app_state::AppState;
+use crate::game::models::Game;
 
 use std::{sync::Arc, time::Duration};
 use tracing::info;
 
 pub async fn cleanup_inactive_games(app_state: Arc<AppState>) {
-    let timeout = Duration::from_secs(1200); // 20 minutes
-
     loop {
-        tokio::time::sleep(Duration::from_secs(600)).await; // Run every 10 min
+        tokio::time::sleep(app_state.config.cleanup_interval).await;
+        let default_timeout = app_state.tunables().idle_timeout;
 
         let mut games = app_state.games.write().await;
         let before_cleanup = games.len();
 
-        games.retain(|_, game| game.last_activity.elapsed().unwrap_or(timeout) < timeout);
+        let kiosk_game = app_state.config.kiosk_game.as_deref();
+        games.retain(|game_id, game| {
+            // Unfinished correspondence games may legitimately sit idle for days
+            (game.correspondence && !game.game_over)
+                // Players on an agreed break said they'd be back
+                || game.on_agreed_break()
+                || kiosk_game == Some(game_id.as_str())
+                || idle_timeout(game, default_timeout)
+                    .is_none_or(|timeout| game.last_activity.elapsed().unwrap_or(timeout) < timeout)
+        });
 
         if before_cleanup != games.len() {
             info!("Cleaned up inactive games. Remaining: {}", games.len());
         }
     }
 }
+
+/// The game's own timeout if its creator set one, otherwise the server's. `None` keeps it.
+fn idle_timeout(game: &Game, default_timeout: Option<Duration>) -> Option<Duration> {
+    match game.config.idle_timeout_minutes {
+        Some(0) => None,
+        Some(minutes) => Some(Duration::from_secs(minutes.saturating_mul(60))),
+        None => default_timeout,
+    }
+}
@@ -0,0 +1,200 @@
+use axum::http::HeaderMap;
+use std::net::{IpAddr, SocketAddr};
+use tracing::warn;
+
+/// Proxies allowed to tell us who their client was, from `TRUSTED_PROXIES`.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<(IpAddr, u8)>,
+}
+
+impl TrustedProxies {
+    /// Parses a comma-separated list of addresses and CIDR ranges, e.g.
+    /// `10.0.0.0/8, 127.0.0.1, fd00::/8`.
+    pub fn parse(list: &str) -> Self {
+        let networks = list
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .filter_map(|entry| {
+                let network = parse_network(entry);
+                if network.is_none() {
+                    warn!("⚠️ Ignoring invalid trusted proxy {:?}", entry);
+                }
+                network
+            })
+            .collect();
+        TrustedProxies { networks }
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        let ip = ip.to_canonical();
+        self.networks
+            .iter()
+            .any(|&(network, prefix)| in_network(ip, network, prefix))
+    }
+}
+
+pub fn parse_network(entry: &str) -> Option<(IpAddr, u8)> {
+    let (address, prefix) = match entry.split_once('/') {
+        Some((address, prefix)) => (address, Some(prefix)),
+        None => (entry, None),
+    };
+    let address: IpAddr = address.parse().ok()?;
+    let address = address.to_canonical();
+    let max_prefix = if address.is_ipv4() { 32 } else { 128 };
+
+    let prefix = match prefix {
+        Some(prefix) => prefix.parse().ok().filter(|prefix| *prefix <= max_prefix)?,
+        None => max_prefix,
+    };
+    Some((address, prefix))
+}
+
+pub fn in_network(ip: IpAddr, network: IpAddr, prefix: u8) -> bool {
+    match (ip, network) {
+        (IpAddr::V4(ip), IpAddr::V4(network)) => {
+            let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+            u32::from(ip) & mask == u32::from(network) & mask
+        }
+        (IpAddr::V6(ip), IpAddr::V6(network)) => {
+            let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+            u128::from(ip) & mask == u128::from(network) & mask
+        }
+        _ => false,
+    }
+}
+
+/// The real client behind `peer`. `X-Forwarded-For` is only believed when the connection
+/// comes from a trusted proxy, since anyone can send it; it is then read right to left,
+/// past any further trusted hops, to the first address we didn't put there ourselves.
+pub fn resolve(peer: IpAddr, headers: &HeaderMap, trusted: &TrustedProxies) -> IpAddr {
+    let mut client = peer.to_canonical();
+    if !trusted.contains(client) {
+        return client;
+    }
+
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(str::trim)
+        .collect();
+
+    for hop in hops.iter().rev() {
+        let Some(ip) = parse_hop(hop) else {
+            break;
+        };
+        client = ip.to_canonical();
+        if !trusted.contains(client) {
+            break;
+        }
+    }
+    client
+}
+
+/// Proxies write hops as bare addresses, but some add a port (`[2001:db8::1]:443`).
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    hop.parse::<IpAddr>()
+        .ok()
+        .or_else(|| hop.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    fn forwarded_for(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn an_untrusted_peer_cannot_spoof_its_address() {
+        let trusted = TrustedProxies::parse("10.0.0.0/8");
+        let headers = forwarded_for(&["198.51.100.7"]);
+        assert_eq!(
+            resolve(ip("203.0.113.9"), &headers, &trusted),
+            ip("203.0.113.9")
+        );
+        assert_eq!(
+            resolve(ip("203.0.113.9"), &headers, &TrustedProxies::default()),
+            ip("203.0.113.9")
+        );
+    }
+
+    #[test]
+    fn trusted_hops_are_skipped_right_to_left() {
+        let trusted = TrustedProxies::parse("10.0.0.0/8, 192.168.1.1");
+        // The client's own claim comes first and is never reached
+        let headers = forwarded_for(&["1.2.3.4, 198.51.100.7", "192.168.1.1, 10.0.0.2"]);
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &headers, &trusted),
+            ip("198.51.100.7")
+        );
+
+        // Every hop trusted: the leftmost is as far as we can see
+        let headers = forwarded_for(&["10.1.1.1, 10.0.0.2"]);
+        assert_eq!(resolve(ip("10.0.0.1"), &headers, &trusted), ip("10.1.1.1"));
+
+        // Garbage stops the walk at the last good hop
+        let headers = forwarded_for(&["198.51.100.7, unknown, 10.0.0.2"]);
+        assert_eq!(resolve(ip("10.0.0.1"), &headers, &trusted), ip("10.0.0.2"));
+
+        // A hop with a port
+        let headers = forwarded_for(&["[2001:db8::1]:443"]);
+        assert_eq!(
+            resolve(ip("10.0.0.1"), &headers, &trusted),
+            ip("2001:db8::1")
+        );
+    }
+
+    #[test]
+    fn prefixes_cover_the_whole_range_down_to_one_address() {
+        let (network, prefix) = parse_network("0.0.0.0/0").unwrap();
+        assert!(in_network(ip("203.0.113.9"), network, prefix));
+        assert!(in_network(ip("0.0.0.0"), network, prefix));
+        let (network, prefix) = parse_network("::/0").unwrap();
+        assert!(in_network(ip("2001:db8::1"), network, prefix));
+
+        let (network, prefix) = parse_network("203.0.113.9/32").unwrap();
+        assert!(in_network(ip("203.0.113.9"), network, prefix));
+        assert!(!in_network(ip("203.0.113.8"), network, prefix));
+        assert_eq!(parse_network("203.0.113.9"), Some((network, 32)));
+        assert_eq!(parse_network("203.0.113.9/33"), None);
+
+        let (network, prefix) = parse_network("2001:db8::1/128").unwrap();
+        assert!(in_network(ip("2001:db8::1"), network, prefix));
+        assert!(!in_network(ip("2001:db8::2"), network, prefix));
+    }
+
+    #[test]
+    fn ipv4_mapped_ipv6_addresses_count_as_ipv4() {
+        let trusted = TrustedProxies::parse("10.0.0.0/8");
+        assert!(trusted.contains(ip("::ffff:10.0.0.1")));
+
+        let headers = forwarded_for(&["::ffff:198.51.100.7"]);
+        assert_eq!(
+            resolve(ip("::ffff:10.0.0.1"), &headers, &trusted),
+            ip("198.51.100.7")
+        );
+
+        let trusted = TrustedProxies::parse("::ffff:10.0.0.0/104");
+        assert_eq!(
+            parse_network("::ffff:10.0.0.0/104"),
+            None,
+            "mapped ranges are IPv4"
+        );
+        assert!(!trusted.contains(ip("10.0.0.1")));
+        assert_eq!(parse_network("::ffff:10.0.0.1"), Some((ip("10.0.0.1"), 32)));
+    }
+}
@@ -0,0 +1,756 @@
+use crate::client_ip::TrustedProxies;
+use crate::log_files::{LogFiles, LogFormat, LogRotation};
+use crate::word_filter::FilterAction;
+
+use clap::Parser;
+use std::{
+    collections::HashMap,
+    env, fs,
+    net::{IpAddr, SocketAddr},
+    path::PathBuf,
+    time::Duration,
+};
+use tracing::{info, warn};
+use tracing_subscriber::EnvFilter;
+
+/// Command-line flags. Each one overrides the environment variable it is named after, which
+/// in turn overrides the same key in the config file.
+#[derive(Debug, Parser)]
+#[command(
+    name = "tic_tac_toe_server",
+    about = "Multiplayer tic-tac-toe server",
+    disable_version_flag = true
+)]
+pub struct Args {
+    /// Print the version and build details, then exit.
+    #[arg(short = 'V', long)]
+    pub version: bool,
+    /// TOML file whose keys are the lowercase environment variable names, e.g. `port = 3000`.
+    #[arg(long, env = "CONFIG_FILE", value_name = "FILE")]
+    pub config: Option<PathBuf>,
+    /// Port for the default `0.0.0.0` listener [default: 3000].
+    #[arg(long)]
+    pub port: Option<u16>,
+    /// Address to listen on; repeat to listen on several.
+    #[arg(long = "bind", value_name = "ADDR")]
+    pub bind_addrs: Vec<String>,
+    /// Host only this game, reset after every match.
+    #[arg(long, value_name = "GAME_ID")]
+    pub kiosk: Option<String>,
+    /// Move clock for new games; 0 turns it off [default: 0].
+    #[arg(long, value_name = "SECONDS")]
+    pub turn_seconds: Option<u64>,
+    /// Remove games left untouched this long; 0 keeps them [default: 20].
+    #[arg(long, value_name = "MINUTES")]
+    pub idle_timeout_minutes: Option<u64>,
+    /// Game events buffered per socket before a slow one starts missing them [default: 500].
+    #[arg(long, value_name = "EVENTS")]
+    pub channel_capacity: Option<usize>,
+    /// Ratings and records [default: players.json].
+    #[arg(long, value_name = "FILE")]
+    pub players_file: Option<PathBuf>,
+    /// Finished rounds [default: replays.json].
+    #[arg(long, value_name = "FILE")]
+    pub replays_file: Option<PathBuf>,
+    /// Unfinished correspondence games [default: correspondence.json].
+    #[arg(long, value_name = "FILE")]
+    pub correspondence_file: Option<PathBuf>,
+    /// Games saved at shutdown and restored at the next start [default: snapshot.json].
+    #[arg(long, value_name = "FILE")]
+    pub snapshot_file: Option<PathBuf>,
+    /// PEM certificate chain; serves TLS together with `--tls-key-file`.
+    #[arg(long, value_name = "FILE")]
+    pub tls_cert_file: Option<PathBuf>,
+    /// PEM private key for `--tls-cert-file`.
+    #[arg(long, value_name = "FILE")]
+    pub tls_key_file: Option<PathBuf>,
+    /// Also write logs to rotating files in this directory.
+    #[arg(long, value_name = "DIR")]
+    pub log_dir: Option<PathBuf>,
+}
+
+impl Args {
+    /// The flags that were given, keyed by the environment variable each one stands for.
+    fn overrides(&self) -> HashMap<&'static str, String> {
+        let path = |path: &Option<PathBuf>| path.as_ref().map(|p| p.display().to_string());
+        let bind_addrs = (!self.bind_addrs.is_empty()).then(|| self.bind_addrs.join(","));
+
+        [
+            ("PORT", self.port.map(|v| v.to_string())),
+            ("BIND_ADDRS", bind_addrs),
+            ("KIOSK_GAME", self.kiosk.clone()),
+            ("TURN_SECONDS", self.turn_seconds.map(|v| v.to_string())),
+            (
+                "IDLE_TIMEOUT_MINUTES",
+                self.idle_timeout_minutes.map(|v| v.to_string()),
+            ),
+            (
+                "CHANNEL_CAPACITY",
+                self.channel_capacity.map(|v| v.to_string()),
+            ),
+            ("PLAYERS_FILE", path(&self.players_file)),
+            ("REPLAYS_FILE", path(&self.replays_file)),
+            ("CORRESPONDENCE_FILE", path(&self.correspondence_file)),
+            ("SNAPSHOT_FILE", path(&self.snapshot_file)),
+            ("TLS_CERT_FILE", path(&self.tls_cert_file)),
+            ("TLS_KEY_FILE", path(&self.tls_key_file)),
+            ("LOG_DIR", path(&self.log_dir)),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value?)))
+        .collect()
+    }
+}
+
+/// Where settings come from, most specific first: command-line flags, the environment,
+/// then the config file.
+struct Sources {
+    overrides: HashMap<&'static str, String>,
+    file: HashMap<String, String>,
+}
+
+impl Sources {
+    fn new(args: &Args) -> Result<Self, String> {
+        let file = match &args.config {
+            Some(path) => {
+                info!("📄 Reading settings from {:?}", path);
+                let text = fs::read_to_string(path)
+                    .map_err(|e| format!("Failed to read config file {:?}: {}", path, e))?;
+                let table: toml::Table = text
+                    .parse()
+                    .map_err(|e| format!("Failed to parse config file {:?}: {}", path, e))?;
+                table
+                    .into_iter()
+                    .filter_map(|(key, value)| {
+                        let value = setting_text(&value);
+                        if value.is_none() {
+                            warn!(
+                                "⚠️ Ignoring config key {:?}: nested tables aren't supported",
+                                key
+                            );
+                        }
+                        Some((key.to_uppercase(), value?))
+                    })
+                    .collect()
+            }
+            None => HashMap::new(),
+        };
+
+        Ok(Sources {
+            overrides: args.overrides(),
+            file,
+        })
+    }
+
+    fn get(&self, name: &str) -> Option<String> {
+        self.overrides
+            .get(name)
+            .cloned()
+            .or_else(|| env::var(name).ok())
+            .or_else(|| self.file.get(name).cloned())
+    }
+}
+
+/// A config file value as it would be written in the environment; arrays become the usual
+/// comma-separated lists.
+fn setting_text(value: &toml::Value) -> Option<String> {
+    match value {
+        toml::Value::String(text) => Some(text.clone()),
+        toml::Value::Array(items) => items
+            .iter()
+            .map(setting_text)
+            .collect::<Option<Vec<_>>>()
+            .map(|items| items.join(",")),
+        toml::Value::Table(_) => None,
+        other => Some(other.to_string()),
+    }
+}
+
+const MINUTE: u64 = 60;
+const DAY: u64 = 24 * 60 * MINUTE;
+/// Longest any duration setting may be, so timestamps it is added to can't overflow.
+const MAX_DURATION_SECS: u64 = 100 * 365 * DAY;
+
+/// `value` of `name`, counted in `unit`-second steps, as seconds. Values too long for any
+/// server to wait out are refused rather than wrapped round.
+fn seconds(name: &str, value: u64, unit: u64) -> Result<u64, String> {
+    value
+        .checked_mul(unit)
+        .filter(|secs| *secs <= MAX_DURATION_SECS)
+        .ok_or_else(|| format!("{} {} is too long; the most is 100 years", name, value))
+}
+
+/// `0.0.0.0:3000` or `[::]:3000` as written, or just the IP (`::` or `[::]` too) to
+/// listen on `port`.
+fn parse_bind_addr(addr: &str, port: u16) -> Result<SocketAddr, std::net::AddrParseError> {
+    addr.parse().or_else(|e| {
+        let ip = addr.strip_prefix('[').and_then(|ip| ip.strip_suffix(']'));
+        ip.unwrap_or(addr)
+            .parse::<IpAddr>()
+            .map(|ip| SocketAddr::new(ip, port))
+            .map_err(|_| e)
+    })
+}
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub bind_addrs: Vec<SocketAddr>,
+    /// Game events buffered for each socket before a slow one starts missing them.
+    pub channel_capacity: usize,
+    /// Settings that can change while the server runs.
+    pub tunables: Tunables,
+    pub players_file: PathBuf,
+    pub replays_file: PathBuf,
+    /// Games whose replays are kept, the longest finished dropped first; `None` keeps all.
+    pub max_replays: Option<usize>,
+    /// Registered accounts; only used when `jwt_secret` is set.
+    pub accounts_file: PathBuf,
+    pub notifications_file: PathBuf,
+    pub correspondence_file: PathBuf,
+    /// Every game in memory, written on a graceful shutdown and read back at startup.
+    pub snapshot_file: PathBuf,
+    /// The current rating season and the standings of past ones.
+    pub seasons_file: PathBuf,
+    /// Player reports and what moderators did about them.
+    pub reports_file: PathBuf,
+    /// Names, accounts and addresses banned from the whole server.
+    pub bans_file: PathBuf,
+    /// How long a rating season lasts; `None` keeps one season forever.
+    pub season_length: Option<Duration>,
+    pub move_timings_file: PathBuf,
+    /// Each game's running score, so it survives a restart.
+    pub scores_file: PathBuf,
+    /// Every submitted move, accepted or not, one JSON line each.
+    pub move_audit_file: PathBuf,
+    /// Bearer token for the `/admin` routes; they are disabled when unset.
+    pub admin_token: Option<String>,
+    /// Processing time a move should fit in; slower moves are logged and counted.
+    pub move_budget: Duration,
+    /// How often idle games are looked for.
+    pub cleanup_interval: Duration,
+    /// How long a player must be away before a correspondence game waiting on them pings them.
+    pub notify_after: Duration,
+    /// HTTP endpoint that turns `{to, subject, text}` into an email.
+    pub notify_email_relay: Option<String>,
+    pub static_dir: Option<PathBuf>,
+    /// Origins allowed to call the HTTP routes from a browser; `*` for any. Empty adds
+    /// no CORS headers.
+    pub cors_origins: Vec<String>,
+    /// Endpoints posted JSON on game created, player joined and game finished events.
+    pub webhook_urls: Vec<String>,
+    /// Key used to sign spectate links for private games.
+    pub spectate_secret: Vec<u8>,
+    pub spectate_link_ttl: u64,
+    /// Key for signing player tokens; token auth is off when unset.
+    pub jwt_secret: Option<Vec<u8>>,
+    pub jwt_ttl: Duration,
+    /// Base URL that shared spectate links point at.
+    pub public_url: String,
+    /// Message of the day shown to clients when they connect.
+    pub motd: Option<String>,
+    /// Seasonal skin id clients should switch to, e.g. `halloween`.
+    pub event_theme: Option<String>,
+    /// The only game this server hosts (`--kiosk GAME_ID`), reset after every match.
+    pub kiosk_game: Option<String>,
+    /// Proxies whose `X-Forwarded-For` header is believed.
+    pub trusted_proxies: TrustedProxies,
+    /// Every connection starts with a HAProxy PROXY protocol header.
+    pub proxy_protocol: bool,
+    /// Certificate and key to serve `https://` and `wss://` with; plain HTTP when unset.
+    pub tls: Option<TlsFiles>,
+    /// Log history kept on disk; stdout only when unset.
+    pub log_files: Option<LogFiles>,
+    pub log_format: LogFormat,
+    /// Words not allowed in player names or chat; empty turns the filter off.
+    pub blocked_words: Vec<String>,
+    pub word_filter_action: FilterAction,
+    /// OpenTelemetry collector spans are exported to over OTLP/HTTP.
+    #[cfg(feature = "otel")]
+    pub otlp_endpoint: Option<String>,
+    /// Sentry project panics and error logs are reported to.
+    #[cfg(feature = "sentry")]
+    pub sentry_dsn: Option<String>,
+}
+
+/// The settings a running server picks up again on SIGHUP or when its config file is
+/// saved, see `reload`. Everything else in `Config` waits for a restart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tunables {
+    /// Move clock for games created from now on.
+    pub turn_seconds: Option<u64>,
+    /// How long a player may stay disconnected before forfeiting a live game.
+    pub abandon_grace: Option<Duration>,
+    /// How long a live game stays paused, clock and all, after a player drops.
+    pub reconnect_grace: Option<Duration>,
+    /// How long a game may sit untouched before it is removed; `None` keeps idle games.
+    pub idle_timeout: Option<Duration>,
+    /// Most games kept in memory at once; finished ones are evicted first.
+    pub max_games: Option<usize>,
+    /// Most WebSocket connections open at once; further ones get `SERVER_FULL`.
+    pub max_connections: Option<usize>,
+    /// How often each connection is pinged; `None` turns heartbeats off. Connections
+    /// already open keep the interval they started with.
+    pub ping_interval: Option<Duration>,
+    /// Consecutive unanswered Pings after which a connection is dropped.
+    pub max_missed_pongs: u32,
+    /// `EnvFilter` directives, e.g. `info` or `warn,tic_tac_toe_server=debug`.
+    pub log_level: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct TlsFiles {
+    /// PEM certificate chain, leaf first.
+    pub cert_file: PathBuf,
+    pub key_file: PathBuf,
+}
+
+impl Config {
+    /// Reads every setting, failing only when the config file can't be read, PORT isn't a
+    /// port or a duration or size is too large to hold; other bad values are warned about
+    /// and replaced by their defaults.
+    pub fn load(args: &Args) -> Result<Self, String> {
+        let sources = Sources::new(args)?;
+        let port = sources.get("PORT").unwrap_or_else(|| "3000".to_string());
+
+        let port: u16 = port
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid PORT {:?}", port))?;
+
+        // e.g. BIND_ADDRS="0.0.0.0:3000,[::]:3000", or "0.0.0.0,::" to use PORT on both
+        let mut bind_addrs: Vec<SocketAddr> = sources
+            .get("BIND_ADDRS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|addr| !addr.is_empty())
+            .filter_map(|addr| match parse_bind_addr(addr, port) {
+                Ok(addr) => Some(addr),
+                Err(e) => {
+                    warn!("⚠️ Ignoring invalid bind address {:?}: {}", addr, e);
+                    None
+                }
+            })
+            .collect();
+
+        if bind_addrs.is_empty() {
+            bind_addrs.push(SocketAddr::from(([0, 0, 0, 0], port)));
+        }
+
+        let channel_capacity = sources
+            .get("CHANNEL_CAPACITY")
+            .and_then(|v| v.parse::<usize>().ok())
+            .filter(|capacity| *capacity > 0)
+            .unwrap_or(500);
+
+        // 0, the default, leaves games without a move clock
+        let turn_seconds = sources
+            .get("TURN_SECONDS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        // 0 lets a disconnected player hold their seat indefinitely
+        let abandon_grace_seconds = sources
+            .get("ABANDON_GRACE_SECONDS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(60);
+
+        // 0 never pauses; the game plays on while the player is away
+        let reconnect_grace_seconds = sources
+            .get("RECONNECT_GRACE_SECONDS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        // 0 keeps idle games around until a restart
+        let idle_timeout_minutes = sources
+            .get("IDLE_TIMEOUT_MINUTES")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(20);
+
+        let cleanup_interval_minutes = sources
+            .get("CLEANUP_INTERVAL_MINUTES")
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|minutes| *minutes > 0)
+            .unwrap_or(10);
+
+        // 0 lifts the cap
+        let max_games = sources
+            .get("MAX_GAMES")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(10_000);
+
+        // 0 lifts the cap
+        let max_connections = sources
+            .get("MAX_CONNECTIONS")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(20_000);
+
+        let move_budget_ms = sources
+            .get("MOVE_BUDGET_MS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(5);
+
+        let players_file = sources
+            .get("PLAYERS_FILE")
+            .unwrap_or_else(|| "players.json".to_string())
+            .into();
+
+        let replays_file = sources
+            .get("REPLAYS_FILE")
+            .unwrap_or_else(|| "replays.json".to_string())
+            .into();
+
+        // 0 lifts the cap
+        let max_replays = sources
+            .get("MAX_REPLAYS")
+            .and_then(|v| v.parse::<usize>().ok())
+            .unwrap_or(5_000);
+
+        let accounts_file = sources
+            .get("ACCOUNTS_FILE")
+            .unwrap_or_else(|| "accounts.json".to_string())
+            .into();
+
+        let notifications_file = sources
+            .get("NOTIFICATIONS_FILE")
+            .unwrap_or_else(|| "notifications.json".to_string())
+            .into();
+
+        let correspondence_file = sources
+            .get("CORRESPONDENCE_FILE")
+            .unwrap_or_else(|| "correspondence.json".to_string())
+            .into();
+
+        let snapshot_file = sources
+            .get("SNAPSHOT_FILE")
+            .unwrap_or_else(|| "snapshot.json".to_string())
+            .into();
+
+        let seasons_file = sources
+            .get("SEASONS_FILE")
+            .unwrap_or_else(|| "seasons.json".to_string())
+            .into();
+
+        let reports_file = sources
+            .get("REPORTS_FILE")
+            .unwrap_or_else(|| "reports.json".to_string())
+            .into();
+
+        let bans_file = sources
+            .get("BANS_FILE")
+            .unwrap_or_else(|| "bans.json".to_string())
+            .into();
+
+        // 0 never ends the season
+        let season_days = sources
+            .get("SEASON_DAYS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(0);
+
+        let move_timings_file = sources
+            .get("MOVE_TIMINGS_FILE")
+            .unwrap_or_else(|| "move_timings.json".to_string())
+            .into();
+
+        let scores_file = sources
+            .get("SCORES_FILE")
+            .unwrap_or_else(|| "scores.json".to_string())
+            .into();
+
+        let move_audit_file = sources
+            .get("MOVE_AUDIT_FILE")
+            .unwrap_or_else(|| "move_audit.jsonl".to_string())
+            .into();
+
+        let admin_token = sources.get("ADMIN_TOKEN").filter(|token| !token.is_empty());
+
+        let notify_after_minutes = sources
+            .get("NOTIFY_AFTER_MINUTES")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+
+        let notify_email_relay = sources
+            .get("NOTIFY_EMAIL_RELAY_URL")
+            .filter(|url| !url.is_empty());
+
+        // Directory holding a built web client (index.html + wasm bundle), served from `/`
+        let static_dir = sources.get("STATIC_DIR").map(PathBuf::from);
+
+        // e.g. CORS_ORIGINS="https://play.example.com,http://localhost:8080", or "*"
+        let cors_origins = sources
+            .get("CORS_ORIGINS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|origin| origin.trim().trim_end_matches('/').to_string())
+            .filter(|origin| !origin.is_empty())
+            .collect();
+
+        // e.g. WEBHOOK_URLS="https://discord.com/api/webhooks/...,https://stats.example.com/ttt"
+        let webhook_urls = sources
+            .get("WEBHOOK_URLS")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|url| !url.is_empty())
+            .map(String::from)
+            .collect();
+
+        let spectate_secret = match sources.get("SPECTATE_SECRET") {
+            Some(secret) if !secret.is_empty() => secret.into_bytes(),
+            _ => {
+                warn!("⚠️ SPECTATE_SECRET not set; spectate links and seat tokens will not survive a restart");
+                rand::random::<[u8; 32]>().to_vec()
+            }
+        };
+
+        let spectate_link_ttl = sources
+            .get("SPECTATE_LINK_TTL")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(24 * 60 * 60);
+        let spectate_link_ttl = seconds("SPECTATE_LINK_TTL", spectate_link_ttl, 1)?;
+
+        let jwt_secret = sources
+            .get("JWT_SECRET")
+            .filter(|secret| !secret.is_empty())
+            .map(String::into_bytes);
+
+        let jwt_ttl_days = sources
+            .get("JWT_TTL_DAYS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(30);
+        let jwt_ttl = seconds("JWT_TTL_DAYS", jwt_ttl_days, DAY)?;
+
+        let public_url = sources
+            .get("PUBLIC_URL")
+            .unwrap_or_else(|| format!("http://localhost:{}", port))
+            .trim_end_matches('/')
+            .to_string();
+
+        let motd = sources.get("MOTD").filter(|motd| !motd.is_empty());
+        let event_theme = sources
+            .get("EVENT_THEME")
+            .map(|theme| theme.trim().to_lowercase())
+            .filter(|theme| !theme.is_empty());
+
+        let kiosk_game = sources.get("KIOSK_GAME").filter(|id| !id.trim().is_empty());
+
+        // e.g. TRUSTED_PROXIES="10.0.0.0/8,127.0.0.1"; empty trusts no one
+        let trusted_proxies =
+            TrustedProxies::parse(&sources.get("TRUSTED_PROXIES").unwrap_or_default());
+        let proxy_protocol = sources
+            .get("PROXY_PROTOCOL")
+            .is_some_and(|v| matches!(v.trim().to_lowercase().as_str(), "1" | "true" | "yes"));
+
+        let tls = match (sources.get("TLS_CERT_FILE"), sources.get("TLS_KEY_FILE")) {
+            (Some(cert_file), Some(key_file)) if !cert_file.is_empty() && !key_file.is_empty() => {
+                Some(TlsFiles {
+                    cert_file: cert_file.into(),
+                    key_file: key_file.into(),
+                })
+            }
+            (None, None) => None,
+            _ => {
+                warn!("⚠️ TLS needs both TLS_CERT_FILE and TLS_KEY_FILE; serving plain HTTP");
+                None
+            }
+        };
+
+        // 0 turns server Pings off
+        let ping_interval_seconds = sources
+            .get("PING_INTERVAL_SECONDS")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(15);
+
+        let max_missed_pongs = sources
+            .get("MAX_MISSED_PONGS")
+            .and_then(|v| v.parse::<u32>().ok())
+            .filter(|missed| *missed > 0)
+            .unwrap_or(3);
+
+        // e.g. LOG_LEVEL="warn,tic_tac_toe_server=debug"
+        let log_level = sources
+            .get("LOG_LEVEL")
+            .map(|level| level.trim().to_string())
+            .filter(|level| !level.is_empty())
+            .and_then(|level| match EnvFilter::try_new(&level) {
+                Ok(_) => Some(level),
+                Err(e) => {
+                    warn!("⚠️ Ignoring invalid LOG_LEVEL {:?}: {}", level, e);
+                    None
+                }
+            })
+            .unwrap_or_else(|| "info".to_string());
+
+        // e.g. LOG_DIR=/var/log/ttt LOG_ROTATION=size LOG_MAX_SIZE_MB=50
+        let log_files = sources
+            .get("LOG_DIR")
+            .filter(|dir| !dir.is_empty())
+            .map(|dir| {
+                let max_size_mb = sources
+                    .get("LOG_MAX_SIZE_MB")
+                    .and_then(|v| v.parse::<u64>().ok())
+                    .filter(|mb| *mb > 0)
+                    .unwrap_or(10);
+                let max_size = max_size_mb
+                    .checked_mul(1024 * 1024)
+                    .ok_or_else(|| format!("LOG_MAX_SIZE_MB {} is too large", max_size_mb))?;
+                let rotation = match sources.get("LOG_ROTATION").as_deref().map(str::trim) {
+                    None | Some("daily") => LogRotation::Daily,
+                    Some("hourly") => LogRotation::Hourly,
+                    Some("size") => LogRotation::Size(max_size),
+                    Some("never") => LogRotation::Never,
+                    Some(other) => {
+                        warn!("⚠️ Unknown LOG_ROTATION {:?}; rotating daily", other);
+                        LogRotation::Daily
+                    }
+                };
+                let max_files = sources
+                    .get("LOG_MAX_FILES")
+                    .and_then(|v| v.parse::<usize>().ok())
+                    .filter(|files| *files > 0)
+                    .unwrap_or(7);
+                Ok::<_, String>(LogFiles {
+                    dir: dir.into(),
+                    rotation,
+                    max_files,
+                })
+            })
+            .transpose()?;
+
+        let log_format = match sources.get("LOG_FORMAT").as_deref().map(str::trim) {
+            None | Some("text") => LogFormat::Text,
+            Some("json") => LogFormat::Json,
+            Some(other) => {
+                warn!("⚠️ Unknown LOG_FORMAT {:?}; writing text logs", other);
+                LogFormat::Text
+            }
+        };
+
+        // e.g. BLOCKED_WORDS="darn,heck" and/or BLOCKED_WORDS_FILE=words.txt, one per line
+        let mut blocked_words: Vec<String> = sources
+            .get("BLOCKED_WORDS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|word| word.trim().to_string())
+            .collect();
+        if let Some(path) = sources.get("BLOCKED_WORDS_FILE").filter(|p| !p.is_empty()) {
+            match fs::read_to_string(&path) {
+                Ok(text) => blocked_words.extend(
+                    text.lines()
+                        .map(str::trim)
+                        .filter(|line| !line.starts_with('#'))
+                        .map(String::from),
+                ),
+                Err(e) => warn!("⚠️ Can't read BLOCKED_WORDS_FILE {:?}: {}", path, e),
+            }
+        }
+        blocked_words.retain(|word| !word.is_empty());
+
+        let word_filter_action = match sources.get("WORD_FILTER_ACTION") {
+            None => FilterAction::default(),
+            Some(action) => FilterAction::parse(&action).unwrap_or_else(|| {
+                warn!("⚠️ Unknown WORD_FILTER_ACTION {:?}; masking", action);
+                FilterAction::default()
+            }),
+        };
+
+        // e.g. OTLP_ENDPOINT=http://localhost:4318
+        let otlp_endpoint = sources.get("OTLP_ENDPOINT").filter(|e| !e.is_empty());
+        #[cfg(not(feature = "otel"))]
+        if otlp_endpoint.is_some() {
+            warn!("⚠️ OTLP_ENDPOINT is set but this build has no `otel` feature; not exporting traces");
+        }
+
+        let sentry_dsn = sources.get("SENTRY_DSN").filter(|dsn| !dsn.is_empty());
+        #[cfg(not(feature = "sentry"))]
+        if sentry_dsn.is_some() {
+            warn!(
+                "⚠️ SENTRY_DSN is set but this build has no `sentry` feature; not reporting errors"
+            );
+        }
+
+        let idle_timeout = seconds("IDLE_TIMEOUT_MINUTES", idle_timeout_minutes, MINUTE)?;
+        let cleanup_interval =
+            seconds("CLEANUP_INTERVAL_MINUTES", cleanup_interval_minutes, MINUTE)?;
+        let season_length = seconds("SEASON_DAYS", season_days, DAY)?;
+        let notify_after = seconds("NOTIFY_AFTER_MINUTES", notify_after_minutes, MINUTE)?;
+
+        Ok(Config {
+            bind_addrs,
+            channel_capacity,
+            tunables: Tunables {
+                turn_seconds: (turn_seconds > 0).then_some(turn_seconds),
+                abandon_grace: (abandon_grace_seconds > 0)
+                    .then(|| Duration::from_secs(abandon_grace_seconds)),
+                reconnect_grace: (reconnect_grace_seconds > 0)
+                    .then(|| Duration::from_secs(reconnect_grace_seconds)),
+                idle_timeout: (idle_timeout > 0).then(|| Duration::from_secs(idle_timeout)),
+                max_games: (max_games > 0).then_some(max_games),
+                max_connections: (max_connections > 0).then_some(max_connections),
+                ping_interval: (ping_interval_seconds > 0)
+                    .then(|| Duration::from_secs(ping_interval_seconds)),
+                max_missed_pongs,
+                log_level,
+            },
+            move_budget: Duration::from_millis(move_budget_ms),
+            cleanup_interval: Duration::from_secs(cleanup_interval),
+            players_file,
+            replays_file,
+            max_replays: (max_replays > 0).then_some(max_replays),
+            accounts_file,
+            notifications_file,
+            correspondence_file,
+            snapshot_file,
+            seasons_file,
+            reports_file,
+            bans_file,
+            season_length: (season_length > 0).then(|| Duration::from_secs(season_length)),
+            move_timings_file,
+            scores_file,
+            move_audit_file,
+            admin_token,
+            notify_after: Duration::from_secs(notify_after),
+            notify_email_relay,
+            static_dir,
+            cors_origins,
+            webhook_urls,
+            spectate_secret,
+            spectate_link_ttl,
+            jwt_secret,
+            jwt_ttl: Duration::from_secs(jwt_ttl),
+            public_url,
+            motd,
+            event_theme,
+            kiosk_game,
+            trusted_proxies,
+            proxy_protocol,
+            tls,
+            log_files,
+            log_format,
+            blocked_words,
+            word_filter_action,
+            #[cfg(feature = "otel")]
+            otlp_endpoint,
+            #[cfg(feature = "sentry")]
+            sentry_dsn,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_too_long_to_hold_are_refused() {
+        assert_eq!(seconds("SEASON_DAYS", 30, DAY), Ok(30 * 24 * 60 * 60));
+        assert_eq!(seconds("IDLE_TIMEOUT_MINUTES", 0, MINUTE), Ok(0));
+        assert!(seconds("SEASON_DAYS", 100 * 365, DAY).is_ok());
+
+        // Would overflow a u64, or any timestamp it's added to
+        let error = seconds("JWT_TTL_DAYS", u64::MAX / 1000, DAY).unwrap_err();
+        assert!(error.contains("JWT_TTL_DAYS"));
+        assert!(seconds("SEASON_DAYS", 100 * 365 + 1, DAY).is_err());
+        assert!(seconds("SPECTATE_LINK_TTL", u64::MAX, 1).is_err());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::app_state::AppState;
+use crate::game::models::{Game, DEFAULT_BOARD_SIZE};
+use crate::versioning::{self, Migration};
+
+use serde_json::{json, Value};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Duration};
+use tracing::{error, info};
+
+const MIGRATIONS: &[Migration] = &[group_rules_into_config];
+
+/// v1 kept each game's rules as loose fields; v2 gathers them into `config`.
+fn group_rules_into_config(data: &mut Value) {
+    let Some(games) = data.as_object_mut() else {
+        return;
+    };
+    for game in games.values_mut().filter_map(Value::as_object_mut) {
+        let board = game.get("board").and_then(Value::as_array);
+        let rows = board.map_or(DEFAULT_BOARD_SIZE, Vec::len);
+        let columns = board
+            .and_then(|board| board.first())
+            .and_then(Value::as_array)
+            .map_or(rows, Vec::len);
+
+        let mut config = json!({ "rows": rows, "columns": columns, "first_player": "X" });
+        for field in ["kind", "win_length", "turn_seconds", "private"] {
+            if let Some(value) = game.remove(field) {
+                config[field] = value;
+            }
+        }
+        game.insert("config".to_string(), config);
+    }
+}
+
+/// Correspondence games saved by a previous run.
+pub fn load(path: &Path) -> HashMap<String, Game> {
+    let mut games: HashMap<String, Game> = match std::fs::read_to_string(path) {
+        Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+            error!("❌ Failed to parse correspondence games {:?}: {}", path, e);
+            HashMap::new()
+        }),
+        Err(_) => HashMap::new(),
+    };
+
+    // Nobody is connected to a game that was just loaded
+    for game in games.values_mut() {
+        game.disconnected = game
+            .players
+            .iter()
+            .copied()
+            .filter(|player| game.ai_player != Some(*player))
+            .collect();
+    }
+
+    info!(
+        "📮 Restored {} correspondence games from {:?}",
+        games.len(),
+        path
+    );
+    games
+}
+
+/// Writes the correspondence games to disk whenever one of them changes.
+pub async fn persist_correspondence_games(app_state: Arc<AppState>) {
+    let mut saved_versions: HashMap<String, u64> = HashMap::new();
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        let snapshot: HashMap<String, Game> = app_state
+            .games
+            .read()
+            .await
+            .iter()
+            .filter(|(_, game)| game.correspondence)
+            .map(|(id, game)| (id.clone(), game.clone()))
+            .collect();
+
+        let versions: HashMap<String, u64> = snapshot
+            .iter()
+            .map(|(id, game)| (id.clone(), game.version))
+            .collect();
+        if versions == saved_versions {
+            continue;
+        }
+
+        let contents = match serde_json::to_string(&versioning::versioned(&snapshot, MIGRATIONS)) {
+            Ok(contents) => contents,
+            Err(e) => {
+                error!("❌ Failed to serialize correspondence games: {}", e);
+                continue;
+            }
+        };
+
+        let path = &app_state.config.correspondence_file;
+        match tokio::fs::write(path, contents).await {
+            Ok(()) => saved_versions = versions,
+            Err(e) => error!("❌ Failed to write correspondence games {:?}: {}", path, e),
+        }
+    }
+}
@@ -0,0 +1,28 @@
+use axum::http::{header, HeaderValue, Method};
+use tower_http::cors::{AllowOrigin, CorsLayer};
+use tracing::warn;
+
+/// Lets browser builds of the client served from other origins call the HTTP routes
+/// (leaderboard, replays, admin). `*` allows any origin; otherwise only the listed ones.
+pub fn layer(origins: &[String]) -> CorsLayer {
+    let allow_origin = if origins.iter().any(|origin| origin == "*") {
+        AllowOrigin::any()
+    } else {
+        let origins: Vec<HeaderValue> = origins
+            .iter()
+            .filter_map(|origin| match HeaderValue::from_str(origin) {
+                Ok(value) => Some(value),
+                Err(_) => {
+                    warn!("⚠️ Ignoring invalid CORS origin {:?}", origin);
+                    None
+                }
+            })
+            .collect();
+        AllowOrigin::list(origins)
+    };
+
+    CorsLayer::new()
+        .allow_origin(allow_origin)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers([header::AUTHORIZATION, header::CONTENT_TYPE])
+}
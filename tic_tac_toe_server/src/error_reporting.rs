@@ -0,0 +1,28 @@
+//! Optional Sentry reporting of panics and error-level log events, each carrying the
+//! connection, game and player of the span it happened in. Only built with the `sentry`
+//! feature.
+
+use crate::version::{GIT_HASH, VERSION};
+
+use sentry::integrations::tracing::SentryLayer;
+use sentry::ClientInitGuard;
+use tracing::Subscriber;
+use tracing_subscriber::registry::LookupSpan;
+
+/// Starts the Sentry client for `dsn` and returns a layer sending it `error!` events, with
+/// info and warning lines before them as breadcrumbs. The guard flushes queued reports when
+/// dropped.
+pub fn layer<S>(dsn: &str) -> (SentryLayer<S>, ClientInitGuard)
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let guard = sentry::init((
+        dsn,
+        sentry::ClientOptions {
+            release: Some(format!("tic_tac_toe_server@{VERSION}+{GIT_HASH}").into()),
+            ..Default::default()
+        },
+    ));
+    let layer = sentry::integrations::tracing::layer().enable_span_attributes();
+    (layer, guard)
+}
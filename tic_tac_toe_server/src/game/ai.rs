@@ -0,0 +1,234 @@
+use super::models::{winning_lines, Board, Game, Player, DEFAULT_BOARD_SIZE};
+use super::rules::GameKind;
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, error};
+
+pub const AI_NAME: &str = "🤖 Computer";
+
+/// How hard the bot tries: random moves, win/block heuristics, or perfect minimax play.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AiDifficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+}
+
+/// The cells of a classic board, the only size minimax can search in reasonable time.
+const ALL_CELLS: [(usize, usize); 9] = [
+    (0, 0),
+    (0, 1),
+    (0, 2),
+    (1, 0),
+    (1, 1),
+    (1, 2),
+    (2, 0),
+    (2, 1),
+    (2, 2),
+];
+
+const LINES: [[(usize, usize); 3]; 8] = [
+    [(0, 0), (0, 1), (0, 2)],
+    [(1, 0), (1, 1), (1, 2)],
+    [(2, 0), (2, 1), (2, 2)],
+    [(0, 0), (1, 0), (2, 0)],
+    [(0, 1), (1, 1), (2, 1)],
+    [(0, 2), (1, 2), (2, 2)],
+    [(0, 0), (1, 1), (2, 2)],
+    [(0, 2), (1, 1), (2, 0)],
+];
+
+/// Plays the bot's move if it's the bot's turn. Returns whether a move was made.
+pub fn play_ai_turn(game: &mut Game) -> bool {
+    let Some(ai_player) = game.ai_player else {
+        return false;
+    };
+    if game.game_over || game.current_turn != ai_player {
+        return false;
+    }
+
+    let playable = game.config.kind.playable_cells(&game.board);
+    let classic = game.config.kind == GameKind::TicTacToe && game.rows() == DEFAULT_BOARD_SIZE;
+    let choice = match game.ai_difficulty {
+        AiDifficulty::Easy => random_of(&playable),
+        AiDifficulty::Hard if classic => minimax_move(&game.board, ai_player),
+        // Anything else is far too large to search, so Hard plays like Medium there
+        AiDifficulty::Medium | AiDifficulty::Hard => heuristic_move(game, &playable, ai_player),
+    };
+
+    let Some((x, y)) = choice else {
+        return false;
+    };
+
+    match game.make_move(ai_player, x, y) {
+        Ok(()) => {
+            debug!(
+                "AI {:?} ({:?}) played ({}, {})",
+                ai_player, game.ai_difficulty, x, y
+            );
+            true
+        }
+        Err(err) => {
+            error!("❌ AI produced an illegal move: {}", err);
+            false
+        }
+    }
+}
+
+/// Wins when possible, otherwise blocks, otherwise plays towards the middle: centre then
+/// corners on a tic-tac-toe board, the column nearest the centre in Connect Four.
+fn heuristic_move(
+    game: &Game,
+    playable: &[(usize, usize)],
+    player: Player,
+) -> Option<(usize, usize)> {
+    let positional = || match game.config.kind {
+        GameKind::TicTacToe => {
+            let last = game.rows() - 1;
+            let centre = (last / 2, last / 2);
+            Some(centre)
+                .filter(|centre| playable.contains(centre))
+                .or_else(|| {
+                    let corners = [(0, 0), (0, last), (last, 0), (last, last)];
+                    random_of(&playable_among(playable, &corners))
+                })
+                .or_else(|| random_of(playable))
+        }
+        GameKind::ConnectFour => {
+            let middle = game.columns() / 2;
+            playable
+                .iter()
+                .copied()
+                .min_by_key(|&(_, y)| y.abs_diff(middle))
+        }
+    };
+
+    completing_cell(game, playable, player)
+        .or_else(|| completing_cell(game, playable, player.opponent()))
+        .or_else(positional)
+}
+
+/// A playable cell that would complete a line for `player`.
+fn completing_cell(
+    game: &Game,
+    playable: &[(usize, usize)],
+    player: Player,
+) -> Option<(usize, usize)> {
+    winning_lines(game.rows(), game.columns(), game.config.win_length)
+        .into_iter()
+        .find_map(|line| {
+            let owned = line
+                .iter()
+                .filter(|&&(x, y)| game.board[x][y] == Some(player))
+                .count();
+            let empty: Vec<_> = line
+                .iter()
+                .filter(|&&(x, y)| game.board[x][y].is_none())
+                .collect();
+
+            (owned == line.len() - 1 && empty.len() == 1 && playable.contains(empty[0]))
+                .then(|| *empty[0])
+        })
+}
+
+fn playable_among(playable: &[(usize, usize)], cells: &[(usize, usize)]) -> Vec<(usize, usize)> {
+    cells
+        .iter()
+        .copied()
+        .filter(|cell| playable.contains(cell))
+        .collect()
+}
+
+fn random_of(cells: &[(usize, usize)]) -> Option<(usize, usize)> {
+    cells.choose(&mut rand::thread_rng()).copied()
+}
+
+/// The move with the best minimax score; ties go to the first cell found.
+fn minimax_move(board: &Board, player: Player) -> Option<(usize, usize)> {
+    // Every opening is a draw with best play; skip the most expensive search
+    if is_empty(board) {
+        return Some((1, 1));
+    }
+
+    let mut best: Option<((usize, usize), i32)> = None;
+    for (cell, score) in move_scores(board, player) {
+        if best.is_none_or(|(_, best_score)| score > best_score) {
+            best = Some((cell, score));
+        }
+    }
+
+    best.map(|(cell, _)| cell)
+}
+
+/// Whether playing `(x, y)` is as good as anything perfect play could find for `player`.
+/// Always false on boards too big to search.
+pub fn is_best_move(board: &Board, player: Player, x: usize, y: usize) -> bool {
+    if board.len() != DEFAULT_BOARD_SIZE || board[0].len() != DEFAULT_BOARD_SIZE {
+        return false;
+    }
+    if is_empty(board) {
+        return true;
+    }
+
+    let scores = move_scores(board, player);
+    let best = scores.iter().map(|(_, score)| *score).max();
+    scores
+        .iter()
+        .any(|&(cell, score)| cell == (x, y) && Some(score) == best)
+}
+
+/// Minimax score of every empty cell for `player`.
+fn move_scores(board: &Board, player: Player) -> Vec<((usize, usize), i32)> {
+    let mut board = board.clone();
+    let mut scores = Vec::new();
+
+    for (x, y) in ALL_CELLS {
+        if board[x][y].is_some() {
+            continue;
+        }
+
+        board[x][y] = Some(player);
+        scores.push(((x, y), -negamax(&mut board, player.opponent(), 1)));
+        board[x][y] = None;
+    }
+
+    scores
+}
+
+fn is_empty(board: &Board) -> bool {
+    board.iter().flatten().all(|cell| cell.is_none())
+}
+
+/// Scores the board for `to_move`; quicker wins and slower losses score higher.
+fn negamax(board: &mut Board, to_move: Player, depth: i32) -> i32 {
+    if let Some(winner) = board_winner(board) {
+        let score = 10 - depth;
+        return if winner == to_move { score } else { -score };
+    }
+
+    let mut best = None;
+    for (x, y) in ALL_CELLS {
+        if board[x][y].is_some() {
+            continue;
+        }
+
+        board[x][y] = Some(to_move);
+        let score = -negamax(board, to_move.opponent(), depth + 1);
+        board[x][y] = None;
+
+        best = Some(best.map_or(score, |best: i32| best.max(score)));
+    }
+
+    // No empty cells left: draw
+    best.unwrap_or(0)
+}
+
+fn board_winner(board: &Board) -> Option<Player> {
+    LINES.iter().find_map(|line| {
+        let [a, b, c] = line.map(|(x, y)| board[x][y]);
+        (a.is_some() && a == b && b == c).then_some(a).flatten()
+    })
+}
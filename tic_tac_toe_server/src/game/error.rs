@@ -0,0 +1,269 @@
+use serde::Serialize;
+use std::fmt;
+
+use super::models::Player;
+
+/// Why the server turned a request down. Sent as a `code` plus the `params` a client needs
+/// to word it in the player's own language, with the English text from `Display` alongside
+/// for clients that don't know the code.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "code", content = "params", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GameError {
+    GameOver,
+    PausedForReconnect {
+        name: String,
+    },
+    PausedForBreak,
+    NotYourTurn {
+        player: Player,
+    },
+    NotYourSeat {
+        player: Player,
+    },
+    AlreadyPaused,
+    NotPaused,
+    WaitingForReconnect {
+        name: String,
+    },
+    NotInGame,
+    RoundInProgress,
+    WaitingForOpponent,
+    AiNoBreaks,
+    NoClock,
+    AiNoDraws,
+    BoardSize {
+        min: usize,
+        max: usize,
+    },
+    WinLength {
+        min: usize,
+        max: usize,
+    },
+    WinnerStaysOn,
+    OutOfBounds,
+    CellTaken,
+    ColumnFull,
+    NotYourTeamTurn {
+        submitter: String,
+    },
+    KioskOnly {
+        game_id: String,
+    },
+    Banned {
+        scope: BanScope,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    GameFull,
+    GameNotFound,
+    GameIdInUse,
+    PrivateGame,
+    NotQueued,
+    NameRequired,
+    InviteCodeNotFound,
+    AiDisabled,
+    NotSpectateLinkPlayer,
+    ReplayNotFound,
+    NotHost,
+    CantRemoveSelf,
+    NotPlaying {
+        name: String,
+    },
+    IsPlaying {
+        name: String,
+    },
+    JoinBeforeNotifications,
+    JoinBeforeChat,
+    TeamChatPlayersOnly,
+    BlockedWord,
+    JoinBeforeReacting,
+    UnknownReaction,
+    Malformed {
+        detail: String,
+    },
+    NameClaimed {
+        name: String,
+    },
+    JoinBeforeReporting,
+    CantReportSelf,
+    ReportTargetAbsent {
+        name: String,
+    },
+    TooManyReports {
+        limit: usize,
+    },
+    UnnamedSeat,
+    WrongOpener {
+        player: Player,
+    },
+    IllegalMove {
+        number: usize,
+    },
+    HistoryMismatch,
+    UnfinishedResult,
+    WrongTurn {
+        player: Player,
+    },
+}
+
+/// Who is keeping a `Banned` player out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BanScope {
+    /// The host of one game.
+    Game,
+    /// An admin, from every game (`/admin/bans`).
+    Server,
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameError::GameOver => write!(f, "Game is over!"),
+            GameError::PausedForReconnect { name } => write!(f, "Paused while {name} reconnects"),
+            GameError::PausedForBreak => write!(f, "Paused until both players resume"),
+            GameError::NotYourTurn { player } => write!(f, "It's not {player:?}'s turn."),
+            GameError::NotYourSeat { player } => write!(f, "You aren't playing as {player:?}"),
+            GameError::AlreadyPaused => write!(f, "Game is already paused"),
+            GameError::NotPaused => write!(f, "Game isn't paused"),
+            GameError::WaitingForReconnect { name } => write!(f, "Waiting for {name} to reconnect"),
+            GameError::NotInGame => write!(f, "Player not in game"),
+            GameError::RoundInProgress => write!(f, "Finish the round before starting another"),
+            GameError::WaitingForOpponent => write!(f, "Waiting for an opponent"),
+            GameError::AiNoBreaks => write!(f, "The computer doesn't take breaks"),
+            GameError::NoClock => write!(f, "Correspondence games have no clock to stop"),
+            GameError::AiNoDraws => write!(f, "The computer never agrees to a draw"),
+            GameError::BoardSize { min, max } => {
+                write!(f, "Board size must be between {min} and {max}")
+            }
+            GameError::WinLength { min, max } => {
+                write!(
+                    f,
+                    "Win length must be between {min} and the board size ({max})"
+                )
+            }
+            GameError::WinnerStaysOn => {
+                write!(
+                    f,
+                    "Winner stays on needs a live game with one player per seat"
+                )
+            }
+            GameError::OutOfBounds => write!(f, "Out of bounds"),
+            GameError::CellTaken => write!(f, "Cell already taken"),
+            GameError::ColumnFull => write!(f, "Column is full"),
+            GameError::NotYourTeamTurn { submitter } => {
+                write!(f, "It's {submitter}'s turn to move for your team")
+            }
+            GameError::KioskOnly { game_id } => write!(f, "This server only hosts game {game_id}"),
+            GameError::Banned {
+                scope: BanScope::Game,
+                ..
+            } => write!(f, "The host removed you from this game"),
+            GameError::Banned {
+                scope: BanScope::Server,
+                reason: Some(reason),
+            } => write!(f, "You are banned from this server: {reason}"),
+            GameError::Banned {
+                scope: BanScope::Server,
+                reason: None,
+            } => write!(f, "You are banned from this server"),
+            GameError::GameFull => write!(f, "Game is full"),
+            GameError::GameNotFound => write!(f, "Game ID not found."),
+            GameError::GameIdInUse => write!(f, "Game ID already in use"),
+            GameError::PrivateGame => {
+                write!(f, "This game is private. Ask a player for a spectate link.")
+            }
+            GameError::NotQueued => write!(f, "You are not waiting for a quick match"),
+            GameError::NameRequired => write!(f, "A name is required to log in"),
+            GameError::InviteCodeNotFound => write!(f, "Invite code not found"),
+            GameError::AiDisabled => write!(f, "Playing the computer is disabled on this server"),
+            GameError::NotSpectateLinkPlayer => {
+                write!(f, "Only players can share a spectate link")
+            }
+            GameError::ReplayNotFound => write!(f, "No replay found for that game."),
+            GameError::NotHost => write!(f, "Only the host can remove people from this game"),
+            GameError::CantRemoveSelf => write!(f, "You can't remove yourself"),
+            GameError::NotPlaying { name } => write!(f, "{name} isn't playing in this game"),
+            GameError::IsPlaying { name } => write!(f, "{name} is playing; kick them instead"),
+            GameError::JoinBeforeNotifications => {
+                write!(f, "Join a game before registering notifications")
+            }
+            GameError::JoinBeforeChat => write!(f, "Join a game before chatting"),
+            GameError::TeamChatPlayersOnly => write!(f, "Only players can use team chat"),
+            GameError::BlockedWord => {
+                write!(f, "Message not sent: it contains a word that isn't allowed")
+            }
+            GameError::JoinBeforeReacting => write!(f, "Join a game before reacting"),
+            GameError::UnknownReaction => write!(f, "Unknown reaction"),
+            GameError::Malformed { detail } => write!(f, "Malformed message: {detail}"),
+            GameError::NameClaimed { name } => {
+                write!(f, "{name} is a claimed name; sign in to play as them")
+            }
+            GameError::JoinBeforeReporting => write!(f, "Join a game before reporting a player"),
+            GameError::CantReportSelf => write!(f, "Name another player to report"),
+            GameError::ReportTargetAbsent { name } => {
+                write!(f, "{name} isn't playing or watching this game")
+            }
+            GameError::TooManyReports { limit } => write!(
+                f,
+                "You already have {limit} open reports; a moderator will get to them soon"
+            ),
+            GameError::UnnamedSeat => write!(f, "Every seated player needs a name"),
+            GameError::WrongOpener { player } => write!(f, "Round 1 has to open with {player:?}"),
+            GameError::IllegalMove { number } => write!(f, "Move {number} can't be played"),
+            GameError::HistoryMismatch => write!(f, "Board doesn't match the move history"),
+            GameError::UnfinishedResult => {
+                write!(f, "The board is won or full, but the game isn't over")
+            }
+            GameError::WrongTurn { player } => write!(f, "It should be {player:?}'s turn"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::message::{error_message, move_failed_message};
+    use serde_json::json;
+
+    #[test]
+    fn errors_carry_a_code_and_their_params() {
+        assert_eq!(
+            json!(GameError::NotYourTurn { player: Player::X }),
+            json!({ "code": "NOT_YOUR_TURN", "params": { "player": "X" } })
+        );
+        assert_eq!(
+            json!(GameError::BoardSize { min: 3, max: 15 }),
+            json!({ "code": "BOARD_SIZE", "params": { "min": 3, "max": 15 } })
+        );
+        assert_eq!(json!(GameError::CellTaken), json!({ "code": "CELL_TAKEN" }));
+    }
+
+    #[test]
+    fn a_ban_without_a_reason_leaves_it_out() {
+        let ban = GameError::Banned {
+            scope: BanScope::Server,
+            reason: None,
+        };
+        assert_eq!(
+            json!(ban),
+            json!({ "code": "BANNED", "params": { "scope": "server" } })
+        );
+    }
+
+    #[test]
+    fn error_messages_always_have_params_and_english_text() {
+        let message = error_message(&GameError::GameFull);
+        assert_eq!(message["type"], "ERROR");
+        assert_eq!(message["code"], "GAME_FULL");
+        assert_eq!(message["params"], json!({}));
+        assert_eq!(message["message"], GameError::GameFull.to_string());
+
+        let error = GameError::NotYourTurn { player: Player::O };
+        let message = move_failed_message(&error, Some("m7"));
+        assert_eq!(message["type"], "MOVE_FAILED");
+        assert_eq!(message["request_id"], "m7");
+        assert_eq!(message["params"]["player"], "O");
+        assert_eq!(message["message"], "It's not O's turn.");
+    }
+}
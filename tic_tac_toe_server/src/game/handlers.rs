@@ -1,200 +1,1585 @@
 use anyhow::Result;
 use serde_json::json;
 
+use crate::accounts;
 use crate::app_state::AppState;
-use crate::game::{models::Game, models::Player};
+use crate::auth;
+use crate::capacity::CapacityLimit;
+use crate::clock::now_secs;
+use crate::game::{
+    ai::{play_ai_turn, AI_NAME},
+    error::{BanScope, GameError},
+    ids::{find_by_invite_code, generate_game_id, generate_invite_code},
+    message::{
+        challenger_queued_message, error_message, game_created_message, move_failed_message,
+        queue_status_message, seat_taken_message, server_full_message, state_update_message,
+        ChatMessage, CreateGameRequest, GameEvent, GameOptions, JoinGameRequest, JoinVsAiRequest,
+        MoveRequest, Reaction, SpectateRequest, UpdateReason, REACTIONS,
+    },
+    models::Game,
+    models::{Player, Seat, TimelineEvent, DEFAULT_LOCALE, MAX_BOARD_SIZE, TEAM_SIZE},
+    rules::GameConfig,
+};
+use crate::leaderboard::{DEFAULT_LEADERBOARD_SIZE, MAX_LEADERBOARD_SIZE};
+use crate::moderation::MAX_OPEN_REPORTS;
+use crate::move_audit::AuditEntry;
+use crate::notifications::NotifyTarget;
+use crate::spectate_link;
+use crate::webhooks::WebhookEvent;
+use crate::wire::ClientSocket;
+use crate::word_filter::Verdict;
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use tracing::{error, info};
+use std::time::Instant;
+use tracing::{error, info, instrument, warn};
 
+const MAX_CHAT_LENGTH: usize = 500;
+const MAX_LOCALE_LENGTH: usize = 16;
+const MAX_CONTACT_LENGTH: usize = 320;
+
+/// A fresh game built from its creator's options, or the error to send back if any of
+/// them is out of range.
+fn new_game(
+    options: &GameOptions,
+    state: &AppState,
+    games: &HashMap<String, Game>,
+) -> Result<Game, GameError> {
+    let (config, board) = options.config(state.tunables().turn_seconds)?;
+    Ok(Game {
+        current_turn: config.first_player,
+        config,
+        board,
+        locale: parse_locale(options.locale.as_deref()),
+        invite_code: generate_invite_code(games),
+        correspondence: options.correspondence,
+        team_mode: options.team,
+        visibility: options.visibility,
+        ..Game::default()
+    })
+}
+
+#[instrument(name = "join_game", skip_all, fields(game_id = %request.game_id))]
 pub async fn handle_join_game(
-    parsed: &serde_json::Value,
+    request: &JoinGameRequest,
     state: &Arc<AppState>,
-    socket: &mut axum::extract::ws::WebSocket,
-) -> Result<()> {
-    let game_id = parsed["game_id"].as_str().unwrap_or("").to_string();
-    let name = parsed["name"].as_str().unwrap_or("Anonymous").to_string();
+    socket: &mut ClientSocket,
+) -> Result<Option<Seat>> {
+    let name = request.name.clone();
 
     info!(
         "📥 Received JOIN_GAME request - Game ID: {}, Name: {}",
-        game_id, name
+        request.game_id, name
     );
 
     let mut games = state.games.write().await;
-    let game = games.entry(game_id.clone()).or_insert_with(|| {
-        info!("🆕 Creating new game with ID: {}", game_id);
-        Game::default()
-    });
+    // No ID hosts a brand new game, rather than one everybody without an ID collides into
+    let game_id = if request.game_id.trim().is_empty() && state.config.kiosk_game.is_none() {
+        generate_game_id(&games)
+    } else {
+        request.game_id.clone()
+    };
+    if !games.contains_key(&game_id) {
+        if let Some(kiosk_game) = &state.config.kiosk_game {
+            drop(games);
+            error!("❌ JOIN_GAME rejected: kiosk only hosts {}", kiosk_game);
+            let error = GameError::KioskOnly {
+                game_id: kiosk_game.clone(),
+            };
+            socket.send_json(&error_message(&error)).await?;
+            return Ok(None);
+        }
+
+        let game = match new_game(&request.options, state, &games) {
+            Ok(game) => game,
+            Err(error) => {
+                drop(games);
+                error!("❌ JOIN_GAME rejected: {}", error);
+                socket.send_json(&error_message(&error)).await?;
+                return Ok(None);
+            }
+        };
+
+        if !state.capacity.make_room(&mut games) {
+            drop(games);
+            error!("❌ JOIN_GAME rejected: server at its game cap");
+            let error_message = server_full_message(CapacityLimit::Games);
+            socket.send_json(&error_message).await?;
+            return Ok(None);
+        }
+
+        info!(
+            "🆕 Creating new game with ID: {} (invite code {})",
+            game_id, game.invite_code
+        );
+        state
+            .webhooks
+            .emit(WebhookEvent::game_created(&game_id, &game));
+        games.insert(game_id.clone(), game);
+    }
+    let game = games.get_mut(&game_id).expect("game inserted above");
 
-    if game.players.len() >= 2 {
+    if game.is_banned(&name) {
+        drop(games);
+        error!(
+            "❌ Join request rejected: {} is banned from {}",
+            name, game_id
+        );
+        socket
+            .send_json(&error_message(&GameError::Banned {
+                scope: BanScope::Game,
+                reason: None,
+            }))
+            .await?;
+        return Ok(None);
+    }
+
+    // A player whose socket dropped gets their seat back by name rather than finding the
+    // game full of their own ghost; correspondence players may return to it at any time
+    let returning_seat = game
+        .seat_of(&name)
+        .filter(|seat| game.correspondence || game.disconnected.contains(seat));
+
+    let seat_capacity = if game.team_mode { TEAM_SIZE } else { 1 };
+    let full = [Player::X, Player::O]
+        .iter()
+        .all(|player| game.seat_size(*player) >= seat_capacity);
+
+    if returning_seat.is_none() && full && game.config.winner_stays_on {
+        let position = game.join_line(&name);
+        info!(
+            "🎟️ {} is #{} in line for a seat in game {}",
+            name, position, game_id
+        );
+        state.broadcast_state(&game_id, game, UpdateReason::Queue);
+        let game_update = state_update_message(&game_id, game, UpdateReason::Queue);
+        drop(games);
+
+        socket
+            .send_json(&challenger_queued_message(&game_id, position))
+            .await?;
+        socket.send_json(&game_update).await?;
+        return Ok(None);
+    }
+
+    if returning_seat.is_none() && full {
         error!("❌ Join request rejected: Game {} is full", game_id);
-        let error_message = json!({ "type": "ERROR", "message": "Game is full" });
         socket
-            .send(axum::extract::ws::Message::Text(
-                error_message.to_string().into(),
-            ))
+            .send_json(&error_message(&GameError::GameFull))
             .await?;
-        return Ok(());
+        return Ok(None);
     }
 
-    let assigned_player = if game.players.contains(&Player::X) {
-        Player::O
-    } else {
-        Player::X
+    let mut seat_taken = None;
+    let assigned_player = match returning_seat {
+        Some(seat) => seat,
+        None => {
+            // Teams fill up X, O, X, O so both sides can start before partners arrive
+            let automatic = if game.seat_size(Player::O) < game.seat_size(Player::X) {
+                Player::O
+            } else {
+                Player::X
+            };
+            let seat = match request.player {
+                Some(wanted) if game.seat_size(wanted) < seat_capacity => wanted,
+                // The game isn't full, so the other seat has room
+                Some(wanted) => {
+                    seat_taken = Some(wanted);
+                    wanted.opponent()
+                }
+                None => automatic,
+            };
+
+            if !game.players.contains(&seat) {
+                let seat_name = request
+                    .team_name
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|team_name| game.team_mode && !team_name.is_empty())
+                    .unwrap_or(&name)
+                    .to_string();
+                game.players.push(seat);
+                game.player_names.insert(seat, seat_name.clone());
+                game.scores.entry(seat).or_insert(0);
+                if !game.team_mode {
+                    game.record(TimelineEvent::Join {
+                        player: seat,
+                        name: seat_name,
+                    });
+                }
+            }
+            if game.team_mode {
+                game.team_members
+                    .entry(seat)
+                    .or_default()
+                    .push(name.clone());
+                game.record(TimelineEvent::Join {
+                    player: seat,
+                    name: name.clone(),
+                });
+            } else {
+                state.players.read().await.refresh_ratings(game);
+            }
+            game.version += 1;
+
+            if game.players.len() == 2 {
+                // Start the move clock once both seats are filled
+                game.turn_started_at = std::time::SystemTime::now();
+                state.scoreboards.read().await.restore(&game_id, game);
+            }
+            seat
+        }
     };
+    let was_waiting = game.waiting_to_start();
+    game.connect(assigned_player);
+    if was_waiting && !game.waiting_to_start() {
+        // Both tournament players are here, so the move clock starts now
+        game.turn_started_at = std::time::SystemTime::now();
+    }
 
-    game.players.push(assigned_player);
-    game.player_names.insert(assigned_player, name.clone());
-    game.scores.entry(assigned_player).or_insert(0);
+    // The first person to sit down hosts the game, unless the server made it for
+    // strangers to be matched into, for a tournament pairing or as the kiosk
+    let kiosk = state.config.kiosk_game.as_ref() == Some(&game_id);
+    if game.host.is_none() && !game.quick_match && game.tournament.is_none() && !kiosk {
+        game.host = Some(name.clone());
+    }
 
-    let _ = state.tx.send((game_id.clone(), game.clone()));
+    state.broadcast_state(&game_id, game, UpdateReason::Join);
+    state.broadcast_system(&game_id, game, "player_joined", json!({ "name": name }));
+    state.webhooks.emit(WebhookEvent::PlayerJoined {
+        game_id: game_id.clone(),
+        player: assigned_player,
+        name: name.clone(),
+    });
 
     info!(
         "✅ Player {:?} ({}) successfully joined game {}",
         assigned_player, name, game_id
     );
 
+    let profile = accounts::profile_or_new(state, &name).await;
+    let head_to_head = match game.player_names.get(&assigned_player.opponent()) {
+        Some(opponent) => {
+            let record = state.players.read().await.head_to_head(&name, opponent);
+            Some(json!({
+                "opponent": opponent,
+                "wins": record.wins,
+                "losses": record.losses,
+                "draws": record.draws
+            }))
+        }
+        None => None,
+    };
     let join_success_msg = json!({
         "type": "JOIN_SUCCESS",
         "player": assigned_player,
         "game_id": game_id,
         "name": name,
         "scores": game.scores,
-        "names": game.player_names
+        "names": game.player_names,
+        "ratings": game.ratings,
+        "invite_code": game.invite_code,
+        "teams": game.team_members,
+        "profile": profile,
+        "head_to_head": head_to_head,
+        "seat_token": auth::seat_token(&state.config.spectate_secret, &game_id, &name)
     });
 
-    socket
-        .send(axum::extract::ws::Message::Text(
-            join_success_msg.to_string().into(),
-        ))
-        .await?;
+    if let Some(requested) = seat_taken {
+        info!(
+            "🪑 {} asked for {:?} in game {}, which was taken",
+            name, requested, game_id
+        );
+        socket
+            .send_json(&seat_taken_message(requested, assigned_player))
+            .await?;
+    }
+    socket.send_json(&join_success_msg).await?;
+
+    let game_update = state_update_message(&game_id, game, UpdateReason::Join);
 
-    let game_update = json!({
-        "type": "UPDATE_STATE",
-        "game_id": game_id,
-        "game": game
+    socket.send_json(&game_update).await?;
+
+    Ok(Some(Seat {
+        game_id,
+        player: assigned_player,
+    }))
+}
+
+pub async fn handle_spectate(
+    request: &SpectateRequest,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<bool> {
+    let game_id = request.game_id.clone();
+    info!("📥 Received SPECTATE request - Game ID: {}", game_id);
+
+    let games = state.games.read().await;
+    let Some(game) = games.get(&game_id) else {
+        drop(games);
+        error!("❌ Spectate request rejected: Game {} not found", game_id);
+        socket
+            .send_json(&error_message(&GameError::GameNotFound))
+            .await?;
+        return Ok(false);
+    };
+
+    if !spectate_link::may_view(
+        &state.config.spectate_secret,
+        &game_id,
+        game.config.private,
+        false,
+        request.token.as_deref(),
+    ) {
+        drop(games);
+        error!(
+            "❌ Spectate request rejected: invalid link for private game {}",
+            game_id
+        );
+        socket
+            .send_json(&error_message(&GameError::PrivateGame))
+            .await?;
+        return Ok(false);
+    }
+
+    if request
+        .name
+        .as_deref()
+        .is_some_and(|name| game.is_banned(name))
+    {
+        drop(games);
+        error!("❌ Spectate request rejected: banned from game {}", game_id);
+        socket
+            .send_json(&error_message(&GameError::Banned {
+                scope: BanScope::Game,
+                reason: None,
+            }))
+            .await?;
+        return Ok(false);
+    }
+
+    let spectate_success_msg = json!({
+        "type": "SPECTATE_SUCCESS",
+        "game_id": game_id
     });
+    let game_update = state_update_message(&game_id, game, UpdateReason::Resync);
+    drop(games);
 
-    socket
-        .send(axum::extract::ws::Message::Text(
-            game_update.to_string().into(),
-        ))
-        .await?;
+    socket.send_json(&spectate_success_msg).await?;
+    socket.send_json(&game_update).await?;
 
-    Ok(())
+    info!("👀 Spectator subscribed to game {}", game_id);
+    Ok(true)
 }
 
-pub async fn handle_make_move(
-    parsed: &serde_json::Value,
+/// Creates a game with exactly the options asked for and seats its creator. Unlike
+/// JOIN_GAME it never joins an existing game, so the options are never silently ignored.
+pub async fn handle_create_game(
+    request: &CreateGameRequest,
     state: &Arc<AppState>,
-    socket: &mut axum::extract::ws::WebSocket,
-) -> Result<()> {
-    let game_id = parsed["game_id"].as_str().unwrap_or("").to_string();
-    let x = parsed["x"].as_u64().unwrap_or(100) as usize;
-    let y = parsed["y"].as_u64().unwrap_or(100) as usize;
+    socket: &mut ClientSocket,
+) -> Result<Option<Seat>> {
+    let requested_id = request.game_id.trim().to_string();
+    info!(
+        "📥 Received CREATE_GAME request - Game ID: {}, Name: {}",
+        requested_id, request.name
+    );
 
-    let player = match parsed["player"].as_str() {
-        Some("X") => Player::X,
-        Some("O") => Player::O,
-        _ => {
-            error!(
-                "❌ Invalid player received in MOVE request: {:?}",
-                parsed["player"]
-            );
-            let error_msg = json!({ "type": "MOVE_FAILED", "message": "Invalid player" });
+    if let Some(kiosk_game) = &state.config.kiosk_game {
+        error!("❌ CREATE_GAME rejected: kiosk only hosts {}", kiosk_game);
+        let error = GameError::KioskOnly {
+            game_id: kiosk_game.clone(),
+        };
+        socket.send_json(&error_message(&error)).await?;
+        return Ok(None);
+    }
+
+    let mut games = state.games.write().await;
+    let game_id = if requested_id.is_empty() {
+        generate_game_id(&games)
+    } else {
+        requested_id
+    };
+
+    if games.contains_key(&game_id) {
+        drop(games);
+        error!("❌ CREATE_GAME rejected: Game {} already exists", game_id);
+        socket
+            .send_json(&error_message(&GameError::GameIdInUse))
+            .await?;
+        return Ok(None);
+    }
+
+    let game = match new_game(&request.options, state, &games) {
+        Ok(game) => game,
+        Err(error) => {
+            drop(games);
+            error!("❌ CREATE_GAME rejected: {}", error);
+            socket.send_json(&error_message(&error)).await?;
+            return Ok(None);
+        }
+    };
+
+    if !state.capacity.make_room(&mut games) {
+        drop(games);
+        error!("❌ CREATE_GAME rejected: server at its game cap");
+        let error_message = server_full_message(CapacityLimit::Games);
+        socket.send_json(&error_message).await?;
+        return Ok(None);
+    }
+
+    info!("🆕 Creating game {} with {:?}", game_id, game.config);
+    let created_message = game_created_message(&game_id, &game);
+    state
+        .webhooks
+        .emit(WebhookEvent::game_created(&game_id, &game));
+    games.insert(game_id.clone(), game);
+    drop(games);
+    socket.send_json(&created_message).await?;
+
+    let join_request = JoinGameRequest {
+        game_id,
+        name: request.name.clone(),
+        team_name: request.team_name.clone(),
+        player: request.player,
+        ..JoinGameRequest::default()
+    };
+    handle_join_game(&join_request, state, socket).await
+}
+
+/// Seats the player opposite whoever has waited longest for a quick match, or opens a new
+/// game and puts them in the queue if nobody is waiting.
+pub async fn handle_quick_match(
+    name: &str,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<Option<Seat>> {
+    info!("📥 Received QUICK_MATCH request - Name: {}", name);
+
+    let mut queued = false;
+    let rating = state.players.read().await.rating(name);
+    let game_id = if let Some(kiosk_game) = &state.config.kiosk_game {
+        kiosk_game.clone()
+    } else {
+        let mut games = state.games.write().await;
+        let mut queue = state.quick_match_queue.write().await;
+
+        match queue.take_match(name, rating, &games) {
+            Some(id) => id,
+            None => {
+                if !state.capacity.make_room(&mut games) {
+                    drop(queue);
+                    drop(games);
+                    error!("❌ QUICK_MATCH rejected: server at its game cap");
+                    let error_message = server_full_message(CapacityLimit::Games);
+                    socket.send_json(&error_message).await?;
+                    return Ok(None);
+                }
+
+                let id = generate_game_id(&games);
+                info!("🆕 Creating quick-match game with ID: {}", id);
+                let game = Game {
+                    quick_match: true,
+                    config: GameConfig {
+                        turn_seconds: state.tunables().turn_seconds,
+                        ..GameConfig::default()
+                    },
+                    invite_code: generate_invite_code(&games),
+                    ..Game::default()
+                };
+                state.webhooks.emit(WebhookEvent::game_created(&id, &game));
+                games.insert(id.clone(), game);
+                queued = true;
+                id
+            }
+        }
+    };
+
+    let join_request = JoinGameRequest {
+        game_id: game_id.clone(),
+        name: name.to_string(),
+        ..JoinGameRequest::default()
+    };
+    let seat = handle_join_game(&join_request, state, socket).await?;
+
+    // Queued only once seated, since a game nobody is connected to is pruned. The periodic
+    // updates take a few seconds to start, so say where they stand right away.
+    if queued && seat.is_some() {
+        // Presence only catches up with this socket after the handler returns
+        let players_online = state.presence.read().await.online_count().max(1);
+        let mut queue = state.quick_match_queue.write().await;
+        queue.enqueue(&game_id, name, rating);
+        let status = queue.status(&game_id, players_online);
+        drop(queue);
+        if let Some(status) = status {
             socket
-                .send(axum::extract::ws::Message::Text(
-                    error_msg.to_string().into(),
-                ))
+                .send_json(&queue_status_message(&game_id, &status))
                 .await?;
-            return Ok(());
         }
+    }
+    Ok(seat)
+}
+
+/// Takes a player who is still waiting for an opponent out of the quick-match queue and
+/// closes their empty game. Returns whether they were in the queue.
+pub async fn handle_cancel_quick_match(
+    seat: Option<&Seat>,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<bool> {
+    info!("📥 Received CANCEL_QUICK_MATCH request");
+
+    let mut games = state.games.write().await;
+    let mut queue = state.quick_match_queue.write().await;
+    queue.prune(&games);
+
+    let Some(seat) = seat.filter(|seat| queue.leave(&seat.game_id)) else {
+        drop(queue);
+        drop(games);
+        socket
+            .send_json(&error_message(&GameError::NotQueued))
+            .await?;
+        return Ok(false);
+    };
+
+    games.remove(&seat.game_id);
+    drop(queue);
+    drop(games);
+    info!(
+        "🚪 Quick-match game {} closed before an opponent arrived",
+        seat.game_id
+    );
+
+    let left_message = json!({ "type": "QUEUE_LEFT", "game_id": seat.game_id });
+    socket.send_json(&left_message).await?;
+    Ok(true)
+}
+
+/// Identifies the socket by name and lists the correspondence games that player is in.
+pub async fn handle_login(
+    name: &str,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<Option<String>> {
+    let name = name.trim().to_string();
+    info!("📥 Received LOGIN request - Name: {}", name);
+
+    if name.is_empty() {
+        socket
+            .send_json(&error_message(&GameError::NameRequired))
+            .await?;
+        return Ok(None);
+    }
+
+    let mut waiting: Vec<serde_json::Value> = state
+        .games
+        .read()
+        .await
+        .iter()
+        .filter(|(_, game)| game.correspondence && !game.game_over)
+        .filter_map(|(game_id, game)| {
+            let (&seat, _) = game.player_names.iter().find(|(_, n)| **n == name)?;
+            Some(json!({
+                "game_id": game_id,
+                "opponent": game.player_names.get(&seat.opponent()),
+                "your_turn": game.players.len() == 2 && game.current_turn == seat,
+                "last_activity": game.last_activity
+            }))
+        })
+        .collect();
+    // Games waiting on this player first
+    waiting.sort_by_key(|entry| !entry["your_turn"].as_bool().unwrap_or(false));
+
+    let response = json!({
+        "type": "WAITING_GAMES",
+        "name": name,
+        "games": waiting
+    });
+    socket.send_json(&response).await?;
+
+    Ok(Some(name))
+}
+
+/// Seats the player in the game an invite code points at.
+pub async fn handle_join_by_code(
+    code: &str,
+    name: &str,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<Option<Seat>> {
+    info!(
+        "📥 Received JOIN_BY_CODE request - Code: {}, Name: {}",
+        code, name
+    );
+
+    let Some(game_id) = find_by_invite_code(&*state.games.read().await, code) else {
+        error!("❌ JOIN_BY_CODE rejected: unknown code {}", code);
+        socket
+            .send_json(&error_message(&GameError::InviteCodeNotFound))
+            .await?;
+        return Ok(None);
+    };
+
+    let join_request = JoinGameRequest {
+        game_id,
+        name: name.to_string(),
+        ..JoinGameRequest::default()
+    };
+    handle_join_game(&join_request, state, socket).await
+}
+
+/// Starts a fresh game with the bot in the second seat.
+pub async fn handle_join_vs_ai(
+    request: &JoinVsAiRequest,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<Option<Seat>> {
+    let requested_id = request.game_id.trim().to_string();
+    let difficulty = request.difficulty;
+    info!(
+        "📥 Received JOIN_VS_AI request - Game ID: {}, Name: {}, Difficulty: {:?}",
+        requested_id, request.name, difficulty
+    );
+
+    if state.config.kiosk_game.is_some() {
+        error!("❌ JOIN_VS_AI rejected: server is in kiosk mode");
+        socket
+            .send_json(&error_message(&GameError::AiDisabled))
+            .await?;
+        return Ok(None);
+    }
+
+    let options = GameOptions {
+        kind: request.kind,
+        board_size: request.board_size,
+        win_length: request.win_length,
+        ..GameOptions::default()
+    };
+    let (config, board) = match options.config(state.tunables().turn_seconds) {
+        Ok(rules) => rules,
+        Err(error) => {
+            error!("❌ JOIN_VS_AI rejected: {}", error);
+            socket.send_json(&error_message(&error)).await?;
+            return Ok(None);
+        }
+    };
+
+    let game_id = {
+        let mut games = state.games.write().await;
+        let game_id = if requested_id.is_empty() {
+            generate_game_id(&games)
+        } else {
+            requested_id
+        };
+
+        if games.contains_key(&game_id) {
+            drop(games);
+            error!("❌ JOIN_VS_AI rejected: Game {} already exists", game_id);
+            socket
+                .send_json(&error_message(&GameError::GameIdInUse))
+                .await?;
+            return Ok(None);
+        }
+
+        if !state.capacity.make_room(&mut games) {
+            drop(games);
+            error!("❌ JOIN_VS_AI rejected: server at its game cap");
+            let error_message = server_full_message(CapacityLimit::Games);
+            socket.send_json(&error_message).await?;
+            return Ok(None);
+        }
+
+        info!("🆕 Creating game {} against the AI", game_id);
+        let mut game = Game {
+            config,
+            board,
+            ai_player: Some(Player::O),
+            ai_difficulty: difficulty,
+            ..Game::default()
+        };
+        game.players.push(Player::O);
+        game.player_names.insert(Player::O, AI_NAME.to_string());
+        game.record(TimelineEvent::Join {
+            player: Player::O,
+            name: AI_NAME.to_string(),
+        });
+        state
+            .webhooks
+            .emit(WebhookEvent::game_created(&game_id, &game));
+        games.insert(game_id.clone(), game);
+        game_id
     };
 
+    let join_request = JoinGameRequest {
+        game_id,
+        name: request.name.clone(),
+        ..JoinGameRequest::default()
+    };
+    handle_join_game(&join_request, state, socket).await
+}
+
+/// Checks that a socket holding `seat` in the game may act as `player`: only the seat it
+/// actually holds, never the opponent's.
+fn check_seat(seat: Option<&Seat>, player: Player) -> Result<(), GameError> {
+    match seat {
+        None => Err(GameError::NotInGame),
+        Some(seat) if seat.player != player => Err(GameError::NotYourSeat { player }),
+        Some(_) => Ok(()),
+    }
+}
+
+pub async fn handle_make_move(
+    request: &MoveRequest,
+    state: &Arc<AppState>,
+    sender: Option<&str>,
+    seat: Option<&Seat>,
+    connection_id: u64,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let &MoveRequest {
+        ref game_id,
+        player,
+        x,
+        y,
+        ..
+    } = request;
+
     info!(
         "📥 MOVE request received - Game ID: {}, Player: {:?}, Position: ({}, {})",
         game_id, player, x, y
     );
 
-    if x >= 3 || y >= 3 {
+    let result = match check_seat(seat, player) {
+        Ok(()) => apply_move(request, state, sender, Some(connection_id)).await,
+        Err(error) => {
+            error!("❌ Move for {:?} rejected: {}", player, error);
+            let entry = AuditEntry::new(request, sender, Some(connection_id), Err(&error));
+            state.move_audit.record(&entry).await;
+            Err(error)
+        }
+    };
+    let reply =
+        result.unwrap_or_else(|error| move_failed_message(&error, request.request_id.as_deref()));
+    socket.send_json(&reply).await?;
+
+    Ok(())
+}
+
+/// Plays `request` for `sender` and tells the room, returning the resulting state update
+/// or why the move was refused. Shared by MAKE_MOVE and `POST /games/{id}/moves`; either
+/// way the attempt goes into the move audit.
+#[instrument(name = "move", skip_all, fields(game_id = %request.game_id))]
+pub async fn apply_move(
+    request: &MoveRequest,
+    state: &Arc<AppState>,
+    sender: Option<&str>,
+    connection_id: Option<u64>,
+) -> Result<serde_json::Value, GameError> {
+    let result = play_move(request, state, sender).await;
+    let outcome = result.as_ref().map(|_| ());
+    let entry = AuditEntry::new(request, sender, connection_id, outcome);
+    state.move_audit.record(&entry).await;
+    // Only the mover's own reply says which of its moves it answers, not the broadcast
+    result.map(|mut update_msg| {
+        if let Some(request_id) = &request.request_id {
+            update_msg["request_id"] = json!(request_id);
+        }
+        update_msg
+    })
+}
+
+async fn play_move(
+    request: &MoveRequest,
+    state: &Arc<AppState>,
+    sender: Option<&str>,
+) -> Result<serde_json::Value, GameError> {
+    let &MoveRequest {
+        ref game_id,
+        player,
+        x,
+        y,
+        ref request_id,
+    } = request;
+
+    // The game's own size is checked once it's locked; this just turns away nonsense early
+    if x >= MAX_BOARD_SIZE || y >= MAX_BOARD_SIZE {
         error!("❌ Invalid MOVE request: Out of bounds - ({}, {})", x, y);
-        let error_msg = json!({ "type": "MOVE_FAILED", "message": "Coordinates out of bounds" });
+        return Err(GameError::OutOfBounds);
+    }
+
+    // Includes waiting for the games lock, which is where contention shows up
+    let started = Instant::now();
+    let mut games = state.games.write().await;
+    let Some(game) = games.get_mut(game_id) else {
+        error!("❌ Game ID {} not found.", game_id);
+        return Err(GameError::GameNotFound);
+    };
+
+    if let Some(request_id) = request_id.as_deref().filter(|id| game.played_request(id)) {
+        info!(
+            "🔁 Move request {} in game {} was already played; acknowledging again",
+            request_id, game_id
+        );
+        return Ok(state_update_message(game_id, game, UpdateReason::Move));
+    }
+
+    if !game.players.contains(&player) {
+        error!(
+            "❌ Player {:?} is not in game {}. Move rejected.",
+            player, game_id
+        );
+        return Err(GameError::NotInGame);
+    }
+
+    if let Some(submitter) = game.submitter(player) {
+        if sender != Some(submitter) {
+            error!(
+                "❌ Team move rejected in game {}: it is {}'s turn to submit",
+                game_id, submitter
+            );
+            return Err(GameError::NotYourTeamTurn {
+                submitter: submitter.to_string(),
+            });
+        }
+    }
+
+    if let Err(err) = game.make_move(player, x, y) {
+        error!("❌ Move failed: {}", err);
+        return Err(err);
+    }
+    state.stats.record_move();
+    if let Some(request_id) = request_id {
+        game.remember_request(request_id);
+    }
+
+    info!(
+        "✅ Move applied: {:?} at ({}, {}) in game {}",
+        player, x, y, game_id
+    );
+    if game.game_over {
+        state.finish_game(game_id, game).await;
+    }
+    let update_msg = state_update_message(game_id, game, UpdateReason::Move);
+    state.broadcast_state(game_id, game, UpdateReason::Move);
+
+    let elapsed = started.elapsed();
+    if state.move_latency.record_handler(elapsed) {
+        warn!(
+            "🐢 Move in game {} took {:?} to process, over the {:?} budget",
+            game_id,
+            elapsed,
+            state.move_latency.budget()
+        );
+    }
+
+    if play_ai_turn(game) {
+        state.stats.record_move();
+        if game.game_over {
+            state.finish_game(game_id, game).await;
+        }
+        state.broadcast_state(game_id, game, UpdateReason::Move);
+    }
+
+    Ok(update_msg)
+}
+
+/// Resigns the seat the socket holds; spectators and other sockets can't resign for it.
+pub async fn handle_resign(
+    game_id: &str,
+    player: Player,
+    seat: Option<&Seat>,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    info!(
+        "📥 Received RESIGN request - Game ID: {}, Player: {:?}",
+        game_id, player
+    );
+    if let Err(err) = check_seat(seat, player) {
+        error!("❌ Resign failed: {}", err);
+        socket.send_json(&error_message(&err)).await?;
+        return Ok(());
+    }
+
+    let mut games = state.games.write().await;
+    let result = match games.get_mut(game_id) {
+        Some(game) => game.resign(player).map(|_| game),
+        None => Err(GameError::GameNotFound),
+    };
+
+    match result {
+        Ok(game) => {
+            info!("🏳️ {:?} resigned game {}", player, game_id);
+            state.finish_game(game_id, game).await;
+            state.broadcast_state(game_id, game, UpdateReason::Resign);
+            let name = game.name_of(player);
+            state.broadcast_system(game_id, game, "player_resigned", json!({ "name": name }));
+        }
+        Err(err) => {
+            drop(games);
+            error!("❌ Resign failed: {}", err);
+            socket.send_json(&error_message(&err)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Offers a draw, ending the game as one if the opponent had already offered.
+pub async fn handle_offer_draw(
+    game_id: &str,
+    player: Player,
+    seat: Option<&Seat>,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    info!(
+        "📥 Received OFFER_DRAW request - Game ID: {}, Player: {:?}",
+        game_id, player
+    );
+    if let Err(err) = check_seat(seat, player) {
+        error!("❌ Draw offer failed: {}", err);
+        socket.send_json(&error_message(&err)).await?;
+        return Ok(());
+    }
+
+    let mut games = state.games.write().await;
+    let result = match games.get_mut(game_id) {
+        Some(game) => game.offer_draw(player).map(|agreed| (game, agreed)),
+        None => Err(GameError::GameNotFound),
+    };
+
+    match result {
+        Ok((game, true)) => {
+            info!("🤝 Draw agreed in game {}", game_id);
+            state.finish_game(game_id, game).await;
+            state.broadcast_state(game_id, game, UpdateReason::Draw);
+            state.broadcast_system(game_id, game, "draw_agreed", json!({}));
+        }
+        Ok((game, false)) => {
+            info!("🤝 {:?} offered a draw in game {}", player, game_id);
+            state.broadcast_state(game_id, game, UpdateReason::DrawOffer);
+            let name = game.name_of(player);
+            state.broadcast_system(game_id, game, "draw_offered", json!({ "name": name }));
+        }
+        Err(err) => {
+            drop(games);
+            error!("❌ Draw offer failed: {}", err);
+            socket.send_json(&error_message(&err)).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Whether a player wants to stop for a while or carry on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakRequest {
+    Pause,
+    Resume,
+}
+
+/// Records `player`'s wish to pause or resume. The game only changes once the opponent
+/// has asked for the same.
+pub async fn handle_break_request(
+    request: BreakRequest,
+    game_id: &str,
+    player: Player,
+    seat: Option<&Seat>,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    info!(
+        "📥 Received {:?} request - Game ID: {}, Player: {:?}",
+        request, game_id, player
+    );
+    if let Err(err) = check_seat(seat, player) {
+        error!("❌ {:?} request failed: {}", request, err);
+        socket.send_json(&error_message(&err)).await?;
+        return Ok(());
+    }
+
+    let mut games = state.games.write().await;
+    let result = match games.get_mut(game_id) {
+        Some(game) => match request {
+            BreakRequest::Pause => game.request_pause(player),
+            BreakRequest::Resume => game.request_resume(player),
+        }
+        .map(|agreed| (game, agreed)),
+        None => Err(GameError::GameNotFound),
+    };
+
+    let (game, agreed) = match result {
+        Ok(result) => result,
+        Err(err) => {
+            drop(games);
+            error!("❌ {:?} request failed: {}", request, err);
+            socket.send_json(&error_message(&err)).await?;
+            return Ok(());
+        }
+    };
+
+    let name = game.name_of(player);
+    let (reason, key) = match (request, agreed) {
+        (BreakRequest::Pause, true) => (UpdateReason::Pause, "pause_agreed"),
+        (BreakRequest::Pause, false) => (UpdateReason::PauseOffer, "pause_requested"),
+        (BreakRequest::Resume, true) => (UpdateReason::Resume, "resume_agreed"),
+        (BreakRequest::Resume, false) => (UpdateReason::ResumeOffer, "resume_requested"),
+    };
+    info!("⏸️ {:?} in game {}: {}", player, game_id, key);
+    state.broadcast_state(game_id, game, reason);
+    state.broadcast_system(game_id, game, key, json!({ "name": name }));
+
+    Ok(())
+}
+
+/// Issues a signed, expiring spectate link to one of the seated players.
+pub async fn handle_create_spectate_link(
+    state: &Arc<AppState>,
+    game_id: Option<&str>,
+    player_name: Option<&str>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let game_id = game_id.unwrap_or("").to_string();
+    info!(
+        "📥 Received CREATE_SPECTATE_LINK request - Game ID: {}",
+        game_id
+    );
+
+    let seated = state
+        .games
+        .read()
+        .await
+        .get(&game_id)
+        .is_some_and(|game| player_name.is_some_and(|name| game.seat_of(name).is_some()));
+
+    let response = if seated {
+        let expires_at = now_secs() + state.config.spectate_link_ttl;
+        let token = spectate_link::sign(&state.config.spectate_secret, &game_id, expires_at);
+        let url = format!(
+            "{}/?spectate={}&token={}",
+            state.config.public_url, game_id, token
+        );
+        json!({
+            "type": "SPECTATE_LINK",
+            "game_id": game_id,
+            "token": token,
+            "expires_at": expires_at,
+            "url": url
+        })
+    } else {
+        error!(
+            "❌ CREATE_SPECTATE_LINK rejected: not a player in game {}",
+            game_id
+        );
+        error_message(&GameError::NotSpectateLinkPlayer)
+    };
+
+    socket.send_json(&response).await?;
+
+    Ok(())
+}
+
+/// Sends every recorded round of `game_id`. Private games' rounds need the socket to be
+/// `admitted` to the game, as a player or a linked spectator, or a valid link `token`.
+pub async fn handle_get_replay(
+    game_id: &str,
+    admitted: bool,
+    token: Option<&str>,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    info!("📥 Received GET_REPLAY request - Game ID: {}", game_id);
+
+    let response = match state.replays.read().await.get(game_id) {
+        Some(replays)
+            if !spectate_link::may_view(
+                &state.config.spectate_secret,
+                game_id,
+                replays.iter().any(|replay| replay.config.private),
+                admitted,
+                token,
+            ) =>
+        {
+            error!(
+                "❌ GET_REPLAY rejected: no link for private game {}",
+                game_id
+            );
+            error_message(&GameError::PrivateGame)
+        }
+        Some(replays) => json!({
+            "type": "REPLAY",
+            "game_id": game_id,
+            "rounds": replays
+        }),
+        None => {
+            error!("❌ No replay found for game {}", game_id);
+            error_message(&GameError::ReplayNotFound)
+        }
+    };
+
+    socket.send_json(&response).await?;
+
+    Ok(())
+}
+
+/// What the host asked to do to someone in their game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostAction {
+    KickPlayer,
+    BanSpectator,
+}
+
+/// Kicks a player or bans a spectator from the host's game. Either way the name goes on
+/// the game's ban list, so they can't simply join or watch again.
+pub async fn handle_host_action(
+    action: HostAction,
+    target: &str,
+    state: &Arc<AppState>,
+    game_id: Option<&str>,
+    host: Option<&str>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let game_id = game_id.unwrap_or("").to_string();
+    let target = target.trim();
+    info!(
+        "📥 Received {:?} request - Game ID: {}, Target: {}",
+        action, game_id, target
+    );
+
+    let mut games = state.games.write().await;
+    let refusal = match games.get_mut(&game_id) {
+        None => Some(GameError::GameNotFound),
+        Some(game) if host.is_none() || game.host.as_deref() != host => Some(GameError::NotHost),
+        Some(_) if host == Some(target) => Some(GameError::CantRemoveSelf),
+        Some(game) => {
+            let seated = game.seat_of(target).is_some();
+            match action {
+                HostAction::KickPlayer if !seated => Some(GameError::NotPlaying {
+                    name: target.to_string(),
+                }),
+                HostAction::BanSpectator if seated => Some(GameError::IsPlaying {
+                    name: target.to_string(),
+                }),
+                _ => {
+                    let key = match action {
+                        HostAction::KickPlayer => "player_kicked",
+                        HostAction::BanSpectator => "spectator_banned",
+                    };
+                    state.remove_from_game(&game_id, game, target, UpdateReason::Kick, key);
+                    None
+                }
+            }
+        }
+    };
+    drop(games);
+
+    if let Some(error) = refusal {
+        error!("❌ {:?} rejected: {}", action, error);
+        socket.send_json(&error_message(&error)).await?;
+        return Ok(());
+    }
+
+    info!("🚫 {} removed from game {}", target, game_id);
+
+    Ok(())
+}
+
+/// Files `reporter`'s report about `target` for a moderator to look at.
+pub async fn handle_report_player(
+    target: &str,
+    reason: &str,
+    state: &Arc<AppState>,
+    game_id: Option<&str>,
+    reporter: Option<&str>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let target = target.trim();
+    let Some(reporter) = reporter else {
+        error!("❌ REPORT_PLAYER rejected: socket has no player name");
+        socket
+            .send_json(&error_message(&GameError::JoinBeforeReporting))
+            .await?;
+        return Ok(());
+    };
+    if target.is_empty() || target == reporter {
+        error!(
+            "❌ REPORT_PLAYER rejected: {} reported {:?}",
+            reporter, target
+        );
         socket
-            .send(axum::extract::ws::Message::Text(
-                error_msg.to_string().into(),
-            ))
+            .send_json(&error_message(&GameError::CantReportSelf))
             .await?;
         return Ok(());
     }
 
-    let mut games = state.games.write().await;
-    if let Some(game) = games.get_mut(&game_id) {
-        if !game.players.contains(&player) {
+    let Some(game_id) = game_id else {
+        error!(
+            "❌ REPORT_PLAYER rejected: {} isn't following a game",
+            reporter
+        );
+        socket
+            .send_json(&error_message(&GameError::JoinBeforeReporting))
+            .await?;
+        return Ok(());
+    };
+    let present = state.games.read().await.get(game_id).is_some_and(|game| {
+        game.seat_of(target).is_some() || game.spectator_names.iter().any(|n| n == target)
+    });
+    if !present {
+        error!(
+            "❌ REPORT_PLAYER rejected: {} isn't in game {}",
+            target, game_id
+        );
+        socket
+            .send_json(&error_message(&GameError::ReportTargetAbsent {
+                name: target.to_string(),
+            }))
+            .await?;
+        return Ok(());
+    }
+
+    let filed = state
+        .reports
+        .write()
+        .await
+        .file(reporter, target, Some(game_id), reason.trim());
+    let Some(report_id) = filed else {
+        error!(
+            "❌ REPORT_PLAYER rejected: {} has too many open reports",
+            reporter
+        );
+        socket
+            .send_json(&error_message(&GameError::TooManyReports {
+                limit: MAX_OPEN_REPORTS,
+            }))
+            .await?;
+        return Ok(());
+    };
+    // Written under a read lock, so other reports can still be looked at meanwhile
+    state.reports.read().await.save().await;
+    warn!(
+        "🚩 {} reported {} in game {} (report {})",
+        reporter, target, game_id, report_id
+    );
+
+    let response = json!({
+        "type": "REPORT_RECEIVED",
+        "report_id": report_id,
+        "target": target
+    });
+    socket.send_json(&response).await?;
+
+    Ok(())
+}
+
+/// Registers (or, with no contact details, clears) where to ping this player about waiting games.
+pub async fn handle_register_notifications(
+    webhook_url: Option<&str>,
+    email: Option<&str>,
+    state: &Arc<AppState>,
+    player_name: Option<&str>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let contact = |value: Option<&str>| {
+        value
+            .map(str::trim)
+            .filter(|value| !value.is_empty() && value.len() <= MAX_CONTACT_LENGTH)
+            .map(str::to_string)
+    };
+    let target = NotifyTarget {
+        webhook_url: contact(webhook_url)
+            .filter(|url| url.starts_with("https://") || url.starts_with("http://")),
+        email: contact(email).filter(|email| email.contains('@')),
+    };
+
+    let response = match player_name {
+        Some(name) => {
+            info!("🔔 Updating notification target for {}", name);
+            let mut relay = state.notifications.write().await;
+            relay.register(name, target.clone());
+            relay.save().await;
+            json!({
+                "type": "NOTIFICATIONS_REGISTERED",
+                "webhook_url": target.webhook_url,
+                "email": target.email
+            })
+        }
+        None => {
+            error!("❌ REGISTER_NOTIFICATIONS rejected: socket has no player name");
+            error_message(&GameError::JoinBeforeNotifications)
+        }
+    };
+
+    socket.send_json(&response).await?;
+
+    Ok(())
+}
+
+pub async fn handle_get_leaderboard(
+    limit: Option<usize>,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let limit = limit
+        .unwrap_or(DEFAULT_LEADERBOARD_SIZE)
+        .min(MAX_LEADERBOARD_SIZE);
+
+    let leaderboard = state.players.read().await.leaderboard(limit);
+    let leaderboard_msg = json!({
+        "type": "LEADERBOARD",
+        "players": leaderboard
+    });
+
+    socket.send_json(&leaderboard_msg).await?;
+
+    Ok(())
+}
+
+/// Sends the state of `game_id`, returning whether it did so the caller can follow the
+/// game. Private games need the socket `admitted` to them or a valid link `token`.
+pub async fn handle_get_state(
+    game_id: &str,
+    admitted: bool,
+    token: Option<&str>,
+    state: &Arc<AppState>,
+    socket: &mut ClientSocket,
+) -> Result<bool> {
+    info!("📥 Received GET_STATE request - Game ID: {}", game_id);
+
+    let games = state.games.read().await;
+    let (response, sent) = match games.get(game_id) {
+        Some(game)
+            if !spectate_link::may_view(
+                &state.config.spectate_secret,
+                game_id,
+                game.config.private,
+                admitted,
+                token,
+            ) =>
+        {
             error!(
-                "❌ Player {:?} is not in game {}. Move rejected.",
-                player, game_id
+                "❌ GET_STATE rejected: no link for private game {}",
+                game_id
+            );
+            (error_message(&GameError::PrivateGame), false)
+        }
+        Some(game) => (
+            state_update_message(game_id, game, UpdateReason::Resync),
+            true,
+        ),
+        None => {
+            error!("❌ Game ID {} not found for GET_STATE.", game_id);
+            (error_message(&GameError::GameNotFound), false)
+        }
+    };
+    drop(games);
+
+    socket.send_json(&response).await?;
+
+    Ok(sent)
+}
+
+pub async fn handle_chat(
+    text: &str,
+    team: bool,
+    state: &Arc<AppState>,
+    game_id: Option<&str>,
+    seat: Option<&Seat>,
+    sender: &str,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let Some(game_id) = game_id else {
+        error!("❌ CHAT rejected: socket has not joined that game");
+        socket
+            .send_json(&error_message(&GameError::JoinBeforeChat))
+            .await?;
+        return Ok(());
+    };
+
+    let team = match seat {
+        _ if !team => None,
+        Some(seat) if seat.game_id == game_id => Some(seat.player),
+        _ => {
+            error!(
+                "❌ Team CHAT rejected: socket holds no seat in game {}",
+                game_id
             );
-            let error_msg = json!({ "type": "MOVE_FAILED", "message": "Player not in game" });
             socket
-                .send(axum::extract::ws::Message::Text(
-                    error_msg.to_string().into(),
-                ))
+                .send_json(&error_message(&GameError::TeamChatPlayersOnly))
                 .await?;
             return Ok(());
         }
+    };
 
-        match game.make_move(player, x, y) {
-            Ok(_) => {
-                info!(
-                    "✅ Move applied: {:?} at ({}, {}) in game {}",
-                    player, x, y, game_id
-                );
-                let update_msg = json!({
-                    "type": "UPDATE_STATE",
-                    "game": game
-                });
-                let _ = state.tx.send((game_id.clone(), game.clone()));
-                socket
-                    .send(axum::extract::ws::Message::Text(
-                        update_msg.to_string().into(),
-                    ))
-                    .await?;
-            }
-            Err(err) => {
-                error!("❌ Move failed: {}", err);
-                let error_msg = json!({ "type": "MOVE_FAILED", "message": err });
-                socket
-                    .send(axum::extract::ws::Message::Text(
-                        error_msg.to_string().into(),
-                    ))
-                    .await?;
-            }
+    let text: String = text.trim().chars().take(MAX_CHAT_LENGTH).collect();
+    if text.is_empty() {
+        return Ok(());
+    }
+    let text = match state.word_filter.check("chat", sender, &text) {
+        Verdict::Allowed => text,
+        Verdict::Masked(masked) => masked,
+        Verdict::Rejected => {
+            error!("❌ CHAT rejected: blocked word from {}", sender);
+            socket
+                .send_json(&error_message(&GameError::BlockedWord))
+                .await?;
+            return Ok(());
         }
-    } else {
-        error!("❌ Game ID {} not found.", game_id);
-        let error_msg = json!({ "type": "MOVE_FAILED", "message": "Game ID not found." });
+    };
+
+    // Anyone without a seat here is watching, so they talk on the spectator channel
+    let spectator = seat.is_none_or(|seat| seat.game_id != game_id);
+    let spectators_only = spectator
+        && state
+            .games
+            .read()
+            .await
+            .get(game_id)
+            .is_some_and(|game| game.config.hide_spectator_chat);
+
+    let timestamp = now_secs();
+
+    info!("💬 Chat in game {} from {}", game_id, sender);
+    state.broadcast_chat(
+        game_id,
+        ChatMessage {
+            sender: sender.to_string(),
+            text,
+            timestamp,
+            team,
+            spectator,
+            spectators_only,
+        },
+    );
+
+    Ok(())
+}
+
+pub async fn handle_reaction(
+    emoji: &str,
+    state: &Arc<AppState>,
+    game_id: Option<&str>,
+    seat: Option<&Seat>,
+    sender: &str,
+    socket: &mut ClientSocket,
+) -> Result<()> {
+    let Some(game_id) = game_id else {
+        error!("❌ REACTION rejected: socket has not joined that game");
+        socket
+            .send_json(&error_message(&GameError::JoinBeforeReacting))
+            .await?;
+        return Ok(());
+    };
+    if !REACTIONS.contains(&emoji) {
+        error!("❌ REACTION rejected: {:?} is not a reaction", emoji);
         socket
-            .send(axum::extract::ws::Message::Text(
-                error_msg.to_string().into(),
-            ))
+            .send_json(&error_message(&GameError::UnknownReaction))
             .await?;
+        return Ok(());
     }
 
+    // Spectators' reactions follow the same rules as their chat
+    let spectator = seat.is_none_or(|seat| seat.game_id != game_id);
+    let spectators_only = spectator
+        && state
+            .games
+            .read()
+            .await
+            .get(game_id)
+            .is_some_and(|game| game.config.hide_spectator_chat);
+
+    info!("{} Reaction in game {} from {}", emoji, game_id, sender);
+    let reaction = Reaction {
+        sender: sender.to_string(),
+        emoji: emoji.to_string(),
+        spectator,
+        spectators_only,
+    };
+    let _ = state
+        .tx
+        .send((game_id.to_string(), GameEvent::Reaction(reaction)));
+
     Ok(())
 }
 
-pub async fn handle_reset_game(parsed: &serde_json::Value, state: &Arc<AppState>) -> Result<()> {
-    let game_id = parsed["game_id"].as_str().unwrap_or("").to_string();
+/// Starts the next round of a finished game, for one of its players.
+pub async fn handle_reset_game(
+    game_id: &str,
+    seat: Option<&Seat>,
+    state: &Arc<AppState>,
+) -> Result<(), GameError> {
     info!("📥 Received RESET_GAME request - Game ID: {}", game_id);
+    if seat.is_none_or(|seat| seat.game_id != game_id) {
+        error!("❌ Reset refused: socket holds no seat in game {}", game_id);
+        return Err(GameError::NotInGame);
+    }
 
     let mut games = state.games.write().await;
-    if let Some(game) = games.get_mut(&game_id) {
-        game.reset();
-        let _ = state.tx.send((game_id.clone(), game.clone()));
-        info!("✅ Game {} has been reset.", game_id);
-    } else {
+    let Some(game) = games.get_mut(game_id) else {
         error!("❌ Game ID {} not found for reset.", game_id);
+        return Err(GameError::GameNotFound);
+    };
+    // A live round is never wiped, rated or not
+    if !game.game_over {
+        error!("❌ Reset refused: game {} is still being played", game_id);
+        return Err(GameError::RoundInProgress);
+    }
+    if let Some(rotation) = game.rotate() {
+        info!(
+            "🔁 {} gives seat {:?} in game {} to {}",
+            rotation.left, rotation.player, game_id, rotation.seated
+        );
+        state.players.read().await.refresh_ratings(game);
+        state.scoreboards.read().await.restore(game_id, game);
+        state.webhooks.emit(WebhookEvent::PlayerJoined {
+            game_id: game_id.to_string(),
+            player: rotation.player,
+            name: rotation.seated.clone(),
+        });
+        state.broadcast_system(
+            game_id,
+            game,
+            "seats_rotated",
+            json!({ "left": rotation.left, "seated": rotation.seated }),
+        );
+        let event = GameEvent::SeatsRotated {
+            player: rotation.player,
+            left: rotation.left,
+            seated: rotation.seated,
+        };
+        let _ = state.tx.send((game_id.to_string(), event));
+    }
+    game.reset();
+    state.broadcast_state(game_id, game, UpdateReason::Reset);
+    state.broadcast_system(
+        game_id,
+        game,
+        "round_starting",
+        json!({ "round": game.round }),
+    );
+    info!("✅ Game {} has been reset.", game_id);
+
+    // The bot may be first to move in the new round
+    if play_ai_turn(game) {
+        state.stats.record_move();
+        state.broadcast_state(game_id, game, UpdateReason::Move);
     }
 
     Ok(())
 }
+
+/// The room language requested by the game creator, e.g. `en` or `pt-BR`.
+pub fn parse_locale(locale: Option<&str>) -> String {
+    locale
+        .map(str::trim)
+        .filter(|locale| {
+            !locale.is_empty()
+                && locale.len() <= MAX_LOCALE_LENGTH
+                && locale
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+        .unwrap_or(DEFAULT_LOCALE)
+        .to_string()
+}
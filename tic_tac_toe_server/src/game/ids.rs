@@ -0,0 +1,50 @@
+use super::models::Game;
+
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use std::collections::HashMap;
+
+const GAME_ID_LENGTH: usize = 8;
+const INVITE_CODE_LENGTH: usize = 6;
+/// Uppercase letters and digits minus look-alikes (0/O, 1/I), so codes survive being read aloud.
+const INVITE_CODE_CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// Generates a random game ID that isn't already taken.
+pub fn generate_game_id<T>(games: &HashMap<String, T>) -> String {
+    loop {
+        let id: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(GAME_ID_LENGTH)
+            .map(|c| char::from(c).to_ascii_lowercase())
+            .collect();
+
+        if !games.contains_key(&id) {
+            return id;
+        }
+    }
+}
+
+/// Generates a short invite code that no live game is using.
+pub fn generate_invite_code(games: &HashMap<String, Game>) -> String {
+    loop {
+        let mut rng = rand::thread_rng();
+        let code: String = (0..INVITE_CODE_LENGTH)
+            .map(|_| char::from(INVITE_CODE_CHARSET[rng.gen_range(0..INVITE_CODE_CHARSET.len())]))
+            .collect();
+
+        if find_by_invite_code(games, &code).is_none() {
+            return code;
+        }
+    }
+}
+
+/// Resolves an invite code (case-insensitively) to its game ID.
+pub fn find_by_invite_code(games: &HashMap<String, Game>, code: &str) -> Option<String> {
+    let code = code.trim();
+    games
+        .iter()
+        .find(|(_, game)| {
+            !game.invite_code.is_empty() && game.invite_code.eq_ignore_ascii_case(code)
+        })
+        .map(|(id, _)| id.clone())
+}
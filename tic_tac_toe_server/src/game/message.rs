@@ -1,17 +1,718 @@
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::time::Instant;
 
-use super::models::Player;
+use super::ai::AiDifficulty;
+use super::error::GameError;
+use super::models::Board;
+use super::models::{Game, Player, Visibility};
+use super::rules::{GameConfig, GameKind};
+use crate::auth;
+use crate::capacity::CapacityLimit;
+use crate::presence::{OnlinePlayer, PresenceChange};
+use crate::version;
+use crate::word_filter::{Verdict, WordFilter};
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Everything a client may send, tagged by its `type` field. Frames that don't match
+/// one of these shapes are rejected before any handler runs. Unknown fields are ignored
+/// rather than denied, so newer clients can add optional fields without breaking older
+/// servers. A socket can follow several games at once, so anything that acts on a game
+/// names it; where `game_id` is optional it falls back to the game joined last.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ClientMessage {
+    JoinGame(JoinGameRequest),
+    CreateGame(CreateGameRequest),
+    Spectate(SpectateRequest),
+    QuickMatch {
+        #[serde(default = "anonymous")]
+        name: String,
+    },
+    /// Gives up waiting for a quick-match opponent.
+    CancelQuickMatch {
+        game_id: Option<String>,
+    },
+    Login {
+        name: String,
+    },
+    JoinByCode {
+        code: String,
+        #[serde(default = "anonymous")]
+        name: String,
+    },
+    JoinVsAi(JoinVsAiRequest),
+    MakeMove(MoveRequest),
+    Resign {
+        game_id: String,
+        player: Player,
+    },
+    OfferDraw {
+        game_id: String,
+        player: Player,
+    },
+    /// Asks for a break, or agrees to the opponent's request.
+    RequestPause {
+        game_id: String,
+        player: Player,
+    },
+    /// Asks to end an agreed break, or agrees to the opponent's request.
+    Resume {
+        game_id: String,
+        player: Player,
+    },
+    ResetGame {
+        game_id: String,
+    },
+    GetState {
+        game_id: String,
+        /// Signed spectate link token, needed for private games the socket isn't in.
+        token: Option<String>,
+    },
+    GetReplay {
+        game_id: String,
+        /// Signed spectate link token, needed for private games the socket isn't in.
+        token: Option<String>,
+    },
+    GetLeaderboard {
+        limit: Option<usize>,
+    },
+    /// Asks who is connected under a name, answered with ONLINE_PLAYERS.
+    WhoIsOnline,
+    CreateSpectateLink {
+        game_id: Option<String>,
+    },
+    /// Host only: takes a player's seat away and keeps them out of the game.
+    KickPlayer {
+        game_id: Option<String>,
+        name: String,
+    },
+    /// Host only: stops a named spectator watching and keeps them out of the game.
+    BanSpectator {
+        game_id: Option<String>,
+        name: String,
+    },
+    /// Stops following a game, giving up this socket's seat or spectator place in it.
+    LeaveGame {
+        game_id: String,
+    },
+    /// Flags `target`'s behaviour for a moderator, with the reporter's own explanation.
+    ReportPlayer {
+        game_id: Option<String>,
+        target: String,
+        #[serde(default)]
+        reason: String,
+    },
+    RegisterNotifications {
+        webhook_url: Option<String>,
+        email: Option<String>,
+    },
+    Chat {
+        game_id: Option<String>,
+        text: String,
+        /// Only the sender's teammates see it.
+        #[serde(default)]
+        team: bool,
+    },
+    /// A quick emoji, one of `REACTIONS`, shown to everyone else in the game.
+    Reaction {
+        game_id: Option<String>,
+        emoji: String,
+    },
+    /// The client's build, sent once it connects.
+    Hello {
+        version: String,
+        #[serde(default = "unknown")]
+        git_hash: String,
+        /// Wants a `MOVE_MADE` after each move instead of the whole game.
+        #[serde(default)]
+        deltas: bool,
+    },
+    /// Asks for the server's build.
+    Version,
+}
+
+impl ClientMessage {
+    /// The message's `type`, e.g. `MAKE_MOVE`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ClientMessage::JoinGame(_) => "JOIN_GAME",
+            ClientMessage::CreateGame(_) => "CREATE_GAME",
+            ClientMessage::Spectate(_) => "SPECTATE",
+            ClientMessage::QuickMatch { .. } => "QUICK_MATCH",
+            ClientMessage::CancelQuickMatch { .. } => "CANCEL_QUICK_MATCH",
+            ClientMessage::Login { .. } => "LOGIN",
+            ClientMessage::JoinByCode { .. } => "JOIN_BY_CODE",
+            ClientMessage::JoinVsAi(_) => "JOIN_VS_AI",
+            ClientMessage::MakeMove(_) => "MAKE_MOVE",
+            ClientMessage::Resign { .. } => "RESIGN",
+            ClientMessage::OfferDraw { .. } => "OFFER_DRAW",
+            ClientMessage::RequestPause { .. } => "REQUEST_PAUSE",
+            ClientMessage::Resume { .. } => "RESUME",
+            ClientMessage::ResetGame { .. } => "RESET_GAME",
+            ClientMessage::GetState { .. } => "GET_STATE",
+            ClientMessage::GetReplay { .. } => "GET_REPLAY",
+            ClientMessage::GetLeaderboard { .. } => "GET_LEADERBOARD",
+            ClientMessage::WhoIsOnline => "WHO_IS_ONLINE",
+            ClientMessage::CreateSpectateLink { .. } => "CREATE_SPECTATE_LINK",
+            ClientMessage::KickPlayer { .. } => "KICK_PLAYER",
+            ClientMessage::BanSpectator { .. } => "BAN_SPECTATOR",
+            ClientMessage::LeaveGame { .. } => "LEAVE_GAME",
+            ClientMessage::ReportPlayer { .. } => "REPORT_PLAYER",
+            ClientMessage::RegisterNotifications { .. } => "REGISTER_NOTIFICATIONS",
+            ClientMessage::Chat { .. } => "CHAT",
+            ClientMessage::Reaction { .. } => "REACTION",
+            ClientMessage::Hello { .. } => "HELLO",
+            ClientMessage::Version => "VERSION",
+        }
+    }
+
+    /// The player name this message asks to act under, for messages that carry one.
+    pub fn name_mut(&mut self) -> Option<&mut String> {
+        match self {
+            ClientMessage::JoinGame(request) => Some(&mut request.name),
+            ClientMessage::CreateGame(request) => Some(&mut request.name),
+            ClientMessage::JoinVsAi(request) => Some(&mut request.name),
+            ClientMessage::Spectate(request) => request.name.as_mut(),
+            ClientMessage::QuickMatch { name }
+            | ClientMessage::Login { name }
+            | ClientMessage::JoinByCode { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// The player name and any team name the message carries.
+    fn names_mut(&mut self) -> Vec<&mut String> {
+        match self {
+            ClientMessage::JoinGame(request) => {
+                [Some(&mut request.name), request.team_name.as_mut()]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+            ClientMessage::CreateGame(request) => {
+                [Some(&mut request.name), request.team_name.as_mut()]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+            other => other.name_mut().into_iter().collect(),
+        }
+    }
+
+    /// Cleans the player and team names the message carries with `auth::clean_name` and
+    /// runs them past `filter`, or says why one can't be used.
+    pub fn clean_names(&mut self, filter: &WordFilter) -> Result<(), String> {
+        let blank = |name: &Option<String>| name.as_deref().is_some_and(|n| n.trim().is_empty());
+        // A blank team name means the player's own, and a blank spectator name anonymity
+        match self {
+            ClientMessage::JoinGame(request) if blank(&request.team_name) => {
+                request.team_name = None;
+            }
+            ClientMessage::CreateGame(request) if blank(&request.team_name) => {
+                request.team_name = None;
+            }
+            ClientMessage::Spectate(request) if blank(&request.name) => request.name = None,
+            _ => {}
+        }
+
+        for name in self.names_mut() {
+            *name = checked_name(name, filter)?;
+        }
+        Ok(())
+    }
+}
+
+/// `name` cleaned with `auth::clean_name` and run past `filter`, or why it can't be used.
+pub fn checked_name(name: &str, filter: &WordFilter) -> Result<String, String> {
+    let cleaned = auth::clean_name(name)?;
+    match filter.check("name", &cleaned, &cleaned) {
+        Verdict::Allowed => Ok(cleaned),
+        Verdict::Masked(masked) => Ok(masked),
+        Verdict::Rejected => Err("That name isn't allowed".to_string()),
+    }
+}
+
+fn unknown() -> String {
+    "unknown".to_string()
+}
+
+fn anonymous() -> String {
+    "Anonymous".to_string()
+}
+
+#[derive(Debug, Default, Deserialize)]
 pub struct JoinGameRequest {
     pub game_id: String,
+    #[serde(default = "anonymous")]
+    pub name: String,
+    /// Names the seat when the join founds a team; defaults to the player's own name.
+    pub team_name: Option<String>,
+    /// The mark the player would like. Granted if that seat is free; otherwise they get
+    /// the other one and a `SEAT_TAKEN` error saying so.
+    pub player: Option<Player>,
+    /// Only used when the join creates the game.
+    #[serde(flatten)]
+    pub options: GameOptions,
+}
+
+/// Explicitly creates a game with the given options and seats its creator, refusing IDs
+/// that are already taken rather than joining them.
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateGameRequest {
+    /// Left empty to have the server pick an ID.
+    #[serde(default)]
+    pub game_id: String,
+    #[serde(default = "anonymous")]
+    pub name: String,
+    pub team_name: Option<String>,
+    /// The creator's mark; missing means X.
     pub player: Option<Player>,
+    #[serde(flatten)]
+    pub options: GameOptions,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// What a player can choose about a game they create.
+#[derive(Debug, Default, Deserialize)]
+pub struct GameOptions {
+    #[serde(default)]
+    pub private: bool,
+    pub locale: Option<String>,
+    #[serde(default)]
+    pub correspondence: bool,
+    /// `0` turns the move clock off; missing uses the server default.
+    pub turn_seconds: Option<u64>,
+    /// Seconds on each player's chess clock for the round; missing or `0` plays without one.
+    pub clock_seconds: Option<u64>,
+    #[serde(default)]
+    pub team: bool,
+    /// `tic_tac_toe` (the default) or `connect_four`.
+    #[serde(default)]
+    pub kind: GameKind,
+    /// Side of the board, from 3 to 10; missing means 3. Ignored by Connect Four.
+    pub board_size: Option<usize>,
+    /// Marks in a row needed to win, from 3 up to the board size; missing means a full line.
+    pub win_length: Option<usize>,
+    /// Missing means X.
+    pub first_player: Option<Player>,
+    /// Keep spectator chat away from the players.
+    #[serde(default)]
+    pub hide_spectator_chat: bool,
+    /// Minutes the game may sit idle before it is removed; `0` never removes it, missing
+    /// uses the server default.
+    pub idle_timeout_minutes: Option<u64>,
+    /// Lets more than two join, queueing the rest to take the loser's seat after each round.
+    #[serde(default)]
+    pub winner_stays_on: bool,
+    /// `private` keeps the game out of the lobby; missing means `public`.
+    #[serde(default)]
+    pub visibility: Visibility,
+}
+
+impl GameOptions {
+    /// The config and empty board these options describe, or the error to send back if
+    /// any of them is out of range.
+    pub fn config(
+        &self,
+        default_turn_seconds: Option<u64>,
+    ) -> Result<(GameConfig, Board), GameError> {
+        let (board, win_length) = self.kind.new_board(self.board_size, self.win_length)?;
+        if self.winner_stays_on && (self.team || self.correspondence) {
+            return Err(GameError::WinnerStaysOn);
+        }
+        let turn_seconds = match self.turn_seconds {
+            // Correspondence turns may take days
+            _ if self.correspondence => None,
+            Some(0) => None,
+            Some(secs) => Some(secs),
+            None => default_turn_seconds,
+        };
+        let clock_seconds = self
+            .clock_seconds
+            .filter(|secs| *secs > 0 && !self.correspondence);
+
+        let config = GameConfig {
+            kind: self.kind,
+            rows: board.len(),
+            columns: board.first().map_or(0, Vec::len),
+            win_length,
+            first_player: self.first_player.unwrap_or(Player::X),
+            turn_seconds,
+            clock_seconds,
+            private: self.private,
+            hide_spectator_chat: self.hide_spectator_chat,
+            idle_timeout_minutes: self.idle_timeout_minutes,
+            winner_stays_on: self.winner_stays_on,
+        };
+        Ok((config, board))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpectateRequest {
+    pub game_id: String,
+    pub name: Option<String>,
+    /// Signed link token, required for private games.
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct JoinVsAiRequest {
+    /// Left empty to have the server pick an ID.
+    #[serde(default)]
+    pub game_id: String,
+    #[serde(default = "anonymous")]
+    pub name: String,
+    #[serde(default)]
+    pub difficulty: AiDifficulty,
+    #[serde(default)]
+    pub kind: GameKind,
+    pub board_size: Option<usize>,
+    pub win_length: Option<usize>,
+}
+
+#[derive(Debug, Deserialize)]
 pub struct MoveRequest {
     pub game_id: String,
     pub player: Player,
     pub x: usize,
     pub y: usize,
+    /// Chosen by the client and echoed in the reply. A move resent with the same id after
+    /// it was already played is acknowledged again instead of being played twice.
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+/// Why the server is sending a state update.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateReason {
+    Join,
+    Move,
+    Reset,
+    Timeout,
+    Resign,
+    DrawOffer,
+    Draw,
+    Abandon,
+    /// A lone player left before anyone joined, freeing the seat.
+    Leave,
+    /// Someone started or stopped watching.
+    Spectators,
+    /// The host kicked a player or banned a spectator.
+    Kick,
+    /// Play stopped: a player dropped, or both agreed to a break.
+    Pause,
+    /// Play picked up again after a pause.
+    Resume,
+    /// One player asked for a break.
+    PauseOffer,
+    /// One player asked to end an agreed break.
+    ResumeOffer,
+    /// Someone joined or left the line for a seat in a winner-stays-on room.
+    Queue,
+    /// The loser of the last round gave their seat to the next in line.
+    Rotate,
+    Resync,
+    /// A moderator took someone out of the game from the admin API.
+    Admin,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatMessage {
+    pub sender: String,
+    pub text: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+    /// Set for team chat, which only reaches the players of this seat.
+    pub team: Option<Player>,
+    /// Sent by someone watching without a seat, on the spectators' own channel.
+    pub spectator: bool,
+    /// Spectator chat in a game whose players asked not to see it.
+    pub spectators_only: bool,
+}
+
+/// Emoji a REACTION may carry, so nobody smuggles chat through it.
+pub const REACTIONS: &[&str] = &["👍", "👏", "😂", "😮", "😢", "😡", "🔥", "🎉"];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reaction {
+    pub sender: String,
+    pub emoji: String,
+    /// Sent by someone watching without a seat.
+    pub spectator: bool,
+    /// A spectator's reaction in a game whose players asked not to hear from them.
+    pub spectators_only: bool,
+}
+
+/// A server-generated chat line, sent as a message key plus parameters so each client can
+/// render it in the room's locale.
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemMessage {
+    pub key: &'static str,
+    pub params: serde_json::Value,
+    pub locale: String,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Where a player waiting for a quick match stands.
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStatus {
+    /// 1 is next to be matched.
+    pub position: usize,
+    /// `None` until the server has seen enough matches to guess.
+    pub estimated_wait_secs: Option<u64>,
+    pub players_online: usize,
+}
+
+/// Events fanned out to every socket subscribed to a game.
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+    StateUpdate {
+        game: Box<Game>,
+        reason: UpdateReason,
+        /// When the update was broadcast, to time how long it takes to reach each socket.
+        sent_at: Instant,
+    },
+    Chat(ChatMessage),
+    Reaction(Reaction),
+    System(SystemMessage),
+    PlayerLeft {
+        player: Player,
+        name: String,
+    },
+    QueueStatus(QueueStatus),
+    /// The host shut `name` out of the game; their sockets stop following it.
+    Removed {
+        name: String,
+    },
+    /// The quick-match player waiting in this game has been paired with the one waiting
+    /// in `game_id`, and should move over to it.
+    Matched {
+        game_id: String,
+    },
+    /// `left` lost the last round of a winner-stays-on room and `seated` took their seat.
+    SeatsRotated {
+        player: Player,
+        left: String,
+        seated: String,
+    },
+}
+
+/// An ERROR carrying `error`'s code and params, so clients can word it in the player's
+/// language, and its English text for those that can't.
+pub fn error_message(error: &GameError) -> serde_json::Value {
+    let mut message = json!(error);
+    message["type"] = json!("ERROR");
+    if message.get("params").is_none() {
+        message["params"] = json!({});
+    }
+    message["message"] = json!(error.to_string());
+    message
+}
+
+/// A MOVE_FAILED answering the move `request_id`, worded like an ERROR.
+pub fn move_failed_message(error: &GameError, request_id: Option<&str>) -> serde_json::Value {
+    let mut message = error_message(error);
+    message["type"] = json!("MOVE_FAILED");
+    message["request_id"] = json!(request_id);
+    message
+}
+
+/// An ERROR that clients can tell apart by its `code`, so they can back off and retry
+/// later rather than treating the server as broken.
+pub fn server_full_message(limit: CapacityLimit) -> serde_json::Value {
+    json!({
+        "type": "ERROR",
+        "code": "SERVER_FULL",
+        "params": { "limit": limit },
+        "limit": limit,
+        "message": "The server is full right now, please try again shortly"
+    })
+}
+
+/// First frame on every connection: the message of the day, any active event theme and
+/// the server's build.
+pub fn hello_message(motd: Option<&str>, theme: Option<&str>) -> serde_json::Value {
+    json!({
+        "type": "HELLO",
+        "motd": motd,
+        "theme": theme,
+        "server": version::build_info()
+    })
+}
+
+pub fn game_created_message(game_id: &str, game: &Game) -> serde_json::Value {
+    json!({
+        "type": "GAME_CREATED",
+        "game_id": game_id,
+        "invite_code": game.invite_code,
+        "config": game.config
+    })
+}
+
+pub fn version_message() -> serde_json::Value {
+    let mut message = json!(version::build_info());
+    message["type"] = json!("VERSION");
+    message
+}
+
+pub fn state_update_message(game_id: &str, game: &Game, reason: UpdateReason) -> serde_json::Value {
+    json!({
+        "type": "UPDATE_STATE",
+        "game_id": game_id,
+        "game": game,
+        "reason": reason,
+        "turn_remaining": game.turn_remaining().map(|d| d.as_secs()),
+        "clock_remaining": game.clocks_remaining(),
+        "pause_remaining": game.pause_remaining().map(|d| d.as_secs())
+    })
+}
+
+/// The last move of `game` on its own, for clients that keep their own copy of the board.
+/// `None` before the first move.
+pub fn move_made_message(game_id: &str, game: &Game) -> Option<serde_json::Value> {
+    let last = game.moves.last()?;
+    let result = game.game_over.then(|| {
+        json!({
+            "winner": game.winner,
+            "draw": game.draw
+        })
+    });
+    Some(json!({
+        "type": "MOVE_MADE",
+        "game_id": game_id,
+        "player": last.player,
+        "x": last.x,
+        "y": last.y,
+        "next_turn": game.current_turn,
+        "result": result,
+        "version": game.version,
+        "turn_remaining": game.turn_remaining().map(|d| d.as_secs()),
+        "clock_remaining": game.clocks_remaining()
+    }))
+}
+
+pub fn chat_message(game_id: &str, chat: &ChatMessage) -> serde_json::Value {
+    json!({
+        "type": "CHAT",
+        "game_id": game_id,
+        "sender": chat.sender,
+        "text": chat.text,
+        "timestamp": chat.timestamp,
+        "team": chat.team,
+        "spectator": chat.spectator
+    })
+}
+
+pub fn reaction_message(game_id: &str, reaction: &Reaction) -> serde_json::Value {
+    json!({
+        "type": "REACTION",
+        "game_id": game_id,
+        "sender": reaction.sender,
+        "emoji": reaction.emoji,
+        "spectator": reaction.spectator
+    })
+}
+
+pub fn player_left_message(game_id: &str, player: Player, name: &str) -> serde_json::Value {
+    json!({
+        "type": "PLAYER_LEFT",
+        "game_id": game_id,
+        "player": player,
+        "name": name
+    })
+}
+
+/// A name the server won't use, e.g. blank or too long, so the client can ask for another.
+pub fn invalid_name_message(reason: &str) -> serde_json::Value {
+    json!({
+        "type": "ERROR",
+        "code": "INVALID_NAME",
+        "params": {},
+        "message": reason
+    })
+}
+
+/// Sent ahead of JOIN_SUCCESS when the mark a player asked for was already taken and
+/// they were seated as the other one instead.
+pub fn seat_taken_message(requested: Player, assigned: Player) -> serde_json::Value {
+    json!({
+        "type": "ERROR",
+        "code": "SEAT_TAKEN",
+        "params": { "requested": requested, "assigned": assigned },
+        "requested": requested,
+        "assigned": assigned,
+        "message": format!("{requested:?} was already taken, so you are playing {assigned:?}")
+    })
+}
+
+pub fn removed_message(game_id: &str) -> serde_json::Value {
+    json!({
+        "type": "REMOVED",
+        "game_id": game_id,
+        "message": "The host removed you from this game"
+    })
+}
+
+/// Sent instead of JOIN_SUCCESS when a winner-stays-on room is full and the player joins
+/// its line for a seat.
+pub fn challenger_queued_message(game_id: &str, position: usize) -> serde_json::Value {
+    json!({
+        "type": "CHALLENGER_QUEUED",
+        "game_id": game_id,
+        "position": position,
+        "message": format!("The room is full, you are #{position} in line to play")
+    })
+}
+
+pub fn seats_rotated_message(
+    game_id: &str,
+    player: Player,
+    left: &str,
+    seated: &str,
+) -> serde_json::Value {
+    json!({
+        "type": "SEATS_ROTATED",
+        "game_id": game_id,
+        "player": player,
+        "left": left,
+        "seated": seated
+    })
+}
+
+pub fn presence_message(change: &PresenceChange) -> serde_json::Value {
+    json!({
+        "type": "PRESENCE",
+        "name": change.name,
+        "online": change.online
+    })
+}
+
+pub fn online_players_message(players: &[OnlinePlayer]) -> serde_json::Value {
+    json!({
+        "type": "ONLINE_PLAYERS",
+        "players": players
+    })
+}
+
+pub fn queue_status_message(game_id: &str, status: &QueueStatus) -> serde_json::Value {
+    json!({
+        "type": "QUEUE_STATUS",
+        "game_id": game_id,
+        "position": status.position,
+        "estimated_wait_secs": status.estimated_wait_secs,
+        "players_online": status.players_online
+    })
+}
+
+pub fn system_message(game_id: &str, system: &SystemMessage) -> serde_json::Value {
+    json!({
+        "type": "SYSTEM",
+        "game_id": game_id,
+        "key": system.key,
+        "params": system.params,
+        "locale": system.locale,
+        "timestamp": system.timestamp
+    })
 }
@@ -1,3 +1,7 @@
+pub mod ai;
+pub mod error;
 pub mod handlers;
+pub mod ids;
 pub mod message;
 pub mod models;
+pub mod rules;
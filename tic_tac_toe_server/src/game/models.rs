@@ -1,62 +1,374 @@
+use super::ai::{self, AiDifficulty};
+use super::error::GameError;
+use super::rules::GameConfig;
+use crate::clock::now_millis;
+
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, time::SystemTime};
+use std::{
+    collections::HashMap,
+    time::{Duration, SystemTime},
+};
 use tracing::debug;
 
+pub const DEFAULT_LOCALE: &str = "en";
+/// Players sharing a seat in a team game.
+pub const TEAM_SIZE: usize = 2;
+/// Classic 3x3, also the smallest board a game can be created with.
+pub const DEFAULT_BOARD_SIZE: usize = 3;
+pub const MAX_BOARD_SIZE: usize = 10;
+/// Request ids of a game's latest moves, remembered so a retried move is acknowledged
+/// rather than played again.
+const RECENT_MOVE_REQUESTS: usize = 16;
+/// Timeline entries kept per game; the oldest go first in long winner-stays-on sessions.
+const TIMELINE_LENGTH: usize = 200;
+
+/// Grid indexed `board[x][y]`, `x` being the row; its shape is fixed when the game is created.
+pub type Board = Vec<Vec<Option<Player>>>;
+
+pub fn empty_board(rows: usize, columns: usize) -> Board {
+    vec![vec![None; columns]; rows]
+}
+
+/// Every straight run of `length` cells on a `rows` x `columns` board: across, down and
+/// along both diagonals. A player holding all the cells of one of them has won.
+pub fn winning_lines(rows: usize, columns: usize, length: usize) -> Vec<Vec<(usize, usize)>> {
+    const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+    let mut lines = Vec::new();
+    for x in 0..rows {
+        for y in 0..columns {
+            for (dx, dy) in DIRECTIONS {
+                let line: Option<Vec<_>> = (0..length as isize)
+                    .map(|step| {
+                        let cx = x.checked_add_signed(dx * step)?;
+                        let cy = y.checked_add_signed(dy * step)?;
+                        (cx < rows && cy < columns).then_some((cx, cy))
+                    })
+                    .collect();
+                lines.extend(line);
+            }
+        }
+    }
+    lines
+}
+
+/// The player holding every cell of some winning line on `board`, if anyone does.
+fn line_owner(board: &Board, length: usize) -> Option<Player> {
+    let columns = board.first().map_or(0, Vec::len);
+    winning_lines(board.len(), columns, length)
+        .iter()
+        .find_map(|line| {
+            let first = board[line[0].0][line[0].1]?;
+            line.iter()
+                .all(|&(x, y)| board[x][y] == Some(first))
+                .then_some(first)
+        })
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Player {
     X,
     O,
 }
 
+impl Player {
+    pub fn opponent(self) -> Player {
+        match self {
+            Player::X => Player::O,
+            Player::O => Player::X,
+        }
+    }
+}
+
+/// A player's place in a particular game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Seat {
+    pub game_id: String,
+    pub player: Player,
+}
+
+/// A seat changing hands between rounds of a winner-stays-on room.
+#[derive(Debug, Clone)]
+pub struct Rotation {
+    pub player: Player,
+    /// The loser giving up the seat.
+    pub left: String,
+    /// The next in line, who takes it.
+    pub seated: String,
+}
+
+/// A live game held still, either while a player who dropped has a chance to come back
+/// or because both players agreed to take a break.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Pause {
+    /// The player who dropped; `None` for a break both players agreed to.
+    pub waiting_for: Option<Player>,
+    pub since: SystemTime,
+    /// When play resumes whether or not they are back. Agreed breaks last until both
+    /// players ask to resume.
+    pub until: Option<SystemTime>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct MoveRecord {
+    pub player: Player,
+    pub x: usize,
+    pub y: usize,
+    /// Milliseconds between the turn starting and the move arriving.
+    #[serde(default)]
+    pub think_ms: u64,
+    /// The move was one perfect play would also have chosen.
+    #[serde(default)]
+    pub engine_move: bool,
+}
+
+/// One entry in a game's timeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimelineEntry {
+    /// Goes up by one with every entry, across rounds.
+    pub seq: u64,
+    /// Milliseconds since the Unix epoch.
+    pub at: u64,
+    pub round: u32,
+    #[serde(flatten)]
+    pub event: TimelineEvent,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TimelineEvent {
+    /// Someone took `player`'s seat, or joined its team.
+    Join {
+        player: Player,
+        name: String,
+    },
+    Move {
+        player: Player,
+        x: usize,
+        y: usize,
+    },
+    /// The round ended; `winner` is `None` for a draw.
+    Result {
+        winner: Option<Player>,
+        draw: bool,
+    },
+    /// A new round started.
+    Reset,
+}
+
+/// Who can find a game without being told its ID.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Listed in the lobby.
+    #[default]
+    Public,
+    /// Only joined by its exact ID or invite code.
+    Private,
+}
+
+/// Saved games may predate any field, so missing ones take their `Default` values.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Game {
-    pub board: [[Option<Player>; 3]; 3],
+    pub config: GameConfig,
+    pub board: Board,
     pub current_turn: Player,
     pub game_over: bool,
     pub draw: bool,
+    /// Set when the game ends with a winner, whether by a line, a timeout or a resignation.
+    pub winner: Option<Player>,
     pub last_activity: SystemTime,
+    /// Moves of the current round, in order.
+    pub moves: Vec<MoveRecord>,
     pub players: Vec<Player>,
     pub scores: HashMap<Player, u32>,
     pub player_names: HashMap<Player, String>,
+    /// Seats whose player has no open socket, so the opponent isn't left waiting blind.
+    pub disconnected: Vec<Player>,
+    /// When each disconnected seat lost its last socket.
+    #[serde(skip)]
+    pub left_at: HashMap<Player, SystemTime>,
+    /// Open sockets per seat; a seat is only flagged disconnected when the last one closes.
+    #[serde(skip)]
+    pub connections: HashMap<Player, usize>,
+    pub ratings: HashMap<Player, u32>,
+    pub turn_started_at: SystemTime,
+    /// Chess clock time each seat had left when its current or last turn began, in
+    /// milliseconds. Seats that haven't moved yet still have the full budget.
+    pub clocks: HashMap<Player, u64>,
+    /// Incremented on every state change so clients can detect missed updates.
+    pub version: u64,
+    /// Created by the quick-match pool, so strangers may be paired into it.
+    pub quick_match: bool,
+    /// Short code players can read out to each other instead of the game ID.
+    pub invite_code: String,
+    /// Language system messages should be rendered in, e.g. `en` or `fr`.
+    pub locale: String,
+    /// Starts at 1 and goes up with every reset.
+    pub round: u32,
+    /// Untimed, long-running game that survives restarts and is never cleaned up for inactivity.
+    pub correspondence: bool,
+    /// Seat with a draw offer on the table; cleared by the next move.
+    pub draw_offer: Option<Player>,
+    /// Consultation game: each seat is a team whose members take turns submitting its moves.
+    pub team_mode: bool,
+    /// Who plays each seat of a team game, in the order they joined. `player_names` holds
+    /// the team names.
+    pub team_members: HashMap<Player, Vec<String>>,
+    /// The seat played by the server-side bot, if any.
+    pub ai_player: Option<Player>,
+    pub ai_difficulty: AiDifficulty,
+    /// Hash chain digest of the round just finished, set once a rated round is archived.
+    pub result_digest: Option<String>,
+    /// Sockets watching without a seat. Sent to clients but never read back, since
+    /// nobody is watching a game restored from disk.
+    #[serde(skip_deserializing)]
+    pub spectators: usize,
+    /// Names the spectators watch under, once per socket; those watching anonymously
+    /// aren't listed.
+    #[serde(skip)]
+    pub spectator_names: Vec<String>,
+    /// Whoever created the game; only they may kick players or ban spectators.
+    pub host: Option<String>,
+    /// Names the host has removed, refused if they try to join or watch again.
+    pub banned: Vec<String>,
+    /// Set while nobody may move and the move clock is stopped.
+    pub paused: Option<Pause>,
+    /// Seat that asked for a break, waiting for the other to agree; cleared by the next move.
+    pub pause_offer: Option<Player>,
+    /// Seat that asked to end an agreed break, waiting for the other to agree.
+    pub resume_offer: Option<Player>,
+    /// Names waiting for a seat in a winner-stays-on room, next up first.
+    pub challengers: Vec<String>,
+    /// `request_id`s of the latest moves played, oldest first.
+    #[serde(skip)]
+    pub move_requests: Vec<String>,
+    /// The tournament this game is a pairing of, if any.
+    pub tournament: Option<String>,
+    /// Set when the game is created.
+    pub visibility: Visibility,
+    /// Joins, moves, results and resets with when they happened, oldest first.
+    pub timeline: Vec<TimelineEntry>,
 }
 
 impl Default for Game {
     fn default() -> Self {
         Game {
-            board: [[None; 3]; 3],
+            config: GameConfig::default(),
+            board: empty_board(DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE),
             current_turn: Player::X,
             game_over: false,
             draw: false,
+            winner: None,
             last_activity: SystemTime::now(),
+            moves: Vec::new(),
             players: Vec::new(),
             player_names: HashMap::new(),
+            disconnected: Vec::new(),
+            left_at: HashMap::new(),
+            connections: HashMap::new(),
+            ratings: HashMap::new(),
             scores: [(Player::X, 0), (Player::O, 0)].into_iter().collect(),
+            turn_started_at: SystemTime::now(),
+            clocks: HashMap::new(),
+            version: 0,
+            quick_match: false,
+            correspondence: false,
+            draw_offer: None,
+            team_mode: false,
+            team_members: HashMap::new(),
+            invite_code: String::new(),
+            locale: DEFAULT_LOCALE.to_string(),
+            round: 1,
+            ai_player: None,
+            ai_difficulty: AiDifficulty::default(),
+            result_digest: None,
+            spectators: 0,
+            spectator_names: Vec::new(),
+            host: None,
+            banned: Vec::new(),
+            paused: None,
+            pause_offer: None,
+            resume_offer: None,
+            challengers: Vec::new(),
+            move_requests: Vec::new(),
+            tournament: None,
+            visibility: Visibility::default(),
+            timeline: Vec::new(),
         }
     }
 }
 
 impl Game {
-    pub fn reset(&mut self) {
-        let players = self.players.clone();
-        let names = self.player_names.clone();
-        let scores = self.scores.clone();
-        let previous_first = self.current_turn;
+    /// Whether the move sent with `request_id` has already been played.
+    pub fn played_request(&self, request_id: &str) -> bool {
+        self.move_requests.iter().any(|id| id == request_id)
+    }
 
-        let mut new_game = Game::default();
+    pub fn remember_request(&mut self, request_id: &str) {
+        if self.move_requests.len() >= RECENT_MOVE_REQUESTS {
+            self.move_requests.remove(0);
+        }
+        self.move_requests.push(request_id.to_string());
+    }
 
-        new_game.players = players;
-        new_game.player_names = names;
-        new_game.scores = scores;
+    /// Adds `event` to the timeline, stamped with the current time and round.
+    pub fn record(&mut self, event: TimelineEvent) {
+        if self.timeline.len() >= TIMELINE_LENGTH {
+            self.timeline.remove(0);
+        }
+        let seq = self.timeline.last().map_or(1, |entry| entry.seq + 1);
+        let at = now_millis();
+        self.timeline.push(TimelineEntry {
+            seq,
+            at,
+            round: self.round,
+            event,
+        });
+    }
 
-        //  Alternate who plays first
-        new_game.current_turn = match previous_first {
-            Player::X => Player::O,
-            Player::O => Player::X,
+    pub fn reset(&mut self) {
+        let new_game = Game {
+            config: self.config.clone(),
+            board: empty_board(self.config.rows, self.config.columns),
+            players: self.players.clone(),
+            player_names: self.player_names.clone(),
+            disconnected: self.disconnected.clone(),
+            left_at: self.left_at.clone(),
+            connections: self.connections.clone(),
+            ratings: self.ratings.clone(),
+            scores: self.scores.clone(),
+            //  Alternate who plays first
+            current_turn: self.current_turn.opponent(),
+            version: self.version + 1,
+            quick_match: self.quick_match,
+            correspondence: self.correspondence,
+            team_mode: self.team_mode,
+            team_members: self.team_members.clone(),
+            invite_code: self.invite_code.clone(),
+            locale: self.locale.clone(),
+            round: self.round + 1,
+            ai_player: self.ai_player,
+            ai_difficulty: self.ai_difficulty,
+            spectators: self.spectators,
+            spectator_names: self.spectator_names.clone(),
+            host: self.host.clone(),
+            banned: self.banned.clone(),
+            challengers: self.challengers.clone(),
+            // A retry of the move that ended the round mustn't become the next round's first
+            move_requests: self.move_requests.clone(),
+            tournament: self.tournament.clone(),
+            visibility: self.visibility,
+            timeline: std::mem::take(&mut self.timeline),
+            ..Game::default()
         };
 
         *self = new_game;
+        self.record(TimelineEvent::Reset);
 
         debug!(
             "Game reset. New first player: {:?}, Names: {:?}, Scores: {:?}",
@@ -64,28 +376,55 @@ impl Game {
         );
     }
 
-    pub fn make_move(&mut self, player: Player, x: usize, y: usize) -> Result<(), String> {
+    pub fn make_move(&mut self, player: Player, x: usize, y: usize) -> Result<(), GameError> {
         if self.game_over {
             debug!("Move rejected: Game is already over.");
-            return Err("Game is over!".to_string());
+            return Err(GameError::GameOver);
+        }
+        if let Some(pause) = self.paused {
+            debug!("Move rejected: Game is paused.");
+            return Err(match pause.waiting_for {
+                Some(player) => GameError::PausedForReconnect {
+                    name: self.name_of(player),
+                },
+                None => GameError::PausedForBreak,
+            });
         }
         if self.current_turn != player {
             debug!("Move rejected: Not {:?}'s turn.", player);
-            return Err(format!("It's not {:?}'s turn.", player));
-        }
-        if x >= 3 || y >= 3 {
-            debug!("Move rejected: Coordinates out of bounds.");
-            return Err("Out of bounds".to_string());
-        }
-        if self.board[x][y].is_some() {
-            debug!("Move rejected: Cell already taken.");
-            return Err("Cell already taken".to_string());
+            return Err(GameError::NotYourTurn { player });
         }
+        let (x, y) = self
+            .config
+            .kind
+            .landing_cell(&self.board, x, y)
+            .inspect_err(|err| {
+                debug!("Move rejected: {}", err);
+            })?;
+
+        let think_ms = self
+            .turn_started_at
+            .elapsed()
+            .unwrap_or_default()
+            .as_millis() as u64;
+        let engine_move = ai::is_best_move(&self.board, player, x, y);
 
+        self.stop_clock();
         self.board[x][y] = Some(player);
+        self.draw_offer = None;
+        self.pause_offer = None;
+        self.moves.push(MoveRecord {
+            player,
+            x,
+            y,
+            think_ms,
+            engine_move,
+        });
+        self.record(TimelineEvent::Move { player, x, y });
 
         if self.check_winner().is_some() {
             self.game_over = true;
+            self.winner = Some(player);
             *self.scores.entry(player).or_insert(0) += 1;
             debug!("Game over: {:?} wins. Score updated.", player);
         } else if self.is_full() {
@@ -93,45 +432,627 @@ impl Game {
             self.draw = true;
             debug!("Game over: It's a draw.");
         } else {
-            self.current_turn = match self.current_turn {
-                Player::X => Player::O,
-                Player::O => Player::X,
-            };
+            self.current_turn = self.current_turn.opponent();
             debug!("Turn switched: Now it's {:?}'s turn.", self.current_turn);
         }
 
         self.last_activity = SystemTime::now();
+        self.turn_started_at = self.last_activity;
+        self.version += 1;
         Ok(())
     }
 
-    fn check_winner(&self) -> Option<Player> {
-        for i in 0..3 {
-            // Check rows and columns for a winner
-            if self.board[i][0] == self.board[i][1] && self.board[i][1] == self.board[i][2] {
-                if let Some(player) = self.board[i][0] {
-                    return Some(player);
-                }
+    /// Time left on the move clock, or `None` when no clock is running.
+    pub fn turn_remaining(&self) -> Option<Duration> {
+        let limit = Duration::from_secs(self.config.turn_seconds?);
+        if self.game_over || self.players.len() < 2 || self.waiting_to_start() {
+            return None;
+        }
+        Some(limit.saturating_sub(self.turn_elapsed()))
+    }
+
+    /// Time left on `player`'s chess clock, which only runs during their turns of a live
+    /// game. `None` when the game has no chess clock.
+    pub fn clock_remaining(&self, player: Player) -> Option<Duration> {
+        let budget = Duration::from_secs(self.config.clock_seconds?);
+        let banked = self
+            .clocks
+            .get(&player)
+            .map_or(budget, |ms| Duration::from_millis(*ms));
+
+        let running = player == self.current_turn
+            && !self.game_over
+            && self.players.len() == 2
+            && !self.waiting_to_start();
+        Some(if running {
+            banked.saturating_sub(self.turn_elapsed())
+        } else {
+            banked
+        })
+    }
+
+    /// Both chess clocks in milliseconds, as sent with every state update.
+    pub fn clocks_remaining(&self) -> Option<HashMap<Player, u64>> {
+        self.config.clock_seconds?;
+        Some(
+            [Player::X, Player::O]
+                .into_iter()
+                .filter_map(|player| {
+                    let left = self.clock_remaining(player)?;
+                    Some((player, left.as_millis() as u64))
+                })
+                .collect(),
+        )
+    }
+
+    /// How long the player to move has been thinking. A paused turn counts up to the
+    /// moment it stopped.
+    fn turn_elapsed(&self) -> Duration {
+        let now = self
+            .paused
+            .map_or_else(SystemTime::now, |pause| pause.since);
+        now.duration_since(self.turn_started_at).unwrap_or_default()
+    }
+
+    /// A tournament game whose paired players haven't both sat down yet, so no clock runs.
+    pub fn waiting_to_start(&self) -> bool {
+        self.tournament.is_some() && self.moves.is_empty() && !self.disconnected.is_empty()
+    }
+
+    /// Banks what's left on the running chess clock, before the turn ends or the game does.
+    fn stop_clock(&mut self) {
+        if let Some(left) = self.clock_remaining(self.current_turn) {
+            self.clocks
+                .insert(self.current_turn, left.as_millis() as u64);
+        }
+    }
+
+    /// Stops play while `player` reconnects, for at most `grace`. Only live games between
+    /// two present players pause; returns whether this one did.
+    pub fn pause(&mut self, player: Player, grace: Duration) -> bool {
+        if self.paused.is_some()
+            || self.game_over
+            || self.correspondence
+            || self.players.len() < 2
+            || self.ai_player.is_some()
+        {
+            return false;
+        }
+
+        let since = SystemTime::now();
+        self.paused = Some(Pause {
+            waiting_for: Some(player),
+            since,
+            until: Some(since + grace),
+        });
+        self.version += 1;
+        debug!("Game paused while {:?} reconnects.", player);
+        true
+    }
+
+    /// Lifts a pause, giving the move clock back the time it stood still. Returns false
+    /// if the game wasn't paused.
+    pub fn resume(&mut self) -> bool {
+        let Some(pause) = self.paused.take() else {
+            return false;
+        };
+        self.resume_offer = None;
+        self.turn_started_at += pause.since.elapsed().unwrap_or_default();
+        self.version += 1;
+        debug!("Game resumed.");
+        true
+    }
+
+    /// Time left before a paused game resumes on its own; `None` for agreed breaks.
+    pub fn pause_remaining(&self) -> Option<Duration> {
+        let until = self.paused?.until?;
+        Some(until.duration_since(SystemTime::now()).unwrap_or_default())
+    }
+
+    /// Whether play stopped because both players asked for a break. Such games are left
+    /// alone by the abandonment and idle-game sweeps until the players come back.
+    pub fn on_agreed_break(&self) -> bool {
+        self.paused.is_some_and(|pause| pause.waiting_for.is_none())
+    }
+
+    /// Asks for a break, or agrees to the one the opponent asked for. Returns true once
+    /// both have agreed and the game is paused.
+    pub fn request_pause(&mut self, player: Player) -> Result<bool, GameError> {
+        self.check_can_break(player)?;
+        if self.paused.is_some() {
+            return Err(GameError::AlreadyPaused);
+        }
+
+        self.last_activity = SystemTime::now();
+        self.version += 1;
+
+        if self.pause_offer == Some(player.opponent()) {
+            self.pause_offer = None;
+            self.paused = Some(Pause {
+                waiting_for: None,
+                since: self.last_activity,
+                until: None,
+            });
+            debug!("Game paused by agreement.");
+            return Ok(true);
+        }
+
+        self.pause_offer = Some(player);
+        debug!("{:?} asked for a pause.", player);
+        Ok(false)
+    }
+
+    /// Asks to end an agreed break, or agrees to the opponent's request. Returns true once
+    /// both have agreed and play is back on.
+    pub fn request_resume(&mut self, player: Player) -> Result<bool, GameError> {
+        self.check_can_break(player)?;
+        match self.paused {
+            None => return Err(GameError::NotPaused),
+            Some(Pause {
+                waiting_for: Some(missing),
+                ..
+            }) => {
+                return Err(GameError::WaitingForReconnect {
+                    name: self.name_of(missing),
+                });
             }
-            if self.board[0][i] == self.board[1][i] && self.board[1][i] == self.board[2][i] {
-                if let Some(player) = self.board[0][i] {
-                    return Some(player);
-                }
+            Some(_) => {}
+        }
+
+        self.last_activity = SystemTime::now();
+        self.version += 1;
+
+        if self.resume_offer == Some(player.opponent()) {
+            self.resume();
+            return Ok(true);
+        }
+
+        self.resume_offer = Some(player);
+        debug!("{:?} asked to resume.", player);
+        Ok(false)
+    }
+
+    fn check_can_break(&self, player: Player) -> Result<(), GameError> {
+        if self.game_over {
+            return Err(GameError::GameOver);
+        }
+        if !self.players.contains(&player) {
+            return Err(GameError::NotInGame);
+        }
+        if self.players.len() < 2 {
+            return Err(GameError::WaitingForOpponent);
+        }
+        if self.ai_player.is_some() {
+            return Err(GameError::AiNoBreaks);
+        }
+        if self.correspondence {
+            return Err(GameError::NoClock);
+        }
+        Ok(())
+    }
+
+    /// Ends the game in the opponent's favour if the current player ran out of time, on
+    /// either the move clock or their chess clock.
+    pub fn forfeit_on_timeout(&mut self) -> bool {
+        if self.game_over {
+            return false;
+        }
+        let flag_fell = self.clock_remaining(self.current_turn) == Some(Duration::ZERO);
+        if self.turn_remaining() != Some(Duration::ZERO) && !flag_fell {
+            return false;
+        }
+
+        self.stop_clock();
+        let winner = self.current_turn.opponent();
+        self.game_over = true;
+        self.winner = Some(winner);
+        *self.scores.entry(winner).or_insert(0) += 1;
+        self.last_activity = SystemTime::now();
+        self.version += 1;
+
+        debug!(
+            "Game over: {:?} ran out of time, {:?} wins.",
+            winner.opponent(),
+            winner
+        );
+        true
+    }
+
+    /// Concedes the game on behalf of `player`, handing the win to their opponent.
+    pub fn resign(&mut self, player: Player) -> Result<(), GameError> {
+        if self.game_over {
+            return Err(GameError::GameOver);
+        }
+        if !self.players.contains(&player) {
+            return Err(GameError::NotInGame);
+        }
+        if self.players.len() < 2 {
+            return Err(GameError::WaitingForOpponent);
+        }
+
+        let winner = player.opponent();
+        self.stop_clock();
+        self.game_over = true;
+        self.winner = Some(winner);
+        *self.scores.entry(winner).or_insert(0) += 1;
+        self.last_activity = SystemTime::now();
+        self.version += 1;
+
+        debug!("Game over: {:?} resigned, {:?} wins.", player, winner);
+        Ok(())
+    }
+
+    /// Frees `player`'s seat for someone else. The running score restarts, since it no
+    /// longer describes the same pair of players.
+    pub fn vacate(&mut self, player: Player) {
+        self.players.retain(|p| *p != player);
+        self.player_names.remove(&player);
+        self.ratings.remove(&player);
+        self.team_members.remove(&player);
+        self.disconnected.retain(|p| *p != player);
+        self.left_at.remove(&player);
+        self.connections.remove(&player);
+        for score in self.scores.values_mut() {
+            *score = 0;
+        }
+        self.version += 1;
+    }
+
+    /// Shuts `name` out of the game for good. A player loses their seat, or just their
+    /// place on a team that has other members. Returns the seat they played for, if any.
+    pub fn ban(&mut self, name: &str) -> Option<Player> {
+        if !self.is_banned(name) {
+            self.banned.push(name.to_string());
+        }
+
+        self.leave_line(name);
+        let seat = self.seat_of(name)?;
+        let teammates = self
+            .team_members
+            .get_mut(&seat)
+            .filter(|members| members.len() > 1);
+        match teammates {
+            Some(members) => {
+                members.retain(|member| member != name);
+                self.version += 1;
             }
+            None => self.vacate(seat),
+        }
+        Some(seat)
+    }
+
+    pub fn is_banned(&self, name: &str) -> bool {
+        self.banned.iter().any(|banned| banned == name)
+    }
+
+    /// Puts `name` at the back of the line for a seat, or keeps their place if they are
+    /// already waiting. Returns their place, 1 being next.
+    pub fn join_line(&mut self, name: &str) -> usize {
+        if let Some(place) = self.challengers.iter().position(|n| n == name) {
+            return place + 1;
+        }
+        self.challengers.push(name.to_string());
+        self.version += 1;
+        self.challengers.len()
+    }
+
+    /// Takes `name` out of the line for a seat. Returns whether they were in it.
+    pub fn leave_line(&mut self, name: &str) -> bool {
+        let before = self.challengers.len();
+        self.challengers.retain(|n| n != name);
+        if self.challengers.len() == before {
+            return false;
+        }
+        self.version += 1;
+        true
+    }
+
+    /// Between rounds of a winner-stays-on room, gives the loser's seat to the next in
+    /// line and sends the loser to the back of it. After a draw the player who has sat
+    /// longer makes way. Nothing changes while nobody is waiting.
+    pub fn rotate(&mut self) -> Option<Rotation> {
+        if !self.config.winner_stays_on || !self.game_over || self.players.len() < 2 {
+            return None;
+        }
+        if self.challengers.is_empty() {
+            return None;
+        }
+
+        let player = self.winner.map_or(self.players[0], Player::opponent);
+        let left = self.name_of(player);
+        let seated = self.challengers.remove(0);
+        let present = !self.disconnected.contains(&player);
+
+        self.vacate(player);
+        self.players.push(player);
+        self.player_names.insert(player, seated.clone());
+        self.connect(player);
+        self.record(TimelineEvent::Join {
+            player,
+            name: seated.clone(),
+        });
+        // A loser who has already gone doesn't get a place to come back to
+        if present {
+            self.challengers.push(left.clone());
+        }
+        Some(Rotation {
+            player,
+            left,
+            seated,
+        })
+    }
+
+    /// Offers `player`'s opponent a draw, or accepts theirs if they offered first.
+    /// Returns true when both sides have agreed and the game is over.
+    pub fn offer_draw(&mut self, player: Player) -> Result<bool, GameError> {
+        if self.game_over {
+            return Err(GameError::GameOver);
+        }
+        if !self.players.contains(&player) {
+            return Err(GameError::NotInGame);
+        }
+        if self.players.len() < 2 {
+            return Err(GameError::WaitingForOpponent);
+        }
+        if self.ai_player.is_some() {
+            return Err(GameError::AiNoDraws);
         }
 
-        // Check diagonals for a winner
-        if self.board[0][0] == self.board[1][1] && self.board[1][1] == self.board[2][2] {
-            if let Some(player) = self.board[0][0] {
-                return Some(player);
+        self.last_activity = SystemTime::now();
+        self.version += 1;
+
+        if self.draw_offer == Some(player.opponent()) {
+            self.stop_clock();
+            self.game_over = true;
+            self.draw = true;
+            self.draw_offer = None;
+            debug!("Game over: draw agreed.");
+            return Ok(true);
+        }
+
+        self.draw_offer = Some(player);
+        debug!("{:?} offered a draw.", player);
+        Ok(false)
+    }
+
+    /// Picks up a game read back from a shutdown snapshot. Nobody is connected yet, and
+    /// the time the server spent down since `saved_at` isn't charged to anyone's clock.
+    pub fn restore(&mut self, saved_at: SystemTime) {
+        let downtime = saved_at.elapsed().unwrap_or_default();
+        self.turn_started_at += downtime;
+        if let Some(pause) = &mut self.paused {
+            pause.since += downtime;
+            pause.until = pause.until.map(|until| until + downtime);
+        }
+
+        let now = SystemTime::now();
+        self.disconnected = self
+            .players
+            .iter()
+            .copied()
+            .filter(|player| self.ai_player != Some(*player))
+            .collect();
+        self.left_at = self
+            .disconnected
+            .iter()
+            .map(|&player| (player, now))
+            .collect();
+    }
+
+    /// Counts a socket as holding `player`'s seat.
+    pub fn connect(&mut self, player: Player) {
+        *self.connections.entry(player).or_insert(0) += 1;
+        self.disconnected.retain(|p| *p != player);
+        self.left_at.remove(&player);
+        if self
+            .paused
+            .is_some_and(|pause| pause.waiting_for == Some(player))
+        {
+            self.resume();
+        }
+    }
+
+    /// Drops a socket holding `player`'s seat. Returns true if that was the last one.
+    pub fn disconnect(&mut self, player: Player) -> bool {
+        let open = self.connections.entry(player).or_insert(0);
+        *open = open.saturating_sub(1);
+        if *open > 0 || self.disconnected.contains(&player) {
+            return false;
+        }
+
+        self.disconnected.push(player);
+        self.left_at.insert(player, SystemTime::now());
+        true
+    }
+
+    /// Ends the game against a player who has been gone longer than `grace`, as long as
+    /// their opponent is still around to take the win. Returns the player who left.
+    pub fn forfeit_on_abandon(&mut self, grace: Duration) -> Option<Player> {
+        // Correspondence players are expected to come and go, as are players on a break
+        if self.game_over || self.correspondence || self.on_agreed_break() || self.players.len() < 2
+        {
+            return None;
+        }
+
+        let loser = *self.disconnected.iter().find(|player| {
+            self.left_at
+                .get(player)
+                .and_then(|at| at.elapsed().ok())
+                .is_some_and(|away| away >= grace)
+        })?;
+        let winner = loser.opponent();
+        if self.disconnected.contains(&winner) {
+            return None;
+        }
+
+        self.stop_clock();
+        self.game_over = true;
+        self.winner = Some(winner);
+        *self.scores.entry(winner).or_insert(0) += 1;
+        self.last_activity = SystemTime::now();
+        self.version += 1;
+
+        debug!("Game over: {:?} abandoned, {:?} wins.", loser, winner);
+        Some(loser)
+    }
+
+    /// How many people play `player`'s seat.
+    pub fn seat_size(&self, player: Player) -> usize {
+        if self.team_mode {
+            self.team_members.get(&player).map_or(0, Vec::len)
+        } else {
+            usize::from(self.players.contains(&player))
+        }
+    }
+
+    /// The seat `name` plays, on their own or as part of a team.
+    pub fn seat_of(&self, name: &str) -> Option<Player> {
+        if self.team_mode {
+            return self
+                .team_members
+                .iter()
+                .find(|(_, members)| members.iter().any(|member| member == name))
+                .map(|(player, _)| *player);
+        }
+
+        self.player_names
+            .iter()
+            .find(|(_, seat_name)| *seat_name == name)
+            .map(|(player, _)| *player)
+    }
+
+    /// In a team game, the member who must submit `player`'s next move. Teammates
+    /// alternate, starting again with the first member each round.
+    pub fn submitter(&self, player: Player) -> Option<&str> {
+        let members = self
+            .team_members
+            .get(&player)
+            .filter(|members| self.team_mode && !members.is_empty())?;
+        let played = self.moves.iter().filter(|m| m.player == player).count();
+        Some(&members[played % members.len()])
+    }
+
+    /// Name of whoever holds `player`'s seat, falling back to the mark itself.
+    pub fn name_of(&self, player: Player) -> String {
+        self.player_names
+            .get(&player)
+            .cloned()
+            .unwrap_or_else(|| format!("{:?}", player))
+    }
+
+    /// Games against the bot, and team games, don't move anyone's rating.
+    pub fn is_rated(&self) -> bool {
+        self.ai_player.is_none() && !self.team_mode
+    }
+
+    /// Readies a game deserialized from outside the server, e.g. saved before a crash or
+    /// fetched from another server, to be played on here. Its board has to be what its
+    /// move history produces. Everyone starts out disconnected and rejoins by name.
+    pub fn prepare_import(&mut self) -> Result<(), GameError> {
+        let (rows, columns) = (self.rows(), self.columns());
+        if !(DEFAULT_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&rows)
+            || !(DEFAULT_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&columns)
+            || self.board.iter().any(|row| row.len() != columns)
+        {
+            return Err(GameError::BoardSize {
+                min: DEFAULT_BOARD_SIZE,
+                max: MAX_BOARD_SIZE,
+            });
+        }
+        if !(DEFAULT_BOARD_SIZE..=rows.max(columns)).contains(&self.config.win_length) {
+            return Err(GameError::WinLength {
+                min: DEFAULT_BOARD_SIZE,
+                max: rows.max(columns),
+            });
+        }
+        if self
+            .players
+            .iter()
+            .any(|player| !self.player_names.contains_key(player))
+        {
+            return Err(GameError::UnnamedSeat);
+        }
+
+        // Round 1 opens with the configured player; later ones with whoever didn't open
+        // the round before, which the game doesn't record
+        let opener = self
+            .moves
+            .first()
+            .map_or(self.current_turn, |first| first.player);
+        if self.round <= 1 && opener != self.config.first_player {
+            return Err(GameError::WrongOpener {
+                player: self.config.first_player,
+            });
+        }
+
+        // Every move has to land where the rules put it, Connect Four pieces included, and
+        // none may follow the one that ended the round
+        let mut replayed = empty_board(rows, columns);
+        for (i, record) in self.moves.iter().enumerate() {
+            let alternates = i == 0 || self.moves[i - 1].player != record.player;
+            let lands = self
+                .config
+                .kind
+                .landing_cell(&replayed, record.x, record.y)
+                .is_ok_and(|cell| cell == (record.x, record.y));
+            let ended = line_owner(&replayed, self.config.win_length).is_some();
+            if !alternates || !lands || ended {
+                return Err(GameError::IllegalMove { number: i + 1 });
             }
+            replayed[record.x][record.y] = Some(record.player);
+        }
+        if replayed != self.board {
+            return Err(GameError::HistoryMismatch);
         }
-        if self.board[0][2] == self.board[1][1] && self.board[1][1] == self.board[2][0] {
-            if let Some(player) = self.board[0][2] {
-                return Some(player);
+        if !self.game_over {
+            if self.check_winner().is_some() || self.is_full() {
+                return Err(GameError::UnfinishedResult);
+            }
+            if let Some(last) = self.moves.last() {
+                if self.current_turn != last.player.opponent() {
+                    return Err(GameError::WrongTurn {
+                        player: last.player.opponent(),
+                    });
+                }
             }
         }
+        self.config.rows = rows;
+        self.config.columns = columns;
 
-        None
+        let now = SystemTime::now();
+        self.connections.clear();
+        self.disconnected = self
+            .players
+            .iter()
+            .copied()
+            .filter(|player| Some(*player) != self.ai_player)
+            .collect();
+        self.left_at = self
+            .disconnected
+            .iter()
+            .map(|player| (*player, now))
+            .collect();
+        self.paused = None;
+        self.spectators = 0;
+        self.spectator_names.clear();
+        self.challengers.clear();
+        self.tournament = None;
+        self.last_activity = now;
+        self.turn_started_at = now;
+        self.version += 1;
+        Ok(())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.board.len()
+    }
+
+    pub fn columns(&self) -> usize {
+        self.board.first().map_or(0, Vec::len)
+    }
+
+    fn check_winner(&self) -> Option<Player> {
+        line_owner(&self.board, self.config.win_length)
     }
 
     fn is_full(&self) -> bool {
@@ -140,3 +1061,262 @@ impl Game {
             .all(|row| row.iter().all(|&cell| cell.is_some()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::game::rules::{GameKind, CONNECT_FOUR_COLUMNS, CONNECT_FOUR_ROWS};
+
+    /// A game as another server would export it, after `moves` alternating from X.
+    fn exported(kind: GameKind, moves: &[(usize, usize)]) -> Game {
+        let (rows, columns) = match kind {
+            GameKind::TicTacToe => (3, 3),
+            GameKind::ConnectFour => (CONNECT_FOUR_ROWS, CONNECT_FOUR_COLUMNS),
+        };
+        let mut game = Game {
+            config: GameConfig {
+                kind,
+                rows,
+                columns,
+                win_length: if kind == GameKind::ConnectFour { 4 } else { 3 },
+                ..GameConfig::default()
+            },
+            board: empty_board(rows, columns),
+            players: vec![Player::X, Player::O],
+            player_names: HashMap::from([
+                (Player::X, "ann".to_string()),
+                (Player::O, "bob".to_string()),
+            ]),
+            ..Game::default()
+        };
+        let mut player = Player::X;
+        for &(x, y) in moves {
+            game.board[x][y] = Some(player);
+            game.moves.push(MoveRecord {
+                player,
+                x,
+                y,
+                think_ms: 0,
+                engine_move: false,
+            });
+            player = player.opponent();
+        }
+        game.current_turn = player;
+        game
+    }
+
+    #[test]
+    fn a_consistent_game_imports() {
+        let mut game = exported(GameKind::TicTacToe, &[(1, 1), (0, 0), (2, 2)]);
+        assert_eq!(game.prepare_import(), Ok(()));
+        assert_eq!(game.disconnected.len(), 2);
+
+        let mut game = exported(GameKind::ConnectFour, &[(5, 3), (4, 3), (5, 2)]);
+        assert_eq!(game.prepare_import(), Ok(()));
+    }
+
+    #[test]
+    fn floating_connect_four_pieces_are_refused() {
+        // The second piece hangs in mid-air above an empty cell
+        let mut game = exported(GameKind::ConnectFour, &[(5, 3), (3, 3)]);
+        assert_eq!(
+            game.prepare_import(),
+            Err(GameError::IllegalMove { number: 2 })
+        );
+    }
+
+    #[test]
+    fn round_one_opens_with_the_configured_player() {
+        let mut game = exported(GameKind::TicTacToe, &[(1, 1), (0, 0)]);
+        game.config.first_player = Player::O;
+        assert_eq!(
+            game.prepare_import(),
+            Err(GameError::WrongOpener { player: Player::O })
+        );
+
+        // Later rounds open with whoever the previous one left to start
+        game.round = 2;
+        assert_eq!(game.prepare_import(), Ok(()));
+    }
+
+    #[test]
+    fn a_decided_board_has_to_be_over() {
+        let top_row = [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        let mut game = exported(GameKind::TicTacToe, &top_row);
+        assert_eq!(game.prepare_import(), Err(GameError::UnfinishedResult));
+
+        let mut game = exported(GameKind::TicTacToe, &top_row);
+        game.game_over = true;
+        game.winner = Some(Player::X);
+        assert_eq!(game.prepare_import(), Ok(()));
+
+        // Nothing can be played once the round is decided
+        let mut game = exported(
+            GameKind::TicTacToe,
+            &[top_row.as_slice(), &[(2, 2)]].concat(),
+        );
+        game.game_over = true;
+        assert_eq!(
+            game.prepare_import(),
+            Err(GameError::IllegalMove { number: 6 })
+        );
+    }
+
+    /// Two seated players on a 60 second chess clock, with X to move.
+    fn clocked() -> Game {
+        Game {
+            config: GameConfig {
+                clock_seconds: Some(60),
+                ..GameConfig::default()
+            },
+            players: vec![Player::X, Player::O],
+            ..Game::default()
+        }
+    }
+
+    #[test]
+    fn only_the_player_to_move_is_charged() {
+        let mut game = clocked();
+        game.turn_started_at = SystemTime::now() - Duration::from_secs(20);
+        let x = game.clock_remaining(Player::X).unwrap();
+        assert!(x <= Duration::from_secs(40) && x > Duration::from_secs(39));
+        assert_eq!(
+            game.clock_remaining(Player::O),
+            Some(Duration::from_secs(60))
+        );
+
+        game.make_move(Player::X, 0, 0).unwrap();
+        let banked = game.clocks[&Player::X];
+        assert!((39_000..=40_000).contains(&banked));
+        // X's time stands still on O's turn
+        game.turn_started_at = SystemTime::now() - Duration::from_secs(5);
+        assert_eq!(
+            game.clock_remaining(Player::X),
+            Some(Duration::from_millis(banked))
+        );
+        assert!(game.clock_remaining(Player::O).unwrap() <= Duration::from_secs(55));
+        assert_eq!(game.clocks_remaining().unwrap()[&Player::X], banked);
+    }
+
+    #[test]
+    fn no_clock_runs_without_a_budget_or_an_opponent() {
+        let mut game = Game {
+            players: vec![Player::X, Player::O],
+            ..Game::default()
+        };
+        assert_eq!(game.clock_remaining(Player::X), None);
+        assert_eq!(game.clocks_remaining(), None);
+
+        let mut waiting = clocked();
+        waiting.players = vec![Player::X];
+        waiting.turn_started_at = SystemTime::now() - Duration::from_secs(90);
+        assert_eq!(
+            waiting.clock_remaining(Player::X),
+            Some(Duration::from_secs(60))
+        );
+        assert!(!waiting.forfeit_on_timeout());
+
+        game.turn_started_at = SystemTime::now() - Duration::from_secs(90);
+        assert!(!game.forfeit_on_timeout());
+    }
+
+    #[test]
+    fn flag_fall_hands_the_opponent_the_game() {
+        let mut game = clocked();
+        game.clocks.insert(Player::X, 1_000);
+        game.turn_started_at = SystemTime::now() - Duration::from_secs(2);
+        assert_eq!(game.clock_remaining(Player::X), Some(Duration::ZERO));
+
+        assert!(game.forfeit_on_timeout());
+        assert!(game.game_over);
+        assert_eq!(game.winner, Some(Player::O));
+        assert_eq!(game.scores[&Player::O], 1);
+        assert_eq!(game.clocks[&Player::X], 0);
+        assert!(!game.forfeit_on_timeout(), "a flag only falls once");
+    }
+
+    #[test]
+    fn a_pause_stops_the_chess_clock() {
+        let mut game = clocked();
+        let since = SystemTime::now() - Duration::from_secs(30);
+        game.turn_started_at = since - Duration::from_secs(10);
+        game.paused = Some(Pause {
+            waiting_for: Some(Player::O),
+            since,
+            until: None,
+        });
+        assert_eq!(
+            game.clock_remaining(Player::X),
+            Some(Duration::from_secs(50))
+        );
+        assert!(!game.forfeit_on_timeout());
+    }
+
+    #[test]
+    fn a_new_round_refills_both_clocks() {
+        let mut game = clocked();
+        game.clocks.insert(Player::X, 0);
+        game.turn_started_at = SystemTime::now() - Duration::from_secs(1);
+        assert!(game.forfeit_on_timeout());
+
+        game.reset();
+        assert!(game.clocks.is_empty());
+        assert_eq!(game.current_turn, Player::O);
+        assert_eq!(
+            game.clock_remaining(Player::X),
+            Some(Duration::from_secs(60))
+        );
+    }
+
+    #[test]
+    fn classic_board_has_eight_lines() {
+        let lines = winning_lines(3, 3, 3);
+        assert_eq!(lines.len(), 8);
+        assert!(lines.contains(&vec![(0, 2), (1, 1), (2, 0)]));
+    }
+
+    #[test]
+    fn shorter_lines_slide_across_a_large_board() {
+        let lines = winning_lines(10, 10, 5);
+        // 6 starts per row and column, 6x6 starts per diagonal direction
+        assert_eq!(lines.len(), 6 * 10 * 2 + 6 * 6 * 2);
+        assert!(lines
+            .iter()
+            .all(|line| line.len() == 5 && line.iter().all(|&(x, y)| x < 10 && y < 10)));
+        assert!(lines.contains(&vec![(5, 5), (6, 6), (7, 7), (8, 8), (9, 9)]));
+        assert!(!lines.contains(&vec![(6, 6), (7, 7), (8, 8), (9, 9)]));
+    }
+
+    #[test]
+    fn anti_diagonals_reach_both_edges() {
+        let lines = winning_lines(10, 10, 5);
+        let anti = |x: usize, y: usize| (0..5).map(|step| (x + step, y - step)).collect::<Vec<_>>();
+        assert!(lines.contains(&anti(0, 4)));
+        assert!(lines.contains(&anti(0, 9)));
+        assert!(lines.contains(&anti(5, 4)));
+        assert!(lines.contains(&anti(5, 9)));
+        // Starts that would run off the left edge or the bottom are skipped
+        let starts_anti = |x: usize, y: usize| {
+            lines
+                .iter()
+                .any(|line| line[0] == (x, y) && line[1] == (x + 1, y - 1))
+        };
+        assert!(!starts_anti(0, 3));
+        assert!(!starts_anti(6, 9));
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|line| line[1].1 + 1 == line[0].1)
+                .count(),
+            6 * 6
+        );
+
+        // Non-square boards keep within both dimensions
+        let lines = winning_lines(3, 5, 3);
+        assert!(lines.contains(&vec![(0, 4), (1, 3), (2, 2)]));
+        assert!(lines
+            .iter()
+            .all(|line| line.iter().all(|&(x, y)| x < 3 && y < 5)));
+    }
+}
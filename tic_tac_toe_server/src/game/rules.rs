@@ -0,0 +1,147 @@
+use super::error::GameError;
+use super::models::{empty_board, Board, Player, DEFAULT_BOARD_SIZE, MAX_BOARD_SIZE};
+
+use serde::{Deserialize, Serialize};
+
+pub const CONNECT_FOUR_ROWS: usize = 6;
+pub const CONNECT_FOUR_COLUMNS: usize = 7;
+pub const CONNECT_FOUR_WIN_LENGTH: usize = 4;
+
+/// Which game a table plays. Both share the lobby, seats and move protocol; they differ in
+/// the board they start from and where a move's mark ends up.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "graphql", derive(async_graphql::Enum))]
+#[serde(rename_all = "snake_case")]
+pub enum GameKind {
+    #[default]
+    TicTacToe,
+    /// Marks drop to the lowest free cell of the chosen column; four in a row wins.
+    ConnectFour,
+}
+
+/// The rules a game was created with. Fixed for the game's lifetime and sent along with
+/// its state, so clients can show what they are playing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub kind: GameKind,
+    pub rows: usize,
+    pub columns: usize,
+    /// Marks in a row needed to win.
+    pub win_length: usize,
+    /// Moves first in round 1; rounds after that alternate.
+    pub first_player: Player,
+    /// Move clock, `None` when turns are untimed.
+    pub turn_seconds: Option<u64>,
+    /// Chess clock: each player's thinking time for the whole round, `None` without one.
+    pub clock_seconds: Option<u64>,
+    /// Spectators need a signed link from one of the players to watch.
+    pub private: bool,
+    /// Spectator chat only reaches other spectators, so watchers can commentate without
+    /// distracting the match.
+    pub hide_spectator_chat: bool,
+    /// Overrides the server's idle timeout for this game; `Some(0)` means never, so a long
+    /// think in a tournament game doesn't get it removed.
+    pub idle_timeout_minutes: Option<u64>,
+    /// More than two may join: the rest wait in line, and the loser of each round gives
+    /// up their seat to whoever is next.
+    pub winner_stays_on: bool,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            kind: GameKind::default(),
+            rows: DEFAULT_BOARD_SIZE,
+            columns: DEFAULT_BOARD_SIZE,
+            win_length: DEFAULT_BOARD_SIZE,
+            first_player: Player::X,
+            turn_seconds: None,
+            clock_seconds: None,
+            private: false,
+            hide_spectator_chat: false,
+            idle_timeout_minutes: None,
+            winner_stays_on: false,
+        }
+    }
+}
+
+impl GameKind {
+    /// The empty board and win length for a new game, or the error to send back if the
+    /// requested size or win length is out of range. Connect Four is always 7x6, four in a
+    /// row, so it ignores both.
+    pub fn new_board(
+        self,
+        size: Option<usize>,
+        win_length: Option<usize>,
+    ) -> Result<(Board, usize), GameError> {
+        if self == GameKind::ConnectFour {
+            let board = empty_board(CONNECT_FOUR_ROWS, CONNECT_FOUR_COLUMNS);
+            return Ok((board, CONNECT_FOUR_WIN_LENGTH));
+        }
+
+        let size = size.unwrap_or(DEFAULT_BOARD_SIZE);
+        if !(DEFAULT_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&size) {
+            return Err(GameError::BoardSize {
+                min: DEFAULT_BOARD_SIZE,
+                max: MAX_BOARD_SIZE,
+            });
+        }
+
+        let win_length = win_length.unwrap_or(size);
+        if !(DEFAULT_BOARD_SIZE..=size).contains(&win_length) {
+            return Err(GameError::WinLength {
+                min: DEFAULT_BOARD_SIZE,
+                max: size,
+            });
+        }
+
+        Ok((empty_board(size, size), win_length))
+    }
+
+    /// The cell a move aimed at `(x, y)` marks. Tic-tac-toe marks exactly that cell;
+    /// Connect Four only looks at the column `y` and drops to the bottom of it.
+    pub fn landing_cell(
+        self,
+        board: &Board,
+        x: usize,
+        y: usize,
+    ) -> Result<(usize, usize), GameError> {
+        let columns = board.first().map_or(0, Vec::len);
+        match self {
+            GameKind::TicTacToe => {
+                if x >= board.len() || y >= columns {
+                    return Err(GameError::OutOfBounds);
+                }
+                if board[x][y].is_some() {
+                    return Err(GameError::CellTaken);
+                }
+                Ok((x, y))
+            }
+            GameKind::ConnectFour => {
+                if y >= columns {
+                    return Err(GameError::OutOfBounds);
+                }
+                (0..board.len())
+                    .rev()
+                    .find(|&row| board[row][y].is_none())
+                    .map(|row| (row, y))
+                    .ok_or(GameError::ColumnFull)
+            }
+        }
+    }
+
+    /// Every cell the player to move could mark right now.
+    pub fn playable_cells(self, board: &Board) -> Vec<(usize, usize)> {
+        let columns = board.first().map_or(0, Vec::len);
+        match self {
+            GameKind::TicTacToe => (0..board.len())
+                .flat_map(|x| (0..columns).map(move |y| (x, y)))
+                .filter(|&(x, y)| board[x][y].is_none())
+                .collect(),
+            GameKind::ConnectFour => (0..columns)
+                .filter_map(|y| self.landing_cell(board, 0, y).ok())
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,272 @@
+//! Read-only GraphQL queries over games, replays and players, built with the `graphql`
+//! feature, for dashboards that would rather not speak the WebSocket protocol.
+
+use crate::app_state::AppState;
+use crate::game::models::{Game, MoveRecord, Player, Visibility};
+use crate::game::rules::GameKind;
+use crate::leaderboard::{DEFAULT_LEADERBOARD_SIZE, MAX_LEADERBOARD_SIZE};
+use crate::players::LeaderboardEntry;
+use crate::replays::Replay;
+use crate::spectate_link;
+
+use async_graphql::http::GraphiQLSource;
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject};
+use axum::extract::State;
+use axum::response::Html;
+use axum::Json;
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+
+type GameSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+fn schema() -> &'static GameSchema {
+    static SCHEMA: OnceLock<GameSchema> = OnceLock::new();
+    SCHEMA.get_or_init(|| Schema::new(QueryRoot, EmptyMutation, EmptySubscription))
+}
+
+#[derive(SimpleObject)]
+struct SeatView {
+    player: Player,
+    name: Option<String>,
+    /// Rounds won in this game.
+    score: u32,
+    rating: Option<u32>,
+}
+
+#[derive(SimpleObject)]
+struct MoveView {
+    player: Player,
+    x: usize,
+    y: usize,
+    /// Milliseconds between the turn starting and the move arriving.
+    think_ms: u64,
+}
+
+impl From<&MoveRecord> for MoveView {
+    fn from(record: &MoveRecord) -> Self {
+        MoveView {
+            player: record.player,
+            x: record.x,
+            y: record.y,
+            think_ms: record.think_ms,
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+struct GameView {
+    id: String,
+    kind: GameKind,
+    rows: usize,
+    columns: usize,
+    win_length: usize,
+    seats: Vec<SeatView>,
+    current_turn: Player,
+    game_over: bool,
+    winner: Option<Player>,
+    draw: bool,
+    round: u32,
+    spectators: usize,
+    version: u64,
+    /// The round in progress, or the one just finished.
+    moves: Vec<MoveView>,
+}
+
+impl GameView {
+    fn new(game_id: &str, game: &Game) -> Self {
+        GameView {
+            id: game_id.to_string(),
+            kind: game.config.kind,
+            rows: game.config.rows,
+            columns: game.config.columns,
+            win_length: game.config.win_length,
+            seats: game
+                .players
+                .iter()
+                .map(|&player| SeatView {
+                    player,
+                    name: game.player_names.get(&player).cloned(),
+                    score: game.scores.get(&player).copied().unwrap_or(0),
+                    rating: game.ratings.get(&player).copied(),
+                })
+                .collect(),
+            current_turn: game.current_turn,
+            game_over: game.game_over,
+            winner: game.winner,
+            draw: game.draw,
+            round: game.round,
+            spectators: game.spectators,
+            version: game.version,
+            moves: game.moves.iter().map(MoveView::from).collect(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+struct PlayerName {
+    player: Player,
+    name: String,
+}
+
+fn player_names(names: &HashMap<Player, String>) -> Vec<PlayerName> {
+    let mut names: Vec<PlayerName> = names
+        .iter()
+        .map(|(&player, name)| PlayerName {
+            player,
+            name: name.clone(),
+        })
+        .collect();
+    names.sort_by_key(|entry| entry.player != Player::X);
+    names
+}
+
+#[derive(SimpleObject)]
+struct RoundView {
+    round: u32,
+    kind: GameKind,
+    players: Vec<PlayerName>,
+    moves: Vec<MoveView>,
+    winner: Option<Player>,
+    draw: bool,
+    /// Seconds since the Unix epoch
+    finished_at: u64,
+    result_digest: Option<String>,
+}
+
+impl From<&Replay> for RoundView {
+    fn from(replay: &Replay) -> Self {
+        RoundView {
+            round: replay.round,
+            kind: replay.config.kind,
+            players: player_names(&replay.player_names),
+            moves: replay.moves.iter().map(MoveView::from).collect(),
+            winner: replay.winner,
+            draw: replay.draw,
+            finished_at: replay.finished_at,
+            result_digest: replay.result_digest.clone(),
+        }
+    }
+}
+
+#[derive(SimpleObject)]
+struct PlayerStats {
+    name: String,
+    rating: u32,
+    wins: u32,
+    losses: u32,
+    draws: u32,
+}
+
+impl From<LeaderboardEntry> for PlayerStats {
+    fn from(entry: LeaderboardEntry) -> Self {
+        PlayerStats {
+            name: entry.name,
+            rating: entry.rating,
+            wins: entry.wins,
+            losses: entry.losses,
+            draws: entry.draws,
+        }
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Public games, most recently active first. Unlisted games are only found by `game`,
+    /// and games needing a spectate link aren't listed at all.
+    async fn games(&self, ctx: &Context<'_>) -> Vec<GameView> {
+        let state = ctx.data_unchecked::<Arc<AppState>>();
+        let games = state.games.read().await;
+        let mut public: Vec<(&String, &Game)> = games
+            .iter()
+            .filter(|(_, game)| game.visibility == Visibility::Public && !game.config.private)
+            .collect();
+        public.sort_by_key(|(_, game)| std::cmp::Reverse(game.last_activity));
+        public
+            .into_iter()
+            .map(|(game_id, game)| GameView::new(game_id, game))
+            .collect()
+    }
+
+    /// A live game by its ID. Private games need a spectate link `token`.
+    async fn game(&self, ctx: &Context<'_>, id: String, token: Option<String>) -> Option<GameView> {
+        let state = ctx.data_unchecked::<Arc<AppState>>();
+        let games = state.games.read().await;
+        games
+            .get(&id)
+            .filter(|game| {
+                spectate_link::may_view(
+                    &state.config.spectate_secret,
+                    &id,
+                    game.config.private,
+                    false,
+                    token.as_deref(),
+                )
+            })
+            .map(|game| GameView::new(&id, game))
+    }
+
+    /// The finished rounds of a game, oldest first. Private games need a spectate link
+    /// `token`.
+    async fn replays(
+        &self,
+        ctx: &Context<'_>,
+        game_id: String,
+        token: Option<String>,
+    ) -> Vec<RoundView> {
+        let state = ctx.data_unchecked::<Arc<AppState>>();
+        let replays = state.replays.read().await;
+        replays
+            .get(&game_id)
+            .filter(|rounds| {
+                spectate_link::may_view(
+                    &state.config.spectate_secret,
+                    &game_id,
+                    rounds.iter().any(|replay| replay.config.private),
+                    false,
+                    token.as_deref(),
+                )
+            })
+            .map(|rounds| rounds.iter().map(RoundView::from).collect())
+            .unwrap_or_default()
+    }
+
+    /// Lifetime results of whoever plays under `name`.
+    async fn player(&self, ctx: &Context<'_>, name: String) -> Option<PlayerStats> {
+        let state = ctx.data_unchecked::<Arc<AppState>>();
+        let players = state.players.read().await;
+        players.players.get(&name).map(|record| PlayerStats {
+            name,
+            rating: record.rating,
+            wins: record.wins,
+            losses: record.losses,
+            draws: record.draws,
+        })
+    }
+
+    /// Top players by rating, as on `GET /leaderboard`.
+    async fn leaderboard(&self, ctx: &Context<'_>, limit: Option<usize>) -> Vec<PlayerStats> {
+        let state = ctx.data_unchecked::<Arc<AppState>>();
+        let limit = limit
+            .unwrap_or(DEFAULT_LEADERBOARD_SIZE)
+            .min(MAX_LEADERBOARD_SIZE);
+        let leaderboard = state.players.read().await.leaderboard(limit);
+        leaderboard.into_iter().map(PlayerStats::from).collect()
+    }
+}
+
+/// `POST /graphql`: runs a query.
+pub async fn graphql_handler(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema().execute(request.data(state)).await)
+}
+
+/// `GET /graphql`: GraphiQL, for exploring the schema from a browser.
+pub async fn graphiql_handler() -> Html<String> {
+    Html(GraphiQLSource::build().endpoint("/graphql").finish())
+}
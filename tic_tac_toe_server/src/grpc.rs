@@ -0,0 +1,495 @@
+//! The bot protocol over gRPC, built with the `grpc` feature and served on the same port
+//! as `/ws`. `proto/bots.proto` describes it for client generators; the messages below
+//! are its Rust side, written out by hand so building doesn't need `protoc`.
+
+use crate::app_state::AppState;
+use crate::auth;
+use crate::game::error::{BanScope, GameError};
+use crate::game::handlers::{apply_move, handle_join_game, handle_reset_game};
+use crate::game::message::{checked_name, GameEvent, JoinGameRequest, MoveRequest, UpdateReason};
+use crate::game::models::{Game, Player, Seat};
+use crate::wire::ClientSocket;
+
+use std::{collections::HashMap, sync::Arc};
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{error, info, info_span, Instrument};
+
+mod generated {
+    include!(concat!(env!("OUT_DIR"), "/tictactoe.v1.Bots.rs"));
+}
+
+use generated::bots_server::{Bots, BotsServer};
+
+/// Events queued for a bot that reads slower than its game changes.
+const OUTGOING_BUFFER: usize = 32;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, prost::Enumeration)]
+#[repr(i32)]
+pub enum Mark {
+    None = 0,
+    X = 1,
+    O = 2,
+}
+
+impl From<Option<Player>> for Mark {
+    fn from(player: Option<Player>) -> Self {
+        match player {
+            Some(Player::X) => Mark::X,
+            Some(Player::O) => Mark::O,
+            None => Mark::None,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct BotMessage {
+    #[prost(oneof = "BotBody", tags = "1, 2, 3")]
+    pub body: Option<BotBody>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum BotBody {
+    #[prost(message, tag = "1")]
+    Join(Join),
+    #[prost(message, tag = "2")]
+    Move(Move),
+    #[prost(message, tag = "3")]
+    Reset(Reset),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Join {
+    #[prost(string, tag = "1")]
+    pub game_id: String,
+    #[prost(string, tag = "2")]
+    pub name: String,
+    #[prost(enumeration = "Mark", tag = "3")]
+    pub player: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Move {
+    #[prost(uint32, tag = "1")]
+    pub x: u32,
+    #[prost(uint32, tag = "2")]
+    pub y: u32,
+    #[prost(string, tag = "3")]
+    pub request_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Reset {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ServerEvent {
+    #[prost(oneof = "ServerBody", tags = "1, 2, 3, 4")]
+    pub body: Option<ServerBody>,
+}
+
+#[derive(Clone, PartialEq, prost::Oneof)]
+pub enum ServerBody {
+    #[prost(message, tag = "1")]
+    Joined(Joined),
+    #[prost(message, tag = "2")]
+    State(GameState),
+    #[prost(message, tag = "3")]
+    MoveFailed(MoveFailed),
+    #[prost(message, tag = "4")]
+    Error(Error),
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Joined {
+    #[prost(string, tag = "1")]
+    pub game_id: String,
+    #[prost(enumeration = "Mark", tag = "2")]
+    pub player: i32,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct GameState {
+    #[prost(string, tag = "1")]
+    pub game_id: String,
+    #[prost(uint64, tag = "2")]
+    pub version: u64,
+    #[prost(string, tag = "3")]
+    pub kind: String,
+    #[prost(uint32, tag = "4")]
+    pub rows: u32,
+    #[prost(uint32, tag = "5")]
+    pub columns: u32,
+    #[prost(uint32, tag = "6")]
+    pub win_length: u32,
+    #[prost(enumeration = "Mark", repeated, tag = "7")]
+    pub cells: Vec<i32>,
+    #[prost(enumeration = "Mark", tag = "8")]
+    pub current_turn: i32,
+    #[prost(bool, tag = "9")]
+    pub your_turn: bool,
+    #[prost(bool, tag = "10")]
+    pub game_over: bool,
+    #[prost(enumeration = "Mark", tag = "11")]
+    pub winner: i32,
+    #[prost(bool, tag = "12")]
+    pub draw: bool,
+    #[prost(map = "string, string", tag = "13")]
+    pub names: HashMap<String, String>,
+    #[prost(string, tag = "14")]
+    pub reason: String,
+    #[prost(uint64, optional, tag = "15")]
+    pub turn_remaining: Option<u64>,
+    #[prost(string, tag = "16")]
+    pub request_id: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct MoveFailed {
+    #[prost(string, tag = "1")]
+    pub message: String,
+    #[prost(string, tag = "2")]
+    pub request_id: String,
+    #[prost(string, tag = "3")]
+    pub code: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Error {
+    #[prost(string, tag = "1")]
+    pub message: String,
+    #[prost(string, tag = "2")]
+    pub code: String,
+}
+
+/// The `code` a JSON ERROR would carry for `error`.
+fn wire_code(error: &GameError) -> String {
+    serde_json::to_value(error)
+        .ok()
+        .and_then(|value| value["code"].as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+/// Same wording as the JSON protocol uses for the enum.
+fn wire_name<T: serde::Serialize>(value: T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+impl GameState {
+    /// `game` as seen from `seat`.
+    fn new(
+        game_id: &str,
+        game: &Game,
+        seat: Player,
+        reason: UpdateReason,
+        request_id: Option<String>,
+    ) -> Self {
+        GameState {
+            game_id: game_id.to_string(),
+            version: game.version,
+            kind: wire_name(game.config.kind),
+            rows: game.config.rows as u32,
+            columns: game.config.columns as u32,
+            win_length: game.config.win_length as u32,
+            cells: game
+                .board
+                .iter()
+                .flatten()
+                .map(|cell| Mark::from(*cell) as i32)
+                .collect(),
+            current_turn: Mark::from(Some(game.current_turn)) as i32,
+            your_turn: game.current_turn == seat
+                && game.players.len() == 2
+                && !game.game_over
+                && game.paused.is_none(),
+            game_over: game.game_over,
+            winner: Mark::from(game.winner) as i32,
+            draw: game.draw,
+            names: game
+                .player_names
+                .iter()
+                .map(|(player, name)| (wire_name(player), name.clone()))
+                .collect(),
+            reason: wire_name(reason),
+            turn_remaining: game.turn_remaining().map(|d| d.as_secs()),
+            request_id: request_id.unwrap_or_default(),
+        }
+    }
+}
+
+pub fn service(state: Arc<AppState>) -> BotsServer<BotService> {
+    BotsServer::new(BotService { state })
+}
+
+pub struct BotService {
+    state: Arc<AppState>,
+}
+
+#[tonic::async_trait]
+impl Bots for BotService {
+    type PlayStream = ReceiverStream<Result<ServerEvent, Status>>;
+
+    async fn play(
+        &self,
+        request: Request<Streaming<BotMessage>>,
+    ) -> Result<Response<Self::PlayStream>, Status> {
+        // As on `/ws`, a token in the call's metadata decides the name played under
+        let headers = request.metadata().clone().into_headers();
+        let identity = match (&self.state.config.jwt_secret, auth::bearer(&headers)) {
+            (Some(secret), Some(token)) => Some(
+                auth::verify(secret, token)
+                    .ok_or_else(|| Status::unauthenticated("Invalid or expired token"))?,
+            ),
+            _ => None,
+        };
+
+        let (outgoing, rx) = mpsc::channel(OUTGOING_BUFFER);
+        let session = BotSession {
+            state: Arc::clone(&self.state),
+            outgoing,
+            identity,
+            seat: None,
+            name: String::new(),
+            sent_version: 0,
+        };
+        tokio::spawn(
+            session
+                .run(request.into_inner())
+                .instrument(info_span!("grpc_bot")),
+        );
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+/// The bot hung up, so there is nobody left to tell.
+struct HungUp;
+
+type Sent = Result<(), HungUp>;
+
+/// One `Play` call: a single seat in a single game, like a WebSocket that joined once.
+struct BotSession {
+    state: Arc<AppState>,
+    outgoing: mpsc::Sender<Result<ServerEvent, Status>>,
+    /// Name proven by the call's token. Overrides the name in `Join`.
+    identity: Option<String>,
+    seat: Option<Seat>,
+    name: String,
+    /// Version of the last state sent, so the broadcast of a move already answered
+    /// directly isn't sent a second time.
+    sent_version: u64,
+}
+
+impl BotSession {
+    async fn run(mut self, mut incoming: Streaming<BotMessage>) {
+        let mut events = self.state.tx.subscribe();
+
+        loop {
+            let sent = tokio::select! {
+                message = incoming.message() => match message {
+                    Ok(Some(message)) => self.handle(message).await,
+                    Ok(None) => break,
+                    Err(status) => {
+                        info!("🤖 gRPC bot stream broke off: {}", status.message());
+                        break;
+                    }
+                },
+                received = events.recv() => match received {
+                    Ok((game_id, GameEvent::StateUpdate { game, reason, .. })) => {
+                        self.forward(&game_id, &game, reason).await
+                    }
+                    Ok(_) => Ok(()),
+                    // Only the latest state matters to a bot
+                    Err(RecvError::Lagged(_)) => self.send_current(UpdateReason::Resync, None).await,
+                    Err(RecvError::Closed) => break,
+                },
+            };
+            if sent.is_err() {
+                break;
+            }
+        }
+
+        if let Some(seat) = &self.seat {
+            self.state.leave_seat(seat).await;
+        }
+        info!("👋 gRPC bot {} disconnected", self.name);
+    }
+
+    async fn handle(&mut self, message: BotMessage) -> Sent {
+        match message.body {
+            Some(BotBody::Join(join)) => self.join(join).await,
+            Some(BotBody::Move(request)) => self.play_move(request).await,
+            Some(BotBody::Reset(_)) => self.reset().await,
+            None => self.error("Empty message", "").await,
+        }
+    }
+
+    async fn send(&self, body: ServerBody) -> Sent {
+        let event = ServerEvent { body: Some(body) };
+        self.outgoing.send(Ok(event)).await.map_err(|_| HungUp)
+    }
+
+    async fn error(&self, message: impl Into<String>, code: &str) -> Sent {
+        self.send(ServerBody::Error(Error {
+            message: message.into(),
+            code: code.to_string(),
+        }))
+        .await
+    }
+
+    async fn join(&mut self, join: Join) -> Sent {
+        if self.seat.is_some() {
+            return self
+                .error(
+                    "Already seated; start another call to play another game",
+                    "",
+                )
+                .await;
+        }
+
+        let name = match &self.identity {
+            Some(identity) => identity.clone(),
+            None => match checked_name(&join.name, &self.state.word_filter) {
+                Ok(name) => name,
+                Err(reason) => return self.error(reason, "INVALID_NAME").await,
+            },
+        };
+        if self.identity.is_none()
+            && self.state.config.jwt_secret.is_some()
+            && self.state.players.read().await.is_claimed(&name)
+        {
+            let message = format!("{name} is a claimed name; sign in to play as them");
+            return self.error(message, "").await;
+        }
+        let ban = self
+            .state
+            .bans
+            .read()
+            .await
+            .find(Some(&name), self.identity.as_deref(), None)
+            .map(|ban| ban.reason.clone());
+        if let Some(reason) = ban {
+            let error = GameError::Banned {
+                scope: BanScope::Server,
+                reason,
+            };
+            return self.error(error.to_string(), &wire_code(&error)).await;
+        }
+
+        let player = match join.player() {
+            Mark::X => Some(Player::X),
+            Mark::O => Some(Player::O),
+            Mark::None => None,
+        };
+        let request = JoinGameRequest {
+            game_id: join.game_id,
+            name: name.clone(),
+            player,
+            ..JoinGameRequest::default()
+        };
+        info!("🤖 gRPC JOIN from {} for game {:?}", name, request.game_id);
+
+        // The join answers as it would on a WebSocket; pass on what a bot needs of that
+        let (mut relay, mut replies) = ClientSocket::relay();
+        let seat = match handle_join_game(&request, &self.state, &mut relay).await {
+            Ok(seat) => seat,
+            Err(e) => {
+                error!("❌ gRPC JOIN failed: {}", e);
+                return self.error(e.to_string(), "").await;
+            }
+        };
+        while let Ok(reply) = replies.try_recv() {
+            let text = |field: &str| reply[field].as_str().unwrap_or_default().to_string();
+            match reply["type"].as_str() {
+                Some("ERROR") => self.error(text("message"), &text("code")).await?,
+                Some("JOIN_SUCCESS") => {
+                    let player = serde_json::from_value(reply["player"].clone()).ok();
+                    self.send(ServerBody::Joined(Joined {
+                        game_id: text("game_id"),
+                        player: Mark::from(player) as i32,
+                    }))
+                    .await?
+                }
+                _ => {}
+            }
+        }
+
+        let Some(seat) = seat else {
+            return Ok(());
+        };
+        self.seat = Some(seat);
+        self.name = name;
+        self.send_current(UpdateReason::Join, None).await
+    }
+
+    async fn play_move(&mut self, request: Move) -> Sent {
+        let Some(seat) = &self.seat else {
+            return self.error("Join a game before moving", "").await;
+        };
+
+        let request_id = Some(request.request_id).filter(|id| !id.is_empty());
+        let move_request = MoveRequest {
+            game_id: seat.game_id.clone(),
+            player: seat.player,
+            x: request.x as usize,
+            y: request.y as usize,
+            request_id: request_id.clone(),
+        };
+        match apply_move(&move_request, &self.state, Some(&self.name), None).await {
+            Ok(_) => self.send_current(UpdateReason::Move, request_id).await,
+            Err(error) => {
+                self.send(ServerBody::MoveFailed(MoveFailed {
+                    message: error.to_string(),
+                    code: wire_code(&error),
+                    request_id: request_id.unwrap_or_default(),
+                }))
+                .await
+            }
+        }
+    }
+
+    async fn reset(&mut self) -> Sent {
+        let Some(seat) = &self.seat else {
+            return self.error("Join a game before resetting it", "").await;
+        };
+        if let Err(e) = handle_reset_game(&seat.game_id, Some(seat), &self.state).await {
+            return self.error(e.to_string(), &wire_code(&e)).await;
+        }
+        Ok(())
+    }
+
+    /// Passes on a broadcast state of this bot's game, unless it was already sent.
+    async fn forward(&mut self, game_id: &str, game: &Game, reason: UpdateReason) -> Sent {
+        let Some(seat) = &self.seat else {
+            return Ok(());
+        };
+        if seat.game_id != game_id || game.version <= self.sent_version {
+            return Ok(());
+        }
+        let state = GameState::new(game_id, game, seat.player, reason, None);
+        self.sent_version = game.version;
+        self.send(ServerBody::State(state)).await
+    }
+
+    /// Sends the game as it is now, e.g. as the answer to a move.
+    async fn send_current(&mut self, reason: UpdateReason, request_id: Option<String>) -> Sent {
+        let Some(seat) = &self.seat else {
+            return Ok(());
+        };
+        let state = self
+            .state
+            .games
+            .read()
+            .await
+            .get(&seat.game_id)
+            .map(|game| GameState::new(&seat.game_id, game, seat.player, reason, request_id));
+        let Some(state) = state else {
+            return self.error("Game ID not found", "").await;
+        };
+        self.sent_version = self.sent_version.max(state.version);
+        self.send(ServerBody::State(state)).await
+    }
+}
@@ -0,0 +1,55 @@
+use crate::config::Tunables;
+
+use tokio::time::{Instant, Interval, MissedTickBehavior};
+
+/// Per-connection liveness check. The server pings on a fixed interval and gives up on a
+/// peer that lets too many Pings in a row go unanswered, rather than waiting for the OS to
+/// notice a dead TCP connection.
+pub struct Heartbeat {
+    ticker: Option<Interval>,
+    unanswered: u32,
+    max_missed: u32,
+}
+
+impl Heartbeat {
+    pub fn new(tunables: &Tunables) -> Self {
+        let ticker = tunables.ping_interval.map(|period| {
+            let mut ticker = tokio::time::interval_at(Instant::now() + period, period);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            ticker
+        });
+        Heartbeat {
+            ticker,
+            unanswered: 0,
+            max_missed: tunables.max_missed_pongs,
+        }
+    }
+
+    /// Resolves when the next Ping is due; never, if heartbeats are turned off.
+    pub async fn due(&mut self) {
+        match &mut self.ticker {
+            Some(ticker) => {
+                ticker.tick().await;
+            }
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Counts a Ping about to go out. Returns false instead once the last `max_missed`
+    /// are all still unanswered, meaning the peer is gone.
+    pub fn ping(&mut self) -> bool {
+        if self.unanswered >= self.max_missed {
+            return false;
+        }
+        self.unanswered += 1;
+        true
+    }
+
+    pub fn max_missed(&self) -> u32 {
+        self.max_missed
+    }
+
+    pub fn pong(&mut self) {
+        self.unanswered = 0;
+    }
+}
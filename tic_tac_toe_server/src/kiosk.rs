@@ -0,0 +1,52 @@
+use crate::app_state::AppState;
+use crate::game::message::UpdateReason;
+
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tracing::info;
+
+/// How long the final board stays up before the kiosk game starts over.
+const RESET_DELAY: Duration = Duration::from_secs(5);
+
+/// Starts the kiosk game over after every match, handing the seats of anyone who walked
+/// away to whoever sits down next.
+pub async fn reset_kiosk_game(app_state: Arc<AppState>) {
+    let Some(game_id) = app_state.config.kiosk_game.clone() else {
+        return;
+    };
+
+    loop {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let mut games = app_state.games.write().await;
+        let Some(game) = games.get_mut(&game_id) else {
+            continue;
+        };
+
+        let shown_for = game.last_activity.elapsed().unwrap_or_default();
+        if !game.game_over || shown_for < RESET_DELAY {
+            continue;
+        }
+
+        let gone: Vec<_> = game
+            .players
+            .iter()
+            .copied()
+            .filter(|player| game.connections.get(player).copied().unwrap_or(0) == 0)
+            .collect();
+        for player in gone {
+            info!("🪑 Kiosk seat {:?} is free again", player);
+            game.vacate(player);
+        }
+
+        game.reset();
+        info!("🔁 Kiosk game {} reset for round {}", game_id, game.round);
+        app_state.broadcast_state(&game_id, game, UpdateReason::Reset);
+        app_state.broadcast_system(
+            &game_id,
+            game,
+            "round_starting",
+            json!({ "round": game.round }),
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+/// Running totals for one stage of the move pipeline.
+#[derive(Default)]
+struct Samples {
+    count: AtomicU64,
+    over_budget: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SampleStats {
+    pub count: u64,
+    pub over_budget: u64,
+    pub mean_us: u64,
+    pub max_us: u64,
+}
+
+impl Samples {
+    fn record(&self, elapsed: Duration, budget: Duration) -> bool {
+        let micros = elapsed.as_micros() as u64;
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(micros, Ordering::Relaxed);
+        self.max_us.fetch_max(micros, Ordering::Relaxed);
+
+        let over = elapsed > budget;
+        if over {
+            self.over_budget.fetch_add(1, Ordering::Relaxed);
+        }
+        over
+    }
+
+    fn stats(&self) -> SampleStats {
+        let count = self.count.load(Ordering::Relaxed);
+        SampleStats {
+            count,
+            over_budget: self.over_budget.load(Ordering::Relaxed),
+            mean_us: self.total_us.load(Ordering::Relaxed) / count.max(1),
+            max_us: self.max_us.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Times each move against `MOVE_BUDGET_MS`: once for the handler (lock held, move
+/// applied, result recorded) and once per subscriber for the broadcast to reach its socket.
+pub struct MoveLatency {
+    budget: Duration,
+    handler: Samples,
+    fanout: Samples,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LatencyStats {
+    pub budget_us: u64,
+    pub handler: SampleStats,
+    pub fanout: SampleStats,
+}
+
+impl MoveLatency {
+    pub fn new(budget: Duration) -> Self {
+        MoveLatency {
+            budget,
+            handler: Samples::default(),
+            fanout: Samples::default(),
+        }
+    }
+
+    pub fn budget(&self) -> Duration {
+        self.budget
+    }
+
+    /// Returns true if the handler went over budget.
+    pub fn record_handler(&self, elapsed: Duration) -> bool {
+        self.handler.record(elapsed, self.budget)
+    }
+
+    /// Returns true if the broadcast took longer than the budget to reach a socket.
+    pub fn record_fanout(&self, elapsed: Duration) -> bool {
+        self.fanout.record(elapsed, self.budget)
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        LatencyStats {
+            budget_us: self.budget.as_micros() as u64,
+            handler: self.handler.stats(),
+            fanout: self.fanout.stats(),
+        }
+    }
+}
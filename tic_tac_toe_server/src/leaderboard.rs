@@ -0,0 +1,41 @@
+use crate::app_state::AppState;
+use crate::players::LeaderboardEntry;
+
+use axum::extract::{Query, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::Deserialize;
+use std::sync::Arc;
+
+pub const DEFAULT_LEADERBOARD_SIZE: usize = 10;
+pub const MAX_LEADERBOARD_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct LeaderboardQuery {
+    pub limit: Option<usize>,
+    /// Standings for one rating season instead of all time; see `GET /seasons`.
+    pub season: Option<u32>,
+}
+
+pub async fn leaderboard_handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<Vec<LeaderboardEntry>>, StatusCode> {
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_LEADERBOARD_SIZE)
+        .min(MAX_LEADERBOARD_SIZE);
+
+    let players = state.players.read().await;
+    let Some(season) = query.season else {
+        return Ok(Json(players.leaderboard(limit)));
+    };
+    if season == players.season {
+        return Ok(Json(players.season_leaderboard(limit)));
+    }
+    drop(players);
+
+    let seasons = state.seasons.read().await;
+    let past = seasons.get(season).ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(past.standings.iter().take(limit).cloned().collect()))
+}
@@ -0,0 +1,209 @@
+use crate::proxy_protocol;
+
+use axum::extract::connect_info::Connected;
+use axum::serve::{IncomingStream, Listener};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{error, warn};
+
+/// Binds a listener, keeping IPv6 sockets v6-only so `0.0.0.0:P` and `[::]:P`
+/// can be bound side by side on dual-stack hosts.
+pub fn bind(addr: SocketAddr) -> std::io::Result<TcpListener> {
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+
+    if addr.is_ipv6() {
+        socket.set_only_v6(true)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+
+    TcpListener::from_std(socket.into())
+}
+
+/// How long a proxy gets to send its PROXY header before the connection is dropped.
+const HEADER_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long a client gets to finish the TLS handshake.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Where a connection comes from: the TCP peer, or with `PROXY_PROTOCOL` on, the client
+/// address the load balancer passed along in its header.
+#[derive(Debug, Clone, Copy)]
+pub struct PeerAddr(pub SocketAddr);
+
+impl Connected<IncomingStream<'_, ClientListener>> for PeerAddr {
+    fn connect_info(stream: IncomingStream<'_, ClientListener>) -> Self {
+        *stream.remote_addr()
+    }
+}
+
+/// An accepted connection, encrypted when the server terminates TLS itself.
+pub enum ClientStream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+/// Hands accepted connections to axum, first reading off the PROXY header when the server
+/// sits behind a load balancer that sends one, and completing the TLS handshake when it
+/// serves `wss://` itself.
+pub enum ClientListener {
+    Direct(TcpListener),
+    /// Headers and handshakes run on their own tasks so one slow connection can't hold up
+    /// the rest.
+    Prepared {
+        local_addr: SocketAddr,
+        incoming: mpsc::Receiver<(ClientStream, PeerAddr)>,
+    },
+}
+
+impl ClientListener {
+    pub fn new(
+        listener: TcpListener,
+        proxy_protocol: bool,
+        tls: Option<TlsAcceptor>,
+    ) -> std::io::Result<Self> {
+        if !proxy_protocol && tls.is_none() {
+            return Ok(ClientListener::Direct(listener));
+        }
+
+        let local_addr = listener.local_addr()?;
+        let (tx, incoming) = mpsc::channel(128);
+        tokio::spawn(accept_prepared(listener, proxy_protocol, tls, tx));
+        Ok(ClientListener::Prepared {
+            local_addr,
+            incoming,
+        })
+    }
+}
+
+async fn accept_prepared(
+    listener: TcpListener,
+    proxy_protocol: bool,
+    tls: Option<TlsAcceptor>,
+    tx: mpsc::Sender<(ClientStream, PeerAddr)>,
+) {
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                error!("❌ Failed to accept connection: {}", e);
+                tokio::time::sleep(Duration::from_millis(100)).await;
+                continue;
+            }
+        };
+
+        let tx = tx.clone();
+        let tls = tls.clone();
+        tokio::spawn(async move {
+            let client = if proxy_protocol {
+                match timeout(HEADER_TIMEOUT, proxy_protocol::read_header(&mut stream)).await {
+                    Ok(Ok(client)) => client.unwrap_or(peer),
+                    Ok(Err(e)) => {
+                        warn!("🚫 Dropping connection from {}: {}", peer, e);
+                        return;
+                    }
+                    Err(_) => {
+                        warn!("🚫 Dropping connection from {}: no PROXY header", peer);
+                        return;
+                    }
+                }
+            } else {
+                peer
+            };
+
+            let stream = match tls {
+                None => ClientStream::Plain(stream),
+                Some(acceptor) => match timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream)).await {
+                    Ok(Ok(stream)) => ClientStream::Tls(Box::new(stream)),
+                    Ok(Err(e)) => {
+                        warn!("🔒 TLS handshake with {} failed: {}", client, e);
+                        return;
+                    }
+                    Err(_) => {
+                        warn!("🔒 TLS handshake with {} timed out", client);
+                        return;
+                    }
+                },
+            };
+            let _ = tx.send((stream, PeerAddr(client))).await;
+        });
+    }
+}
+
+impl Listener for ClientListener {
+    type Io = ClientStream;
+    type Addr = PeerAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self {
+            ClientListener::Direct(listener) => {
+                let (stream, peer) = Listener::accept(listener).await;
+                (ClientStream::Plain(stream), PeerAddr(peer))
+            }
+            ClientListener::Prepared { incoming, .. } => match incoming.recv().await {
+                Some(connection) => connection,
+                // The accept task only stops if the runtime is shutting down
+                None => std::future::pending().await,
+            },
+        }
+    }
+
+    fn local_addr(&self) -> std::io::Result<Self::Addr> {
+        match self {
+            ClientListener::Direct(listener) => listener.local_addr().map(PeerAddr),
+            ClientListener::Prepared { local_addr, .. } => Ok(PeerAddr(*local_addr)),
+        }
+    }
+}
+
+impl AsyncRead for ClientStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            ClientStream::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for ClientStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            ClientStream::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            ClientStream::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            ClientStream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            ClientStream::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
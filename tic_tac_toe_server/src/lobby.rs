@@ -0,0 +1,48 @@
+use crate::app_state::AppState;
+use crate::game::models::{Game, Player, Visibility};
+use crate::game::rules::GameConfig;
+
+use axum::extract::State;
+use axum::Json;
+use serde::Serialize;
+use std::{collections::HashMap, sync::Arc};
+
+/// A public game someone can still sit down at.
+#[derive(Debug, Serialize)]
+pub struct OpenGame {
+    pub game_id: String,
+    pub config: GameConfig,
+    pub player_names: HashMap<Player, String>,
+    pub spectators: usize,
+    /// Names waiting for a seat in a winner-stays-on room.
+    pub challengers: usize,
+}
+
+/// Whether the lobby should offer `game`: public, not against the server's bot or part of
+/// a tournament, and with a seat free or a line to wait in.
+fn is_open(game: &Game) -> bool {
+    game.visibility == Visibility::Public
+        && game.ai_player.is_none()
+        && game.tournament.is_none()
+        && (game.players.len() < 2 || game.config.winner_stays_on)
+}
+
+/// `GET /games`: the public games waiting for players. Private games are left out, so they
+/// can only be joined by their ID or invite code.
+pub async fn lobby_handler(State(state): State<Arc<AppState>>) -> Json<Vec<OpenGame>> {
+    let games = state.games.read().await;
+    let mut open: Vec<(&String, &Game)> = games.iter().filter(|(_, game)| is_open(game)).collect();
+    // Most recently active first, since those are likeliest to still have someone waiting
+    open.sort_by_key(|(_, game)| std::cmp::Reverse(game.last_activity));
+    let open = open
+        .into_iter()
+        .map(|(game_id, game)| OpenGame {
+            game_id: game_id.clone(),
+            config: game.config.clone(),
+            player_names: game.player_names.clone(),
+            spectators: game.spectators,
+            challengers: game.challengers.len(),
+        })
+        .collect();
+    Json(open)
+}
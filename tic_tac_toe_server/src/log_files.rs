@@ -0,0 +1,221 @@
+use crate::config::Config;
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+use tracing::Subscriber;
+use tracing_appender::{
+    non_blocking::WorkerGuard,
+    rolling::{RollingFileAppender, Rotation},
+};
+#[cfg(not(all(feature = "otel", feature = "sentry")))]
+use tracing_subscriber::layer::Identity;
+use tracing_subscriber::{
+    fmt::{self, MakeWriter},
+    layer::SubscriberExt,
+    registry::LookupSpan,
+    reload,
+    util::SubscriberInitExt,
+    EnvFilter, Layer, Registry,
+};
+
+const FILE_PREFIX: &str = "tic_tac_toe_server";
+
+/// When the log file is closed and a fresh one started.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogRotation {
+    Daily,
+    Hourly,
+    /// Once the current file reaches this many bytes.
+    Size(u64),
+    Never,
+}
+
+/// How each log line is written, on stdout and in log files alike.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFormat {
+    /// One readable line per event, prefixed with its spans.
+    Text,
+    /// One JSON object per line, with the fields of every span it happened in under
+    /// `spans`, e.g. a connection's `connection_id`, `game_id` and `player`.
+    Json,
+}
+
+/// Where to keep log history on disk, next to the usual stdout output.
+#[derive(Debug, Clone)]
+pub struct LogFiles {
+    pub dir: PathBuf,
+    pub rotation: LogRotation,
+    /// Rotated files kept before the oldest is deleted.
+    pub max_files: usize,
+}
+
+/// Swaps the log level filter of the running subscriber.
+pub type LogFilter = reload::Handle<EnvFilter, Registry>;
+
+/// Flushes the file writer, spans not yet exported and queued error reports when dropped.
+pub struct LogGuards {
+    _file: Option<WorkerGuard>,
+    #[cfg(feature = "otel")]
+    _telemetry: Option<crate::telemetry::TelemetryGuard>,
+    #[cfg(feature = "sentry")]
+    _sentry: Option<sentry::ClientInitGuard>,
+}
+
+/// Installs the global subscriber: stdout as always, plus log files, span export and error
+/// reporting when `config` asks for them. Keep the returned guards until shutdown; the
+/// filter handle changes `LOG_LEVEL` later on.
+pub fn init(config: &Config) -> io::Result<(LogGuards, LogFilter)> {
+    let (file_layer, guard) = match &config.log_files {
+        Some(files) => {
+            fs::create_dir_all(&files.dir)?;
+            let (writer, guard) = match files.rotation {
+                LogRotation::Size(max_size) => tracing_appender::non_blocking(
+                    SizeRotatingFile::open(&files.dir, max_size, files.max_files)?,
+                ),
+                rotation => tracing_appender::non_blocking(timed_appender(files, rotation)?),
+            };
+            let layer = format_layer(config.log_format, writer, false);
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    #[cfg(feature = "otel")]
+    let (otel_layer, telemetry) = match config.otlp_endpoint.as_deref() {
+        Some(endpoint) => {
+            let (layer, guard) = crate::telemetry::layer(endpoint)?;
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+    #[cfg(not(feature = "otel"))]
+    let otel_layer = None::<Identity>;
+
+    #[cfg(feature = "sentry")]
+    let (sentry_layer, sentry) = match config.sentry_dsn.as_deref() {
+        Some(dsn) => {
+            let (layer, guard) = crate::error_reporting::layer(dsn);
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+    #[cfg(not(feature = "sentry"))]
+    let sentry_layer = None::<Identity>;
+
+    let (filter, filter_handle) = reload::Layer::new(EnvFilter::new(&config.tunables.log_level));
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(format_layer(config.log_format, io::stdout, true))
+        .with(file_layer)
+        .with(otel_layer)
+        .with(sentry_layer)
+        .init();
+    let guards = LogGuards {
+        _file: guard,
+        #[cfg(feature = "otel")]
+        _telemetry: telemetry,
+        #[cfg(feature = "sentry")]
+        _sentry: sentry,
+    };
+    Ok((guards, filter_handle))
+}
+
+fn format_layer<S, W>(format: LogFormat, writer: W, ansi: bool) -> Box<dyn Layer<S> + Send + Sync>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+    W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+{
+    let layer = fmt::layer().with_writer(writer);
+    match format {
+        LogFormat::Text => layer.with_ansi(ansi).boxed(),
+        LogFormat::Json => layer
+            .json()
+            .flatten_event(true)
+            .with_current_span(false)
+            .with_span_list(true)
+            .boxed(),
+    }
+}
+
+/// `tic_tac_toe_server.2024-05-01.log`, or `.2024-05-01-13.log` when hourly.
+fn timed_appender(files: &LogFiles, rotation: LogRotation) -> io::Result<RollingFileAppender> {
+    let rotation = match rotation {
+        LogRotation::Daily => Rotation::DAILY,
+        LogRotation::Hourly => Rotation::HOURLY,
+        _ => Rotation::NEVER,
+    };
+    RollingFileAppender::builder()
+        .rotation(rotation)
+        .filename_prefix(FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(files.max_files)
+        .build(&files.dir)
+        .map_err(io::Error::other)
+}
+
+/// `tic_tac_toe_server.log`, shifted to `.log.1`, `.log.2`, … as it fills up.
+struct SizeRotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    max_size: u64,
+    max_files: usize,
+}
+
+impl SizeRotatingFile {
+    fn open(dir: &Path, max_size: u64, max_files: usize) -> io::Result<Self> {
+        let path = dir.join(format!("{FILE_PREFIX}.log"));
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let written = file.metadata()?.len();
+        Ok(SizeRotatingFile {
+            path,
+            file,
+            written,
+            max_size,
+            max_files,
+        })
+    }
+
+    fn numbered(&self, n: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{n}"));
+        path.into()
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        // The current file counts towards `max_files`, so it keeps one fewer numbered file
+        let kept = self.max_files.saturating_sub(1);
+        if kept == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.numbered(kept));
+            for n in (1..kept).rev() {
+                let _ = fs::rename(self.numbered(n), self.numbered(n + 1));
+            }
+            fs::rename(&self.path, self.numbered(1))?;
+            self.file = File::create(&self.path)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for SizeRotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // Each call is one whole event, so lines never straddle two files
+        if self.written > 0 && self.written + buf.len() as u64 > self.max_size {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.written += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
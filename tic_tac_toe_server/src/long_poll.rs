@@ -0,0 +1,163 @@
+use crate::app_state::AppState;
+use crate::auth;
+use crate::bans::refuse_banned;
+use crate::client_ip;
+use crate::game::error::GameError;
+use crate::game::handlers::apply_move;
+use crate::game::message::{state_update_message, GameEvent, MoveRequest, UpdateReason};
+use crate::listener::PeerAddr;
+use crate::spectate_link;
+
+use axum::extract::{ConnectInfo, Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::info;
+
+/// How long a poll is held open waiting for a change, kept under the idle timeout of most
+/// proxies. Clients just poll again with the same `since` after a 204.
+const POLL_TIMEOUT: Duration = Duration::from_secs(25);
+
+#[derive(Debug, Deserialize)]
+pub struct StateQuery {
+    /// The `version` of the last state the client saw; omitted to get the state right away.
+    pub since: Option<u64>,
+    /// Signed spectate link token, needed for private games unless signed in as a player.
+    pub token: Option<String>,
+}
+
+/// `GET /games/{id}/state?since=<version>`: the game's state as an UPDATE_STATE once its
+/// version is past `since`, or 204 if nothing changed within the poll timeout. Lets
+/// clients that can't hold a WebSocket open follow a game over plain HTTP. Private games
+/// answer only their players, by bearer token, or holders of a spectate link.
+pub async fn state_handler(
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<String>,
+    Query(query): Query<StateQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let viewer = auth::bearer_name(&state, &headers);
+    // Subscribe before looking, so an update between the two isn't missed
+    let mut updates = state.tx.subscribe();
+    let since = query.since.unwrap_or(0);
+    let deadline = tokio::time::Instant::now() + POLL_TIMEOUT;
+
+    loop {
+        {
+            let games = state.games.read().await;
+            let Some(game) = games.get(&game_id) else {
+                return (StatusCode::NOT_FOUND, "Game ID not found").into_response();
+            };
+            let seated = viewer
+                .as_deref()
+                .is_some_and(|name| game.seat_of(name).is_some());
+            if !spectate_link::may_view(
+                &state.config.spectate_secret,
+                &game_id,
+                game.config.private,
+                seated,
+                query.token.as_deref(),
+            ) {
+                return (StatusCode::FORBIDDEN, GameError::PrivateGame.to_string()).into_response();
+            }
+            if query.since.is_none() || game.version > since {
+                return Json(state_update_message(&game_id, game, UpdateReason::Resync))
+                    .into_response();
+            }
+        }
+
+        // Wait for this game to change, or for the game to go away
+        loop {
+            match tokio::time::timeout_at(deadline, updates.recv()).await {
+                Err(_) => return StatusCode::NO_CONTENT.into_response(),
+                Ok(Ok((id, GameEvent::StateUpdate { .. }))) if id == game_id => break,
+                Ok(Ok(_)) => continue,
+                // Missed some updates; the games map has the latest anyway
+                Ok(Err(RecvError::Lagged(_))) => break,
+                Ok(Err(RecvError::Closed)) => {
+                    return StatusCode::SERVICE_UNAVAILABLE.into_response();
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HttpMove {
+    /// Who is moving; must already hold a seat in the game under this name.
+    pub name: String,
+    pub x: usize,
+    pub y: usize,
+    /// As on MAKE_MOVE: echoed back, and a repeat is acknowledged rather than played again.
+    pub request_id: Option<String>,
+    /// From the JOIN_SUCCESS that seated `name`; not needed with a bearer token.
+    pub seat_token: Option<String>,
+}
+
+/// `POST /games/{id}/moves`: plays a move for a seated player, answering with the
+/// resulting UPDATE_STATE. The mover proves who they are with a bearer token, or holds
+/// the seat by the `seat_token` they were given on joining. Banned players and addresses
+/// are refused, as on `/ws`.
+pub async fn moves_handler(
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<String>,
+    ConnectInfo(PeerAddr(peer)): ConnectInfo<PeerAddr>,
+    headers: HeaderMap,
+    Json(request): Json<HttpMove>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let mut name =
+        auth::clean_name(&request.name).map_err(|reason| (StatusCode::BAD_REQUEST, reason))?;
+    let signed_in = match (&state.config.jwt_secret, auth::bearer(&headers)) {
+        (Some(secret), Some(token)) => {
+            name = auth::verify(secret, token).ok_or((
+                StatusCode::UNAUTHORIZED,
+                "Invalid or expired token".to_string(),
+            ))?;
+            true
+        }
+        _ => false,
+    };
+    // A name alone proves nothing, so without a sign-in the seat's own token is needed
+    let holds_seat = request.seat_token.as_deref().is_some_and(|token| {
+        auth::verify_seat_token(&state.config.spectate_secret, &game_id, &name, token)
+    });
+    if !signed_in && !holds_seat {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Moving over HTTP needs the seat_token from JOIN_SUCCESS or a bearer token".to_string(),
+        ));
+    }
+
+    let client_ip = client_ip::resolve(peer.ip(), &headers, &state.config.trusted_proxies);
+    refuse_banned(&state, &name, Some(client_ip)).await?;
+
+    let player = {
+        let games = state.games.read().await;
+        let game = games
+            .get(&game_id)
+            .ok_or((StatusCode::NOT_FOUND, "Game ID not found".to_string()))?;
+        game.seat_of(&name).ok_or((
+            StatusCode::FORBIDDEN,
+            format!("{name} has no seat in this game"),
+        ))?
+    };
+
+    info!(
+        "📥 HTTP move from {} ({:?}) in game {}: ({}, {})",
+        name, player, game_id, request.x, request.y
+    );
+    let move_request = MoveRequest {
+        game_id,
+        player,
+        x: request.x,
+        y: request.y,
+        request_id: request.request_id,
+    };
+    apply_move(&move_request, &state, Some(&name), None)
+        .await
+        .map(Json)
+        .map_err(|error| (StatusCode::CONFLICT, error.to_string()))
+}
@@ -1,44 +1,252 @@
-use axum::{routing::get, Router};
-use std::env;
+use axum::{
+    routing::{delete, get, post},
+    Router,
+};
 use std::sync::Arc;
-use tokio::net::TcpListener;
 use tokio::sync::broadcast;
+use tokio::task::JoinSet;
+use tower_http::services::{ServeDir, ServeFile};
 use tracing::{error, info};
 use tracing_subscriber::EnvFilter;
 
+mod abandonment;
+mod accounts;
+mod admin;
+mod anticheat;
 mod app_state;
+mod auth;
+mod bans;
+mod capacity;
+#[cfg(feature = "chaos")]
+mod chaos;
 mod cleanup;
+mod client_ip;
+mod clock;
+mod config;
+mod correspondence;
+mod cors;
+#[cfg(feature = "sentry")]
+mod error_reporting;
 mod game;
+#[cfg(feature = "graphql")]
+mod graphql;
+#[cfg(feature = "grpc")]
+mod grpc;
+mod heartbeat;
+mod kiosk;
+mod latency;
+mod leaderboard;
+mod listener;
+mod lobby;
+mod log_files;
+mod long_poll;
+mod matchmaking;
+mod metrics;
+mod moderation;
+mod move_audit;
+mod notation;
+mod notifications;
+mod players;
+mod presence;
+mod proxy_protocol;
+mod reload;
+mod replays;
+mod result_chain;
+mod scoreboards;
+mod seasons;
+mod snapshot;
+mod spectate_link;
+mod stats;
+#[cfg(feature = "otel")]
+mod telemetry;
+mod tls;
+mod tournaments;
+mod turn_timer;
+mod version;
+mod versioning;
+mod webhooks;
+mod wire;
+mod word_filter;
 mod ws_socket;
 
+use abandonment::enforce_abandonment;
+use accounts::{login_handler, profile_handler, register_handler};
+use admin::{
+    capacity_handler, cheat_flags_handler, client_versions_handler, import_game_handler,
+    latency_handler, log_level_handler, move_audit_handler, set_log_level_handler,
+    word_flags_handler,
+};
 use app_state::AppState;
+use auth::auth_handler;
+use bans::{ban_handler, bans_handler, unban_handler};
+use clap::Parser;
 use cleanup::cleanup_inactive_games;
+use config::{Args, Config};
+use correspondence::persist_correspondence_games;
+use kiosk::reset_kiosk_game;
+use leaderboard::leaderboard_handler;
+use listener::{ClientListener, PeerAddr};
+use lobby::lobby_handler;
+use long_poll::{moves_handler, state_handler};
+use matchmaking::announce_queue_status;
+use metrics::metrics_handler;
+use moderation::{reports_handler, resolve_report_handler};
+use notation::export_handler;
+use notifications::relay_turn_notifications;
+use presence::online_handler;
+use reload::watch_config;
+use replays::{replay_handler, verify_replay_handler};
+use seasons::{rating_history_handler, rotate_seasons, seasons_handler};
+use stats::stats_handler;
+use tournaments::{create_tournament_handler, tournament_handler};
+use turn_timer::enforce_turn_timers;
+use version::version_handler;
 use ws_socket::ws_handler;
 
 #[tokio::main]
 async fn main() {
-    tracing_subscriber::fmt()
-        .with_env_filter(EnvFilter::new("info"))
-        .init();
+    let args = Args::parse();
+    if args.version {
+        println!("{}", version::describe());
+        return;
+    }
 
-    let (tx, _) = broadcast::channel(500);
-    let app_state = Arc::new(AppState::new(tx));
+    // Settings decide where logs go, so anything said while reading them only reaches stdout
+    let startup_logger = tracing_subscriber::fmt()
+        .with_env_filter(EnvFilter::new("info"))
+        .finish();
+    let config = tracing::subscriber::with_default(startup_logger, || Config::load(&args))
+        .expect("Failed to load settings");
+    let (_log_guards, log_filter) = log_files::init(&config).expect("Failed to set up logging");
+    if let Some(files) = &config.log_files {
+        info!(
+            "📝 Writing logs to {:?} ({:?} rotation)",
+            files.dir, files.rotation
+        );
+    }
+    #[cfg(feature = "otel")]
+    if let Some(endpoint) = &config.otlp_endpoint {
+        info!("🔭 Exporting traces to {}", endpoint);
+    }
+    #[cfg(feature = "sentry")]
+    if config.sentry_dsn.is_some() {
+        info!("🚨 Reporting panics and errors to Sentry");
+    }
+    let (tx, _) = broadcast::channel(config.channel_capacity);
+    let app_state = Arc::new(AppState::new(tx, config, log_filter));
 
-    let app = Router::new()
+    let mut app = Router::new()
         .route("/ws", get(ws_handler))
-        .with_state(Arc::clone(&app_state));
+        .route("/auth", post(auth_handler))
+        .route("/register", post(register_handler))
+        .route("/login", post(login_handler))
+        .route("/players/{name}", get(profile_handler))
+        .route("/players/{name}/ratings", get(rating_history_handler))
+        .route("/games/{game_id}/state", get(state_handler))
+        .route("/games/{game_id}/moves", post(moves_handler))
+        .route("/version", get(version_handler))
+        .route("/stats", get(stats_handler))
+        .route("/metrics", get(metrics_handler))
+        .route("/leaderboard", get(leaderboard_handler))
+        .route("/seasons", get(seasons_handler))
+        .route("/online", get(online_handler))
+        .route("/games", get(lobby_handler))
+        .route("/replays/{game_id}", get(replay_handler))
+        .route("/replays/{game_id}/verify", get(verify_replay_handler))
+        .route("/replays/{game_id}/notation", get(export_handler))
+        .route("/tournaments/{tournament_id}", get(tournament_handler))
+        .route("/admin/cheat-flags", get(cheat_flags_handler))
+        .route("/admin/capacity", get(capacity_handler))
+        .route("/admin/latency", get(latency_handler))
+        .route("/admin/client-versions", get(client_versions_handler))
+        .route("/admin/moves", get(move_audit_handler))
+        .route("/admin/word-flags", get(word_flags_handler))
+        .route(
+            "/admin/log-level",
+            get(log_level_handler).put(set_log_level_handler),
+        )
+        .route("/admin/reports", get(reports_handler))
+        .route("/admin/reports/{report_id}", post(resolve_report_handler))
+        .route("/admin/bans", get(bans_handler).post(ban_handler))
+        .route("/admin/bans/{ban_id}", delete(unban_handler))
+        .route("/admin/games/{game_id}", post(import_game_handler))
+        .route("/admin/tournaments", post(create_tournament_handler));
+
+    #[cfg(feature = "graphql")]
+    {
+        use graphql::{graphiql_handler, graphql_handler};
+        app = app.route("/graphql", get(graphiql_handler).post(graphql_handler));
+    }
+
+    #[cfg(feature = "grpc")]
+    {
+        info!("🤖 Serving the bot protocol over gRPC as tictactoe.v1.Bots");
+        let bots = grpc::service(Arc::clone(&app_state));
+        app = app.route_service("/tictactoe.v1.Bots/{*method}", bots);
+    }
+
+    if let Some(dir) = &app_state.config.static_dir {
+        info!("🌐 Serving web client from {:?}", dir);
+        let index = ServeFile::new(dir.join("index.html"));
+        // Prefer `client_bg.wasm.br` / `.gz` when the build left one next to the original
+        let files = ServeDir::new(dir)
+            .precompressed_br()
+            .precompressed_gzip()
+            .fallback(index);
+        app = app.fallback_service(files);
+    }
+
+    if !app_state.config.cors_origins.is_empty() {
+        info!("🌍 CORS enabled for {:?}", app_state.config.cors_origins);
+        app = app.layer(cors::layer(&app_state.config.cors_origins));
+    }
 
-    let port = env::var("PORT").unwrap_or_else(|_| "3000".to_string());
-    let addr = format!("0.0.0.0:{}", port);
+    let app = app.with_state(Arc::clone(&app_state));
 
-    let listener = TcpListener::bind(&addr)
-        .await
-        .expect("Failed to bind to address");
+    let tls = app_state.config.tls.as_ref().map(|tls| {
+        info!("🔒 Serving TLS with certificate {:?}", tls.cert_file);
+        tls::load_acceptor(&tls.cert_file, &tls.key_file).expect("Failed to load TLS certificate")
+    });
 
-    info!("Server is running on {}", listener.local_addr().unwrap());
+    let mut servers = JoinSet::new();
+
+    for addr in &app_state.config.bind_addrs {
+        let listener = listener::bind(*addr).expect("Failed to bind to address");
+        info!("Server is running on {}", listener.local_addr().unwrap());
+        let listener = ClientListener::new(listener, app_state.config.proxy_protocol, tls.clone())
+            .expect("Failed to set up listener");
+
+        let app = app.clone();
+        servers.spawn(async move {
+            axum::serve(
+                listener,
+                app.into_make_service_with_connect_info::<PeerAddr>(),
+            )
+            .await
+        });
+    }
 
     tokio::spawn(cleanup_inactive_games(Arc::clone(&app_state)));
-    if let Err(e) = axum::serve(listener, app.into_make_service()).await {
-        error!("❌ Server error: {}", e);
+    tokio::spawn(enforce_turn_timers(Arc::clone(&app_state)));
+    tokio::spawn(enforce_abandonment(Arc::clone(&app_state)));
+    tokio::spawn(relay_turn_notifications(Arc::clone(&app_state)));
+    tokio::spawn(persist_correspondence_games(Arc::clone(&app_state)));
+    tokio::spawn(reset_kiosk_game(Arc::clone(&app_state)));
+    tokio::spawn(announce_queue_status(Arc::clone(&app_state)));
+    tokio::spawn(rotate_seasons(Arc::clone(&app_state)));
+    tokio::spawn(watch_config(Arc::clone(&app_state), args));
+
+    let serving = async {
+        while let Some(result) = servers.join_next().await {
+            match result {
+                Ok(Err(e)) => error!("❌ Server error: {}", e),
+                Err(e) => error!("❌ Listener task failed: {}", e),
+                Ok(Ok(())) => {}
+            }
+        }
+    };
+    tokio::select! {
+        _ = serving => {}
+        _ = snapshot::shutdown_signal() => snapshot::save(&app_state).await,
     }
 }
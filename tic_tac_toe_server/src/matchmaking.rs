@@ -0,0 +1,292 @@
+use crate::app_state::AppState;
+use crate::game::message::{GameEvent, QueueStatus};
+use crate::game::models::Game;
+
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::info;
+
+/// How often a waiting player hears where they stand in the queue.
+const STATUS_INTERVAL: Duration = Duration::from_secs(3);
+
+/// Finished waits the estimate is averaged over.
+const RECENT_WAITS: usize = 20;
+
+/// Rating gap a quick match accepts straight away.
+const RATING_WINDOW: u32 = 100;
+/// How much wider the window gets for every second a player has waited, so nobody waits
+/// forever for an opponent of their own level.
+const RATING_WINDOW_GROWTH: u32 = 10;
+
+/// A quick-match game holding a single player.
+struct Waiting {
+    since: Instant,
+    name: String,
+    rating: u32,
+}
+
+impl Waiting {
+    /// Whether someone rated `rating` is close enough to play this player yet.
+    fn accepts(&self, rating: u32) -> bool {
+        let waited = self.since.elapsed().as_secs().min(u32::MAX as u64) as u32;
+        let window = RATING_WINDOW.saturating_add(RATING_WINDOW_GROWTH.saturating_mul(waited));
+        self.rating.abs_diff(rating) <= window
+    }
+}
+
+/// Quick-match games holding a single player, paired by rating.
+#[derive(Default)]
+pub struct QuickMatchQueue {
+    waiting: HashMap<String, Waiting>,
+    recent_waits: VecDeque<Duration>,
+}
+
+impl QuickMatchQueue {
+    pub fn enqueue(&mut self, game_id: &str, name: &str, rating: u32) {
+        self.waiting
+            .entry(game_id.to_string())
+            .or_insert_with(|| Waiting {
+                since: Instant::now(),
+                name: name.to_string(),
+                rating,
+            });
+    }
+
+    /// Takes the longest-waiting game whose player will accept `name`, rated `rating`, as an
+    /// opponent, counting its wait towards the estimate. `games` filters out anything that
+    /// stopped waiting behind our back.
+    pub fn take_match(
+        &mut self,
+        name: &str,
+        rating: u32,
+        games: &HashMap<String, Game>,
+    ) -> Option<String> {
+        self.prune(games);
+        let game_id = self
+            .waiting
+            .iter()
+            .filter(|(_, waiting)| waiting.name != name && waiting.accepts(rating))
+            .min_by_key(|(_, waiting)| waiting.since)
+            .map(|(id, _)| id.clone())?;
+
+        self.take(&game_id);
+        Some(game_id)
+    }
+
+    /// Pairs players already in the queue whose windows have grown wide enough to take each
+    /// other. Returns `(host, guest)` game IDs: each guest should move into its host's game.
+    pub fn pair_waiting(&mut self) -> Vec<(String, String)> {
+        let mut queue: Vec<(&String, &Waiting)> = self.waiting.iter().collect();
+        queue.sort_by_key(|(_, waiting)| waiting.since);
+
+        let mut paired: Vec<(String, String)> = Vec::new();
+        let mut taken: Vec<&String> = Vec::new();
+        for (i, (host, waiting)) in queue.iter().enumerate() {
+            if taken.contains(host) {
+                continue;
+            }
+            let guest = queue[i + 1..].iter().find(|(guest, other)| {
+                !taken.contains(guest)
+                    && other.name != waiting.name
+                    && waiting.accepts(other.rating)
+            });
+            if let Some((guest, _)) = guest {
+                taken.extend([*host, *guest]);
+                paired.push(((*host).clone(), (*guest).clone()));
+            }
+        }
+
+        for (host, guest) in &paired {
+            self.take(host);
+            self.take(guest);
+        }
+        paired
+    }
+
+    /// Drops a matched game from the queue, counting its wait towards the estimate.
+    fn take(&mut self, game_id: &str) {
+        let Some(waiting) = self.waiting.remove(game_id) else {
+            return;
+        };
+        if self.recent_waits.len() == RECENT_WAITS {
+            self.recent_waits.pop_front();
+        }
+        self.recent_waits.push_back(waiting.since.elapsed());
+    }
+
+    /// Drops `game_id` without counting its wait, e.g. when the player gave up.
+    pub fn leave(&mut self, game_id: &str) -> bool {
+        self.waiting.remove(game_id).is_some()
+    }
+
+    /// Forgets games that were removed, finished, filled some other way, or whose player
+    /// closed the tab while waiting.
+    pub fn prune(&mut self, games: &HashMap<String, Game>) {
+        self.waiting
+            .retain(|game_id, _| games.get(game_id).is_some_and(still_waiting));
+    }
+
+    /// Where `game_id` stands, with 1 next in line, and roughly how much longer it will wait.
+    pub fn status(&self, game_id: &str, players_online: usize) -> Option<QueueStatus> {
+        let since = self.waiting.get(game_id)?.since;
+        let position = 1 + self
+            .waiting
+            .values()
+            .filter(|other| other.since < since)
+            .count();
+
+        // No estimate until someone has actually been matched
+        let estimated_wait_secs = (!self.recent_waits.is_empty()).then(|| {
+            let average =
+                self.recent_waits.iter().sum::<Duration>() / self.recent_waits.len() as u32;
+            average.saturating_sub(since.elapsed()).as_secs()
+        });
+
+        Some(QueueStatus {
+            position,
+            estimated_wait_secs,
+            players_online,
+        })
+    }
+}
+
+fn still_waiting(game: &Game) -> bool {
+    game.quick_match
+        && game.players.len() == 1
+        && !game.game_over
+        && game.connections.values().any(|open| *open > 0)
+}
+
+/// Keeps everyone waiting for a quick match posted on their place in the queue, and pairs
+/// those whose rating windows have widened enough to take each other.
+pub async fn announce_queue_status(app_state: Arc<AppState>) {
+    loop {
+        tokio::time::sleep(STATUS_INTERVAL).await;
+
+        let players_online = app_state.presence.read().await.online_count();
+        let games = app_state.games.read().await;
+        let mut queue = app_state.quick_match_queue.write().await;
+        queue.prune(&games);
+        drop(games);
+
+        for (host, guest) in queue.pair_waiting() {
+            info!("🤝 Moving the player waiting in {} into {}", guest, host);
+            let _ = app_state
+                .tx
+                .send((guest, GameEvent::Matched { game_id: host }));
+        }
+
+        let statuses: Vec<_> = queue
+            .waiting
+            .keys()
+            .filter_map(|game_id| Some((game_id.clone(), queue.status(game_id, players_online)?)))
+            .collect();
+        drop(queue);
+
+        for (game_id, status) in statuses {
+            let _ = app_state.tx.send((game_id, GameEvent::QueueStatus(status)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::models::Player;
+
+    /// A queue whose players have each waited the given number of seconds.
+    fn queued(players: &[(&str, u32, u64)]) -> (QuickMatchQueue, HashMap<String, Game>) {
+        let mut queue = QuickMatchQueue::default();
+        let mut games = HashMap::new();
+        for &(name, rating, waited) in players {
+            let game_id = format!("{name}-game");
+            queue.waiting.insert(
+                game_id.clone(),
+                Waiting {
+                    since: Instant::now() - Duration::from_secs(waited),
+                    name: name.to_string(),
+                    rating,
+                },
+            );
+            let game = Game {
+                quick_match: true,
+                players: vec![Player::X],
+                connections: HashMap::from([(Player::X, 1)]),
+                ..Game::default()
+            };
+            games.insert(game_id, game);
+        }
+        (queue, games)
+    }
+
+    #[test]
+    fn newcomers_arent_paired_with_experts() {
+        let (mut queue, games) = queued(&[("expert", 1800, 0)]);
+        assert_eq!(queue.take_match("newcomer", 1000, &games), None);
+        assert_eq!(
+            queue.take_match("rival", 1750, &games),
+            Some("expert-game".to_string())
+        );
+        assert!(queue.waiting.is_empty());
+        assert_eq!(queue.recent_waits.len(), 1);
+    }
+
+    #[test]
+    fn the_window_widens_while_a_player_waits() {
+        let (mut queue, games) = queued(&[("patient", 1500, 30)]);
+        // 100 straight away plus 10 for each of the 30 seconds waited
+        assert_eq!(queue.take_match("far", 1950, &games), None);
+        assert_eq!(
+            queue.take_match("near", 1900, &games),
+            Some("patient-game".to_string())
+        );
+    }
+
+    #[test]
+    fn the_longest_wait_is_matched_first() {
+        let (mut queue, games) = queued(&[("early", 1200, 20), ("late", 1200, 5)]);
+        assert_eq!(
+            queue.take_match("new", 1210, &games),
+            Some("early-game".to_string())
+        );
+        assert_eq!(queue.status("late-game", 3).unwrap().position, 1);
+    }
+
+    #[test]
+    fn nobody_is_matched_with_themselves() {
+        let (mut queue, games) = queued(&[("ann", 1200, 0)]);
+        assert_eq!(queue.take_match("ann", 1200, &games), None);
+    }
+
+    #[test]
+    fn waiting_players_pair_once_their_windows_meet() {
+        let (mut queue, _) = queued(&[("low", 1000, 0), ("high", 1300, 0), ("mid", 1090, 0)]);
+        let paired = queue.pair_waiting();
+        assert_eq!(paired.len(), 1);
+        let (host, guest) = &paired[0];
+        let mut pair = [host.as_str(), guest.as_str()];
+        pair.sort();
+        assert_eq!(pair, ["low-game", "mid-game"]);
+        assert!(queue.waiting.contains_key("high-game"));
+
+        let (mut queue, _) = queued(&[("low", 1000, 25), ("high", 1300, 0)]);
+        assert_eq!(
+            queue.pair_waiting(),
+            vec![("low-game".to_string(), "high-game".to_string())]
+        );
+    }
+
+    #[test]
+    fn games_that_stopped_waiting_are_skipped() {
+        let (mut queue, mut games) = queued(&[("gone", 1200, 10), ("here", 1200, 0)]);
+        games.get_mut("gone-game").unwrap().connections.clear();
+        assert_eq!(
+            queue.take_match("new", 1200, &games),
+            Some("here-game".to_string())
+        );
+        assert!(queue.waiting.is_empty());
+    }
+}
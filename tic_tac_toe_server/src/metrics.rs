@@ -0,0 +1,80 @@
+//! Prometheus counters for the WebSocket protocol, so operators can spot a flood of one
+//! message type or a client whose requests keep failing.
+
+use crate::app_state::AppState;
+
+use axum::extract::State;
+use axum::http::{header, StatusCode};
+use axum::response::IntoResponse;
+use prometheus::{Encoder, IntCounterVec, Opts, Registry, TextEncoder};
+use std::sync::Arc;
+use tracing::error;
+
+/// Label for frames that don't decode as any `ClientMessage`.
+pub const UNKNOWN_MESSAGE: &str = "unknown";
+
+/// Whether the server accepted a message or answered it with an error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    Failed,
+}
+
+impl Outcome {
+    fn label(self) -> &'static str {
+        match self {
+            Outcome::Ok => "ok",
+            Outcome::Failed => "failed",
+        }
+    }
+}
+
+pub struct Metrics {
+    registry: Registry,
+    messages: IntCounterVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        let registry = Registry::new();
+        let messages = IntCounterVec::new(
+            Opts::new(
+                "ws_messages_total",
+                "WebSocket messages received, by type and outcome",
+            ),
+            &["type", "outcome"],
+        )
+        .expect("Invalid metric definition");
+        registry
+            .register(Box::new(messages.clone()))
+            .expect("Metric registered twice");
+
+        Metrics { registry, messages }
+    }
+}
+
+impl Metrics {
+    /// Counts one inbound message of `kind`, e.g. `MAKE_MOVE` or `UNKNOWN_MESSAGE`.
+    pub fn record_message(&self, kind: &str, outcome: Outcome) {
+        self.messages
+            .with_label_values(&[kind, outcome.label()])
+            .inc();
+    }
+
+    fn encode(&self) -> Result<String, prometheus::Error> {
+        let mut buffer = Vec::new();
+        TextEncoder::new().encode(&self.registry.gather(), &mut buffer)?;
+        Ok(String::from_utf8_lossy(&buffer).into_owned())
+    }
+}
+
+/// `GET /metrics`: every counter in the Prometheus text format.
+pub async fn metrics_handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.metrics.encode() {
+        Ok(body) => Ok(([(header::CONTENT_TYPE, prometheus::TEXT_FORMAT)], body)),
+        Err(e) => {
+            error!("❌ Failed to encode metrics: {}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
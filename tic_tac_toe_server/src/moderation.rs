@@ -0,0 +1,315 @@
+use crate::admin::authorize;
+use crate::app_state::AppState;
+use crate::clock::now_secs;
+use crate::game::message::UpdateReason;
+use crate::versioning::{self, Migration};
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc};
+use tracing::{error, info};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// Longest reason kept with a report; the rest is cut off.
+pub const MAX_REASON_LENGTH: usize = 500;
+
+/// Open reports one player may have waiting for a moderator at a time.
+pub const MAX_OPEN_REPORTS: usize = 5;
+
+/// One player's complaint about another, waiting in `/admin/reports` for a moderator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub id: u64,
+    pub reporter: String,
+    pub target: String,
+    /// The game it happened in, if the reporter was following one.
+    pub game_id: Option<String>,
+    pub reason: String,
+    pub reported_at: u64,
+    /// `None` while the report is still open.
+    pub resolution: Option<Resolution>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Resolution {
+    pub action: ModerationAction,
+    pub note: Option<String>,
+    pub resolved_at: u64,
+}
+
+/// What a moderator did about a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModerationAction {
+    /// Nothing wrong was found.
+    Dismiss,
+    /// Took the target out of the reported game and kept them out of it, as a host's kick
+    /// would.
+    Remove,
+    /// Dealt with some other way, described in the note.
+    Resolve,
+}
+
+/// Every report received, in the order they came in, saved to `REPORTS_FILE`.
+pub struct ModerationQueue {
+    path: PathBuf,
+    reports: Vec<Report>,
+}
+
+impl ModerationQueue {
+    pub fn load(path: PathBuf) -> Self {
+        let reports: Vec<Report> = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse reports {:?}: {}", path, e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+
+        let open = reports.iter().filter(|r| r.resolution.is_none()).count();
+        info!(
+            "🚩 Loaded {} reports ({} open) from {:?}",
+            reports.len(),
+            open,
+            path
+        );
+        ModerationQueue { path, reports }
+    }
+
+    pub async fn save(&self) {
+        let contents =
+            match serde_json::to_string(&versioning::versioned(&self.reports, MIGRATIONS)) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("❌ Failed to serialize reports: {}", e);
+                    return;
+                }
+            };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write reports {:?}: {}", self.path, e);
+        }
+    }
+
+    /// Files a report and returns its id. A reporter who already has an open report about
+    /// the same player in the same game gets that one's id back instead, so repeatedly
+    /// clicking "report" doesn't flood the queue, and one with `MAX_OPEN_REPORTS` open
+    /// reports gets `None` until a moderator deals with some.
+    pub fn file(
+        &mut self,
+        reporter: &str,
+        target: &str,
+        game_id: Option<&str>,
+        reason: &str,
+    ) -> Option<u64> {
+        let open: Vec<&Report> = self
+            .reports
+            .iter()
+            .filter(|report| report.resolution.is_none() && report.reporter == reporter)
+            .collect();
+        let existing = open
+            .iter()
+            .find(|report| report.target == target && report.game_id.as_deref() == game_id);
+        if let Some(report) = existing {
+            return Some(report.id);
+        }
+        if open.len() >= MAX_OPEN_REPORTS {
+            return None;
+        }
+
+        let id = self.reports.last().map_or(1, |report| report.id + 1);
+        self.reports.push(Report {
+            id,
+            reporter: reporter.to_string(),
+            target: target.to_string(),
+            game_id: game_id.map(String::from),
+            reason: reason.chars().take(MAX_REASON_LENGTH).collect(),
+            reported_at: now_secs(),
+            resolution: None,
+        });
+        Some(id)
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Report> {
+        self.reports.iter().find(|report| report.id == id)
+    }
+
+    fn resolve(&mut self, id: u64, action: ModerationAction, note: Option<String>) {
+        if let Some(report) = self.reports.iter_mut().find(|report| report.id == id) {
+            report.resolution = Some(Resolution {
+                action,
+                note,
+                resolved_at: now_secs(),
+            });
+        }
+    }
+
+    /// Reports oldest first, optionally only the open or only the resolved ones.
+    pub fn list(&self, open: Option<bool>) -> Vec<Report> {
+        self.reports
+            .iter()
+            .filter(|report| open.is_none_or(|open| report.resolution.is_none() == open))
+            .cloned()
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFilter {
+    Open,
+    Resolved,
+    All,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportsQuery {
+    /// `open` (the default), `resolved` or `all`.
+    pub status: Option<ReportFilter>,
+}
+
+/// `GET /admin/reports`: the moderation queue, oldest report first.
+pub async fn reports_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ReportsQuery>,
+) -> Result<Json<Vec<Report>>, StatusCode> {
+    authorize(&state, &headers)?;
+
+    let open = match query.status.unwrap_or(ReportFilter::Open) {
+        ReportFilter::Open => Some(true),
+        ReportFilter::Resolved => Some(false),
+        ReportFilter::All => None,
+    };
+    Ok(Json(state.reports.read().await.list(open)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveRequest {
+    pub action: ModerationAction,
+    pub note: Option<String>,
+}
+
+/// `POST /admin/reports/{id}`: closes a report, first removing the reported player from
+/// the game when the action is `remove`.
+pub async fn resolve_report_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Path(id): Path<u64>,
+    Json(request): Json<ResolveRequest>,
+) -> Response {
+    if let Err(status) = authorize(&state, &headers) {
+        return status.into_response();
+    }
+
+    let Some(report) = state.reports.read().await.get(id).cloned() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    if report.resolution.is_some() {
+        return (StatusCode::CONFLICT, "Report is already resolved").into_response();
+    }
+
+    if request.action == ModerationAction::Remove {
+        let Some(game_id) = &report.game_id else {
+            return (StatusCode::CONFLICT, "Report isn't about a game").into_response();
+        };
+        let mut games = state.games.write().await;
+        let Some(game) = games.get_mut(game_id) else {
+            return (StatusCode::CONFLICT, "Game is no longer running").into_response();
+        };
+        state.remove_from_game(
+            game_id,
+            game,
+            &report.target,
+            UpdateReason::Admin,
+            "moderator_removed",
+        );
+        info!(
+            "🚫 Moderator removed {} from game {} (report {})",
+            report.target, game_id, id
+        );
+    }
+
+    let note = request.note.filter(|note| !note.trim().is_empty());
+    state
+        .reports
+        .write()
+        .await
+        .resolve(id, request.action, note);
+    // Written under a read lock, so other reports can still be looked at meanwhile
+    let reports = state.reports.read().await;
+    reports.save().await;
+    info!("🚩 Report {} resolved: {:?}", id, request.action);
+    Json(reports.get(id).cloned()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue() -> ModerationQueue {
+        ModerationQueue {
+            path: PathBuf::from("reports.json"),
+            reports: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn repeat_reports_reuse_the_open_one() {
+        let mut queue = queue();
+        let first = queue.file("ann", "bob", Some("g1"), "spam").unwrap();
+        assert_eq!(
+            queue.file("ann", "bob", Some("g1"), "more spam"),
+            Some(first)
+        );
+        let other_game = queue.file("ann", "bob", Some("g2"), "spam").unwrap();
+        assert_ne!(other_game, first);
+        assert_eq!(queue.list(Some(true)).len(), 2);
+
+        queue.resolve(first, ModerationAction::Dismiss, None);
+        let again = queue
+            .file("ann", "bob", Some("g1"), "still spamming")
+            .unwrap();
+        assert!(again > other_game, "a resolved report isn't reopened");
+    }
+
+    #[test]
+    fn open_reports_are_capped_per_reporter() {
+        let mut queue = queue();
+        for i in 0..MAX_OPEN_REPORTS {
+            assert!(queue.file("ann", &format!("p{i}"), None, "rude").is_some());
+        }
+        assert_eq!(queue.file("ann", "one-more", None, "rude"), None);
+        assert!(queue.file("bob", "one-more", None, "rude").is_some());
+
+        queue.resolve(1, ModerationAction::Resolve, Some("warned".to_string()));
+        assert!(queue.file("ann", "one-more", None, "rude").is_some());
+    }
+
+    #[test]
+    fn long_reasons_are_cut_off() {
+        let mut queue = queue();
+        let id = queue.file("ann", "bob", None, &"é".repeat(MAX_REASON_LENGTH + 10));
+        let report = queue.get(id.unwrap()).unwrap();
+        assert_eq!(report.reason.chars().count(), MAX_REASON_LENGTH);
+    }
+
+    #[test]
+    fn listing_filters_on_resolution() {
+        let mut queue = queue();
+        let open = queue.file("ann", "bob", Some("g1"), "spam").unwrap();
+        let closed = queue.file("cat", "bob", Some("g1"), "spam").unwrap();
+        queue.resolve(closed, ModerationAction::Remove, None);
+
+        let ids = |reports: Vec<Report>| reports.iter().map(|r| r.id).collect::<Vec<_>>();
+        assert_eq!(ids(queue.list(Some(true))), vec![open]);
+        assert_eq!(ids(queue.list(Some(false))), vec![closed]);
+        assert_eq!(ids(queue.list(None)), vec![open, closed]);
+        let resolution = queue.get(closed).unwrap().resolution.as_ref().unwrap();
+        assert_eq!(resolution.action, ModerationAction::Remove);
+    }
+}
@@ -0,0 +1,128 @@
+use crate::clock::now_millis;
+use crate::game::error::GameError;
+use crate::game::message::MoveRequest;
+use crate::game::models::Player;
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::{fs::OpenOptions, io::AsyncWriteExt, sync::Mutex};
+use tracing::{error, info};
+
+/// One submitted move and what the server made of it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub timestamp_ms: u64,
+    pub game_id: String,
+    pub player: Player,
+    /// Who sent it, when the connection had told us.
+    pub name: Option<String>,
+    pub x: usize,
+    pub y: usize,
+    #[serde(flatten)]
+    pub result: MoveResult,
+    /// The WebSocket connection it arrived on; `None` for `POST /games/{id}/moves`.
+    pub connection_id: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "lowercase")]
+pub enum MoveResult {
+    Accepted,
+    Rejected { reason: String },
+}
+
+impl AuditEntry {
+    pub fn new(
+        request: &MoveRequest,
+        name: Option<&str>,
+        connection_id: Option<u64>,
+        outcome: Result<(), &GameError>,
+    ) -> Self {
+        let timestamp_ms = now_millis();
+        let result = match outcome {
+            Ok(()) => MoveResult::Accepted,
+            Err(reason) => MoveResult::Rejected {
+                reason: reason.to_string(),
+            },
+        };
+
+        AuditEntry {
+            timestamp_ms,
+            game_id: request.game_id.clone(),
+            player: request.player,
+            name: name.map(String::from),
+            x: request.x,
+            y: request.y,
+            result,
+            connection_id,
+        }
+    }
+}
+
+/// Append-only record of every move anyone submitted, one JSON object per line in
+/// `MOVE_AUDIT_FILE`, so "the server skipped my move" can be checked after the fact.
+pub struct MoveAudit {
+    path: PathBuf,
+    // Serializes appends so concurrent moves never interleave within a line
+    writer: Mutex<()>,
+}
+
+impl MoveAudit {
+    pub fn new(path: PathBuf) -> Self {
+        info!("🧾 Auditing moves to {:?}", path);
+        MoveAudit {
+            path,
+            writer: Mutex::new(()),
+        }
+    }
+
+    pub async fn record(&self, entry: &AuditEntry) {
+        let mut line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                error!("❌ Failed to serialize move audit entry: {}", e);
+                return;
+            }
+        };
+        line.push('\n');
+
+        let _guard = self.writer.lock().await;
+        let appended = async {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            file.sync_data().await
+        };
+        if let Err(e) = appended.await {
+            error!("❌ Failed to append to move audit {:?}: {}", self.path, e);
+        }
+    }
+
+    /// The newest `limit` entries matching the filters, oldest first.
+    pub async fn query(
+        &self,
+        game_id: Option<&str>,
+        name: Option<&str>,
+        limit: usize,
+    ) -> Vec<AuditEntry> {
+        let contents = match tokio::fs::read_to_string(&self.path).await {
+            Ok(contents) => contents,
+            Err(_) => return Vec::new(),
+        };
+
+        let mut entries: Vec<AuditEntry> = contents
+            .lines()
+            .rev()
+            // A line cut short by a crash mid-write is skipped rather than failing the query
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|entry| game_id.is_none_or(|id| entry.game_id == id))
+            .filter(|entry| name.is_none_or(|name| entry.name.as_deref() == Some(name)))
+            .take(limit)
+            .collect();
+        entries.reverse();
+        entries
+    }
+}
@@ -0,0 +1,158 @@
+use crate::app_state::AppState;
+use crate::game::models::Player;
+use crate::game::rules::GameKind;
+use crate::replays::{self, Replay};
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::IntoResponse;
+use serde::Deserialize;
+use std::sync::Arc;
+
+/// File extension for exported games.
+pub const EXTENSION: &str = "ttn";
+
+/// Writes a finished round in the text notation shared by export and import, modelled on
+/// chess PGN: quoted tag pairs, a blank line, then numbered moves and the result.
+///
+/// ```text
+/// [Game "r1"]
+/// [Round "2"]
+/// [Date "2026.10.16"]
+/// [Kind "tic_tac_toe"]
+/// [Board "3x3"]
+/// [WinLength "3"]
+/// [X "Ann"]
+/// [O "Ben"]
+/// [First "X"]
+/// [Result "1-0"]
+///
+/// 1. a1 b2 2. a2 c3 3. a3 1-0
+/// ```
+///
+/// Cells are a column letter and a row number counted from the top, so `b3` is the
+/// middle column of the third row. Connect Four moves name the cell the mark landed in.
+/// Results read from X's side: `1-0` X won, `0-1` O won, `1/2-1/2` a draw.
+pub fn export(game_id: &str, replay: &Replay) -> String {
+    let config = &replay.config;
+    let kind = match config.kind {
+        GameKind::TicTacToe => "tic_tac_toe",
+        GameKind::ConnectFour => "connect_four",
+    };
+    let name = |player| replay.player_names.get(&player).map_or("?", String::as_str);
+    let first = replay
+        .moves
+        .first()
+        .map_or(config.first_player, |record| record.player);
+    let result = result_token(replay);
+
+    let tags = [
+        ("Game", game_id.to_string()),
+        ("Round", replay.round.to_string()),
+        ("Date", date(replay.finished_at)),
+        ("Kind", kind.to_string()),
+        ("Board", format!("{}x{}", config.columns, config.rows)),
+        ("WinLength", config.win_length.to_string()),
+        ("X", name(Player::X).to_string()),
+        ("O", name(Player::O).to_string()),
+        ("First", format!("{first:?}")),
+        ("Result", result.to_string()),
+    ];
+    let mut text: String = tags
+        .iter()
+        .map(|(tag, value)| format!("[{tag} {}]\n", quote(value)))
+        .collect();
+    text.push('\n');
+
+    let mut movetext: Vec<String> = Vec::new();
+    for (turn, pair) in replay.moves.chunks(2).enumerate() {
+        movetext.push(format!("{}.", turn + 1));
+        movetext.extend(pair.iter().map(|record| cell(record.x, record.y)));
+    }
+    movetext.push(result.to_string());
+    text.push_str(&movetext.join(" "));
+    text.push('\n');
+    text
+}
+
+/// `a1` for the top-left cell of row `x`, column `y`.
+pub fn cell(x: usize, y: usize) -> String {
+    let column = (b'a' + y as u8) as char;
+    format!("{column}{}", x + 1)
+}
+
+fn result_token(replay: &Replay) -> &'static str {
+    match (replay.draw, replay.winner) {
+        (true, _) => "1/2-1/2",
+        (false, Some(Player::X)) => "1-0",
+        (false, Some(Player::O)) => "0-1",
+        (false, None) => "*",
+    }
+}
+
+/// Tag values are quoted, with quotes and backslashes in names escaped.
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// `YYYY.MM.DD` in UTC for a Unix timestamp.
+fn date(unix_secs: u64) -> String {
+    // Days to civil date, after Howard Hinnant's `civil_from_days`
+    let days = (unix_secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    /// Missing exports the latest round.
+    pub round: Option<u32>,
+    /// Signed spectate link token, as for `GET /replays/{id}`.
+    pub token: Option<String>,
+}
+
+/// Downloads one finished round of `game_id` as notation.
+pub async fn export_handler(
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<String>,
+    Query(query): Query<ExportQuery>,
+    headers: HeaderMap,
+) -> Result<impl IntoResponse, StatusCode> {
+    let replays = state.replays.read().await;
+    let rounds = replays.get(&game_id).ok_or(StatusCode::NOT_FOUND)?;
+    if !replays::may_view(&state, &game_id, rounds, &headers, query.token.as_deref()) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    let replay = match query.round {
+        Some(round) => rounds.iter().find(|replay| replay.round == round),
+        None => rounds.last(),
+    }
+    .ok_or(StatusCode::NOT_FOUND)?;
+
+    let filename = format!("{game_id}-round{}.{EXTENSION}", replay.round);
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                "text/plain; charset=utf-8".to_string(),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename={}", quote(&filename)),
+            ),
+        ],
+        export(&game_id, replay),
+    ))
+}
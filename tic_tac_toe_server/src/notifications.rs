@@ -0,0 +1,182 @@
+use crate::app_state::AppState;
+use crate::versioning::{self, Migration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+use tracing::{error, info};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// Where to reach a player when a game is waiting on them.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotifyTarget {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+}
+
+impl NotifyTarget {
+    pub fn is_empty(&self) -> bool {
+        self.webhook_url.is_none() && self.email.is_none()
+    }
+}
+
+pub struct NotificationRelay {
+    path: PathBuf,
+    targets: HashMap<String, NotifyTarget>,
+    /// Game ID -> state version we last notified for, so each turn is announced once.
+    notified: HashMap<String, u64>,
+    client: reqwest::Client,
+}
+
+impl NotificationRelay {
+    pub fn load(path: PathBuf) -> Self {
+        let targets = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse notification targets {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        NotificationRelay {
+            path,
+            targets,
+            notified: HashMap::new(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub async fn save(&self) {
+        let contents =
+            match serde_json::to_string_pretty(&versioning::versioned(&self.targets, MIGRATIONS)) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("❌ Failed to serialize notification targets: {}", e);
+                    return;
+                }
+            };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!(
+                "❌ Failed to write notification targets {:?}: {}",
+                self.path, e
+            );
+        }
+    }
+
+    /// Stores `target` for `name`; an empty target unregisters them.
+    pub fn register(&mut self, name: &str, target: NotifyTarget) {
+        if target.is_empty() {
+            self.targets.remove(name);
+        } else {
+            self.targets.insert(name.to_string(), target);
+        }
+    }
+}
+
+struct PendingNotice {
+    game_id: String,
+    version: u64,
+    player: String,
+    opponent: String,
+    waiting: Duration,
+}
+
+/// Pings players who have been away for a while when a correspondence game is waiting on them.
+pub async fn relay_turn_notifications(app_state: Arc<AppState>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+
+        let notify_after = app_state.config.notify_after;
+        let mut pending = Vec::new();
+        {
+            let games = app_state.games.read().await;
+            let presence = app_state.presence.read().await;
+
+            for (game_id, game) in games.iter() {
+                if !game.correspondence || game.game_over || game.players.len() < 2 {
+                    continue;
+                }
+                if game.ai_player == Some(game.current_turn) {
+                    continue;
+                }
+
+                let player = game.name_of(game.current_turn);
+                let Some(offline_for) = presence.offline_for(&player) else {
+                    continue;
+                };
+                let waiting = game.turn_started_at.elapsed().unwrap_or_default();
+                if offline_for >= notify_after && waiting >= notify_after {
+                    pending.push(PendingNotice {
+                        game_id: game_id.clone(),
+                        version: game.version,
+                        player,
+                        opponent: game.name_of(game.current_turn.opponent()),
+                        waiting,
+                    });
+                }
+            }
+        }
+
+        for notice in pending {
+            let (target, client) = {
+                let mut relay = app_state.notifications.write().await;
+                if relay.notified.get(&notice.game_id) == Some(&notice.version) {
+                    continue;
+                }
+                let Some(target) = relay.targets.get(&notice.player).cloned() else {
+                    continue;
+                };
+                relay
+                    .notified
+                    .insert(notice.game_id.clone(), notice.version);
+                (target, relay.client.clone())
+            };
+
+            send_notice(&client, &app_state, &target, &notice).await;
+        }
+    }
+}
+
+async fn send_notice(
+    client: &reqwest::Client,
+    app_state: &AppState,
+    target: &NotifyTarget,
+    notice: &PendingNotice,
+) {
+    let minutes = notice.waiting.as_secs() / 60;
+    info!(
+        "🔔 Notifying {} that game {} is waiting on them",
+        notice.player, notice.game_id
+    );
+
+    if let Some(url) = &target.webhook_url {
+        let payload = json!({
+            "event": "your_turn",
+            "game_id": notice.game_id,
+            "player": notice.player,
+            "opponent": notice.opponent,
+            "waiting_minutes": minutes
+        });
+        if let Err(e) = client.post(url).json(&payload).send().await {
+            error!("❌ Webhook notification to {} failed: {}", url, e);
+        }
+    }
+
+    if let (Some(email), Some(relay_url)) = (&target.email, &app_state.config.notify_email_relay) {
+        let payload = json!({
+            "to": email,
+            "subject": format!("Your move against {}", notice.opponent),
+            "text": format!(
+                "{} has been waiting {} minutes for your move in game {}.",
+                notice.opponent, minutes, notice.game_id
+            )
+        });
+        if let Err(e) = client.post(relay_url).json(&payload).send().await {
+            error!("❌ Email notification to {} failed: {}", email, e);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use crate::clock::now_secs;
+use crate::game::models::{Game, Player};
+use crate::versioning::{self, Migration};
+
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf};
+use tracing::{error, info};
+
+const MIGRATIONS: &[Migration] = &[];
+
+pub const DEFAULT_RATING: u32 = 1200;
+const K_FACTOR: f64 = 32.0;
+/// Most rating changes kept in each player's history.
+const RATING_HISTORY_LENGTH: usize = 500;
+/// Share of its distance from `DEFAULT_RATING` a rating keeps into a new season.
+const SEASON_CARRY_OVER: f64 = 0.5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerRecord {
+    pub rating: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+    /// Someone holds a token for this name, so it can't be played under without one.
+    pub claimed: bool,
+    /// Lifetime results against each opponent by name. Unlike a game's `scores`, these
+    /// outlive the game.
+    #[serde(skip_serializing_if = "HashMap::is_empty")]
+    pub opponents: HashMap<String, HeadToHead>,
+    /// Results in the current season only, cleared when the next one starts.
+    pub season: SeasonRecord,
+    /// The rating after every change, oldest first.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub history: Vec<RatingChange>,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SeasonRecord {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl SeasonRecord {
+    fn played(&self) -> bool {
+        self.wins + self.losses + self.draws > 0
+    }
+}
+
+/// A player's rating from `at` (Unix seconds) on, after a result or a season reset.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct RatingChange {
+    pub at: u64,
+    pub season: u32,
+    pub rating: u32,
+}
+
+/// Results against one opponent, from the side of the player whose record holds them.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HeadToHead {
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+impl Default for PlayerRecord {
+    fn default() -> Self {
+        PlayerRecord {
+            rating: DEFAULT_RATING,
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            claimed: false,
+            opponents: HashMap::new(),
+            season: SeasonRecord::default(),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl PlayerRecord {
+    fn set_rating(&mut self, rating: u32, season: u32) {
+        self.rating = rating;
+        let at = now_secs();
+        self.history.push(RatingChange { at, season, rating });
+        if self.history.len() > RATING_HISTORY_LENGTH {
+            self.history.remove(0);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaderboardEntry {
+    pub name: String,
+    pub rating: u32,
+    pub wins: u32,
+    pub losses: u32,
+    pub draws: u32,
+}
+
+pub struct PlayerRegistry {
+    path: PathBuf,
+    pub players: HashMap<String, PlayerRecord>,
+    /// The rating season results count towards, kept by `seasons`.
+    pub season: u32,
+}
+
+impl PlayerRegistry {
+    pub fn load(path: PathBuf) -> Self {
+        let players = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse player registry {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        info!("📇 Loaded {} player records from {:?}", players.len(), path);
+        PlayerRegistry {
+            path,
+            players,
+            season: 1,
+        }
+    }
+
+    pub async fn save(&self) {
+        let contents =
+            match serde_json::to_string_pretty(&versioning::versioned(&self.players, MIGRATIONS)) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("❌ Failed to serialize player registry: {}", e);
+                    return;
+                }
+            };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write player registry {:?}: {}", self.path, e);
+        }
+    }
+
+    pub fn rating(&self, name: &str) -> u32 {
+        self.players
+            .get(name)
+            .map(|record| record.rating)
+            .unwrap_or(DEFAULT_RATING)
+    }
+
+    pub fn is_claimed(&self, name: &str) -> bool {
+        self.players.get(name).is_some_and(|record| record.claimed)
+    }
+
+    /// Reserves `name` for token holders. Returns true if it wasn't claimed before.
+    pub fn claim(&mut self, name: &str) -> bool {
+        let record = self.players.entry(name.to_string()).or_default();
+        !std::mem::replace(&mut record.claimed, true)
+    }
+
+    /// Top players ordered by rating, then by wins.
+    pub fn leaderboard(&self, limit: usize) -> Vec<LeaderboardEntry> {
+        let entries = self
+            .players
+            .iter()
+            .map(|(name, record)| LeaderboardEntry {
+                name: name.clone(),
+                rating: record.rating,
+                wins: record.wins,
+                losses: record.losses,
+                draws: record.draws,
+            })
+            .collect();
+
+        rank(entries, limit)
+    }
+
+    /// Like `leaderboard`, but counting only this season's results, among those who have
+    /// played in it.
+    pub fn season_leaderboard(&self, limit: usize) -> Vec<LeaderboardEntry> {
+        let entries = self
+            .players
+            .iter()
+            .filter(|(_, record)| record.season.played())
+            .map(|(name, record)| LeaderboardEntry {
+                name: name.clone(),
+                rating: record.rating,
+                wins: record.season.wins,
+                losses: record.season.losses,
+                draws: record.season.draws,
+            })
+            .collect();
+
+        rank(entries, limit)
+    }
+
+    /// Starts season `season`: every rating moves part of the way back to `DEFAULT_RATING`
+    /// and the season's results start again from nothing.
+    pub fn start_season(&mut self, season: u32) {
+        self.season = season;
+        for record in self.players.values_mut() {
+            record.season = SeasonRecord::default();
+            let offset = record.rating as f64 - DEFAULT_RATING as f64;
+            let rating = (DEFAULT_RATING as f64 + offset * SEASON_CARRY_OVER).round() as u32;
+            if rating != record.rating {
+                record.set_rating(rating, season);
+            }
+        }
+    }
+
+    pub fn history(&self, name: &str) -> Option<&[RatingChange]> {
+        self.players
+            .get(name)
+            .map(|record| record.history.as_slice())
+    }
+
+    /// How `name` has done against `opponent` over every rated game between them.
+    pub fn head_to_head(&self, name: &str, opponent: &str) -> HeadToHead {
+        self.players
+            .get(name)
+            .and_then(|record| record.opponents.get(opponent))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Copies the seated players' current ratings onto the game.
+    pub fn refresh_ratings(&self, game: &mut Game) {
+        game.ratings = game
+            .player_names
+            .iter()
+            .map(|(player, name)| (*player, self.rating(name)))
+            .collect();
+    }
+
+    /// Updates records and ratings for a finished game.
+    pub fn record_result(&mut self, game: &mut Game) {
+        if !game.game_over || !game.is_rated() {
+            return;
+        }
+
+        let (Some(name_x), Some(name_o)) = (
+            game.player_names.get(&Player::X).cloned(),
+            game.player_names.get(&Player::O).cloned(),
+        ) else {
+            return;
+        };
+
+        match game.winner {
+            Some(winner) => {
+                let (winner_name, loser_name) = match winner {
+                    Player::X => (name_x, name_o),
+                    Player::O => (name_o, name_x),
+                };
+
+                let (winner_rating, loser_rating) =
+                    elo_update(self.rating(&winner_name), self.rating(&loser_name));
+
+                let season = self.season;
+                let winner_record = self.players.entry(winner_name.clone()).or_default();
+                winner_record.set_rating(winner_rating, season);
+                winner_record.wins += 1;
+                winner_record.season.wins += 1;
+                winner_record
+                    .opponents
+                    .entry(loser_name.clone())
+                    .or_default()
+                    .wins += 1;
+
+                let loser_record = self.players.entry(loser_name).or_default();
+                loser_record.set_rating(loser_rating, season);
+                loser_record.losses += 1;
+                loser_record.season.losses += 1;
+                loser_record
+                    .opponents
+                    .entry(winner_name)
+                    .or_default()
+                    .losses += 1;
+            }
+            None => {
+                let record_x = self.players.entry(name_x.clone()).or_default();
+                record_x.draws += 1;
+                record_x.season.draws += 1;
+                record_x.opponents.entry(name_o.clone()).or_default().draws += 1;
+
+                let record_o = self.players.entry(name_o).or_default();
+                record_o.draws += 1;
+                record_o.season.draws += 1;
+                record_o.opponents.entry(name_x).or_default().draws += 1;
+            }
+        }
+
+        self.refresh_ratings(game);
+    }
+}
+
+/// Orders by rating, then by wins, and keeps the top `limit`.
+fn rank(mut entries: Vec<LeaderboardEntry>, limit: usize) -> Vec<LeaderboardEntry> {
+    entries.sort_by(|a, b| {
+        b.rating
+            .cmp(&a.rating)
+            .then(b.wins.cmp(&a.wins))
+            .then(a.name.cmp(&b.name))
+    });
+    entries.truncate(limit);
+    entries
+}
+
+/// Returns the new (winner, loser) ratings.
+fn elo_update(winner: u32, loser: u32) -> (u32, u32) {
+    let expected = 1.0 / (1.0 + 10f64.powf((loser as f64 - winner as f64) / 400.0));
+    let delta = (K_FACTOR * (1.0 - expected)).round() as u32;
+
+    (winner + delta, loser.saturating_sub(delta))
+}
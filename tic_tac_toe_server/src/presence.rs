@@ -0,0 +1,92 @@
+use crate::app_state::AppState;
+
+use axum::extract::State;
+use axum::Json;
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+struct Presence {
+    connections: usize,
+    last_seen: SystemTime,
+}
+
+/// A name that came online or went offline, sent to every open socket.
+#[derive(Debug, Clone)]
+pub struct PresenceChange {
+    pub name: String,
+    pub online: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnlinePlayer {
+    pub name: String,
+    /// Seated in a game that is under way, so not free to take a challenge.
+    pub playing: bool,
+}
+
+/// Tracks which player names currently have an open socket.
+#[derive(Default)]
+pub struct PresenceTracker {
+    players: HashMap<String, Presence>,
+}
+
+impl PresenceTracker {
+    /// Counts a socket for `name`. Returns true if they weren't online before.
+    pub fn connect(&mut self, name: &str) -> bool {
+        let presence = self
+            .players
+            .entry(name.to_string())
+            .or_insert_with(|| Presence {
+                connections: 0,
+                last_seen: SystemTime::now(),
+            });
+        presence.connections += 1;
+        presence.last_seen = SystemTime::now();
+        presence.connections == 1
+    }
+
+    /// Drops a socket for `name`. Returns true if that was their last one.
+    pub fn disconnect(&mut self, name: &str) -> bool {
+        let Some(presence) = self.players.get_mut(name) else {
+            return false;
+        };
+        let was_online = presence.connections > 0;
+        presence.connections = presence.connections.saturating_sub(1);
+        presence.last_seen = SystemTime::now();
+        was_online && presence.connections == 0
+    }
+
+    /// Names with at least one open socket, alphabetically.
+    pub fn online(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .players
+            .iter()
+            .filter(|(_, presence)| presence.connections > 0)
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Names with at least one open socket.
+    pub fn online_count(&self) -> usize {
+        self.players
+            .values()
+            .filter(|presence| presence.connections > 0)
+            .count()
+    }
+
+    /// How long `name` has had no open socket, or `None` if they are online or were never seen.
+    pub fn offline_for(&self, name: &str) -> Option<Duration> {
+        let presence = self.players.get(name)?;
+        (presence.connections == 0).then(|| presence.last_seen.elapsed().unwrap_or_default())
+    }
+}
+
+pub async fn online_handler(State(state): State<Arc<AppState>>) -> Json<Vec<OnlinePlayer>> {
+    Json(state.online_players().await)
+}
@@ -0,0 +1,97 @@
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+const V2_SIGNATURE: [u8; 12] = *b"\r\n\r\n\0\r\nQUIT\n";
+/// Longest v1 header the spec allows, CRLF included.
+const V1_MAX_LENGTH: usize = 107;
+
+/// Reads the HAProxy PROXY protocol header (v1 or v2) that a load balancer sends ahead of
+/// the connection's own bytes, and returns the client address it carries. `None` means a
+/// valid header without an address (`UNKNOWN`, or a `LOCAL` health check).
+pub async fn read_header<S: AsyncRead + Unpin>(stream: &mut S) -> io::Result<Option<SocketAddr>> {
+    // Shorter than any valid header, so this never reads into the payload
+    let mut start = [0u8; 12];
+    stream.read_exact(&mut start).await?;
+
+    if start == V2_SIGNATURE {
+        read_v2(stream).await
+    } else if start.starts_with(b"PROXY ") {
+        read_v1(stream, &start).await
+    } else {
+        Err(invalid("missing PROXY protocol header"))
+    }
+}
+
+/// `PROXY TCP4 203.0.113.7 10.0.0.1 51234 443\r\n`
+async fn read_v1<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    start: &[u8],
+) -> io::Result<Option<SocketAddr>> {
+    let mut line = start.to_vec();
+    while !line.ends_with(b"\r\n") {
+        if line.len() >= V1_MAX_LENGTH {
+            return Err(invalid("PROXY v1 header too long"));
+        }
+        line.push(stream.read_u8().await?);
+    }
+
+    let line = std::str::from_utf8(&line[..line.len() - 2])
+        .map_err(|_| invalid("PROXY v1 header is not text"))?;
+    let fields: Vec<&str> = line.split(' ').collect();
+
+    match fields.as_slice() {
+        ["PROXY", "UNKNOWN", ..] => Ok(None),
+        ["PROXY", "TCP4" | "TCP6", source, _, source_port, _] => {
+            let ip: IpAddr = source
+                .parse()
+                .map_err(|_| invalid("bad PROXY v1 source address"))?;
+            let port: u16 = source_port
+                .parse()
+                .map_err(|_| invalid("bad PROXY v1 source port"))?;
+            Ok(Some(SocketAddr::new(ip, port)))
+        }
+        _ => Err(invalid("malformed PROXY v1 header")),
+    }
+}
+
+async fn read_v2<S: AsyncRead + Unpin>(stream: &mut S) -> io::Result<Option<SocketAddr>> {
+    let mut meta = [0u8; 4];
+    stream.read_exact(&mut meta).await?;
+    let [version_command, family, length_high, length_low] = meta;
+    if version_command >> 4 != 2 {
+        return Err(invalid("unsupported PROXY protocol version"));
+    }
+
+    // Always read the whole address block, even the parts skipped below
+    let mut addresses = vec![0u8; u16::from_be_bytes([length_high, length_low]) as usize];
+    stream.read_exact(&mut addresses).await?;
+
+    const LOCAL: u8 = 0;
+    if version_command & 0x0F == LOCAL {
+        return Ok(None);
+    }
+
+    let source = match family >> 4 {
+        // AF_INET: source, destination, source port, destination port
+        1 if addresses.len() >= 12 => {
+            let ip = Ipv4Addr::new(addresses[0], addresses[1], addresses[2], addresses[3]);
+            let port = u16::from_be_bytes([addresses[8], addresses[9]]);
+            Some(SocketAddr::new(ip.into(), port))
+        }
+        // AF_INET6, laid out the same way with 16-byte addresses
+        2 if addresses.len() >= 36 => {
+            let mut octets = [0u8; 16];
+            octets.copy_from_slice(&addresses[..16]);
+            let port = u16::from_be_bytes([addresses[32], addresses[33]]);
+            Some(SocketAddr::new(Ipv6Addr::from(octets).into(), port))
+        }
+        // AF_UNSPEC or AF_UNIX: nothing we can use as an IP
+        _ => None,
+    };
+    Ok(source)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
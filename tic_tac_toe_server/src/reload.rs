@@ -0,0 +1,126 @@
+//! Applies edits to the timeouts, capacity limits and log level without a restart, on
+//! SIGHUP or when the config file is saved. Games and connections are left alone.
+
+use crate::app_state::AppState;
+use crate::config::{Args, Config, Tunables};
+
+use std::{
+    path::Path,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{error, info, warn};
+
+/// How often the config file's modification time is checked.
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Reloads the settings whenever SIGHUP arrives or `CONFIG_FILE` changes on disk. Flags and
+/// environment variables still win over the file, as they did at startup.
+pub async fn watch_config(app_state: Arc<AppState>, args: Args) {
+    let mut hangups = Hangups::listen();
+    let mut poll = tokio::time::interval(POLL_INTERVAL);
+    poll.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut modified = args.config.as_deref().and_then(modified_at);
+
+    loop {
+        tokio::select! {
+            _ = hangups.recv() => {
+                info!("📨 SIGHUP received, reloading settings");
+            }
+            _ = poll.tick() => {
+                let Some(path) = args.config.as_deref() else {
+                    continue;
+                };
+                if modified_at(path) == modified {
+                    continue;
+                }
+                info!("📄 {:?} changed, reloading settings", path);
+            }
+        }
+        modified = args.config.as_deref().and_then(modified_at);
+        reload(&app_state, &args);
+    }
+}
+
+fn reload(app_state: &AppState, args: &Args) {
+    let tunables = match Config::load(args) {
+        Ok(config) => config.tunables,
+        Err(e) => {
+            warn!("⚠️ Keeping the current settings: {}", e);
+            return;
+        }
+    };
+
+    let changes = changes(&app_state.tunables(), &tunables);
+    if changes.is_empty() {
+        info!("🔄 Settings reloaded, nothing changed");
+        return;
+    }
+    match app_state.apply_tunables(tunables) {
+        Ok(()) => info!("🔄 Settings reloaded: {}", changes.join(", ")),
+        Err(e) => error!("❌ Keeping the current settings: {}", e),
+    }
+}
+
+/// `name: old → new` for every setting that differs.
+fn changes(old: &Tunables, new: &Tunables) -> Vec<String> {
+    macro_rules! compare {
+        ($($field:ident),*) => {
+            [$((stringify!($field), format!("{:?}", old.$field), format!("{:?}", new.$field))),*]
+        };
+    }
+
+    compare!(
+        turn_seconds,
+        abandon_grace,
+        reconnect_grace,
+        idle_timeout,
+        max_games,
+        max_connections,
+        ping_interval,
+        max_missed_pongs,
+        log_level
+    )
+    .into_iter()
+    .filter(|(_, old, new)| old != new)
+    .map(|(name, old, new)| format!("{}: {} → {}", name, old, new))
+    .collect()
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
+/// SIGHUP, where the platform has it.
+struct Hangups {
+    #[cfg(unix)]
+    signal: Option<tokio::signal::unix::Signal>,
+}
+
+impl Hangups {
+    fn listen() -> Self {
+        #[cfg(unix)]
+        {
+            use tokio::signal::unix::{signal, SignalKind};
+            let signal = signal(SignalKind::hangup())
+                .inspect_err(|e| warn!("⚠️ Can't listen for SIGHUP: {}", e))
+                .ok();
+            Hangups { signal }
+        }
+        #[cfg(not(unix))]
+        Hangups {}
+    }
+
+    /// Resolves on the next SIGHUP; never, where there is none.
+    async fn recv(&mut self) {
+        #[cfg(unix)]
+        if let Some(signal) = &mut self.signal {
+            signal.recv().await;
+            return;
+        }
+        std::future::pending::<()>().await
+    }
+}
@@ -0,0 +1,207 @@
+use crate::app_state::AppState;
+use crate::auth;
+use crate::clock::now_secs;
+use crate::game::models::{Game, MoveRecord, Player};
+use crate::game::rules::GameConfig;
+use crate::result_chain;
+use crate::spectate_link;
+use crate::versioning::{self, Migration};
+
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+use tracing::{error, info};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// A finished round, kept after the live game has been cleaned up.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Replay {
+    pub round: u32,
+    /// Board and rules the round was played with; older replays assume 3x3 tic-tac-toe.
+    pub config: GameConfig,
+    pub player_names: HashMap<Player, String>,
+    pub moves: Vec<MoveRecord>,
+    pub winner: Option<Player>,
+    pub draw: bool,
+    /// Seconds since the Unix epoch
+    pub finished_at: u64,
+    /// Rated rounds only: the result chain digest, see `result_chain::digest`.
+    pub result_digest: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RoundVerification {
+    pub round: u32,
+    pub result_digest: Option<String>,
+    /// Whether the stored round still hashes to its digest; `None` for unrated rounds.
+    pub verified: Option<bool>,
+}
+
+pub struct ReplayStore {
+    path: PathBuf,
+    replays: HashMap<String, Vec<Replay>>,
+    /// Games kept before the one finished longest ago is dropped; `None` keeps every game.
+    max_games: Option<usize>,
+}
+
+impl ReplayStore {
+    pub fn load(path: PathBuf, max_games: Option<usize>) -> Self {
+        let replays: HashMap<String, Vec<Replay>> = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse replay store {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        info!(
+            "🎞️ Loaded replays for {} games from {:?}",
+            replays.len(),
+            path
+        );
+        let mut store = ReplayStore {
+            path,
+            replays,
+            max_games,
+        };
+        store.prune();
+        store
+    }
+
+    pub async fn save(&self) {
+        let contents =
+            match serde_json::to_string(&versioning::versioned(&self.replays, MIGRATIONS)) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("❌ Failed to serialize replay store: {}", e);
+                    return;
+                }
+            };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write replay store {:?}: {}", self.path, e);
+        }
+    }
+
+    /// Every recorded round of `game_id`, oldest first.
+    pub fn get(&self, game_id: &str) -> Option<&Vec<Replay>> {
+        self.replays.get(game_id)
+    }
+
+    /// Archives the round `game` just finished, returning its result digest if it was rated.
+    pub fn record(&mut self, game_id: &str, game: &Game) -> Option<String> {
+        let rounds = self.replays.entry(game_id.to_string()).or_default();
+        let finished_at = now_secs();
+
+        let mut replay = Replay {
+            round: rounds.len() as u32 + 1,
+            config: game.config.clone(),
+            player_names: game.player_names.clone(),
+            moves: game.moves.clone(),
+            winner: game.winner,
+            draw: game.draw,
+            finished_at,
+            result_digest: None,
+        };
+        if game.is_rated() {
+            replay.result_digest = Some(result_chain::digest(game_id, &replay));
+        }
+
+        let result_digest = replay.result_digest.clone();
+        rounds.push(replay);
+        self.prune();
+        result_digest
+    }
+
+    /// Drops the games whose last round finished longest ago until at most `max_games`
+    /// are left.
+    fn prune(&mut self) {
+        let Some(max_games) = self.max_games else {
+            return;
+        };
+        let excess = self.replays.len().saturating_sub(max_games);
+        if excess == 0 {
+            return;
+        }
+
+        let mut by_age: Vec<(u64, String)> = self
+            .replays
+            .iter()
+            .map(|(game_id, rounds)| {
+                let finished_at = rounds.last().map_or(0, |replay| replay.finished_at);
+                (finished_at, game_id.clone())
+            })
+            .collect();
+        by_age.sort_unstable();
+        for (_, game_id) in by_age.into_iter().take(excess) {
+            self.replays.remove(&game_id);
+        }
+        info!("🎞️ Dropped the replays of {} old games", excess);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplayQuery {
+    /// Signed spectate link token, needed for private games unless signed in as a player.
+    pub token: Option<String>,
+}
+
+/// Whether the rounds of `game_id` may be shown to this request: private games only to
+/// their players, by bearer token, or to holders of a spectate link.
+pub fn may_view(
+    state: &AppState,
+    game_id: &str,
+    rounds: &[Replay],
+    headers: &HeaderMap,
+    token: Option<&str>,
+) -> bool {
+    let played = auth::bearer_name(state, headers).is_some_and(|name| {
+        rounds
+            .iter()
+            .any(|replay| replay.player_names.values().any(|player| *player == name))
+    });
+    spectate_link::may_view(
+        &state.config.spectate_secret,
+        game_id,
+        rounds.iter().any(|replay| replay.config.private),
+        played,
+        token,
+    )
+}
+
+pub async fn replay_handler(
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<String>,
+    Query(query): Query<ReplayQuery>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<Replay>>, StatusCode> {
+    let replays = state.replays.read().await;
+    let rounds = replays.get(&game_id).ok_or(StatusCode::NOT_FOUND)?;
+    if !may_view(&state, &game_id, rounds, &headers, query.token.as_deref()) {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    Ok(Json(rounds.clone()))
+}
+
+/// Recomputes the result chain of every stored round of `game_id`.
+pub async fn verify_replay_handler(
+    State(state): State<Arc<AppState>>,
+    Path(game_id): Path<String>,
+) -> Result<Json<Vec<RoundVerification>>, StatusCode> {
+    let replays = state.replays.read().await;
+    let rounds = replays.get(&game_id).ok_or(StatusCode::NOT_FOUND)?;
+
+    let verifications = rounds
+        .iter()
+        .map(|replay| RoundVerification {
+            round: replay.round,
+            result_digest: replay.result_digest.clone(),
+            verified: result_chain::verify(&game_id, replay),
+        })
+        .collect();
+    Ok(Json(verifications))
+}
@@ -0,0 +1,153 @@
+use crate::game::models::Player;
+use crate::replays::Replay;
+
+use sha2::{Digest, Sha256};
+
+/// Bumped if the link layout below ever changes, so old digests can't verify by accident.
+const CHAIN_TAG: &str = "ttt-result-chain-v1";
+
+/// Final link of a hash chain over a rated round: the first link commits to the game and
+/// who played it, each move extends the chain, and the last link adds the result the
+/// ratings were updated with. Recomputing it from a stored replay shows whether the
+/// replay still matches that result.
+pub fn digest(game_id: &str, replay: &Replay) -> String {
+    let name = |player| replay.player_names.get(&player).map_or("", String::as_str);
+    let mut link = chain(
+        &[0; 32],
+        &[
+            CHAIN_TAG,
+            game_id,
+            &replay.round.to_string(),
+            name(Player::X),
+            name(Player::O),
+        ],
+    );
+
+    for record in &replay.moves {
+        link = chain(
+            &link,
+            &[
+                "move",
+                mark(Some(record.player)),
+                &record.x.to_string(),
+                &record.y.to_string(),
+            ],
+        );
+    }
+
+    let outcome = if replay.draw { "draw" } else { "win" };
+    hex::encode(chain(&link, &["result", outcome, mark(replay.winner)]))
+}
+
+/// Whether `replay` carries a digest and it still matches its contents. `None` for
+/// unrated rounds, which never get one.
+pub fn verify(game_id: &str, replay: &Replay) -> Option<bool> {
+    let recorded = replay.result_digest.as_deref()?;
+    Some(recorded == digest(game_id, replay))
+}
+
+fn mark(player: Option<Player>) -> &'static str {
+    match player {
+        Some(Player::X) => "X",
+        Some(Player::O) => "O",
+        None => "-",
+    }
+}
+
+/// Hashes the previous link with `fields`, each length-prefixed so that names containing
+/// separators can't be shifted between fields.
+fn chain(previous: &[u8], fields: &[&str]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(previous);
+    for field in fields {
+        hasher.update((field.len() as u64).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.finalize().into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::models::MoveRecord;
+    use std::collections::HashMap;
+
+    /// X takes the top row while O plays below it.
+    fn rated_replay() -> Replay {
+        let moves = [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)];
+        let mut replay = Replay {
+            round: 1,
+            player_names: HashMap::from([
+                (Player::X, "ann".to_string()),
+                (Player::O, "bob".to_string()),
+            ]),
+            moves: moves
+                .iter()
+                .enumerate()
+                .map(|(i, &(x, y))| MoveRecord {
+                    player: if i % 2 == 0 { Player::X } else { Player::O },
+                    x,
+                    y,
+                    think_ms: 0,
+                    engine_move: false,
+                })
+                .collect(),
+            winner: Some(Player::X),
+            ..Replay::default()
+        };
+        replay.result_digest = Some(digest("g1", &replay));
+        replay
+    }
+
+    #[test]
+    fn an_untouched_replay_verifies() {
+        let replay = rated_replay();
+        assert_eq!(verify("g1", &replay), Some(true));
+        assert_eq!(digest("g1", &replay), digest("g1", &replay.clone()));
+    }
+
+    #[test]
+    fn unrated_rounds_have_nothing_to_verify() {
+        let replay = Replay {
+            result_digest: None,
+            ..rated_replay()
+        };
+        assert_eq!(verify("g1", &replay), None);
+    }
+
+    #[test]
+    fn any_change_to_the_record_breaks_the_chain() {
+        let mut moved = rated_replay();
+        moved.moves[1].y = 2;
+        let mut reordered = rated_replay();
+        reordered.moves.swap(0, 2);
+        let mut result = rated_replay();
+        result.winner = Some(Player::O);
+        let mut drawn = rated_replay();
+        drawn.draw = true;
+        let mut renamed = rated_replay();
+        renamed.player_names.insert(Player::O, "eve".to_string());
+        let mut round = rated_replay();
+        round.round = 2;
+
+        for tampered in [moved, reordered, result, drawn, renamed, round] {
+            assert_eq!(verify("g1", &tampered), Some(false));
+        }
+        assert_eq!(
+            verify("g2", &rated_replay()),
+            Some(false),
+            "digest names its game"
+        );
+    }
+
+    #[test]
+    fn fields_cant_be_shifted_between_names() {
+        let mut left = rated_replay();
+        left.player_names.insert(Player::X, "ab".to_string());
+        left.player_names.insert(Player::O, "c".to_string());
+        let mut right = rated_replay();
+        right.player_names.insert(Player::X, "a".to_string());
+        right.player_names.insert(Player::O, "bc".to_string());
+        assert_ne!(digest("g1", &left), digest("g1", &right));
+    }
+}
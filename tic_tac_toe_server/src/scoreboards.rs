@@ -0,0 +1,90 @@
+use crate::game::models::{Game, Player};
+use crate::versioning::{self, Migration};
+
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::PathBuf};
+use tracing::{debug, error, info};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// The running score of a game, saved after every finished round so a restart in the
+/// middle of a session doesn't take a rivalry back to 0–0.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scoreboard {
+    pub player_names: HashMap<Player, String>,
+    pub scores: HashMap<Player, u32>,
+}
+
+pub struct ScoreStore {
+    path: PathBuf,
+    boards: HashMap<String, Scoreboard>,
+}
+
+impl ScoreStore {
+    pub fn load(path: PathBuf) -> Self {
+        let boards = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode(&contents, MIGRATIONS).unwrap_or_else(|e| {
+                error!("❌ Failed to parse scoreboards {:?}: {}", path, e);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+
+        info!("🏆 Loaded {} scoreboards from {:?}", boards.len(), path);
+        ScoreStore { path, boards }
+    }
+
+    pub async fn save(&self) {
+        let contents =
+            match serde_json::to_string_pretty(&versioning::versioned(&self.boards, MIGRATIONS)) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    error!("❌ Failed to serialize scoreboards: {}", e);
+                    return;
+                }
+            };
+
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write scoreboards {:?}: {}", self.path, e);
+        }
+    }
+
+    pub fn record(&mut self, game_id: &str, game: &Game) {
+        self.boards.insert(
+            game_id.to_string(),
+            Scoreboard {
+                player_names: game.player_names.clone(),
+                scores: game.scores.clone(),
+            },
+        );
+    }
+
+    /// Carries the saved score of `game_id` over to `game` once both seats are taken, if
+    /// the same two names are playing it again. Seats are matched by name, so it doesn't
+    /// matter who sits as X this time.
+    pub fn restore(&self, game_id: &str, game: &mut Game) {
+        let Some(board) = self.boards.get(game_id) else {
+            return;
+        };
+
+        let saved_score = |name: &String| {
+            let seat = board
+                .player_names
+                .iter()
+                .find(|(_, saved)| *saved == name)?
+                .0;
+            Some(board.scores.get(seat).copied().unwrap_or(0))
+        };
+        let restored: Option<Vec<(Player, u32)>> = [Player::X, Player::O]
+            .into_iter()
+            .map(|seat| Some((seat, saved_score(game.player_names.get(&seat)?)?)))
+            .collect();
+        let Some(restored) = restored else {
+            return;
+        };
+
+        game.scores.extend(restored);
+        debug!("Restored scores for game {}: {:?}", game_id, game.scores);
+    }
+}
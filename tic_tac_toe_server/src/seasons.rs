@@ -0,0 +1,202 @@
+//! Rating seasons: every `SEASON_DAYS` the season's standings are archived, ratings are
+//! pulled part of the way back to the default, and a new season starts.
+
+use crate::app_state::AppState;
+use crate::clock::now_secs;
+use crate::leaderboard::MAX_LEADERBOARD_SIZE;
+use crate::players::{LeaderboardEntry, RatingChange};
+use crate::versioning::{self, Migration};
+
+use axum::extract::{Path, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, sync::Arc, time::Duration};
+use tracing::{error, info};
+
+const MIGRATIONS: &[Migration] = &[];
+
+/// How often the current season is checked for having run its course.
+const CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Season {
+    pub number: u32,
+    /// Unix seconds.
+    pub started_at: u64,
+}
+
+/// A finished season and where everyone who played in it ended up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PastSeason {
+    pub number: u32,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub standings: Vec<LeaderboardEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SeasonStore {
+    #[serde(skip)]
+    path: PathBuf,
+    pub current: Season,
+    pub past: Vec<PastSeason>,
+}
+
+impl SeasonStore {
+    pub fn load(path: PathBuf) -> Self {
+        let stored = match std::fs::read_to_string(&path) {
+            Ok(contents) => versioning::decode::<SeasonStore>(&contents, MIGRATIONS)
+                .inspect_err(|e| error!("❌ Failed to parse seasons {:?}: {}", path, e))
+                .ok(),
+            Err(_) => None,
+        };
+
+        let Some(mut store) = stored else {
+            let store = SeasonStore {
+                path,
+                current: Season {
+                    number: 1,
+                    started_at: now_secs(),
+                },
+                past: Vec::new(),
+            };
+            // Written straight away, so the first season's start survives a restart
+            if let Some(contents) = store.contents() {
+                if let Err(e) = std::fs::write(&store.path, contents) {
+                    error!("❌ Failed to write seasons {:?}: {}", store.path, e);
+                }
+            }
+            return store;
+        };
+
+        info!(
+            "🗓️ Season {} with {} past seasons loaded from {:?}",
+            store.current.number,
+            store.past.len(),
+            path
+        );
+        store.path = path;
+        store
+    }
+
+    pub async fn save(&self) {
+        let Some(contents) = self.contents() else {
+            return;
+        };
+        if let Err(e) = tokio::fs::write(&self.path, contents).await {
+            error!("❌ Failed to write seasons {:?}: {}", self.path, e);
+        }
+    }
+
+    fn contents(&self) -> Option<String> {
+        serde_json::to_string_pretty(&versioning::versioned(self, MIGRATIONS))
+            .inspect_err(|e| error!("❌ Failed to serialize seasons: {}", e))
+            .ok()
+    }
+
+    pub fn get(&self, number: u32) -> Option<&PastSeason> {
+        self.past.iter().find(|season| season.number == number)
+    }
+}
+
+/// Ends the current season once it has lasted `SEASON_DAYS`: its standings are archived
+/// and the next season starts with softened ratings. A server that was down past the end
+/// catches up with a single new season starting now.
+pub async fn rotate_seasons(app_state: Arc<AppState>) {
+    let Some(length) = app_state.config.season_length else {
+        return;
+    };
+
+    let mut check = tokio::time::interval(CHECK_INTERVAL);
+    loop {
+        check.tick().await;
+
+        let mut players = app_state.players.write().await;
+        let mut seasons = app_state.seasons.write().await;
+        let now = now_secs();
+        if now < seasons.current.started_at + length.as_secs() {
+            continue;
+        }
+
+        let ended = PastSeason {
+            number: seasons.current.number,
+            started_at: seasons.current.started_at,
+            ended_at: now,
+            standings: players.season_leaderboard(MAX_LEADERBOARD_SIZE),
+        };
+        let number = ended.number + 1;
+        seasons.past.push(ended);
+        seasons.current = Season {
+            number,
+            started_at: now,
+        };
+        players.start_season(number);
+        players.save().await;
+        seasons.save().await;
+        info!("🗓️ Season {} is over; season {} begins", number - 1, number);
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeasonsResponse {
+    pub current: CurrentSeason,
+    pub past: Vec<SeasonSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CurrentSeason {
+    pub number: u32,
+    pub started_at: u64,
+    /// `None` when seasons are turned off and this one never ends.
+    pub ends_at: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeasonSummary {
+    pub number: u32,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub players: usize,
+}
+
+/// `GET /seasons`: the current season and every finished one, newest first. Their
+/// standings are at `GET /leaderboard?season=N`.
+pub async fn seasons_handler(State(state): State<Arc<AppState>>) -> Json<SeasonsResponse> {
+    let seasons = state.seasons.read().await;
+    let current = CurrentSeason {
+        number: seasons.current.number,
+        started_at: seasons.current.started_at,
+        ends_at: state
+            .config
+            .season_length
+            .map(|length| seasons.current.started_at + length.as_secs()),
+    };
+    let past = seasons
+        .past
+        .iter()
+        .rev()
+        .map(|season| SeasonSummary {
+            number: season.number,
+            started_at: season.started_at,
+            ended_at: season.ended_at,
+            players: season.standings.len(),
+        })
+        .collect();
+
+    Json(SeasonsResponse { current, past })
+}
+
+/// `GET /players/{name}/ratings`: every change to a player's rating, oldest first.
+pub async fn rating_history_handler(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<RatingChange>>, StatusCode> {
+    state
+        .players
+        .read()
+        .await
+        .history(&name)
+        .map(|history| Json(history.to_vec()))
+        .ok_or(StatusCode::NOT_FOUND)
+}
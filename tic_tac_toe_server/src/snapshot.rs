@@ -0,0 +1,102 @@
+//! Keeps live games across a quick restart: every game in memory is written to
+//! `SNAPSHOT_FILE` on a graceful shutdown and read back, then deleted, at the next start.
+
+use crate::app_state::AppState;
+use crate::game::models::Game;
+use crate::versioning::{self, Migration};
+
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, path::Path, time::Duration, time::SystemTime};
+use tracing::{error, info, warn};
+
+const MIGRATIONS: &[Migration] = &[];
+
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    saved_at: SystemTime,
+    games: HashMap<String, Game>,
+}
+
+/// Games saved by the last shutdown. Live ones are paused for `reconnect_grace`, as if the
+/// player to move had just dropped, so nobody's clock runs out while sockets find their
+/// way back.
+pub fn load(path: &Path, reconnect_grace: Option<Duration>) -> HashMap<String, Game> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(_) => return HashMap::new(),
+    };
+    let snapshot: Snapshot = match versioning::decode(&contents, MIGRATIONS) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            error!("❌ Failed to parse snapshot {:?}: {}", path, e);
+            return HashMap::new();
+        }
+    };
+
+    let mut games = snapshot.games;
+    for game in games.values_mut() {
+        game.restore(snapshot.saved_at);
+        if let Some(grace) = reconnect_grace {
+            game.pause(game.current_turn, grace);
+        }
+    }
+
+    // A crash later on mustn't bring these games back a second time
+    if let Err(e) = std::fs::remove_file(path) {
+        warn!("⚠️ Failed to remove snapshot {:?}: {}", path, e);
+    }
+    info!("📸 Restored {} games from {:?}", games.len(), path);
+    games
+}
+
+/// Writes every game in memory to `SNAPSHOT_FILE`, just before the server exits. The games
+/// lock is held until the file is written, so no move is accepted and then forgotten.
+pub async fn save(app_state: &AppState) {
+    let games = app_state.games.write().await;
+    let snapshot = Snapshot {
+        saved_at: SystemTime::now(),
+        games: games.clone(),
+    };
+
+    let contents = match serde_json::to_string(&versioning::versioned(&snapshot, MIGRATIONS)) {
+        Ok(contents) => contents,
+        Err(e) => {
+            error!("❌ Failed to serialize snapshot: {}", e);
+            return;
+        }
+    };
+
+    let path = &app_state.config.snapshot_file;
+    match tokio::fs::write(path, contents).await {
+        Ok(()) => info!("📸 Saved {} games to {:?}", snapshot.games.len(), path),
+        Err(e) => error!("❌ Failed to write snapshot {:?}: {}", path, e),
+    }
+
+    // Results still being written in the background; holding the games lock, no more come
+    app_state.save_results().await;
+}
+
+/// Resolves on Ctrl-C, or SIGTERM where there is one.
+pub async fn shutdown_signal() {
+    #[cfg(unix)]
+    let terminate = async {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                warn!("⚠️ Can't listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate => {}
+    }
+    info!("🛑 Shutting down");
+}
@@ -0,0 +1,48 @@
+use crate::clock::now_secs;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Issues a token of the form `<expires_at>.<hex signature>` granting spectator access to
+/// `game_id` until `expires_at` (seconds since the Unix epoch).
+pub fn sign(secret: &[u8], game_id: &str, expires_at: u64) -> String {
+    let signature = mac(secret, game_id, expires_at).finalize().into_bytes();
+    format!("{}.{}", expires_at, hex::encode(signature))
+}
+
+/// Checks that `token` was issued for `game_id` and has not expired.
+pub fn verify(secret: &[u8], game_id: &str, token: &str) -> bool {
+    let Some((expires_at, signature)) = token.split_once('.') else {
+        return false;
+    };
+    let (Ok(expires_at), Ok(signature)) = (expires_at.parse::<u64>(), hex::decode(signature))
+    else {
+        return false;
+    };
+
+    expires_at > now_secs()
+        && mac(secret, game_id, expires_at)
+            .verify_slice(&signature)
+            .is_ok()
+}
+
+/// Whether a game may be shown: public games to anyone, private ones only to those already
+/// `admitted` (seated, or let in by a link earlier) or presenting a valid `token`.
+pub fn may_view(
+    secret: &[u8],
+    game_id: &str,
+    private: bool,
+    admitted: bool,
+    token: Option<&str>,
+) -> bool {
+    !private || admitted || token.is_some_and(|token| verify(secret, game_id, token))
+}
+
+fn mac(secret: &[u8], game_id: &str, expires_at: u64) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(game_id.as_bytes());
+    mac.update(b"\n");
+    mac.update(expires_at.to_string().as_bytes());
+    mac
+}
@@ -0,0 +1,84 @@
+use crate::app_state::AppState;
+use crate::game::models::Game;
+
+use axum::extract::State;
+use axum::Json;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::{sync::Arc, time::Instant};
+
+/// Running totals since the server started, for a dashboard that doesn't need full metrics.
+pub struct ServerStats {
+    started_at: Instant,
+    moves: AtomicU64,
+    completed_games: AtomicU64,
+    /// Moves and thinking time of the completed games, for their averages.
+    completed_moves: AtomicU64,
+    completed_ms: AtomicU64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatsSnapshot {
+    pub uptime_seconds: u64,
+    pub active_games: usize,
+    pub connected_clients: usize,
+    /// Rounds finished since the server started, each round of a rematch counting once.
+    pub completed_games: u64,
+    /// Moves applied, the server's bot included.
+    pub moves_processed: u64,
+    pub average_game_moves: f64,
+    /// Time spent thinking over the moves of a completed game.
+    pub average_game_seconds: f64,
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        ServerStats {
+            started_at: Instant::now(),
+            moves: AtomicU64::new(0),
+            completed_games: AtomicU64::new(0),
+            completed_moves: AtomicU64::new(0),
+            completed_ms: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ServerStats {
+    pub fn record_move(&self) {
+        self.moves.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn record_finished(&self, game: &Game) {
+        let think_ms: u64 = game.moves.iter().map(|m| m.think_ms).sum();
+        self.completed_games.fetch_add(1, Ordering::Relaxed);
+        self.completed_moves
+            .fetch_add(game.moves.len() as u64, Ordering::Relaxed);
+        self.completed_ms.fetch_add(think_ms, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self, active_games: usize, connected_clients: usize) -> StatsSnapshot {
+        let completed_games = self.completed_games.load(Ordering::Relaxed);
+        let per_game = |total: &AtomicU64| {
+            total.load(Ordering::Relaxed) as f64 / completed_games.max(1) as f64
+        };
+        StatsSnapshot {
+            uptime_seconds: self.started_at.elapsed().as_secs(),
+            active_games,
+            connected_clients,
+            completed_games,
+            moves_processed: self.moves.load(Ordering::Relaxed),
+            average_game_moves: per_game(&self.completed_moves),
+            average_game_seconds: per_game(&self.completed_ms) / 1000.0,
+        }
+    }
+}
+
+/// `GET /stats`: game, connection and move totals since the server started.
+pub async fn stats_handler(State(state): State<Arc<AppState>>) -> Json<StatsSnapshot> {
+    let active_games = state.games.read().await.len();
+    Json(
+        state
+            .stats
+            .snapshot(active_games, state.connections.open_connections()),
+    )
+}
@@ -0,0 +1,48 @@
+//! Optional OTLP export of the server's tracing spans, for latency breakdowns in Jaeger,
+//! Tempo or any other OpenTelemetry backend. Only built with the `otel` feature.
+
+use opentelemetry::trace::TracerProvider;
+use opentelemetry_otlp::{SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    trace::{SdkTracer, SdkTracerProvider},
+    Resource,
+};
+use std::io;
+use tracing::Subscriber;
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::registry::LookupSpan;
+
+const SERVICE_NAME: &str = "tic_tac_toe_server";
+
+/// Sends the spans still buffered when dropped, so the last ones before shutdown aren't lost.
+pub struct TelemetryGuard(SdkTracerProvider);
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if let Err(e) = self.0.shutdown() {
+            eprintln!("Failed to flush traces: {e}");
+        }
+    }
+}
+
+/// A layer exporting spans over OTLP/HTTP to the collector at `endpoint`, e.g.
+/// `http://localhost:4318`.
+pub fn layer<S>(endpoint: &str) -> io::Result<(OpenTelemetryLayer<S, SdkTracer>, TelemetryGuard)>
+where
+    S: Subscriber + for<'span> LookupSpan<'span>,
+{
+    let exporter = SpanExporter::builder()
+        .with_http()
+        .with_endpoint(format!("{}/v1/traces", endpoint.trim_end_matches('/')))
+        .build()
+        .map_err(io::Error::other)?;
+    let provider = SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_resource(Resource::builder().with_service_name(SERVICE_NAME).build())
+        .build();
+    let tracer = provider.tracer(SERVICE_NAME);
+    Ok((
+        tracing_opentelemetry::layer().with_tracer(tracer),
+        TelemetryGuard(provider),
+    ))
+}
@@ -0,0 +1,29 @@
+use anyhow::{Context, Result};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer};
+use std::path::Path;
+use std::sync::Arc;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::TlsAcceptor;
+
+/// Builds the acceptor for `wss://` from a PEM certificate chain (leaf first, as issued
+/// by e.g. Let's Encrypt's `fullchain.pem`) and its PEM private key.
+pub fn load_acceptor(cert_file: &Path, key_file: &Path) -> Result<TlsAcceptor> {
+    let certs = CertificateDer::pem_file_iter(cert_file)
+        .with_context(|| format!("reading TLS certificate {cert_file:?}"))?
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("parsing TLS certificate {cert_file:?}"))?;
+    let key = PrivateKeyDer::from_pem_file(key_file)
+        .with_context(|| format!("reading TLS key {key_file:?}"))?;
+
+    let provider = Arc::new(tokio_rustls::rustls::crypto::ring::default_provider());
+    let mut config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS certificate and key don't match")?;
+    // WebSocket upgrades need HTTP/1.1
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
@@ -0,0 +1,829 @@
+use crate::admin::authorize;
+use crate::app_state::AppState;
+use crate::game::handlers::parse_locale;
+use crate::game::ids::generate_game_id;
+use crate::game::message::GameOptions;
+use crate::game::models::Player;
+use crate::game::rules::GameConfig;
+
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+use tracing::info;
+
+/// Half-points for a win or bye and for a draw, so standings never need floats.
+const WIN_HALF_POINTS: u32 = 2;
+const DRAW_HALF_POINTS: u32 = 1;
+/// Largest field a tournament can start with.
+const MAX_PLAYERS: usize = 256;
+/// Pairings tried in search of a round without rematches before settling for one with.
+const MAX_PAIRING_STEPS: usize = 10_000;
+
+/// How a tournament pairs its rounds and decides its winner.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TournamentFormat {
+    /// A fixed number of rounds, each pairing players on the same score who haven't met.
+    #[default]
+    Swiss,
+    /// A first loss drops a player to the losers' bracket and a second knocks them out,
+    /// until one player is left.
+    DoubleElimination,
+}
+
+/// Ranks players who are level on points, tried in the order the tournament lists them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TieBreak {
+    /// Sum of the opponents' points.
+    Buchholz,
+    /// Points of the opponents beaten, plus half those of the opponents drawn with.
+    SonnebornBerger,
+    /// Games won over the board, byes not counted.
+    Wins,
+}
+
+impl TieBreak {
+    fn value(self, standing: &Standing) -> f32 {
+        match self {
+            TieBreak::Buchholz => standing.buchholz,
+            TieBreak::SonnebornBerger => standing.sonneborn_berger,
+            TieBreak::Wins => standing.wins as f32,
+        }
+    }
+}
+
+fn default_tie_breaks() -> Vec<TieBreak> {
+    vec![TieBreak::Buchholz]
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTournamentRequest {
+    pub name: String,
+    #[serde(default)]
+    pub format: TournamentFormat,
+    /// Swiss only; double elimination plays until one player is left.
+    #[serde(default)]
+    pub rounds: u32,
+    /// In seeding order, which breaks ties when pairing the first round.
+    pub players: Vec<String>,
+    #[serde(default = "default_tie_breaks")]
+    pub tie_breaks: Vec<TieBreak>,
+    /// Rules for every game of the tournament, as for a game created with JOIN_GAME.
+    #[serde(flatten)]
+    pub options: GameOptions,
+}
+
+/// One game of a round, or a bye when `o` is empty.
+#[derive(Debug, Clone, Serialize)]
+pub struct Pairing {
+    pub round: u32,
+    pub game_id: Option<String>,
+    pub x: String,
+    pub o: Option<String>,
+    pub finished: bool,
+    /// Who won; `None` once finished means a draw.
+    pub winner: Option<String>,
+    /// Double elimination only: the game was drawn, but the two had drawn before, so
+    /// `winner` is the better seed.
+    pub by_seed: bool,
+}
+
+impl Pairing {
+    fn half_points(&self, name: &str) -> u32 {
+        match &self.winner {
+            _ if !self.finished || !self.involves(name) => 0,
+            Some(winner) if winner == name => WIN_HALF_POINTS,
+            Some(_) => 0,
+            None => DRAW_HALF_POINTS,
+        }
+    }
+
+    fn opponent_of(&self, name: &str) -> Option<&str> {
+        match self.o.as_deref() {
+            Some(o) if self.x == name => Some(o),
+            Some(o) if o == name => Some(&self.x),
+            _ => None,
+        }
+    }
+
+    fn involves(&self, name: &str) -> bool {
+        self.x == name || self.o.as_deref() == Some(name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Standing {
+    pub name: String,
+    pub points: f32,
+    /// Sum of the opponents' points.
+    pub buchholz: f32,
+    /// Points of the opponents beaten, plus half those of the opponents drawn with.
+    pub sonneborn_berger: f32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    pub byes: u32,
+    /// Out of a double-elimination tournament after a second loss.
+    pub eliminated: bool,
+}
+
+/// A tournament in one of the `TournamentFormat`s. Either way each round pairs players
+/// ranked alike who haven't met yet, with a bye for the odd one out.
+#[derive(Debug, Clone, Serialize)]
+pub struct Tournament {
+    pub id: String,
+    pub name: String,
+    pub format: TournamentFormat,
+    /// Rounds a Swiss tournament lasts; `None` in double elimination.
+    pub rounds: Option<u32>,
+    pub players: Vec<String>,
+    pub tie_breaks: Vec<TieBreak>,
+    /// The round being played; `0` until the first one is paired.
+    pub round: u32,
+    pub finished: bool,
+    pub pairings: Vec<Pairing>,
+    #[serde(skip)]
+    pub config: GameConfig,
+    #[serde(skip)]
+    pub locale: String,
+}
+
+impl Tournament {
+    /// Everyone ranked by points, then the tie-breaks in order, then seed. In double
+    /// elimination players still in go first, those without a loss ahead of the rest.
+    pub fn standings(&self) -> Vec<Standing> {
+        let half_points: HashMap<&str, u32> = self
+            .players
+            .iter()
+            .map(|name| {
+                let total = self.pairings.iter().map(|p| p.half_points(name)).sum();
+                (name.as_str(), total)
+            })
+            .collect();
+
+        let mut standings: Vec<(usize, Standing)> = self
+            .players
+            .iter()
+            .enumerate()
+            .map(|(seed, name)| {
+                let played: Vec<&Pairing> = self
+                    .pairings
+                    .iter()
+                    .filter(|p| p.finished && p.involves(name))
+                    .collect();
+                let count = |half: u32, bye: bool| {
+                    played
+                        .iter()
+                        .filter(|p| p.o.is_none() == bye && p.half_points(name) == half)
+                        .count() as u32
+                };
+                let buchholz: u32 = played
+                    .iter()
+                    .filter_map(|p| p.opponent_of(name))
+                    .map(|opponent| half_points[opponent])
+                    .sum();
+                // Opponent's half-points times our own, so quarter-points
+                let sonneborn_berger: u32 = played
+                    .iter()
+                    .filter_map(|p| Some(half_points[p.opponent_of(name)?] * p.half_points(name)))
+                    .sum();
+                let losses = count(0, false);
+                let standing = Standing {
+                    name: name.clone(),
+                    points: half_points[name.as_str()] as f32 / 2.0,
+                    buchholz: buchholz as f32 / 2.0,
+                    sonneborn_berger: sonneborn_berger as f32 / 4.0,
+                    wins: count(WIN_HALF_POINTS, false),
+                    draws: count(DRAW_HALF_POINTS, false),
+                    losses,
+                    byes: count(WIN_HALF_POINTS, true),
+                    eliminated: self.format == TournamentFormat::DoubleElimination && losses >= 2,
+                };
+                (seed, standing)
+            })
+            .collect();
+
+        let elimination = self.format == TournamentFormat::DoubleElimination;
+        standings.sort_by(|(seed_a, a), (seed_b, b)| {
+            let bracket = if elimination {
+                a.eliminated
+                    .cmp(&b.eliminated)
+                    .then(a.losses.cmp(&b.losses))
+            } else {
+                Ordering::Equal
+            };
+            let tie_break = || {
+                self.tie_breaks
+                    .iter()
+                    .map(|tie_break| tie_break.value(b).total_cmp(&tie_break.value(a)))
+                    .find(|order| order.is_ne())
+                    .unwrap_or(Ordering::Equal)
+            };
+            bracket
+                .then(b.points.total_cmp(&a.points))
+                .then_with(tie_break)
+                .then(seed_a.cmp(seed_b))
+        });
+        standings
+            .into_iter()
+            .map(|(_, standing)| standing)
+            .collect()
+    }
+
+    fn have_met(&self, a: &str, b: &str) -> bool {
+        self.pairings.iter().any(|p| p.opponent_of(a) == Some(b))
+    }
+
+    /// Whether the two players of `game` already drew a finished game against each other.
+    fn have_drawn(&self, game: &Pairing) -> bool {
+        let Some(o) = game.o.as_deref() else {
+            return false;
+        };
+        self.pairings
+            .iter()
+            .any(|p| p.finished && p.winner.is_none() && p.opponent_of(&game.x) == Some(o))
+    }
+
+    fn better_seed<'a>(&self, a: &'a str, b: &'a str) -> &'a str {
+        let seed = |name: &str| self.players.iter().position(|p| p == name);
+        if seed(b) < seed(a) {
+            b
+        } else {
+            a
+        }
+    }
+
+    fn games_as_x(&self, name: &str) -> usize {
+        self.pairings
+            .iter()
+            .filter(|p| p.o.is_some() && p.x == name)
+            .count()
+    }
+
+    /// Pairs the next round, or marks the tournament finished after the last one.
+    /// Returns the new pairings that need a game.
+    pub fn pair_next_round(&mut self) -> Vec<Pairing> {
+        let standings = self.standings();
+        let brackets = match self.format {
+            TournamentFormat::Swiss => {
+                if self.round >= self.rounds.unwrap_or(0) {
+                    self.finished = true;
+                    return Vec::new();
+                }
+                vec![standings.into_iter().map(|s| s.name).collect()]
+            }
+            TournamentFormat::DoubleElimination => {
+                let (winners, losers): (Vec<Standing>, Vec<Standing>) = standings
+                    .into_iter()
+                    .filter(|s| !s.eliminated)
+                    .partition(|s| s.losses == 0);
+                let winners: Vec<String> = winners.into_iter().map(|s| s.name).collect();
+                let losers: Vec<String> = losers.into_iter().map(|s| s.name).collect();
+                match (winners.len(), losers.len()) {
+                    (0, 0..=1) | (1, 0) => {
+                        self.finished = true;
+                        return Vec::new();
+                    }
+                    // The grand final; if the losers' bracket wins it, both are on one
+                    // loss and play again
+                    (1, 1) => vec![[winners, losers].concat()],
+                    _ => vec![winners, losers],
+                }
+            }
+        };
+        self.round += 1;
+
+        let mut paired = Vec::new();
+        for ranked in brackets {
+            self.pair_bracket(ranked, &mut paired);
+        }
+        paired
+    }
+
+    /// Pairs players of one bracket, ranked best first, adding the games to `paired`.
+    fn pair_bracket(&mut self, mut ranked: Vec<String>, paired: &mut Vec<Pairing>) {
+        // The lowest-ranked player who hasn't had one sits out with a bye
+        if ranked.len() % 2 == 1 {
+            let had_bye =
+                |name: &String| self.pairings.iter().any(|p| p.o.is_none() && p.x == *name);
+            let index = ranked
+                .iter()
+                .rposition(|name| !had_bye(name))
+                .unwrap_or(ranked.len() - 1);
+            let name = ranked.remove(index);
+            self.pairings.push(Pairing {
+                round: self.round,
+                game_id: None,
+                x: name.clone(),
+                o: None,
+                finished: true,
+                winner: Some(name),
+                by_seed: false,
+            });
+        }
+
+        // Rematches are only allowed once there is no way round them, or none was found
+        // in time. Allowing them, the first partner tried always works.
+        let mut steps = MAX_PAIRING_STEPS;
+        let pairs = pair_off(&ranked, &|a, b| self.have_met(a, b), &mut steps)
+            .or_else(|| pair_off(&ranked, &|_, _| false, &mut ranked.len()))
+            .unwrap_or_default();
+
+        for (a, b) in pairs {
+            // Whoever has had X less often gets it, the higher ranked on a tie
+            let (x, o) = if self.games_as_x(&b) < self.games_as_x(&a) {
+                (b, a)
+            } else {
+                (a, b)
+            };
+            let table = paired.len() + 1;
+            let pairing = Pairing {
+                round: self.round,
+                game_id: Some(format!("{}-r{}-t{}", self.id, self.round, table)),
+                x,
+                o: Some(o),
+                finished: false,
+                winner: None,
+                by_seed: false,
+            };
+            paired.push(pairing.clone());
+            self.pairings.push(pairing);
+        }
+    }
+
+    /// Records the first result of one of this tournament's games; later rounds played in
+    /// the same game don't count. Returns true once every game of the round is finished.
+    ///
+    /// Nobody is knocked out by a draw, so a drawn elimination game is paired again. If
+    /// the same two players draw a second time, the better seed goes through.
+    pub fn record(&mut self, game_id: &str, winner: Option<Player>) -> bool {
+        let Some(index) = self
+            .pairings
+            .iter()
+            .position(|p| !p.finished && p.game_id.as_deref() == Some(game_id))
+        else {
+            return false;
+        };
+        let pairing = &self.pairings[index];
+        let winner = match winner {
+            Some(Player::X) => Some(pairing.x.clone()),
+            Some(Player::O) => pairing.o.clone(),
+            None => None,
+        };
+        let by_seed = winner.is_none()
+            && self.format == TournamentFormat::DoubleElimination
+            && self.have_drawn(pairing);
+        let winner = if by_seed {
+            let o = pairing.o.as_deref().unwrap_or_default();
+            Some(self.better_seed(&pairing.x, o).to_string())
+        } else {
+            winner
+        };
+
+        let pairing = &mut self.pairings[index];
+        pairing.finished = true;
+        pairing.winner = winner;
+        pairing.by_seed = by_seed;
+
+        let round = self.round;
+        self.pairings
+            .iter()
+            .filter(|p| p.round == round)
+            .all(|p| p.finished)
+    }
+}
+
+/// Pairs the players in rank order, each with the highest-ranked player left that `met`
+/// allows, backtracking when that leaves someone without an opponent. Gives up once it
+/// has tried `steps` pairings.
+fn pair_off(
+    players: &[String],
+    met: &dyn Fn(&str, &str) -> bool,
+    steps: &mut usize,
+) -> Option<Vec<(String, String)>> {
+    let Some((first, rest)) = players.split_first() else {
+        return Some(Vec::new());
+    };
+
+    for (i, partner) in rest.iter().enumerate() {
+        if met(first, partner) {
+            continue;
+        }
+        if *steps == 0 {
+            return None;
+        }
+        *steps -= 1;
+        let mut remaining = rest.to_vec();
+        remaining.remove(i);
+        if let Some(mut pairs) = pair_off(&remaining, met, steps) {
+            pairs.insert(0, (first.clone(), partner.clone()));
+            return Some(pairs);
+        }
+    }
+    None
+}
+
+#[derive(Default)]
+pub struct TournamentStore {
+    tournaments: HashMap<String, Tournament>,
+}
+
+impl TournamentStore {
+    pub fn get(&self, id: &str) -> Option<&Tournament> {
+        self.tournaments.get(id)
+    }
+
+    pub fn get_mut(&mut self, id: &str) -> Option<&mut Tournament> {
+        self.tournaments.get_mut(id)
+    }
+
+    /// Adds `tournament` under a fresh ID and returns that ID.
+    pub fn create(&mut self, mut tournament: Tournament) -> String {
+        let id = generate_game_id(&self.tournaments);
+        tournament.id = id.clone();
+        self.tournaments.insert(id.clone(), tournament);
+        id
+    }
+}
+
+/// The tournament's standings and every pairing so far, so players can find their next game.
+fn tournament_json(tournament: &Tournament) -> serde_json::Value {
+    json!({
+        "id": tournament.id,
+        "name": tournament.name,
+        "format": tournament.format,
+        "rounds": tournament.rounds,
+        "tie_breaks": tournament.tie_breaks,
+        "round": tournament.round,
+        "finished": tournament.finished,
+        "standings": tournament.standings(),
+        "pairings": tournament.pairings
+    })
+}
+
+/// Starts a tournament between the given players and pairs its first round.
+pub async fn create_tournament_handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<CreateTournamentRequest>,
+) -> Response {
+    if let Err(status) = authorize(&state, &headers) {
+        return status.into_response();
+    }
+
+    let mut players: Vec<String> = Vec::new();
+    for name in request.players.iter().map(|name| name.trim()) {
+        if !name.is_empty() && !players.iter().any(|p| p == name) {
+            players.push(name.to_string());
+        }
+    }
+    if players.len() < 2 {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Needs at least two players",
+        )
+            .into_response();
+    }
+    if players.len() > MAX_PLAYERS {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            format!("At most {MAX_PLAYERS} players can take part"),
+        )
+            .into_response();
+    }
+    let rounds = match request.format {
+        TournamentFormat::Swiss => Some(request.rounds),
+        TournamentFormat::DoubleElimination => None,
+    };
+    if rounds.is_some_and(|rounds| rounds == 0 || rounds as usize >= players.len()) {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Rounds must be at least 1 and fewer than the number of players",
+        )
+            .into_response();
+    }
+    let options = &request.options;
+    if options.team || options.correspondence || options.winner_stays_on {
+        return (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Tournament games are live, one player per seat",
+        )
+            .into_response();
+    }
+    let (mut config, _) = match options.config(state.tunables().turn_seconds) {
+        Ok(config) => config,
+        Err(reason) => {
+            return (StatusCode::UNPROCESSABLE_ENTITY, reason.to_string()).into_response()
+        }
+    };
+    // A round waits for its slowest game, so none of them may be cleaned up meanwhile
+    config.idle_timeout_minutes = Some(0);
+
+    let name = request.name.trim().to_string();
+    let id = state.tournaments.write().await.create(Tournament {
+        id: String::new(),
+        name: name.clone(),
+        format: request.format,
+        rounds,
+        players,
+        tie_breaks: request.tie_breaks,
+        round: 0,
+        finished: false,
+        pairings: Vec::new(),
+        config,
+        locale: parse_locale(options.locale.as_deref()),
+    });
+    info!(
+        "🏅 Created {:?} tournament {} ({})",
+        request.format, id, name
+    );
+    state.start_tournament_round(&id).await;
+
+    let tournaments = state.tournaments.read().await;
+    let tournament = tournaments.get(&id).expect("created above");
+    (StatusCode::CREATED, Json(tournament_json(tournament))).into_response()
+}
+
+pub async fn tournament_handler(
+    State(state): State<Arc<AppState>>,
+    Path(tournament_id): Path<String>,
+) -> Result<Json<serde_json::Value>, StatusCode> {
+    let tournaments = state.tournaments.read().await;
+    let tournament = tournaments
+        .get(&tournament_id)
+        .ok_or(StatusCode::NOT_FOUND)?;
+    Ok(Json(tournament_json(tournament)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tournament(format: TournamentFormat, rounds: Option<u32>, players: &[&str]) -> Tournament {
+        Tournament {
+            id: "t".to_string(),
+            name: "Test".to_string(),
+            format,
+            rounds,
+            players: players.iter().map(|name| name.to_string()).collect(),
+            tie_breaks: default_tie_breaks(),
+            round: 0,
+            finished: false,
+            pairings: Vec::new(),
+            config: GameConfig::default(),
+            locale: "en".to_string(),
+        }
+    }
+
+    /// Pairs the next round and plays every game of it, `winner` picking each result.
+    fn play_round(tournament: &mut Tournament, winner: impl Fn(&Pairing) -> Option<Player>) {
+        for pairing in tournament.pair_next_round() {
+            let game_id = pairing.game_id.clone().expect("games have an ID");
+            tournament.record(&game_id, winner(&pairing));
+        }
+    }
+
+    fn game(round: u32, x: &str, o: &str, winner: Option<&str>) -> Pairing {
+        Pairing {
+            round,
+            game_id: Some(format!("{x}-{o}")),
+            x: x.to_string(),
+            o: Some(o.to_string()),
+            finished: true,
+            winner: winner.map(str::to_string),
+            by_seed: false,
+        }
+    }
+
+    #[test]
+    fn nobody_meets_the_same_opponent_twice_while_avoidable() {
+        let mut tournament = tournament(TournamentFormat::Swiss, Some(3), &["a", "b", "c", "d"]);
+        for _ in 0..3 {
+            play_round(&mut tournament, |_| Some(Player::X));
+        }
+
+        let mut met: Vec<(String, String)> = tournament
+            .pairings
+            .iter()
+            .filter_map(|p| {
+                let mut pair = [p.x.clone(), p.o.clone()?];
+                pair.sort();
+                Some((pair[0].clone(), pair[1].clone()))
+            })
+            .collect();
+        assert_eq!(met.len(), 6);
+        met.sort();
+        met.dedup();
+        assert_eq!(
+            met.len(),
+            6,
+            "a pair met twice in a round robin's worth of rounds"
+        );
+    }
+
+    #[test]
+    fn rematches_are_allowed_once_unavoidable() {
+        let mut tournament = tournament(TournamentFormat::Swiss, Some(2), &["a", "b"]);
+        play_round(&mut tournament, |_| Some(Player::X));
+        let second = tournament.pair_next_round();
+        assert_eq!(second.len(), 1);
+        assert!(second[0].involves("a") && second[0].involves("b"));
+    }
+
+    #[test]
+    fn pairing_gives_up_on_avoiding_rematches_in_bounded_time() {
+        // Three players who have met everyone but each other, ranked last behind an odd
+        // number of newcomers: no round avoids a rematch, and proving it means trying
+        // every way of pairing the newcomers
+        let names: Vec<String> = (0..40).map(|i| format!("p{i:02}")).collect();
+        let triangle = |name: &str| name >= "p37";
+        let met = |a: &str, b: &str| triangle(a) != triangle(b);
+
+        let mut steps = MAX_PAIRING_STEPS;
+        assert_eq!(pair_off(&names, &met, &mut steps), None);
+        assert_eq!(steps, 0, "the search stopped on its budget");
+
+        let players: Vec<&str> = names.iter().map(String::as_str).collect();
+        let mut tournament = tournament(TournamentFormat::Swiss, Some(39), &players);
+        tournament.round = 1;
+        for (table, newcomer) in names[..37].iter().enumerate() {
+            for other in &names[37..] {
+                tournament.pairings.push(Pairing {
+                    game_id: Some(format!("{newcomer}-{other}-{table}")),
+                    ..game(1, newcomer, other, None)
+                });
+            }
+        }
+        let round = tournament.pair_next_round();
+        assert_eq!(round.len(), 20, "everyone still gets a game");
+    }
+
+    #[test]
+    fn byes_go_to_the_lowest_ranked_player_without_one() {
+        let mut tournament = tournament(TournamentFormat::Swiss, Some(3), &["a", "b", "c"]);
+        let mut byes = Vec::new();
+        for _ in 0..3 {
+            play_round(&mut tournament, |_| Some(Player::X));
+            let round = tournament.round;
+            let bye = tournament
+                .pairings
+                .iter()
+                .find(|p| p.round == round && p.o.is_none())
+                .expect("an odd field needs a bye every round");
+            assert_eq!(bye.winner.as_ref(), Some(&bye.x), "a bye scores a win");
+            byes.push(bye.x.clone());
+        }
+
+        // The first round is paired by seed, so the bottom seed sits out first
+        assert_eq!(byes[0], "c");
+        byes.sort();
+        assert_eq!(byes, ["a", "b", "c"], "nobody has a second bye");
+        assert!(tournament.standings().iter().all(|s| s.byes == 1));
+    }
+
+    #[test]
+    fn standings_rank_by_points_then_buchholz_then_seed() {
+        let mut tournament = tournament(TournamentFormat::Swiss, Some(2), &["a", "b", "c", "d"]);
+        // "c" and "d" end on 1 point each, but "c" drew with the leader
+        tournament.pairings = vec![
+            game(1, "a", "b", Some("a")),
+            game(1, "c", "d", None),
+            game(2, "a", "c", None),
+            game(2, "d", "b", None),
+        ];
+        let standings = tournament.standings();
+        let names: Vec<&str> = standings.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["a", "c", "d", "b"]);
+        assert_eq!(standings[0].points, 1.5);
+        assert_eq!(standings[1].points, 1.0);
+        assert_eq!(standings[2].points, 1.0);
+        assert!(standings[1].buchholz > standings[2].buchholz);
+
+        // Level on points and Buchholz, the better seed goes first
+        tournament.pairings = vec![game(1, "a", "b", None), game(1, "c", "d", None)];
+        let names: Vec<String> = tournament.standings().into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["a", "b", "c", "d"]);
+    }
+
+    #[test]
+    fn double_elimination_plays_until_one_player_is_left() {
+        let players = ["a", "b", "c", "d", "e"];
+        let mut tournament = tournament(TournamentFormat::DoubleElimination, None, &players);
+        // The better seed always wins, so "a" never loses
+        let seed = |name: &str| players.iter().position(|p| *p == name).unwrap();
+        while !tournament.finished {
+            assert!(tournament.round < 20, "tournament never finished");
+            play_round(&mut tournament, |p| {
+                if seed(&p.x) < seed(p.o.as_deref().unwrap()) {
+                    Some(Player::X)
+                } else {
+                    Some(Player::O)
+                }
+            });
+        }
+
+        let standings = tournament.standings();
+        assert_eq!(standings[0].name, "a");
+        assert_eq!(standings[0].losses, 0);
+        assert!(standings[1..].iter().all(|s| s.eliminated && s.losses == 2));
+    }
+
+    #[test]
+    fn double_elimination_finishes_when_every_game_is_drawn() {
+        let players = ["a", "b", "c", "d", "e"];
+        let mut tournament = tournament(TournamentFormat::DoubleElimination, None, &players);
+        while !tournament.finished {
+            assert!(
+                tournament.round < 50,
+                "drawn games kept the bracket going forever"
+            );
+            play_round(&mut tournament, |_| None);
+        }
+
+        let standings = tournament.standings();
+        assert_eq!(standings[0].name, "a", "the top seed wins every tie-break");
+        assert!(standings[1..].iter().all(|s| s.eliminated));
+        assert!(tournament
+            .pairings
+            .iter()
+            .filter(|p| p.by_seed)
+            .all(|p| p.winner.is_some()));
+    }
+
+    #[test]
+    fn a_second_draw_between_the_same_players_goes_to_the_better_seed() {
+        let mut tournament = tournament(TournamentFormat::DoubleElimination, None, &["a", "b"]);
+        play_round(&mut tournament, |_| None);
+        assert!(tournament.standings().iter().all(|s| s.losses == 0));
+        assert!(!tournament.finished);
+
+        // The replay is drawn too, so "a" goes through to the grand final
+        let replay = tournament.pair_next_round();
+        assert_eq!(replay.len(), 1);
+        let game_id = replay[0].game_id.clone().unwrap();
+        tournament.record(&game_id, None);
+        let decided = tournament.pairings.last().unwrap();
+        assert!(decided.by_seed);
+        assert_eq!(decided.winner.as_deref(), Some("a"));
+
+        let standings = tournament.standings();
+        assert_eq!(standings[0].name, "a");
+        assert_eq!(standings[1].losses, 1);
+        assert_eq!(standings[1].draws, 1);
+    }
+
+    #[test]
+    fn double_elimination_pairs_brackets_apart() {
+        let mut tournament = tournament(
+            TournamentFormat::DoubleElimination,
+            None,
+            &["a", "b", "c", "d"],
+        );
+        play_round(&mut tournament, |_| Some(Player::X));
+        let losses: HashMap<String, u32> = tournament
+            .standings()
+            .into_iter()
+            .map(|s| (s.name, s.losses))
+            .collect();
+
+        for pairing in tournament.pair_next_round() {
+            let o = pairing.o.expect("four players need no bye");
+            assert_eq!(losses[&pairing.x], losses[&o]);
+        }
+    }
+
+    #[test]
+    fn standings_use_the_tie_breaks_in_order() {
+        let mut tournament = tournament(TournamentFormat::Swiss, Some(2), &["a", "b", "c", "d"]);
+        // "b" and "c" both end on 1 point with the same Buchholz: "b" won a game, while
+        // "c" drew against stronger opposition
+        tournament.pairings = vec![
+            game(1, "a", "b", Some("a")),
+            game(1, "c", "d", None),
+            game(2, "a", "c", None),
+            game(2, "b", "d", Some("b")),
+        ];
+        let ranking = |tournament: &Tournament| -> Vec<String> {
+            tournament.standings().into_iter().map(|s| s.name).collect()
+        };
+
+        tournament.tie_breaks = vec![TieBreak::Buchholz];
+        assert_eq!(ranking(&tournament), ["a", "b", "c", "d"]);
+
+        tournament.tie_breaks = vec![TieBreak::Buchholz, TieBreak::SonnebornBerger];
+        assert_eq!(ranking(&tournament), ["a", "c", "b", "d"]);
+
+        tournament.tie_breaks = vec![TieBreak::Wins, TieBreak::SonnebornBerger];
+        assert_eq!(ranking(&tournament), ["a", "b", "c", "d"]);
+
+        let standings = tournament.standings();
+        assert_eq!(standings[1].buchholz, standings[2].buchholz);
+        assert_eq!(standings[1].sonneborn_berger, 0.5);
+        assert_eq!(standings[2].sonneborn_berger, 1.0);
+    }
+}
@@ -0,0 +1,43 @@
+use crate::app_state::AppState;
+use crate::game::message::UpdateReason;
+
+use serde_json::json;
+use std::{sync::Arc, time::Duration};
+use tracing::info;
+
+pub async fn enforce_turn_timers(app_state: Arc<AppState>) {
+    loop {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let mut games = app_state.games.write().await;
+
+        for (game_id, game) in games.iter_mut() {
+            // Pauses run out on their own, and don't outlive the game
+            let pause_over = game.pause_remaining() == Some(Duration::ZERO) || game.game_over;
+            if pause_over && game.resume() {
+                info!("▶️ Game {} resumed", game_id);
+                app_state.broadcast_state(game_id, game, UpdateReason::Resume);
+            }
+
+            if game.forfeit_on_timeout() {
+                info!(
+                    "⏰ Turn timer expired in game {}. {:?} wins.",
+                    game_id, game.winner
+                );
+                app_state.finish_game(game_id, game).await;
+                app_state.broadcast_state(game_id, game, UpdateReason::Timeout);
+
+                if let Some(winner) = game.winner {
+                    let loser = winner.opponent();
+                    let key = if game.clock_remaining(loser) == Some(Duration::ZERO) {
+                        "flag_fell"
+                    } else {
+                        "turn_timeout"
+                    };
+                    let name = game.name_of(loser);
+                    app_state.broadcast_system(game_id, game, key, json!({ "name": name }));
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use axum::Json;
+use serde::Serialize;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Short commit hash, or `unknown` when built outside a git checkout.
+pub const GIT_HASH: &str = env!("GIT_HASH");
+const BUILD_TIME: &str = env!("BUILD_TIME");
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    pub git_hash: &'static str,
+    /// Unix seconds.
+    pub built_at: u64,
+}
+
+pub fn build_info() -> BuildInfo {
+    BuildInfo {
+        version: VERSION,
+        git_hash: GIT_HASH,
+        built_at: BUILD_TIME.parse().unwrap_or(0),
+    }
+}
+
+/// One-line summary printed by `--version`.
+pub fn describe() -> String {
+    let info = build_info();
+    format!(
+        "tic_tac_toe_server {} ({}, built at {})",
+        info.version, info.git_hash, info.built_at
+    )
+}
+
+pub async fn version_handler() -> Json<BuildInfo> {
+    Json(build_info())
+}
+
+/// Open sockets by the client build they reported in HELLO, so an operator can see how
+/// many players are still on an old client after a deploy.
+#[derive(Default)]
+pub struct ClientVersions {
+    connected: HashMap<String, usize>,
+    skewed_connections: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClientVersionStats {
+    pub server: BuildInfo,
+    pub connected: HashMap<String, usize>,
+    /// Connections made so far by a client that wasn't built from the server's commit.
+    pub skewed_connections: u64,
+}
+
+impl ClientVersions {
+    /// Counts a socket under `version (git_hash)` and returns that label.
+    pub fn connect(&mut self, version: &str, git_hash: &str) -> String {
+        let label = format!("{version} ({git_hash})");
+        *self.connected.entry(label.clone()).or_insert(0) += 1;
+
+        if version != VERSION || git_hash != GIT_HASH {
+            self.skewed_connections += 1;
+            warn!(
+                "🧬 Client {} differs from server {} ({})",
+                label, VERSION, GIT_HASH
+            );
+        } else {
+            info!("🧬 Client {} matches the server build", label);
+        }
+        label
+    }
+
+    pub fn disconnect(&mut self, label: &str) {
+        if let Some(count) = self.connected.get_mut(label) {
+            *count -= 1;
+            if *count == 0 {
+                self.connected.remove(label);
+            }
+        }
+    }
+
+    pub fn stats(&self) -> ClientVersionStats {
+        ClientVersionStats {
+            server: build_info(),
+            connected: self.connected.clone(),
+            skewed_connections: self.skewed_connections,
+        }
+    }
+}
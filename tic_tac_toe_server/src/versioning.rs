@@ -0,0 +1,57 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Rewrites a stored payload from one format version into the next.
+pub type Migration = fn(&mut Value);
+
+/// What the persisted JSON files hold on disk: the payload plus the format version it was
+/// written in. Files from before versioning are a bare payload and count as version 1.
+#[derive(Serialize)]
+pub struct Versioned<'a, T> {
+    pub version: usize,
+    pub data: &'a T,
+}
+
+/// Wraps `data` for saving, stamped with the newest version `migrations` knows about.
+pub fn versioned<'a, T: Serialize>(data: &'a T, migrations: &[Migration]) -> Versioned<'a, T> {
+    Versioned {
+        version: current_version(migrations),
+        data,
+    }
+}
+
+/// Reads a saved file, running `migrations[i]` on version `i + 1` payloads until they are
+/// current. Files written by a newer server are refused rather than half-understood.
+pub fn decode<T: DeserializeOwned>(contents: &str, migrations: &[Migration]) -> Result<T, String> {
+    let value: Value = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+
+    let (version, mut data) = match value {
+        Value::Object(mut map) if is_envelope(&map) => {
+            let version = map["version"].as_u64().unwrap_or(1) as usize;
+            (version, map.remove("data").unwrap_or(Value::Null))
+        }
+        legacy => (1, legacy),
+    };
+
+    let current = current_version(migrations);
+    if version > current {
+        return Err(format!(
+            "written in format version {version}, but this server only reads up to {current}"
+        ));
+    }
+
+    for migrate in &migrations[version.max(1) - 1..] {
+        migrate(&mut data);
+    }
+
+    serde_json::from_value(data).map_err(|e| e.to_string())
+}
+
+fn current_version(migrations: &[Migration]) -> usize {
+    migrations.len() + 1
+}
+
+fn is_envelope(map: &serde_json::Map<String, Value>) -> bool {
+    map.len() == 2 && map.get("version").is_some_and(Value::is_u64) && map.contains_key("data")
+}
@@ -0,0 +1,115 @@
+use crate::clock::now_secs;
+use crate::game::models::{Game, Player};
+use crate::game::rules::GameConfig;
+
+use serde::Serialize;
+use std::{collections::HashMap, time::Duration};
+use tracing::warn;
+
+/// Slow receivers get cut off rather than piling up requests.
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What gets posted, tagged by its `event` field.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum WebhookEvent {
+    GameCreated {
+        game_id: String,
+        config: GameConfig,
+    },
+    PlayerJoined {
+        game_id: String,
+        player: Player,
+        name: String,
+    },
+    /// A round ended; games that carry on to a rematch send one of these per round.
+    GameFinished {
+        game_id: String,
+        round: u32,
+        winner: Option<Player>,
+        draw: bool,
+        names: HashMap<Player, String>,
+        scores: HashMap<Player, u32>,
+    },
+}
+
+impl WebhookEvent {
+    pub fn game_created(game_id: &str, game: &Game) -> Self {
+        WebhookEvent::GameCreated {
+            game_id: game_id.to_string(),
+            config: game.config.clone(),
+        }
+    }
+
+    pub fn game_finished(game_id: &str, game: &Game) -> Self {
+        WebhookEvent::GameFinished {
+            game_id: game_id.to_string(),
+            round: game.round,
+            winner: game.winner,
+            draw: game.draw,
+            names: game.player_names.clone(),
+            scores: game.scores.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Delivery<'a> {
+    #[serde(flatten)]
+    event: &'a WebhookEvent,
+    /// Seconds since the Unix epoch.
+    timestamp: u64,
+}
+
+/// Operator-configured endpoints (`WEBHOOK_URLS`) that hear about games being created,
+/// joined and finished, e.g. for a Discord bot or a classroom dashboard.
+pub struct Webhooks {
+    urls: Vec<String>,
+    client: reqwest::Client,
+}
+
+impl Webhooks {
+    pub fn new(urls: Vec<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        Webhooks { urls, client }
+    }
+
+    /// Posts `event` to every URL in the background, so handlers never wait on a receiver.
+    /// Failed deliveries are logged and dropped.
+    pub fn emit(&self, event: WebhookEvent) {
+        if self.urls.is_empty() {
+            return;
+        }
+
+        let payload = match serde_json::to_value(Delivery {
+            event: &event,
+            timestamp: now_secs(),
+        }) {
+            Ok(payload) => payload,
+            Err(e) => {
+                warn!("⚠️ Failed to serialize webhook event: {}", e);
+                return;
+            }
+        };
+
+        for url in &self.urls {
+            let client = self.client.clone();
+            let url = url.clone();
+            let payload = payload.clone();
+            tokio::spawn(async move {
+                let result = client
+                    .post(&url)
+                    .json(&payload)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status());
+                if let Err(e) = result {
+                    warn!("⚠️ Webhook delivery to {} failed: {}", url, e);
+                }
+            });
+        }
+    }
+}
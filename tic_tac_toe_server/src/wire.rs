@@ -0,0 +1,121 @@
+use anyhow::Result;
+use axum::extract::ws::{Message, WebSocket};
+use serde::de::DeserializeOwned;
+use tracing::info;
+
+/// Subprotocol a client offers to get MessagePack frames instead of JSON text.
+pub const MSGPACK_PROTOCOL: &str = "ttt.msgpack";
+pub const JSON_PROTOCOL: &str = "ttt.json";
+
+/// How frames are encoded on a connection. Clients that don't negotiate get JSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Json,
+    MsgPack,
+}
+
+/// A client connection that writes frames in whichever encoding the client negotiated.
+pub struct ClientSocket {
+    transport: Transport,
+    encoding: Encoding,
+    /// ERROR and MOVE_FAILED replies sent so far, to tell which requests failed.
+    errors_sent: u64,
+}
+
+enum Transport {
+    WebSocket(Box<WebSocket>),
+    /// Handlers' replies passed on as they are, for a session on another protocol to
+    /// translate, e.g. a gRPC stream.
+    #[cfg(feature = "grpc")]
+    Relay(tokio::sync::mpsc::UnboundedSender<serde_json::Value>),
+}
+
+impl ClientSocket {
+    pub fn new(socket: WebSocket) -> Self {
+        let encoding = match socket.protocol().and_then(|p| p.to_str().ok()) {
+            Some(MSGPACK_PROTOCOL) => Encoding::MsgPack,
+            _ => Encoding::Json,
+        };
+
+        ClientSocket {
+            transport: Transport::WebSocket(Box::new(socket)),
+            encoding,
+            errors_sent: 0,
+        }
+    }
+
+    /// A socket whose outgoing messages arrive on the returned receiver instead of a
+    /// WebSocket. It never receives anything.
+    #[cfg(feature = "grpc")]
+    pub fn relay() -> (
+        Self,
+        tokio::sync::mpsc::UnboundedReceiver<serde_json::Value>,
+    ) {
+        let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+        let socket = ClientSocket {
+            transport: Transport::Relay(tx),
+            encoding: Encoding::Json,
+            errors_sent: 0,
+        };
+        (socket, rx)
+    }
+
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    pub async fn recv(&mut self) -> Option<Result<Message, axum::Error>> {
+        match &mut self.transport {
+            Transport::WebSocket(socket) => socket.recv().await,
+            #[cfg(feature = "grpc")]
+            Transport::Relay(_) => None,
+        }
+    }
+
+    pub async fn send(&mut self, message: Message) -> Result<()> {
+        match &mut self.transport {
+            Transport::WebSocket(socket) => socket.send(message).await?,
+            // Pings and closes mean nothing to the other protocol
+            #[cfg(feature = "grpc")]
+            Transport::Relay(_) => {}
+        }
+        Ok(())
+    }
+
+    pub fn errors_sent(&self) -> u64 {
+        self.errors_sent
+    }
+
+    pub async fn send_json(&mut self, value: &serde_json::Value) -> Result<()> {
+        if matches!(value["type"].as_str(), Some("ERROR" | "MOVE_FAILED")) {
+            self.errors_sent += 1;
+        }
+
+        #[cfg(feature = "grpc")]
+        if let Transport::Relay(tx) = &self.transport {
+            let _ = tx.send(value.clone());
+            return Ok(());
+        }
+
+        let message = match self.encoding {
+            Encoding::Json => Message::Text(value.to_string().into()),
+            Encoding::MsgPack => Message::Binary(rmp_serde::to_vec_named(value)?.into()),
+        };
+        self.send(message).await
+    }
+}
+
+/// Decodes a frame from either encoding; clients may mix them freely.
+pub fn decode<T: DeserializeOwned>(message: &Message) -> Option<Result<T, String>> {
+    match message {
+        Message::Text(text) => {
+            info!("📩 Received WebSocket message: {}", text);
+            Some(serde_json::from_str(text).map_err(|e| e.to_string()))
+        }
+        Message::Binary(bytes) => {
+            info!("📩 Received {} byte MessagePack message", bytes.len());
+            Some(rmp_serde::from_slice(bytes).map_err(|e| e.to_string()))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,135 @@
+use crate::clock::now_secs;
+
+use serde::Serialize;
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::Mutex,
+};
+use tracing::warn;
+
+/// Flagged names and messages kept for `/admin/word-flags`; older ones are dropped.
+const MAX_FLAGS: usize = 500;
+
+/// What happens to a name or chat line containing a blocked word.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FilterAction {
+    /// Refused with an error.
+    Reject,
+    /// Let through with the word starred out.
+    #[default]
+    Mask,
+    /// Let through unchanged, and listed for a moderator.
+    Flag,
+}
+
+impl FilterAction {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "reject" => Some(FilterAction::Reject),
+            "mask" => Some(FilterAction::Mask),
+            "flag" => Some(FilterAction::Flag),
+            _ => None,
+        }
+    }
+}
+
+/// How a name or chat line fared against the filter.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// Use the text as it was.
+    Allowed,
+    /// Use this instead.
+    Masked(String),
+    Rejected,
+}
+
+/// A name or chat line that got through with a blocked word in it.
+#[derive(Debug, Clone, Serialize)]
+pub struct WordFlag {
+    /// `name` or `chat`.
+    pub field: &'static str,
+    pub sender: String,
+    pub text: String,
+    /// Seconds since the Unix epoch
+    pub flagged_at: u64,
+}
+
+/// Matches whole words case-insensitively, so "class" doesn't trip over a word inside it.
+pub struct WordFilter {
+    words: HashSet<String>,
+    action: FilterAction,
+    flags: Mutex<VecDeque<WordFlag>>,
+}
+
+impl WordFilter {
+    pub fn new(words: &[String], action: FilterAction) -> Self {
+        WordFilter {
+            words: words
+                .iter()
+                .map(|word| word.trim().to_lowercase())
+                .collect(),
+            action,
+            flags: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Byte ranges of the blocked words in `text`.
+    fn matches(&self, text: &str) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        let mut start = None;
+        for (i, c) in text.char_indices().chain([(text.len(), ' ')]) {
+            match (c.is_alphanumeric(), start) {
+                (true, None) => start = Some(i),
+                (false, Some(from)) => {
+                    if self.words.contains(&text[from..i].to_lowercase()) {
+                        found.push((from, i));
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+        found
+    }
+
+    /// Checks `text` sent by `sender` as its `field`, flagging it if that is the action.
+    pub fn check(&self, field: &'static str, sender: &str, text: &str) -> Verdict {
+        let matches = self.matches(text);
+        if matches.is_empty() {
+            return Verdict::Allowed;
+        }
+
+        match self.action {
+            FilterAction::Reject => Verdict::Rejected,
+            FilterAction::Mask => {
+                let mut masked = text.to_string();
+                for (from, to) in matches.into_iter().rev() {
+                    let stars = "*".repeat(text[from..to].chars().count());
+                    masked.replace_range(from..to, &stars);
+                }
+                Verdict::Masked(masked)
+            }
+            FilterAction::Flag => {
+                warn!("🚩 Flagged {} from {}: {:?}", field, sender, text);
+                let flagged_at = now_secs();
+                let mut flags = self.flags.lock().expect("word flags lock poisoned");
+                flags.push_back(WordFlag {
+                    field,
+                    sender: sender.to_string(),
+                    text: text.to_string(),
+                    flagged_at,
+                });
+                if flags.len() > MAX_FLAGS {
+                    flags.pop_front();
+                }
+                Verdict::Allowed
+            }
+        }
+    }
+
+    /// Flagged names and messages, newest first.
+    pub fn flags(&self) -> Vec<WordFlag> {
+        let flags = self.flags.lock().expect("word flags lock poisoned");
+        flags.iter().rev().cloned().collect()
+    }
+}
@@ -1,71 +1,580 @@
 use crate::app_state::AppState;
-use crate::game::handlers::{handle_join_game, handle_make_move, handle_reset_game};
+use crate::auth;
+use crate::capacity::CapacityLimit;
+use crate::client_ip;
+use crate::game::error::{BanScope, GameError};
+use crate::game::handlers::{
+    handle_break_request, handle_cancel_quick_match, handle_chat, handle_create_game,
+    handle_create_spectate_link, handle_get_leaderboard, handle_get_replay, handle_get_state,
+    handle_host_action, handle_join_by_code, handle_join_game, handle_join_vs_ai, handle_login,
+    handle_make_move, handle_offer_draw, handle_quick_match, handle_reaction,
+    handle_register_notifications, handle_report_player, handle_reset_game, handle_resign,
+    handle_spectate, BreakRequest, HostAction,
+};
+use crate::game::message::{
+    chat_message, error_message, hello_message, invalid_name_message, move_made_message,
+    online_players_message, player_left_message, presence_message, queue_status_message,
+    reaction_message, removed_message, seats_rotated_message, server_full_message,
+    state_update_message, system_message, version_message, ClientMessage, GameEvent,
+    JoinGameRequest, UpdateReason,
+};
+use crate::game::models::Seat;
+use crate::heartbeat::Heartbeat;
+use crate::listener::PeerAddr;
+use crate::metrics::{Outcome, UNKNOWN_MESSAGE};
+use crate::wire::{self, ClientSocket, JSON_PROTOCOL, MSGPACK_PROTOCOL};
 
 use anyhow::Result;
-use axum::extract::{State, WebSocketUpgrade};
+use axum::extract::{ConnectInfo, Query, State, WebSocketUpgrade};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
 use serde_json::json;
+use std::collections::{hash_map::Entry, HashMap, HashSet};
 use std::sync::Arc;
-use tracing::{error, info};
+use tokio::sync::broadcast::error::RecvError;
+use tracing::{error, field, info, info_span, instrument, warn, Instrument, Span};
+
+/// What a socket has claimed, released again when it closes.
+#[derive(Default)]
+struct Session {
+    present_as: Option<String>,
+    /// One seat per game this socket plays in, keyed by game id.
+    seats: HashMap<String, Seat>,
+    /// Client build counted in `AppState::client_versions`.
+    client_version: Option<String>,
+    /// Name proven by the token presented at the handshake. Overrides whatever name the
+    /// client's messages carry.
+    identity: Option<String>,
+    /// Games this socket is counted as a spectator of, with the name it watches under.
+    watching: HashMap<String, Option<String>>,
+    /// Games whose events this socket receives.
+    subscriptions: HashSet<String>,
+    /// The game messages without a `game_id` act on: the one joined or watched last.
+    focus: Option<String>,
+    /// Tells this connection apart from others by the same player, e.g. in the move audit.
+    connection_id: u64,
+    /// Asked in HELLO for moves as `MOVE_MADE` rather than the whole game.
+    deltas: bool,
+}
+
+impl Session {
+    /// Takes `seat`, letting go of any other seat this socket held in the same game. Seats
+    /// in other games are kept, so one socket can play several at once.
+    async fn claim_seat(&mut self, state: &AppState, seat: Option<Seat>) {
+        let Some(seat) = seat else {
+            return;
+        };
+        self.unwatch(state, &seat.game_id).await;
+        self.focus(seat.game_id.clone());
+        if let Some(old) = self.seats.insert(seat.game_id.clone(), seat) {
+            state.leave_seat(&old).await;
+        }
+    }
+
+    /// Counts this socket as a spectator of `game_id`, under `name` if it gave one.
+    async fn watch(&mut self, state: &AppState, game_id: String, name: Option<String>) {
+        self.focus(game_id.clone());
+        if let Entry::Vacant(entry) = self.watching.entry(game_id) {
+            state
+                .count_spectator(entry.key(), name.as_deref(), true)
+                .await;
+            entry.insert(name);
+        }
+    }
+
+    async fn unwatch(&mut self, state: &AppState, game_id: &str) {
+        if let Some(name) = self.watching.remove(game_id) {
+            state.count_spectator(game_id, name.as_deref(), false).await;
+        }
+    }
+
+    /// Whether this socket was let into `game_id`, with a seat or as a spectator.
+    fn admitted(&self, game_id: &str) -> bool {
+        self.seats.contains_key(game_id) || self.watching.contains_key(game_id)
+    }
+
+    /// Starts receiving `game_id`'s events; it becomes the default game only if there was none.
+    fn subscribe(&mut self, game_id: String) {
+        self.focus.get_or_insert_with(|| game_id.clone());
+        self.subscriptions.insert(game_id);
+    }
+
+    /// Subscribes to `game_id` and makes it the default game, which the connection's logs
+    /// are tagged with from then on.
+    fn focus(&mut self, game_id: String) {
+        // Log lines show every value recorded, so only a change is worth recording
+        if self.focus.as_deref() != Some(game_id.as_str()) {
+            Span::current().record("game_id", game_id.as_str());
+        }
+        self.subscriptions.insert(game_id.clone());
+        self.focus = Some(game_id);
+    }
+
+    /// The game a message refers to: its own `game_id`, or the default game.
+    fn target(&self, game_id: Option<String>) -> Option<String> {
+        game_id.or_else(|| self.focus.clone())
+    }
+
+    /// Watches every winner-stays-on room `name` is waiting in line for, so this socket
+    /// hears when they get a seat. Returns whether they are in any line.
+    async fn wait_in_line(&mut self, state: &AppState, name: &str) -> bool {
+        let game_ids = state.lines_joined(name).await;
+        for game_id in &game_ids {
+            if !self.watching.contains_key(game_id) {
+                self.watch(state, game_id.clone(), Some(name.to_string()))
+                    .await;
+            }
+        }
+        !game_ids.is_empty()
+    }
+
+    /// Stops following `game_id`, giving up any seat, spectator or line place held in it.
+    async fn leave(&mut self, state: &AppState, game_id: &str) {
+        if let Some(seat) = self.seats.remove(game_id) {
+            state.leave_seat(&seat).await;
+        }
+        if let Some(name) = &self.present_as {
+            state.leave_line(game_id, name).await;
+        }
+        self.unwatch(state, game_id).await;
+        self.subscriptions.remove(game_id);
+        if self.focus.as_deref() == Some(game_id) {
+            self.focus = None;
+        }
+    }
+
+    /// Lets go of everything, for when the socket closes. Seats and spectator places always
+    /// come with a subscription, so leaving every subscribed game covers them.
+    async fn leave_all(&mut self, state: &AppState) {
+        let game_ids: Vec<String> = self.subscriptions.iter().cloned().collect();
+        for game_id in game_ids {
+            self.leave(state, &game_id).await;
+        }
+    }
+
+    async fn report_version(&mut self, state: &AppState, version: &str, git_hash: &str) {
+        let mut client_versions = state.client_versions.write().await;
+        if let Some(old) = self.client_version.take() {
+            client_versions.disconnect(&old);
+        }
+        self.client_version = Some(client_versions.connect(version, git_hash));
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WsQuery {
+    /// Player token from `POST /auth`.
+    token: Option<String>,
+}
 
 #[axum::debug_handler]
+#[instrument(name = "ws_handshake", skip_all)]
 pub async fn ws_handler(
     ws: WebSocketUpgrade,
     State(state): State<Arc<AppState>>,
-) -> impl axum::response::IntoResponse {
-    info!("🔗 WebSocket connection attempt received!");
+    ConnectInfo(PeerAddr(peer)): ConnectInfo<PeerAddr>,
+    Query(query): Query<WsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    let client_ip = client_ip::resolve(peer.ip(), &headers, &state.config.trusted_proxies);
+    info!("🔗 WebSocket connection attempt from {}", client_ip);
+
+    // Browsers can't set headers on a WebSocket, so they pass the token in the URL
+    let token = query.token.as_deref().or_else(|| auth::bearer(&headers));
+    let identity = match (&state.config.jwt_secret, token) {
+        (Some(secret), Some(token)) => match auth::verify(secret, token) {
+            Some(name) => Some(name),
+            None => {
+                warn!("🔐 Rejected a bad or expired token from {}", client_ip);
+                return (StatusCode::UNAUTHORIZED, "Invalid or expired token").into_response();
+            }
+        },
+        _ => None,
+    };
+    let ban = state
+        .bans
+        .read()
+        .await
+        .find(None, identity.as_deref(), Some(client_ip))
+        .map(|ban| ban.reason.clone());
 
+    let slot = state.connections.try_acquire();
+    let ws = ws.protocols([MSGPACK_PROTOCOL, JSON_PROTOCOL]);
     ws.on_upgrade(move |socket| async move {
-        info!("✅ WebSocket upgrade successful.");
-        if let Err(e) = handle_socket(socket, state).await {
-            error!("❌ WebSocket processing failed: {}", e);
+        let mut socket = ClientSocket::new(socket);
+        if let Some(reason) = ban {
+            warn!("🚫 Turning away {}: banned", client_ip);
+            let error = GameError::Banned {
+                scope: BanScope::Server,
+                reason,
+            };
+            let _ = socket.send_json(&error_message(&error)).await;
+            let _ = socket.send(axum::extract::ws::Message::Close(None)).await;
+            return;
+        }
+        let Some(slot) = slot else {
+            // Upgrade anyway so the client gets a reason it can act on, not a bare 503
+            warn!("🚫 Turning away {}: connection cap reached", client_ip);
+            let _ = socket
+                .send_json(&server_full_message(CapacityLimit::Connections))
+                .await;
+            let _ = socket.send(axum::extract::ws::Message::Close(None)).await;
+            return;
+        };
+
+        // Every log line of the connection carries its id, and its game and player once
+        // known, so lines from concurrent sockets can be told apart
+        let span = info_span!(
+            "connection",
+            connection_id = slot.id(),
+            game_id = field::Empty,
+            player = field::Empty
+        );
+        async move {
+            info!(
+                "✅ WebSocket upgrade successful for {} ({:?} frames), connection {}.",
+                client_ip,
+                socket.encoding(),
+                slot.id()
+            );
+            let mut session = Session {
+                identity,
+                connection_id: slot.id(),
+                ..Session::default()
+            };
+            if let Err(e) = handle_socket(socket, Arc::clone(&state), &mut session).await {
+                error!("❌ WebSocket processing failed for {}: {}", client_ip, e);
+            }
+            info!("👋 Connection from {} closed", client_ip);
+
+            // Runs on every exit path, including handler errors
+            session.leave_all(&state).await;
+            if let Some(name) = session.present_as {
+                state.mark_offline(&name).await;
+            }
+            if let Some(client_version) = session.client_version {
+                state
+                    .client_versions
+                    .write()
+                    .await
+                    .disconnect(&client_version);
+            }
         }
+        .instrument(span)
+        .await
     })
+    .into_response()
+}
+
+/// Sends the current state of each of `game_ids`, for a socket that missed updates.
+async fn resync(
+    socket: &mut ClientSocket,
+    state: &AppState,
+    game_ids: &HashSet<String>,
+) -> Result<()> {
+    let snapshots: Vec<_> = {
+        let games = state.games.read().await;
+        game_ids
+            .iter()
+            .filter_map(|id| {
+                let game = games.get(id)?;
+                Some(state_update_message(id, game, UpdateReason::Resync))
+            })
+            .collect()
+    };
+    for snapshot in &snapshots {
+        socket.send_json(snapshot).await?;
+    }
+    Ok(())
 }
 
 async fn handle_socket(
-    mut socket: axum::extract::ws::WebSocket,
+    mut socket: ClientSocket,
     state: Arc<AppState>,
+    session: &mut Session,
 ) -> Result<()> {
     let mut rx = state.tx.subscribe();
-    let mut subscribed_game_id: Option<String> = None;
+    let mut presence_rx = state.presence_tx.subscribe();
+    let mut player_name: Option<String> = None;
+    let mut heartbeat = Heartbeat::new(&state.tunables());
 
     info!("✅ WebSocket connection established.");
 
+    let hello = hello_message(
+        state.config.motd.as_deref(),
+        state.config.event_theme.as_deref(),
+    );
+    socket.send_json(&hello).await?;
+
     loop {
         info!("🕵️ Waiting for WebSocket message...");
 
         tokio::select! {
-            Some(Ok(msg)) = socket.recv() => {
+            incoming = socket.recv() => {
+                // A closed or broken socket frees its connection slot right away
+                let Some(Ok(msg)) = incoming else {
+                    info!("❌ WebSocket stream ended.");
+                    break;
+                };
                 match msg {
-                    axum::extract::ws::Message::Text(text) => {
-                        info!("📩 Received WebSocket message: {}", text);
+                    axum::extract::ws::Message::Text(_) | axum::extract::ws::Message::Binary(_) => {
+                        let Some(decoded) = wire::decode::<ClientMessage>(&msg) else {
+                            continue;
+                        };
 
-                        let parsed: serde_json::Value = match serde_json::from_str(&text) {
-                            Ok(json) => json,
-                            Err(_) => {
-                                error!("❌ Failed to parse WebSocket message: {}", text);
+                        #[cfg(feature = "chaos")]
+                        crate::chaos::delay_frame().await;
+
+                        let mut message = match decoded {
+                            Ok(message) => message,
+                            Err(e) => {
+                                error!("❌ Rejected malformed WebSocket message: {}", e);
+                                state.metrics.record_message(UNKNOWN_MESSAGE, Outcome::Failed);
+                                let error = GameError::Malformed { detail: e.to_string() };
+                                socket.send_json(&error_message(&error)).await?;
                                 continue;
                             }
                         };
+                        let kind = message.kind();
+                        let errors_before = socket.errors_sent();
 
-                        match parsed["type"].as_str() {
-                            Some("JOIN_GAME") => {
+                        if let Err(reason) = message.clean_names(&state.word_filter) {
+                            error!("❌ Rejected a message with an unusable name: {}", reason);
+                            state.metrics.record_message(kind, Outcome::Failed);
+                            socket.send_json(&invalid_name_message(&reason)).await?;
+                            continue;
+                        }
+                        if let Some(name) = message.name_mut() {
+                            if let Some(identity) = &session.identity {
+                                name.clone_from(identity);
+                            } else if state.config.jwt_secret.is_some()
+                                && state.players.read().await.is_claimed(name.trim())
+                            {
+                                let error = GameError::NameClaimed { name: name.trim().to_string() };
+                                state.metrics.record_message(kind, Outcome::Failed);
+                                socket.send_json(&error_message(&error)).await?;
+                                continue;
+                            }
+                            let ban = state
+                                .bans
+                                .read()
+                                .await
+                                .find(Some(name.trim()), session.identity.as_deref(), None)
+                                .map(|ban| ban.reason.clone());
+                            if let Some(reason) = ban {
+                                warn!("🚫 Refused banned player {:?}", name.trim());
+                                let error = GameError::Banned { scope: BanScope::Server, reason };
+                                state.metrics.record_message(kind, Outcome::Failed);
+                                socket.send_json(&error_message(&error)).await?;
+                                continue;
+                            }
+                        }
+
+                        match message {
+                            ClientMessage::Hello { version, git_hash, deltas } => {
+                                session.report_version(&state, &version, &git_hash).await;
+                                session.deltas = deltas;
+                            }
+                            ClientMessage::Version => {
+                                socket.send_json(&version_message()).await?;
+                            }
+                            ClientMessage::JoinGame(request) => {
                                 info!("✅ Processing JOIN_GAME message.");
-                                handle_join_game(&parsed, &state, &mut socket).await?;
-                                subscribed_game_id = parsed["game_id"].as_str().map(|s| s.to_string());
+                                // A refused join (full, private, banned…) follows nothing, unless
+                                // it put the player in line for a winner-stays-on room
+                                match handle_join_game(&request, &state, &mut socket).await? {
+                                    Some(seat) => {
+                                        session.claim_seat(&state, Some(seat)).await;
+                                        player_name = Some(request.name);
+                                    }
+                                    None => {
+                                        if session.wait_in_line(&state, &request.name).await {
+                                            player_name = Some(request.name);
+                                        }
+                                    }
+                                }
+                            }
+                            ClientMessage::CreateGame(request) => {
+                                info!("✅ Processing CREATE_GAME message.");
+                                if let Some(seat) = handle_create_game(&request, &state, &mut socket).await? {
+                                    player_name = Some(request.name);
+                                    session.claim_seat(&state, Some(seat)).await;
+                                }
+                            }
+                            ClientMessage::Spectate(request) => {
+                                info!("✅ Processing SPECTATE message.");
+                                if handle_spectate(&request, &state, &mut socket).await? {
+                                    session.watch(&state, request.game_id, request.name.clone()).await;
+                                    // A seat elsewhere keeps the name it was taken under
+                                    if player_name.is_none() {
+                                        player_name = request.name;
+                                    }
+                                }
+                            }
+                            ClientMessage::QuickMatch { name } => {
+                                info!("✅ Processing QUICK_MATCH message.");
+                                if let Some(seat) = handle_quick_match(&name, &state, &mut socket).await? {
+                                    player_name = Some(name);
+                                    session.claim_seat(&state, Some(seat)).await;
+                                }
                             }
-                            Some("MAKE_MOVE") => {
+                            ClientMessage::CancelQuickMatch { game_id } => {
+                                info!("✅ Processing CANCEL_QUICK_MATCH message.");
+                                let game_id = session.target(game_id).unwrap_or_default();
+                                if handle_cancel_quick_match(session.seats.get(&game_id), &state, &mut socket).await? {
+                                    // The game is gone, so there is no seat left to give up
+                                    session.seats.remove(&game_id);
+                                    session.leave(&state, &game_id).await;
+                                }
+                            }
+                            ClientMessage::Login { name } => {
+                                info!("✅ Processing LOGIN message.");
+                                if let Some(name) = handle_login(&name, &state, &mut socket).await? {
+                                    player_name = Some(name);
+                                }
+                            }
+                            ClientMessage::JoinByCode { code, name } => {
+                                info!("✅ Processing JOIN_BY_CODE message.");
+                                match handle_join_by_code(&code, &name, &state, &mut socket).await? {
+                                    Some(seat) => {
+                                        player_name = Some(name);
+                                        session.claim_seat(&state, Some(seat)).await;
+                                    }
+                                    None => {
+                                        if session.wait_in_line(&state, &name).await {
+                                            player_name = Some(name);
+                                        }
+                                    }
+                                }
+                            }
+                            ClientMessage::JoinVsAi(request) => {
+                                info!("✅ Processing JOIN_VS_AI message.");
+                                if let Some(seat) = handle_join_vs_ai(&request, &state, &mut socket).await? {
+                                    player_name = Some(request.name);
+                                    session.claim_seat(&state, Some(seat)).await;
+                                }
+                            }
+                            ClientMessage::MakeMove(request) => {
                                 info!("✅ Processing MAKE_MOVE message.");
-                                handle_make_move(&parsed, &state, &mut socket).await?;
-                                if subscribed_game_id.is_none() {
-                                    subscribed_game_id = parsed["game_id"].as_str().map(|s| s.to_string());
+                                let seat = session.seats.get(&request.game_id);
+                                handle_make_move(&request, &state, player_name.as_deref(), seat, session.connection_id, &mut socket).await?;
+                                // A refused move mustn't become a way to follow someone else's game
+                                if session.admitted(&request.game_id) {
+                                    session.subscribe(request.game_id);
                                 }
                             }
-                            Some("RESET_GAME") => {
+                            ClientMessage::Resign { game_id, player } => {
+                                info!("✅ Processing RESIGN message.");
+                                handle_resign(&game_id, player, session.seats.get(&game_id), &state, &mut socket).await?;
+                            }
+                            ClientMessage::OfferDraw { game_id, player } => {
+                                info!("✅ Processing OFFER_DRAW message.");
+                                handle_offer_draw(&game_id, player, session.seats.get(&game_id), &state, &mut socket).await?;
+                            }
+                            ClientMessage::RequestPause { game_id, player } => {
+                                info!("✅ Processing REQUEST_PAUSE message.");
+                                handle_break_request(BreakRequest::Pause, &game_id, player, session.seats.get(&game_id), &state, &mut socket).await?;
+                            }
+                            ClientMessage::Resume { game_id, player } => {
+                                info!("✅ Processing RESUME message.");
+                                handle_break_request(BreakRequest::Resume, &game_id, player, session.seats.get(&game_id), &state, &mut socket).await?;
+                            }
+                            ClientMessage::ResetGame { game_id } => {
                                 info!("✅ Processing RESET_GAME message.");
-                                handle_reset_game(&parsed, &state).await?;
+                                if let Err(err) = handle_reset_game(&game_id, session.seats.get(&game_id), &state).await {
+                                    socket.send_json(&error_message(&err)).await?;
+                                }
+                            }
+                            ClientMessage::GetState { game_id, token } => {
+                                info!("✅ Processing GET_STATE message.");
+                                let admitted = session.admitted(&game_id);
+                                if handle_get_state(&game_id, admitted, token.as_deref(), &state, &mut socket).await? {
+                                    session.subscribe(game_id);
+                                }
+                            }
+                            ClientMessage::GetReplay { game_id, token } => {
+                                info!("✅ Processing GET_REPLAY message.");
+                                let admitted = session.admitted(&game_id);
+                                handle_get_replay(&game_id, admitted, token.as_deref(), &state, &mut socket).await?;
+                            }
+                            ClientMessage::GetLeaderboard { limit } => {
+                                info!("✅ Processing GET_LEADERBOARD message.");
+                                handle_get_leaderboard(limit, &state, &mut socket).await?;
+                            }
+                            ClientMessage::WhoIsOnline => {
+                                info!("✅ Processing WHO_IS_ONLINE message.");
+                                let players = state.online_players().await;
+                                socket.send_json(&online_players_message(&players)).await?;
+                            }
+                            ClientMessage::CreateSpectateLink { game_id } => {
+                                info!("✅ Processing CREATE_SPECTATE_LINK message.");
+                                let game_id = session.target(game_id);
+                                handle_create_spectate_link(&state, game_id.as_deref(), player_name.as_deref(), &mut socket).await?;
+                            }
+                            ClientMessage::KickPlayer { game_id, name } => {
+                                info!("✅ Processing KICK_PLAYER message.");
+                                let game_id = session.target(game_id);
+                                handle_host_action(HostAction::KickPlayer, &name, &state, game_id.as_deref(), player_name.as_deref(), &mut socket).await?;
+                            }
+                            ClientMessage::BanSpectator { game_id, name } => {
+                                info!("✅ Processing BAN_SPECTATOR message.");
+                                let game_id = session.target(game_id);
+                                handle_host_action(HostAction::BanSpectator, &name, &state, game_id.as_deref(), player_name.as_deref(), &mut socket).await?;
+                            }
+                            ClientMessage::LeaveGame { game_id } => {
+                                info!("✅ Processing LEAVE_GAME message.");
+                                session.leave(&state, &game_id).await;
+                                socket.send_json(&json!({ "type": "LEFT_GAME", "game_id": game_id })).await?;
+                            }
+                            ClientMessage::ReportPlayer { game_id, target, reason } => {
+                                info!("✅ Processing REPORT_PLAYER message.");
+                                let game_id = session.target(game_id);
+                                handle_report_player(&target, &reason, &state, game_id.as_deref(), player_name.as_deref(), &mut socket).await?;
+                            }
+                            ClientMessage::RegisterNotifications { webhook_url, email } => {
+                                info!("✅ Processing REGISTER_NOTIFICATIONS message.");
+                                handle_register_notifications(webhook_url.as_deref(), email.as_deref(), &state, player_name.as_deref(), &mut socket).await?;
+                            }
+                            ClientMessage::Chat { game_id, text, team } => {
+                                info!("✅ Processing CHAT message.");
+                                let sender = player_name.as_deref().unwrap_or("Spectator");
+                                // Only a seat or a spectator place lets a socket speak in a game
+                                let game_id = session.target(game_id).filter(|id| session.admitted(id));
+                                let seat = game_id.as_ref().and_then(|id| session.seats.get(id));
+                                handle_chat(&text, team, &state, game_id.as_deref(), seat, sender, &mut socket).await?;
+                            }
+                            ClientMessage::Reaction { game_id, emoji } => {
+                                info!("✅ Processing REACTION message.");
+                                let sender = player_name.as_deref().unwrap_or("Spectator");
+                                // Only a seat or a spectator place lets a socket speak in a game
+                                let game_id = session.target(game_id).filter(|id| session.admitted(id));
+                                let seat = game_id.as_ref().and_then(|id| session.seats.get(id));
+                                handle_reaction(&emoji, &state, game_id.as_deref(), seat, sender, &mut socket).await?;
                             }
-                            _ => error!("⚠️ Unknown message type received: {:?}", parsed["type"]),
+                        }
+                        let outcome = if socket.errors_sent() > errors_before {
+                            Outcome::Failed
+                        } else {
+                            Outcome::Ok
+                        };
+                        state.metrics.record_message(kind, outcome);
+
+                        if session.present_as != player_name {
+                            if let Some(old) = session.present_as.take() {
+                                state.mark_offline(&old).await;
+                            }
+                            if let Some(new) = &player_name {
+                                Span::current().record("player", new.as_str());
+                                state.mark_online(new).await;
+                            }
+                            session.present_as = player_name.clone();
+                        }
+
+                        #[cfg(feature = "chaos")]
+                        if crate::chaos::force_disconnect() {
+                            tracing::warn!("🐒 Chaos: forcing disconnect.");
+                            break;
                         }
                     }
                     axum::extract::ws::Message::Ping(data) => {
@@ -74,32 +583,175 @@ async fn handle_socket(
                     }
                     axum::extract::ws::Message::Pong(data) => {
                         info!("📩 Received Pong: {:?}", data);
+                        heartbeat.pong();
                     }
                     axum::extract::ws::Message::Close(reason) => {
                         info!("❌ WebSocket closed: {:?}", reason);
                         break;
                     }
-                    _ => error!("⚠️ Received unexpected WebSocket message."),
                 }
             }
 
-            Ok((game_id, game)) = rx.recv() => {
-                info!("📩 WebSocket received game update for game_id={}", game_id);
-                if let Some(ref subscribed_id) = subscribed_game_id {
-                    if *subscribed_id == game_id {
-                        let game_update = json!({
-                            "type": "UPDATE_STATE",
-                            "game_id": game_id,
-                            "game": game
-                        });
-
-                        info!("📤 Sending WebSocket update: {}", game_update);
-                        if let Err(e) = socket
-                            .send(axum::extract::ws::Message::Text(game_update.to_string().into()))
-                            .await
-                        {
-                            error!("❌ Failed to send game update: {}", e);
+            _ = heartbeat.due() => {
+                if !heartbeat.ping() {
+                    warn!(
+                        "💀 No Pong after {} Pings, dropping the connection.",
+                        heartbeat.max_missed()
+                    );
+                    break;
+                }
+                socket.send(axum::extract::ws::Message::Ping(Default::default())).await?;
+            }
+
+            Ok(change) = presence_rx.recv() => {
+                // Nobody needs telling that they themselves came online
+                if session.present_as.as_ref() == Some(&change.name) {
+                    continue;
+                }
+                socket.send_json(&presence_message(&change)).await?;
+            }
+
+            received = rx.recv() => {
+                let (game_id, event) = match received {
+                    Ok(update) => update,
+                    Err(RecvError::Lagged(missed)) => {
+                        // The missed events are gone, but the latest state of each game
+                        // covers every update among them
+                        warn!(
+                            "🐌 Socket fell {} events behind the broadcast channel, resyncing",
+                            missed
+                        );
+                        resync(&mut socket, &state, &session.subscriptions).await?;
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                };
+                info!("📩 WebSocket received game event for game_id={}", game_id);
+                if !session.subscriptions.contains(&game_id) {
+                    continue;
+                }
+
+                #[cfg(feature = "chaos")]
+                {
+                    if crate::chaos::drop_broadcast() {
+                        tracing::warn!("🐒 Chaos: dropping update for game_id={}", game_id);
+                        continue;
+                    }
+                    crate::chaos::delay_frame().await;
+                }
+
+                let move_sent_at = match &event {
+                    GameEvent::StateUpdate { reason: UpdateReason::Move, sent_at, .. } => Some(*sent_at),
+                    _ => None,
+                };
+
+                let outgoing = match event {
+                    GameEvent::StateUpdate { game, reason, .. } => {
+                        match move_made_message(&game_id, &game) {
+                            Some(delta) if session.deltas && reason == UpdateReason::Move => {
+                                if !game.game_over {
+                                    delta
+                                } else {
+                                    // Ending the round also moved the scores and ratings,
+                                    // which only the full state carries
+                                    socket.send_json(&delta).await?;
+                                    state_update_message(&game_id, &game, reason)
+                                }
+                            }
+                            _ => state_update_message(&game_id, &game, reason),
+                        }
+                    }
+                    GameEvent::Chat(chat) => {
+                        // Team chat only reaches sockets seated on that team, and
+                        // hidden spectator chat only sockets without a seat here
+                        let seated = session.seats.get(&game_id);
+                        let teammate = seated.is_some_and(|seat| Some(seat.player) == chat.team);
+                        if chat.team.is_some() && !teammate {
+                            continue;
+                        }
+                        if chat.spectators_only && seated.is_some() {
+                            continue;
+                        }
+                        chat_message(&game_id, &chat)
+                    }
+                    GameEvent::Reaction(reaction) => {
+                        // The sender already knows, and hidden spectators stay hidden
+                        if player_name.as_deref() == Some(reaction.sender.as_str()) {
+                            continue;
+                        }
+                        if reaction.spectators_only && session.seats.contains_key(&game_id) {
+                            continue;
+                        }
+                        reaction_message(&game_id, &reaction)
+                    }
+                    GameEvent::System(system) => system_message(&game_id, &system),
+                    GameEvent::PlayerLeft { player, name } => {
+                        player_left_message(&game_id, player, &name)
+                    }
+                    GameEvent::QueueStatus(status) => {
+                        // Spectators of a waiting game have nobody to wait for
+                        if !session.seats.contains_key(&game_id) {
+                            continue;
                         }
+                        queue_status_message(&game_id, &status)
+                    }
+                    GameEvent::Removed { name } => {
+                        if player_name.as_deref() != Some(name.as_str()) {
+                            continue;
+                        }
+                        session.leave(&state, &game_id).await;
+                        removed_message(&game_id)
+                    }
+                    GameEvent::Matched { game_id: host } => {
+                        let seated = session.seats.contains_key(&game_id);
+                        let Some(name) = player_name.clone().filter(|_| seated) else {
+                            continue;
+                        };
+                        // The waiting game is dropped rather than left, so nobody sees a
+                        // disconnect from a player who only moved on
+                        session.seats.remove(&game_id);
+                        session.leave(&state, &game_id).await;
+                        state.games.write().await.remove(&game_id);
+
+                        let request = JoinGameRequest {
+                            game_id: host,
+                            name,
+                            ..JoinGameRequest::default()
+                        };
+                        if let Some(seat) = handle_join_game(&request, &state, &mut socket).await? {
+                            session.claim_seat(&state, Some(seat)).await;
+                        }
+                        continue;
+                    }
+                    GameEvent::SeatsRotated { player, left, seated } => {
+                        // The seat itself has already changed hands, so it is given up
+                        // without the usual disconnect
+                        if player_name.as_deref() == Some(left.as_str()) {
+                            session.seats.remove(&game_id);
+                            session.watch(&state, game_id.clone(), Some(left.clone())).await;
+                        }
+                        if player_name.as_deref() == Some(seated.as_str()) {
+                            let seat = Seat { game_id: game_id.clone(), player };
+                            session.claim_seat(&state, Some(seat)).await;
+                        }
+                        seats_rotated_message(&game_id, player, &left, &seated)
+                    }
+                };
+
+                info!("📤 Sending WebSocket update: {}", outgoing);
+                let sent = socket
+                    .send_json(&outgoing)
+                    .instrument(info_span!("fanout", game_id = %game_id))
+                    .await;
+                if let Err(e) = sent {
+                    error!("❌ Failed to send game update: {}", e);
+                } else if let Some(sent_at) = move_sent_at {
+                    let elapsed = sent_at.elapsed();
+                    if state.move_latency.record_fanout(elapsed) {
+                        warn!(
+                            "🐢 Move update for game {} took {:?} to reach a socket, over the {:?} budget",
+                            game_id, elapsed, state.move_latency.budget()
+                        );
                     }
                 }
             }
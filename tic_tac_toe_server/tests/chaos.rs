@@ -0,0 +1,269 @@
+//! Plays full games against a chaos-enabled server and checks that a client following the
+//! reconnect/resync protocol (rejoin + `GET_STATE` after reconnect, `GET_STATE` on version
+//! gaps or unconfirmed moves) always converges on the authoritative board.
+//!
+//! Run with `cargo test --features chaos`.
+
+use futures_util::{SinkExt, StreamExt};
+use serde_json::{json, Value};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio_tungstenite::{connect_async, tungstenite::Message, MaybeTlsStream, WebSocketStream};
+
+type Ws = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const ROUNDS: usize = 3;
+const MOVE_ACK_TIMEOUT: Duration = Duration::from_millis(1500);
+const GAME_DEADLINE: Duration = Duration::from_secs(60);
+
+/// X and O alternate; the game ends in a draw with no line completed along the way.
+const MOVES: [(usize, usize); 9] = [
+    (0, 0),
+    (1, 1),
+    (2, 2),
+    (0, 2),
+    (2, 0),
+    (1, 0),
+    (1, 2),
+    (2, 1),
+    (0, 1),
+];
+
+/// The server process and the scratch directory it keeps its files in.
+struct Server(Child, PathBuf);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+        let _ = std::fs::remove_dir_all(&self.1);
+    }
+}
+
+fn start_server() -> (Server, String) {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .expect("no free port")
+        .port();
+
+    // Every saved file (ratings, scores, replays, snapshot…) lands in a fresh directory,
+    // so state left over from earlier runs can't leak into the games
+    let dir = std::env::temp_dir().join(format!("chaos-{port}"));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("failed to create scratch directory");
+
+    let child = Command::new(env!("CARGO_BIN_EXE_tic_tac_toe_server"))
+        .current_dir(&dir)
+        .env("PORT", port.to_string())
+        .env("TURN_SECONDS", "0")
+        .env("CHAOS_MAX_DELAY_MS", "40")
+        .env("CHAOS_DROP_RATE", "0.3")
+        .env("CHAOS_DISCONNECT_RATE", "0.15")
+        .stdout(Stdio::null())
+        .spawn()
+        .expect("failed to start server");
+
+    (Server(child, dir), format!("ws://127.0.0.1:{port}/ws"))
+}
+
+struct TestClient {
+    url: String,
+    name: String,
+    game_id: String,
+    ws: Option<Ws>,
+    game: Value,
+    version: u64,
+}
+
+impl TestClient {
+    fn new(url: &str, name: &str, game_id: &str) -> Self {
+        TestClient {
+            url: url.to_string(),
+            name: name.to_string(),
+            game_id: game_id.to_string(),
+            ws: None,
+            game: Value::Null,
+            version: 0,
+        }
+    }
+
+    async fn connect(&mut self) {
+        loop {
+            match connect_async(&self.url).await {
+                Ok((ws, _)) => {
+                    self.ws = Some(ws);
+                    let join =
+                        json!({ "type": "JOIN_GAME", "game_id": self.game_id, "name": self.name });
+                    let resync = json!({ "type": "GET_STATE", "game_id": self.game_id });
+                    if self.send(join).await && self.send(resync).await {
+                        return;
+                    }
+                }
+                Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+            }
+        }
+    }
+
+    async fn send(&mut self, message: Value) -> bool {
+        let Some(ws) = self.ws.as_mut() else {
+            return false;
+        };
+
+        if ws
+            .send(Message::Text(message.to_string().into()))
+            .await
+            .is_err()
+        {
+            self.ws = None;
+            return false;
+        }
+        true
+    }
+
+    async fn resync(&mut self) {
+        let request = json!({ "type": "GET_STATE", "game_id": self.game_id });
+        if !self.send(request).await {
+            self.connect().await;
+        }
+    }
+
+    /// Processes incoming messages for up to `wait`, reconnecting when the socket drops.
+    async fn pump(&mut self, wait: Duration) {
+        let deadline = Instant::now() + wait;
+
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            if self.ws.is_none() {
+                self.connect().await;
+            }
+            let ws = self.ws.as_mut().expect("connected above");
+
+            let message = match tokio::time::timeout(remaining, ws.next()).await {
+                Err(_) => return,
+                Ok(Some(Ok(message))) => message,
+                Ok(_) => {
+                    self.ws = None;
+                    continue;
+                }
+            };
+
+            let Message::Text(text) = message else {
+                if let Message::Close(_) = message {
+                    self.ws = None;
+                }
+                continue;
+            };
+
+            let parsed: Value = serde_json::from_str(&text).expect("server sent invalid JSON");
+            if parsed["type"] != "UPDATE_STATE" {
+                continue;
+            }
+
+            let version = parsed["game"]["version"].as_u64().unwrap_or(0);
+            if version <= self.version && !self.game.is_null() {
+                continue;
+            }
+
+            let missed_updates = self.version != 0 && version > self.version + 1;
+            self.version = version;
+            self.game = parsed["game"].clone();
+
+            if missed_updates {
+                self.resync().await;
+            }
+        }
+    }
+
+    fn cell(&self, x: usize, y: usize) -> &Value {
+        &self.game["board"][x][y]
+    }
+}
+
+async fn play_round(url: &str, round: usize) {
+    let game_id = format!("chaos-{round}");
+    let mut x = TestClient::new(url, "Chaos X", &game_id);
+    let mut o = TestClient::new(url, "Chaos O", &game_id);
+
+    x.connect().await;
+    x.pump(Duration::from_millis(200)).await;
+    o.connect().await;
+
+    let started = Instant::now();
+
+    for (turn, &(row, col)) in MOVES.iter().enumerate() {
+        let (mover, other, mark) = if turn % 2 == 0 {
+            (&mut x, &mut o, "X")
+        } else {
+            (&mut o, &mut x, "O")
+        };
+
+        while mover.cell(row, col) != mark {
+            assert!(
+                started.elapsed() < GAME_DEADLINE,
+                "round {round} stalled at move {turn}"
+            );
+
+            let request = json!({
+                "type": "MAKE_MOVE",
+                "game_id": game_id,
+                "player": mark,
+                "x": row,
+                "y": col
+            });
+            if !mover.send(request).await {
+                mover.connect().await;
+                continue;
+            }
+
+            let sent_at = Instant::now();
+            while mover.cell(row, col) != mark && sent_at.elapsed() < MOVE_ACK_TIMEOUT {
+                mover.pump(Duration::from_millis(100)).await;
+            }
+
+            if mover.cell(row, col) != mark {
+                // Unconfirmed: ask for the authoritative state before retrying
+                mover.resync().await;
+                mover.pump(Duration::from_millis(300)).await;
+            }
+        }
+
+        other.pump(Duration::from_millis(50)).await;
+    }
+
+    for client in [&mut x, &mut o] {
+        while !client.game["game_over"].as_bool().unwrap_or(false) {
+            assert!(
+                started.elapsed() < GAME_DEADLINE,
+                "round {round} never finished"
+            );
+            client.resync().await;
+            client.pump(Duration::from_millis(300)).await;
+        }
+    }
+
+    assert_eq!(
+        x.game["board"], o.game["board"],
+        "boards diverged in round {round}"
+    );
+    assert_eq!(
+        x.game["draw"],
+        json!(true),
+        "unexpected result in round {round}"
+    );
+
+    for (turn, &(row, col)) in MOVES.iter().enumerate() {
+        let mark = if turn % 2 == 0 { "X" } else { "O" };
+        assert_eq!(x.cell(row, col), mark, "wrong mark at ({row}, {col})");
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn clients_recover_to_a_consistent_board() {
+    let (_server, url) = start_server();
+
+    for round in 0..ROUNDS {
+        play_round(&url, round).await;
+    }
+}